@@ -48,6 +48,11 @@ pub struct BaseMetadataBuilder {
     origin_prover_sync: Arc<RwLock<MerkleTreeBuilder>>,
     origin_validator_announce: Arc<dyn ValidatorAnnounce>,
     allow_local_checkpoint_syncers: bool,
+    checkpoint_fetch_concurrency: usize,
+    /// Only use fetched checkpoints signed with the scheme they declare
+    verify_signatures: bool,
+    /// Checkpoint syncers used in place of the announced storage locations
+    validator_syncers: Arc<HashMap<H160, CheckpointSyncerConf>>,
     metrics: Arc<CoreMetrics>,
     /// ISMs can be structured recursively. We keep track of the depth
     /// of the recursion to avoid infinite loops.
@@ -182,6 +187,22 @@ impl BaseMetadataBuilder {
         // Only use the most recently announced location for now.
         let mut checkpoint_syncers: HashMap<H160, Arc<dyn CheckpointSyncer>> = HashMap::new();
         for (&validator, validator_storage_locations) in validators.iter().zip(storage_locations) {
+            if let Some(config) = self.validator_syncers.get(&validator.into()) {
+                match config.build(None) {
+                    Ok(checkpoint_syncer) => {
+                        checkpoint_syncers.insert(validator.into(), checkpoint_syncer.into());
+                        continue;
+                    }
+                    Err(err) => {
+                        warn!(
+                            error=%err,
+                            ?config,
+                            ?validator,
+                            "Error when loading the configured checkpoint syncer; falling back to the announced storage locations"
+                        );
+                    }
+                }
+            }
             for storage_location in validator_storage_locations.iter().rev() {
                 let Ok(config) = CheckpointSyncerConf::from_str(storage_location) else {
                     debug!(?validator, ?storage_location, "Could not parse checkpoint syncer config for validator");
@@ -228,6 +249,11 @@ impl BaseMetadataBuilder {
                 }
             }
         }
-        Ok(MultisigCheckpointSyncer::new(checkpoint_syncers))
+        Ok(MultisigCheckpointSyncer::new(
+            checkpoint_syncers,
+            self.checkpoint_fetch_concurrency,
+            self.verify_signatures,
+            self.metrics.checkpoint_signature_mismatches(),
+        ))
     }
 }
@@ -1,6 +1,6 @@
 use std::fmt::{Debug, Formatter};
 use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use async_trait::async_trait;
 use derive_new::new;
@@ -9,8 +9,11 @@ use hyperlane_base::db::HyperlaneRocksDB;
 use prometheus::{IntCounter, IntGauge};
 use tracing::{debug, error, info, instrument, trace, warn};
 
-use hyperlane_base::CoreMetrics;
-use hyperlane_core::{HyperlaneChain, HyperlaneDomain, HyperlaneMessage, Mailbox, U256};
+use hyperlane_base::{settings::pad_gas_limit, CoreMetrics};
+use hyperlane_core::{
+    BatchItem, HyperlaneChain, HyperlaneDomain, HyperlaneMessage, HyperlaneProvider, Mailbox,
+    TxOutcome, U256,
+};
 
 use super::{
     gas_payment::GasPaymentEnforcer,
@@ -42,9 +45,24 @@ pub struct MessageContext {
     /// Hard limit on transaction gas when submitting a transaction to the
     /// destination.
     pub transaction_gas_limit: Option<U256>,
+    /// The destination chain's cap on the estimated gas of a transaction.
+    pub max_gas_limit: Option<U256>,
+    /// The destination chain's factor estimated gas limits are padded by.
+    pub gas_limit_multiplier: f64,
+    /// Drop messages which could not be delivered within this long of being
+    /// dispatched.
+    pub message_ttl: Option<MessageTtl>,
     pub metrics: MessageSubmissionMetrics,
 }
 
+/// How long after being dispatched a message may still be delivered.
+pub struct MessageTtl {
+    pub ttl: Duration,
+    /// Used to look up the timestamp of the block the message was dispatched
+    /// in.
+    pub origin_provider: Arc<dyn HyperlaneProvider>,
+}
+
 /// A message that the submitter can and should try to submit.
 #[derive(new)]
 pub struct PendingMessage {
@@ -60,6 +78,9 @@ pub struct PendingMessage {
     last_attempted_at: Instant,
     #[new(default)]
     next_attempt_after: Option<Instant>,
+    /// Unix timestamp of the block the message was dispatched in, once known.
+    #[new(default)]
+    dispatched_at: Option<u64>,
 }
 
 /// State for the next submission attempt generated by a prepare call.
@@ -113,6 +134,10 @@ impl PendingOperation for PendingMessage {
             return PendingOperationResult::NotReady;
         }
 
+        if self.is_expired().await {
+            return PendingOperationResult::Drop;
+        }
+
         // If the message has already been processed, e.g. due to another relayer having
         // already processed, then mark it as already-processed, and move on to
         // the next tick.
@@ -195,7 +220,7 @@ impl PendingOperation for PendingMessage {
             "Gas payment requirement met, ready to process message"
         );
 
-        let gas_limit = tx_cost_estimate.gas_limit;
+        let gas_limit = pad_gas_limit(tx_cost_estimate.gas_limit, self.ctx.gas_limit_multiplier);
 
         if let Some(max_limit) = self.ctx.transaction_gas_limit {
             if gas_limit > max_limit {
@@ -204,6 +229,18 @@ impl PendingOperation for PendingMessage {
             }
         }
 
+        if let Some(max_gas_limit) = self.ctx.max_gas_limit {
+            if gas_limit > max_gas_limit {
+                warn!(
+                    id = ?self.message.id(),
+                    ?gas_limit,
+                    ?max_gas_limit,
+                    "Skipping message, estimated gas exceeds the destination chain's max gas limit"
+                );
+                return self.on_reprepare();
+            }
+        }
+
         self.submission_data = Some(Box::new(SubmissionData {
             metadata,
             gas_limit,
@@ -238,23 +275,29 @@ impl PendingOperation for PendingMessage {
                 .await,
             "processing message"
         );
+        self.on_tx_outcome(tx_outcome)
+    }
 
-        op_try!(critical: self.ctx.origin_gas_payment_enforcer.record_tx_outcome(&self.message, tx_outcome), "recording tx outcome");
-        if tx_outcome.executed {
-            info!(
-                txid=?tx_outcome.transaction_id,
-                "Message successfully processed by transaction"
-            );
-            self.submitted = true;
-            self.reset_attempts();
-            self.next_attempt_after = Some(Instant::now() + CONFIRM_DELAY);
-            PendingOperationResult::Success
-        } else {
-            info!(
-                txid=?tx_outcome.transaction_id,
-                "Transaction attempting to process message reverted"
-            );
-            self.on_reprepare()
+    fn take_batch_item(&mut self) -> Option<BatchItem> {
+        if self.submitted {
+            return None;
+        }
+        let state = self
+            .submission_data
+            .take()
+            .expect("Pending message must be prepared before it can be submitted");
+        Some(BatchItem {
+            message: self.message.clone(),
+            metadata: state.metadata,
+            gas_limit: state.gas_limit,
+        })
+    }
+
+    #[instrument]
+    fn on_batch_submitted(&mut self, tx_outcome: Option<TxOutcome>) -> PendingOperationResult {
+        match tx_outcome {
+            Some(tx_outcome) => self.on_tx_outcome(tx_outcome),
+            None => self.on_reprepare(),
         }
     }
 
@@ -327,6 +370,29 @@ impl PendingMessage {
         pm
     }
 
+    /// Record the outcome of the transaction this message was processed in.
+    fn on_tx_outcome(&mut self, tx_outcome: TxOutcome) -> PendingOperationResult {
+        make_op_try!(|| self.on_reprepare());
+
+        op_try!(critical: self.ctx.origin_gas_payment_enforcer.record_tx_outcome(&self.message, tx_outcome), "recording tx outcome");
+        if tx_outcome.executed {
+            info!(
+                txid=?tx_outcome.transaction_id,
+                "Message successfully processed by transaction"
+            );
+            self.submitted = true;
+            self.reset_attempts();
+            self.next_attempt_after = Some(Instant::now() + CONFIRM_DELAY);
+            PendingOperationResult::Success
+        } else {
+            info!(
+                txid=?tx_outcome.transaction_id,
+                "Transaction attempting to process message reverted"
+            );
+            self.on_reprepare()
+        }
+    }
+
     fn on_reprepare(&mut self) -> PendingOperationResult {
         self.inc_attempts();
         self.submitted = false;
@@ -339,6 +405,58 @@ impl PendingMessage {
             .unwrap_or(true)
     }
 
+    /// Whether the message was dispatched longer than the message TTL ago.
+    /// Messages whose dispatch time can not be determined, e.g. because they
+    /// were indexed before the dispatch block hash was stored, never expire.
+    async fn is_expired(&mut self) -> bool {
+        let ctx = self.ctx.clone();
+        let Some(message_ttl) = &ctx.message_ttl else {
+            return false;
+        };
+        if self.dispatched_at.is_none() {
+            match self.fetch_dispatched_at(message_ttl).await {
+                Ok(dispatched_at) => self.dispatched_at = dispatched_at,
+                Err(err) => {
+                    debug!(?err, "Failed to look up when the message was dispatched");
+                    return false;
+                }
+            }
+        }
+        let Some(dispatched_at) = self.dispatched_at else {
+            return false;
+        };
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let age = Duration::from_secs(now.saturating_sub(dispatched_at));
+        if age <= message_ttl.ttl {
+            return false;
+        }
+        warn!(
+            id = ?self.message.id(),
+            ?age,
+            ttl = ?message_ttl.ttl,
+            "Dropping message because it was dispatched longer than the message TTL ago"
+        );
+        true
+    }
+
+    async fn fetch_dispatched_at(&self, message_ttl: &MessageTtl) -> Result<Option<u64>> {
+        let Some(block_hash) = self
+            .ctx
+            .origin_db
+            .retrieve_dispatched_block_hash_by_nonce(&self.message.nonce)?
+        else {
+            return Ok(None);
+        };
+        let block = message_ttl
+            .origin_provider
+            .get_block_by_hash(&block_hash)
+            .await?;
+        Ok(Some(block.timestamp))
+    }
+
     /// Record in HyperlaneDB and various metrics that this process has observed
     /// the successful processing of a message. An `Ok(())` value returned by
     /// this function is the 'commit' point in a message's lifetime for
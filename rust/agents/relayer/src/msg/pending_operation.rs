@@ -5,7 +5,7 @@ use async_trait::async_trait;
 use enum_dispatch::enum_dispatch;
 use eyre::Report;
 
-use hyperlane_core::HyperlaneDomain;
+use hyperlane_core::{BatchItem, HyperlaneDomain, TxOutcome};
 
 #[allow(unused_imports)] // required for enum_dispatch
 use super::pending_message::PendingMessage;
@@ -44,6 +44,16 @@ pub trait PendingOperation {
     /// or not.
     async fn submit(&mut self) -> PendingOperationResult;
 
+    /// Take what was prepared so this operation can be submitted together
+    /// with others in a single transaction. Returns `None` if it has to be
+    /// submitted on its own instead.
+    fn take_batch_item(&mut self) -> Option<BatchItem>;
+
+    /// Report the outcome of the batch this operation was submitted in, in
+    /// place of calling `submit`. The outcome is `None` if the batch
+    /// transaction failed.
+    fn on_batch_submitted(&mut self, tx_outcome: Option<TxOutcome>) -> PendingOperationResult;
+
     /// This will be called after the operation has been submitted and is
     /// responsible for checking if the operation has reached a point at
     /// which we consider it safe from reorgs.
@@ -212,7 +212,11 @@ mod test {
 
     use hyperlane_base::{
         db::{test_utils, HyperlaneRocksDB},
-        settings::{ChainConf, ChainConnectionConf, Settings},
+        settings::{
+            ChainConf, ChainConnectionConf, Settings, DEFAULT_BATCH_FLUSH_INTERVAL,
+            DEFAULT_MAX_BATCH_SIZE, DEFAULT_MAX_IN_FLIGHT_TRANSACTIONS,
+        },
+        DEFAULT_CHECKPOINT_FETCH_CONCURRENCY,
     };
     use hyperlane_test::mocks::{MockMailboxContract, MockValidatorAnnounceContract};
     use prometheus::{IntCounter, Registry};
@@ -251,12 +255,41 @@ mod test {
     fn dummy_chain_conf(domain: &HyperlaneDomain) -> ChainConf {
         ChainConf {
             domain: domain.clone(),
+            chain_id: None,
+            index_only: false,
             signer: Default::default(),
+            role_signers: Default::default(),
+            assume_instant_finality: Default::default(),
+            testnet: Default::default(),
+            finality: Default::default(),
             finality_blocks: Default::default(),
+            reorg_period: Default::default(),
+            block_time: Default::default(),
+            poll_interval: Default::default(),
+            max_batch_size: DEFAULT_MAX_BATCH_SIZE,
+            max_in_flight_transactions: DEFAULT_MAX_IN_FLIGHT_TRANSACTIONS,
+            batch_flush_interval: DEFAULT_BATCH_FLUSH_INTERVAL,
+            batch_strategy: Default::default(),
+            nonce_strategy: Default::default(),
+            tx_type: Default::default(),
+            confirmation_timeout: Default::default(),
+            gas_oracle: Default::default(),
+            max_gas_limit: Default::default(),
+            gas_limit_multiplier: 1.0,
+            native_token: Default::default(),
             addresses: Default::default(),
-            connection: ChainConnectionConf::Ethereum(hyperlane_ethereum::ConnectionConf::Http {
-                url: "http://example.com".parse().unwrap(),
-            }),
+            connection: ChainConnectionConf::Ethereum(
+                hyperlane_ethereum::ChainConnections::shared(
+                    hyperlane_ethereum::ConnectionConf::Http {
+                        url: "http://example.com".parse().unwrap(),
+                        headers: Default::default(),
+                        tls: None,
+                        request_timeout: None,
+                        max_connections: None,
+                        retry: Default::default(),
+                    },
+                ),
+            ),
             metrics_conf: Default::default(),
             index: Default::default(),
         }
@@ -271,12 +304,20 @@ mod test {
             .chains
             .insert(domain.name().to_owned(), dummy_chain_conf(domain));
         let destination_chain_conf = settings.chain_setup(domain).unwrap();
-        let core_metrics = CoreMetrics::new("dummy_relayer", 37582, Registry::new()).unwrap();
+        let core_metrics = CoreMetrics::new(
+            "dummy_relayer",
+            ([0, 0, 0, 0], 37582).into(),
+            Registry::new(),
+        )
+        .unwrap();
         BaseMetadataBuilder::new(
             destination_chain_conf.clone(),
             Arc::new(RwLock::new(MerkleTreeBuilder::new(db.clone()))),
             Arc::new(MockValidatorAnnounceContract::default()),
             false,
+            DEFAULT_CHECKPOINT_FETCH_CONCURRENCY,
+            true,
+            Default::default(),
             Arc::new(core_metrics),
             5,
         )
@@ -297,6 +338,9 @@ mod test {
             metadata_builder: base_metadata_builder,
             origin_gas_payment_enforcer: Arc::new(GasPaymentEnforcer::new([], db.clone())),
             transaction_gas_limit: Default::default(),
+            max_gas_limit: Default::default(),
+            gas_limit_multiplier: 1.0,
+            message_ttl: None,
             metrics: dummy_submission_metrics(),
         });
 
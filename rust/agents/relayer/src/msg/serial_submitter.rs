@@ -10,14 +10,14 @@ use prometheus::{IntCounter, IntGauge};
 use tokio::spawn;
 use tokio::sync::{
     mpsc::{self},
-    Mutex,
+    Mutex, Semaphore, SemaphorePermit,
 };
 use tokio::task::JoinHandle;
-use tokio::time::sleep;
-use tracing::{debug, info_span, instrument, instrument::Instrumented, trace, Instrument};
+use tokio::time::{sleep, timeout_at, Instant};
+use tracing::{debug, info_span, instrument, instrument::Instrumented, trace, warn, Instrument};
 
-use hyperlane_base::CoreMetrics;
-use hyperlane_core::HyperlaneDomain;
+use hyperlane_base::{settings::BatchStrategy, CoreMetrics};
+use hyperlane_core::{HyperlaneDomain, Mailbox, TxOutcome, U256};
 
 use super::pending_operation::*;
 
@@ -76,6 +76,19 @@ pub struct SerialSubmitter {
     domain: HyperlaneDomain,
     /// Receiver for new messages to submit.
     rx: mpsc::UnboundedReceiver<Box<DynPendingOperation>>,
+    /// Maximum number of prepared operations submitted in one batch. Larger
+    /// sets are split across multiple batches.
+    max_batch_size: u32,
+    /// Maximum number of submitted operations waiting for confirmation, no
+    /// more are submitted until one of them is confirmed.
+    max_in_flight_transactions: u32,
+    /// How long a partial batch waits for more prepared operations before it
+    /// is submitted anyway.
+    batch_flush_interval: Duration,
+    /// How operations are grouped into batches for the destination.
+    batch_strategy: BatchStrategy,
+    /// Mailbox on the destination, batches are processed through it.
+    mailbox: Arc<dyn Mailbox>,
     /// Metrics for serial submitter.
     metrics: SerialSubmitterMetrics,
 }
@@ -91,15 +104,35 @@ impl SerialSubmitter {
             domain,
             metrics,
             rx: rx_prepare,
+            max_batch_size,
+            max_in_flight_transactions,
+            batch_flush_interval,
+            batch_strategy,
+            mailbox,
         } = self;
+        let max_batch_size = max_batch_size.max(1) as usize;
+        let max_in_flight_transactions = max_in_flight_transactions.max(1) as usize;
+        // without multicall every operation goes out in its own transaction,
+        // so batches only hold a single operation. A batch can't hold more
+        // operations than may be in flight at once.
+        let (submit_batch_size, batch_mailbox) = match batch_strategy {
+            BatchStrategy::None => (1, None),
+            BatchStrategy::Multicall3(_) => (
+                max_batch_size.min(max_in_flight_transactions),
+                Some(mailbox),
+            ),
+        };
         let prepare_queue: OpQueue = Default::default();
         let confirm_queue: OpQueue = Default::default();
+        // one permit per operation which may be submitted while the previous
+        // ones have not been confirmed, reprepared or dropped yet
+        let in_flight = Arc::new(Semaphore::new(max_in_flight_transactions));
 
         // This is a channel because we want to only have a small number of messages
         // sitting ready to go at a time and this acts as a synchronization tool
         // to slow down the preparation of messages when the submitter gets
-        // behind.
-        let (tx_submit, rx_submit) = mpsc::channel(1);
+        // behind. It holds at most one batch.
+        let (tx_submit, rx_submit) = mpsc::channel(max_batch_size);
 
         let tasks = [
             spawn(receive_task(
@@ -118,12 +151,17 @@ impl SerialSubmitter {
                 rx_submit,
                 prepare_queue.clone(),
                 confirm_queue.clone(),
+                submit_batch_size,
+                batch_flush_interval,
+                batch_mailbox,
+                in_flight.clone(),
                 metrics.clone(),
             )),
             spawn(confirm_task(
                 domain.clone(),
                 prepare_queue,
                 confirm_queue,
+                in_flight,
                 metrics,
             )),
         ];
@@ -200,45 +238,127 @@ async fn prepare_task(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 #[instrument(skip_all, fields(%domain))]
 async fn submit_task(
     domain: HyperlaneDomain,
     mut rx_submit: mpsc::Receiver<Box<DynPendingOperation>>,
     prepare_queue: OpQueue,
     confirm_queue: OpQueue,
+    max_batch_size: usize,
+    batch_flush_interval: Duration,
+    batch_mailbox: Option<Arc<dyn Mailbox>>,
+    in_flight: Arc<Semaphore>,
     metrics: SerialSubmitterMetrics,
 ) -> Result<()> {
-    while let Some(mut op) = rx_submit.recv().await {
-        trace!(?op, "Submitting operation");
-        debug_assert_eq!(*op.domain(), domain);
+    let mut batch = Vec::with_capacity(max_batch_size);
+    while let Some(op) = rx_submit.recv().await {
+        // Wait for more operations until the batch is full or the flush
+        // interval has passed since the first one; the rest is left for the
+        // next batch.
+        let flush_at = Instant::now() + batch_flush_interval;
+        batch.push(op);
+        while batch.len() < max_batch_size {
+            let Ok(Some(op)) = timeout_at(flush_at, rx_submit.recv()).await else {
+                break;
+            };
+            batch.push(op);
+        }
+        trace!(batch_size = batch.len(), "Submitting batch");
 
-        match op.submit().await {
-            PendingOperationResult::Success => {
-                debug!(?op, "Operation submitted");
-                metrics.ops_submitted.inc();
-                confirm_queue.lock().await.push(Reverse(op));
-            }
-            PendingOperationResult::NotReady => {
-                panic!("Pending operation was prepared and therefore must be ready")
-            }
-            PendingOperationResult::Reprepare => {
-                metrics.ops_failed.inc();
-                prepare_queue.lock().await.push(Reverse(op));
+        // hold off until enough of the submitted transactions are confirmed
+        // to not flood the mempool
+        trace!("Waiting for in-flight transactions to confirm");
+        let mut permits = Vec::with_capacity(batch.len());
+        for _ in 0..batch.len() {
+            permits.push(in_flight.acquire().await?);
+        }
+
+        let mut batched = Vec::with_capacity(batch.len());
+        let mut items = Vec::with_capacity(batch.len());
+        for (mut op, permit) in batch.drain(..).zip(permits) {
+            debug_assert_eq!(*op.domain(), domain);
+            if batch_mailbox.is_some() {
+                if let Some(item) = op.take_batch_item() {
+                    items.push(item);
+                    batched.push((op, permit));
+                    continue;
+                }
             }
-            PendingOperationResult::Drop => {
-                metrics.ops_dropped.inc();
+            trace!(?op, "Submitting operation");
+            let result = op.submit().await;
+            on_submitted(op, result, permit, &prepare_queue, &confirm_queue, &metrics).await?;
+        }
+        let Some(mailbox) = batch_mailbox.as_ref().filter(|_| !batched.is_empty()) else {
+            continue;
+        };
+
+        trace!(
+            batch_size = items.len(),
+            "Submitting operations in one transaction"
+        );
+        let batch_result = match mailbox.process_batch(&items).await {
+            Ok(batch_result) => Some(batch_result),
+            Err(e) => {
+                warn!(error=?e, "Error when processing batch");
+                None
             }
-            PendingOperationResult::CriticalFailure(e) => return Err(e),
+        };
+        // the gas used by the batch is split evenly among its operations
+        let items_len = U256::from(items.len());
+        for (i, (mut op, permit)) in batched.into_iter().enumerate() {
+            let tx_outcome = batch_result.as_ref().map(|batch_result| TxOutcome {
+                executed: !batch_result.failed_indexes.contains(&i),
+                gas_used: batch_result.outcome.gas_used / items_len,
+                ..batch_result.outcome
+            });
+            let result = op.on_batch_submitted(tx_outcome);
+            on_submitted(op, result, permit, &prepare_queue, &confirm_queue, &metrics).await?;
         }
     }
     bail!("Internal submitter channel was closed");
 }
 
+/// Move a submitted operation on to the queue for the result of its
+/// submission.
+async fn on_submitted(
+    op: Box<DynPendingOperation>,
+    result: PendingOperationResult,
+    permit: SemaphorePermit<'_>,
+    prepare_queue: &OpQueue,
+    confirm_queue: &OpQueue,
+    metrics: &SerialSubmitterMetrics,
+) -> Result<()> {
+    match result {
+        PendingOperationResult::Success => {
+            debug!(?op, "Operation submitted");
+            metrics.ops_submitted.inc();
+            // the permit is given back by the confirm task once the
+            // operation leaves the confirm queue
+            permit.forget();
+            confirm_queue.lock().await.push(Reverse(op));
+        }
+        PendingOperationResult::NotReady => {
+            panic!("Pending operation was prepared and therefore must be ready")
+        }
+        PendingOperationResult::Reprepare => {
+            metrics.ops_failed.inc();
+            prepare_queue.lock().await.push(Reverse(op));
+        }
+        PendingOperationResult::Drop => {
+            metrics.ops_dropped.inc();
+        }
+        PendingOperationResult::CriticalFailure(e) => return Err(e),
+    }
+    Ok(())
+}
+
 #[instrument(skip_all, fields(%domain))]
 async fn confirm_task(
     domain: HyperlaneDomain,
     prepare_queue: OpQueue,
     confirm_queue: OpQueue,
+    in_flight: Arc<Semaphore>,
     metrics: SerialSubmitterMetrics,
 ) -> Result<()> {
     loop {
@@ -259,6 +379,7 @@ async fn confirm_task(
             PendingOperationResult::Success => {
                 debug!(?op, "Operation confirmed");
                 metrics.ops_confirmed.inc();
+                in_flight.add_permits(1);
             }
             PendingOperationResult::NotReady => {
                 // none of the operations are ready yet, so wait for a little bit
@@ -267,10 +388,12 @@ async fn confirm_task(
             }
             PendingOperationResult::Reprepare => {
                 metrics.ops_reorged.inc();
+                in_flight.add_permits(1);
                 prepare_queue.lock().await.push(Reverse(op));
             }
             PendingOperationResult::Drop => {
                 metrics.ops_dropped.inc();
+                in_flight.add_permits(1);
             }
             PendingOperationResult::CriticalFailure(e) => return Err(e),
         }
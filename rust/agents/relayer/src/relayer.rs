@@ -9,10 +9,18 @@ use derive_more::AsRef;
 use eyre::Result;
 use hyperlane_base::{
     db::{HyperlaneRocksDB, DB},
-    run_all, BaseAgent, ContractSyncMetrics, CoreMetrics, HyperlaneAgentCore, MessageContractSync,
+    run_all,
+    settings::{
+        BatchStrategy, DEFAULT_BATCH_FLUSH_INTERVAL, DEFAULT_MAX_BATCH_SIZE,
+        DEFAULT_MAX_IN_FLIGHT_TRANSACTIONS,
+    },
+    BaseAgent, ContractSyncMetrics, CoreMetrics, HyperlaneAgentCore, MessageContractSync,
     WatermarkContractSync,
 };
-use hyperlane_core::{HyperlaneDomain, InterchainGasPayment, U256};
+use hyperlane_core::{
+    HyperlaneDomain, HyperlaneDomainProtocol, HyperlaneProvider, InterchainGasPayment, Mailbox,
+    U256,
+};
 use tokio::{
     sync::{
         mpsc::{self, UnboundedReceiver, UnboundedSender},
@@ -20,14 +28,14 @@ use tokio::{
     },
     task::JoinHandle,
 };
-use tracing::{info, info_span, instrument::Instrumented, Instrument};
+use tracing::{info, info_span, instrument::Instrumented, warn, Instrument};
 
 use crate::{
     merkle_tree_builder::MerkleTreeBuilder,
     msg::{
         gas_payment::GasPaymentEnforcer,
         metadata::BaseMetadataBuilder,
-        pending_message::{MessageContext, MessageSubmissionMetrics},
+        pending_message::{MessageContext, MessageSubmissionMetrics, MessageTtl},
         pending_operation::DynPendingOperation,
         processor::{MessageProcessor, MessageProcessorMetrics},
         serial_submitter::{SerialSubmitter, SerialSubmitterMetrics},
@@ -54,6 +62,7 @@ pub struct Relayer {
     /// Context data for each (origin, destination) chain pair a message can be
     /// sent between
     msg_ctxs: HashMap<ContextKey, Arc<MessageContext>>,
+    destination_mailboxes: HashMap<HyperlaneDomain, Arc<dyn Mailbox>>,
     prover_syncs: HashMap<HyperlaneDomain, Arc<RwLock<MerkleTreeBuilder>>>,
     dbs: HashMap<HyperlaneDomain, HyperlaneRocksDB>,
     whitelist: Arc<MatchingList>,
@@ -90,6 +99,15 @@ impl BaseAgent for Relayer {
     where
         Self: Sized,
     {
+        info!(config = %settings.to_effective_json(), "Loaded relayer settings");
+        if !settings.verify_signatures {
+            for origin in &settings.origin_chains {
+                warn!(
+                    %origin,
+                    "Checkpoint signature schemes are not verified, this is unsafe and only meant for validators whose checkpoints declare the wrong scheme"
+                );
+            }
+        }
         let core = settings.build_hyperlane_core(metrics.clone());
         let db = DB::from_path(&settings.db)?;
         let dbs = settings
@@ -105,6 +123,25 @@ impl BaseAgent for Relayer {
             .build_validator_announces(settings.origin_chains.iter(), &metrics)
             .await?;
 
+        // expiring messages needs the timestamp of the block they were
+        // dispatched in, which can only be looked up on ethereum chains
+        let mut origin_providers: HashMap<HyperlaneDomain, Arc<dyn HyperlaneProvider>> =
+            HashMap::new();
+        if let Some(message_ttl) = settings.message_ttl {
+            for origin in &settings.origin_chains {
+                if origin.domain_protocol() == HyperlaneDomainProtocol::Ethereum {
+                    let provider = settings.build_provider(origin, &metrics).await?;
+                    origin_providers.insert(origin.clone(), provider.into());
+                } else {
+                    warn!(
+                        %origin,
+                        ?message_ttl,
+                        "Message TTL is not supported for this origin, its messages will not expire"
+                    );
+                }
+            }
+        }
+
         let contract_sync_metrics = Arc::new(ContractSyncMetrics::new(&metrics));
 
         let message_syncs = settings
@@ -173,6 +210,7 @@ impl BaseAgent for Relayer {
             .collect();
 
         let mut msg_ctxs = HashMap::new();
+        let validator_syncers = Arc::new(settings.validator_syncers.clone());
         for destination in &settings.destination_chains {
             let destination_chain_setup = core.settings.chain_setup(destination).unwrap().clone();
 
@@ -189,6 +227,9 @@ impl BaseAgent for Relayer {
                     prover_syncs[origin].clone(),
                     validator_announces[origin].clone(),
                     settings.allow_local_checkpoint_syncers,
+                    settings.checkpoint_fetch_concurrency,
+                    settings.verify_signatures,
+                    validator_syncers.clone(),
                     core.metrics.clone(),
                     5,
                 );
@@ -204,6 +245,14 @@ impl BaseAgent for Relayer {
                         metadata_builder,
                         origin_gas_payment_enforcer: gas_payment_enforcers[origin].clone(),
                         transaction_gas_limit,
+                        max_gas_limit: destination_chain_setup.max_gas_limit,
+                        gas_limit_multiplier: destination_chain_setup.gas_limit_multiplier,
+                        message_ttl: settings.message_ttl.zip(origin_providers.get(origin)).map(
+                            |(ttl, origin_provider)| MessageTtl {
+                                ttl,
+                                origin_provider: origin_provider.clone(),
+                            },
+                        ),
                         metrics: MessageSubmissionMetrics::new(&metrics, origin, destination),
                     }),
                 );
@@ -215,6 +264,7 @@ impl BaseAgent for Relayer {
             origin_chains: settings.origin_chains,
             destination_chains: settings.destination_chains,
             msg_ctxs,
+            destination_mailboxes: mailboxes,
             core,
             message_syncs,
             interchain_gas_payment_syncs,
@@ -260,12 +310,13 @@ impl Relayer {
         &self,
         origin: &HyperlaneDomain,
     ) -> Instrumented<JoinHandle<eyre::Result<()>>> {
-        let index_settings = self.as_ref().settings.chains[origin.name()].index_settings();
+        let index_settings =
+            self.as_ref().settings.chains[origin.name()].contract_index_settings("mailbox");
         let contract_sync = self.message_syncs.get(origin).unwrap().clone();
-        let cursor = contract_sync
-            .forward_backward_message_sync_cursor(index_settings)
-            .await;
         tokio::spawn(async move {
+            let cursor = contract_sync
+                .forward_backward_message_sync_cursor(index_settings)
+                .await?;
             contract_sync
                 .clone()
                 .sync("dispatched_messages", cursor)
@@ -278,15 +329,18 @@ impl Relayer {
         &self,
         origin: &HyperlaneDomain,
     ) -> Instrumented<JoinHandle<eyre::Result<()>>> {
-        let index_settings = self.as_ref().settings.chains[origin.name()].index_settings();
+        let index_settings = self.as_ref().settings.chains[origin.name()]
+            .contract_index_settings("interchain_gas_paymaster");
         let contract_sync = self
             .interchain_gas_payment_syncs
             .get(origin)
             .unwrap()
             .clone();
-        let cursor = contract_sync.rate_limited_cursor(index_settings).await;
-        tokio::spawn(async move { contract_sync.clone().sync("gas_payments", cursor).await })
-            .instrument(info_span!("ContractSync"))
+        tokio::spawn(async move {
+            let cursor = contract_sync.rate_limited_cursor(index_settings).await?;
+            contract_sync.clone().sync("gas_payments", cursor).await
+        })
+        .instrument(info_span!("ContractSync"))
     }
 
     fn run_message_processor(
@@ -341,9 +395,32 @@ impl Relayer {
         destination: &HyperlaneDomain,
         receiver: UnboundedReceiver<Box<DynPendingOperation>>,
     ) -> Instrumented<JoinHandle<Result<()>>> {
+        let (max_batch_size, max_in_flight_transactions, batch_flush_interval, batch_strategy) =
+            self.core
+                .settings
+                .chain_setup(destination)
+                .map(|setup| {
+                    (
+                        setup.max_batch_size,
+                        setup.max_in_flight_transactions,
+                        setup.batch_flush_interval,
+                        setup.batch_strategy,
+                    )
+                })
+                .unwrap_or((
+                    DEFAULT_MAX_BATCH_SIZE,
+                    DEFAULT_MAX_IN_FLIGHT_TRANSACTIONS,
+                    DEFAULT_BATCH_FLUSH_INTERVAL,
+                    BatchStrategy::None,
+                ));
         let serial_submitter = SerialSubmitter::new(
             destination.clone(),
             receiver,
+            max_batch_size,
+            max_in_flight_transactions,
+            batch_flush_interval,
+            batch_strategy,
+            self.destination_mailboxes[destination].clone(),
             SerialSubmitterMetrics::new(&self.core.metrics, destination),
         );
         let span = info_span!("SerialSubmitter", destination=%destination);
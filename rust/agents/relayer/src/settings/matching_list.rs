@@ -209,6 +209,20 @@ impl<'a> From<&'a HyperlaneMessage> for MatchInfo<'a> {
 }
 
 impl MatchingList {
+    /// Match messages to any of the given destination domains. Without any
+    /// domains there is no list, so the default applies.
+    pub fn with_destination_domains(domains: Vec<u32>) -> Self {
+        if domains.is_empty() {
+            return Self(None);
+        }
+        Self(Some(vec![ListElement {
+            origin_domain: Filter::Wildcard,
+            sender_address: Filter::Wildcard,
+            destination_domain: Filter::Enumerated(domains),
+            recipient_address: Filter::Wildcard,
+        }]))
+    }
+
     /// Check if a message matches any of the rules.
     /// - `default`: What to return if the the matching list is empty.
     pub fn msg_matches(&self, msg: &HyperlaneMessage, default: bool) -> bool {
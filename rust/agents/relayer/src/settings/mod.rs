@@ -4,19 +4,25 @@
 //! and validations it defines are not applied here, we should mirror them.
 //! ANY CHANGES HERE NEED TO BE REFLECTED IN THE TYPESCRIPT SDK.
 
-use std::{collections::HashSet, path::PathBuf};
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    str::FromStr,
+    time::Duration,
+};
 
 use derive_more::{AsMut, AsRef, Deref, DerefMut};
 use eyre::{eyre, Context};
 use hyperlane_base::{
     impl_loadable_from_settings,
     settings::{
-        deprecated_parser::DeprecatedRawSettings,
+        deprecated_parser::{DeprecatedRawCheckpointSyncerConf, DeprecatedRawSettings},
         parser::{RawAgentConf, ValueParser},
-        Settings,
+        CheckpointSyncerConf, Settings, SignerRole,
     },
+    DEFAULT_CHECKPOINT_FETCH_CONCURRENCY,
 };
-use hyperlane_core::{cfg_unwrap_all, config::*, HyperlaneDomain, U256};
+use hyperlane_core::{cfg_unwrap_all, config::*, HyperlaneDomain, H160, U256};
 use itertools::Itertools;
 use serde::Deserialize;
 use serde_json::Value;
@@ -171,6 +177,20 @@ pub struct RelayerSettings {
     /// If true, allows local storage based checkpoint syncers.
     /// Not intended for production use.
     pub allow_local_checkpoint_syncers: bool,
+    /// The maximum number of validator checkpoint syncers to read from at
+    /// once when building multisig metadata.
+    pub checkpoint_fetch_concurrency: usize,
+    /// Only use fetched checkpoints which were signed with the signature
+    /// scheme they declare. Checkpoints must be signed by the validator whose
+    /// syncer they were read from either way. Disabling this is unsafe, the
+    /// scheme of a checkpoint is no longer checked against the signature.
+    pub verify_signatures: bool,
+    /// Messages dispatched longer than this ago are dropped instead of being
+    /// retried. Messages never expire if not set.
+    pub message_ttl: Option<Duration>,
+    /// Checkpoint syncers to read the checkpoints of these validators from
+    /// instead of their announced storage locations.
+    pub validator_syncers: HashMap<H160, CheckpointSyncerConf>,
 }
 
 #[derive(Debug, Deserialize, AsMut)]
@@ -189,8 +209,10 @@ pub struct DeprecatedRawRelayerSettings {
     // Comma separated list of destination chains.
     #[deprecated(note = "Use `relaychains` instead")]
     destinationchainnames: Option<String>,
-    /// The gas payment enforcement configuration as JSON. Expects an ordered array of `GasPaymentEnforcementConfig`.
-    gaspaymentenforcement: Option<String>,
+    /// The gas payment enforcement configuration as JSON. Expects an ordered array of `GasPaymentEnforcementConfig`,
+    /// either as is or stringified.
+    #[serde(alias = "gasPaymentEnforcement")]
+    gaspaymentenforcement: Option<Value>,
     /// This is optional. If no whitelist is provided ALL messages will be considered on the
     /// whitelist.
     whitelist: Option<String>,
@@ -207,6 +229,22 @@ pub struct DeprecatedRawRelayerSettings {
     /// Not intended for production use. Defaults to false.
     #[serde(default)]
     allowlocalcheckpointsyncers: bool,
+    /// The maximum number of validator checkpoint syncers to read from at
+    /// once. Must be at least 1, defaults to 4.
+    #[serde(alias = "checkpointFetchConcurrency")]
+    checkpointfetchconcurrency: Option<StrOrInt>,
+    /// Reject fetched checkpoints which were not signed with the scheme they
+    /// declare, defaults to true. Unsafe to disable.
+    #[serde(alias = "verifySignatures")]
+    verifysignatures: Option<bool>,
+    /// How long after being dispatched messages are dropped instead of being
+    /// retried, e.g. `24h`. Integers are seconds.
+    #[serde(alias = "messageTtl")]
+    messagettl: Option<StrOrInt>,
+    /// A map of validator addresses to the checkpoint syncer their
+    /// checkpoints are read from, either as is or stringified.
+    #[serde(alias = "validatorSyncers")]
+    validatorsyncers: Option<Value>,
 }
 
 impl_loadable_from_settings!(Relayer, DeprecatedRawRelayerSettings -> RelayerSettings);
@@ -245,87 +283,24 @@ impl FromRawConf<RawRelayerSettings> for RelayerSettings {
             .parse_from_str("Expected database path")
             .unwrap_or_else(|| std::env::current_dir().unwrap().join("hyperlane_db"));
 
-        let (raw_gas_payment_enforcement_path, raw_gas_payment_enforcement) = match p
+        let gas_payment_enforcement = p
+            .chain(&mut err)
             .get_opt_key("gasPaymentEnforcement")
-            .take_config_err_flat(&mut err)
-        {
-            None => None,
-            Some(ValueParser {
-                val: Value::String(policy_str),
-                cwp,
-            }) => serde_json::from_str::<Value>(policy_str)
-                .context("Expected JSON string")
-                .take_err(&mut err, || cwp.clone())
-                .map(|v| (cwp, v)),
-            Some(ValueParser {
-                val: value @ Value::Array(_),
-                cwp,
-            }) => Some((cwp, value.clone())),
-            Some(_) => Err(eyre!("Expected JSON array or stringified JSON"))
-                .take_err(&mut err, || cwp.clone()),
-        }
-        .unwrap_or_else(|| (&p.cwp + "gas_payment_enforcement", Value::Array(vec![])));
-
-        let gas_payment_enforcement_parser = ValueParser::new(
-            raw_gas_payment_enforcement_path,
-            &raw_gas_payment_enforcement,
-        );
-        let gas_payment_enforcement = gas_payment_enforcement_parser.into_array_iter().map(|itr| {
-            itr.filter_map(|policy| {
-                let policy_type = policy.chain(&mut err).get_opt_key("type").parse_string().end();
-                let minimum_is_defined = matches!(policy.get_opt_key("minimum"), Ok(Some(_)));
-
-                let matching_list = policy.chain(&mut err).get_opt_key("matchingList").and_then(parse_matching_list).unwrap_or_default();
-
-                let parse_minimum = |p| GasPaymentEnforcementPolicy::Minimum { payment: p };
-                match policy_type {
-                    Some("minimum") => policy.chain(&mut err).get_opt_key("payment").parse_u256().end().map(parse_minimum),
-                    None if minimum_is_defined => policy.chain(&mut err).get_opt_key("payment").parse_u256().end().map(parse_minimum),
-                    Some("none") | None => Some(GasPaymentEnforcementPolicy::None),
-                    Some("onChainFeeQuoting") => {
-                        let gas_fraction = policy.chain(&mut err)
-                            .get_opt_key("gasFraction")
-                            .parse_string()
-                            .map(|v| v.replace(' ', ""))
-                            .unwrap_or_else(|| default_gasfraction().to_owned());
-                        let (numerator, denominator) = gas_fraction
-                            .split_once('/')
-                            .ok_or_else(|| eyre!("Invalid `gas_fraction` for OnChainFeeQuoting gas payment enforcement policy; expected `numerator / denominator`"))
-                            .take_err(&mut err, || &policy.cwp + "gas_fraction")
-                            .unwrap_or(("1", "1"));
-
-                        Some(GasPaymentEnforcementPolicy::OnChainFeeQuoting {
-                            gas_fraction_numerator: numerator
-                                .parse()
-                                .context("Error parsing gas fraction numerator")
-                                .take_err(&mut err, || &policy.cwp + "gas_fraction")
-                                .unwrap_or(1),
-                            gas_fraction_denominator: denominator
-                                .parse()
-                                .context("Error parsing gas fraction denominator")
-                                .take_err(&mut err, || &policy.cwp + "gas_fraction")
-                                .unwrap_or(1),
-                        })
-                    }
-                    Some(pt) => Err(eyre!("Unknown gas payment enforcement policy type `{pt}`"))
-                        .take_err(&mut err, || cwp + "type"),
-                }.map(|policy| GasPaymentEnforcementConf {
-                    policy,
-                    matching_list,
-                })
-            }).collect_vec()
-        }).unwrap_or_default();
+            .and_then(parse_gas_payment_enforcement)
+            .unwrap_or_default();
 
-        let whitelist = p
+        let (whitelist, whitelist_domains) = p
             .chain(&mut err)
             .get_opt_key("whitelist")
-            .and_then(parse_matching_list)
+            .and_then(parse_relay_list)
             .unwrap_or_default();
-        let blacklist = p
+        let (blacklist, blacklist_domains) = p
             .chain(&mut err)
             .get_opt_key("blacklist")
-            .and_then(parse_matching_list)
+            .and_then(parse_relay_list)
             .unwrap_or_default();
+        check_disjoint_domains(&whitelist_domains, &blacklist_domains)
+            .take_err(&mut err, || cwp + "blacklist");
 
         let transaction_gas_limit = p
             .chain(&mut err)
@@ -346,6 +321,35 @@ impl FromRawConf<RawRelayerSettings> for RelayerSettings {
             .parse_bool()
             .unwrap_or(false);
 
+        let checkpoint_fetch_concurrency = p
+            .chain(&mut err)
+            .get_opt_key("checkpointFetchConcurrency")
+            .parse_u64()
+            .and_then(|c| {
+                parse_checkpoint_fetch_concurrency(c)
+                    .into_config_result(|| cwp + "checkpoint_fetch_concurrency")
+            })
+            .unwrap_or(DEFAULT_CHECKPOINT_FETCH_CONCURRENCY);
+
+        let verify_signatures = p
+            .chain(&mut err)
+            .get_opt_key("verifySignatures")
+            .parse_bool()
+            .unwrap_or(true);
+
+        let message_ttl = p
+            .chain(&mut err)
+            .get_opt_key("messageTtl")
+            .parse_value::<StrOrInt>("Expected message TTL duration")
+            .and_then(|ttl| parse_message_ttl(&ttl).into_config_result(|| cwp + "message_ttl"))
+            .end();
+
+        let validator_syncers = p
+            .chain(&mut err)
+            .get_opt_key("validatorSyncers")
+            .and_then(parse_validator_syncers)
+            .unwrap_or_default();
+
         cfg_unwrap_all!(cwp, err: [base]);
 
         let skip_transaction_gas_limit_for = skip_transaction_gas_limit_for_names
@@ -370,21 +374,188 @@ impl FromRawConf<RawRelayerSettings> for RelayerSettings {
             })
             .collect();
 
+        // relay chains are also destinations, which index only chains can not be
+        for domain in &relay_chains {
+            if base
+                .chain_setup(domain)
+                .map_or(false, |chain| chain.index_only)
+            {
+                err.push(
+                    cwp + "relayChains",
+                    eyre!(
+                        "Index only chain `{}` can not be a relay chain since it would be a \
+                         destination",
+                        domain.name()
+                    ),
+                );
+            }
+        }
+        let destination_chains = relay_chains.clone();
+
         err.into_result(RelayerSettings {
             base,
             db,
-            origin_chains: relay_chains.clone(),
-            destination_chains: relay_chains,
+            origin_chains: relay_chains,
+            destination_chains,
             gas_payment_enforcement,
             whitelist,
             blacklist,
             transaction_gas_limit,
             skip_transaction_gas_limit_for,
             allow_local_checkpoint_syncers,
+            checkpoint_fetch_concurrency,
+            verify_signatures,
+            message_ttl,
+            validator_syncers,
         })
     }
 }
 
+/// Expects a map of validator addresses to checkpoint syncers, either as an
+/// object or stringified JSON. Every validator may only be configured once.
+fn parse_validator_syncers(p: ValueParser) -> ConfigResult<HashMap<H160, CheckpointSyncerConf>> {
+    let mut err = ConfigParsingError::default();
+
+    let raw_syncers = match &p {
+        ValueParser {
+            val: Value::String(syncers_str),
+            cwp,
+        } => serde_json::from_str::<Value>(syncers_str)
+            .context("Expected JSON string")
+            .take_err(&mut err, || cwp.clone()),
+        ValueParser {
+            val: value @ Value::Object(_),
+            ..
+        } => Some((*value).clone()),
+        _ => Err(eyre!("Expected JSON object or stringified JSON"))
+            .take_err(&mut err, || p.cwp.clone()),
+    };
+    let Some(Value::Object(raw_syncers)) = raw_syncers else {
+        if err.is_ok() {
+            err.push(p.cwp.clone(), eyre!("Expected a JSON object"));
+        }
+        return Err(err);
+    };
+
+    let mut syncers = HashMap::new();
+    for (validator, raw_syncer) in raw_syncers.iter().sorted_by_key(|(k, _)| *k) {
+        // the key is only part of the path once it is known to be an address
+        let Some(address) = H160::from_str(validator.trim())
+            .with_context(|| format!("Invalid validator address `{validator}`"))
+            .take_err(&mut err, || p.cwp.clone())
+        else {
+            continue;
+        };
+        let cwp = &p.cwp + format!("{address:?}");
+        let syncer =
+            serde_json::from_value::<DeprecatedRawCheckpointSyncerConf>(raw_syncer.clone())
+                .context("Expected a checkpoint syncer")
+                .take_err(&mut err, || cwp.clone())
+                .and_then(|raw| {
+                    raw.parse_config::<CheckpointSyncerConf>(&cwp)
+                        .take_config_err(&mut err)
+                });
+        if syncers.contains_key(&address) {
+            err.push(
+                cwp,
+                eyre!("Validator `{address:?}` has more than one checkpoint syncer"),
+            );
+        } else if let Some(syncer) = syncer {
+            syncers.insert(address, syncer);
+        }
+    }
+    err.into_result(syncers)
+}
+
+fn parse_checkpoint_fetch_concurrency(concurrency: u64) -> eyre::Result<usize> {
+    if concurrency == 0 {
+        return Err(eyre!("Checkpoint fetch concurrency must be at least 1"));
+    }
+    Ok(concurrency as usize)
+}
+
+/// Parse the message TTL, where integers are a number of seconds.
+fn parse_message_ttl(ttl: &StrOrInt) -> eyre::Result<Duration> {
+    let ttl = Duration::try_from(ttl).context("Invalid `messageTtl`, expected a duration")?;
+    if ttl.is_zero() {
+        return Err(eyre!("Invalid `messageTtl`, must be greater than 0"));
+    }
+    Ok(ttl)
+}
+
+/// Parse an ordered list of gas payment enforcement policies, given as a JSON
+/// array or stringified JSON. Messages are held to the first policy whose
+/// matching list they match.
+fn parse_gas_payment_enforcement(p: ValueParser) -> ConfigResult<Vec<GasPaymentEnforcementConf>> {
+    let mut err = ConfigParsingError::default();
+
+    let raw_policies = match &p {
+        ValueParser {
+            val: Value::String(policy_str),
+            cwp,
+        } => serde_json::from_str::<Value>(policy_str)
+            .context("Expected JSON string")
+            .take_err(&mut err, || cwp.clone()),
+        ValueParser {
+            val: value @ Value::Array(_),
+            ..
+        } => Some((*value).clone()),
+        _ => Err(eyre!("Expected JSON array or stringified JSON"))
+            .take_err(&mut err, || p.cwp.clone()),
+    };
+    let Some(raw_policies) = raw_policies else {
+        return err.into_result(Vec::new());
+    };
+
+    let policies = ValueParser::new(p.cwp.clone(), &raw_policies).into_array_iter().map(|itr| {
+        itr.filter_map(|policy| {
+            let policy_type = policy.chain(&mut err).get_opt_key("type").parse_string().end();
+            let minimum_is_defined = matches!(policy.get_opt_key("minimum"), Ok(Some(_)));
+
+            let matching_list = policy.chain(&mut err).get_opt_key("matchingList").and_then(parse_matching_list).unwrap_or_default();
+
+            let parse_minimum = |p| GasPaymentEnforcementPolicy::Minimum { payment: p };
+            match policy_type {
+                Some("minimum") => policy.chain(&mut err).get_key("payment").parse_u256().end().map(parse_minimum),
+                None if minimum_is_defined => policy.chain(&mut err).get_opt_key("payment").parse_u256().end().map(parse_minimum),
+                Some("none") | None => Some(GasPaymentEnforcementPolicy::None),
+                Some("onChainFeeQuoting") => {
+                    let gas_fraction = policy.chain(&mut err)
+                        .get_opt_key("gasFraction")
+                        .parse_string()
+                        .map(|v| v.replace(' ', ""))
+                        .unwrap_or_else(|| default_gasfraction().to_owned());
+                    let (numerator, denominator) = gas_fraction
+                        .split_once('/')
+                        .ok_or_else(|| eyre!("Invalid `gas_fraction` for OnChainFeeQuoting gas payment enforcement policy; expected `numerator / denominator`"))
+                        .take_err(&mut err, || &policy.cwp + "gas_fraction")
+                        .unwrap_or(("1", "1"));
+
+                    Some(GasPaymentEnforcementPolicy::OnChainFeeQuoting {
+                        gas_fraction_numerator: numerator
+                            .parse()
+                            .context("Error parsing gas fraction numerator")
+                            .take_err(&mut err, || &policy.cwp + "gas_fraction")
+                            .unwrap_or(1),
+                        gas_fraction_denominator: denominator
+                            .parse()
+                            .context("Error parsing gas fraction denominator")
+                            .take_err(&mut err, || &policy.cwp + "gas_fraction")
+                            .unwrap_or(1),
+                    })
+                }
+                Some(pt) => Err(eyre!("Unknown gas payment enforcement policy type `{pt}`"))
+                    .take_err(&mut err, || &policy.cwp + "type"),
+            }.map(|policy| GasPaymentEnforcementConf {
+                policy,
+                matching_list,
+            })
+        }).collect_vec()
+    }).unwrap_or_default();
+
+    err.into_result(policies)
+}
+
 fn parse_matching_list(p: ValueParser) -> ConfigResult<MatchingList> {
     let mut err = ConfigParsingError::default();
 
@@ -414,6 +585,50 @@ fn parse_matching_list(p: ValueParser) -> ConfigResult<MatchingList> {
     err.into_result(ml)
 }
 
+/// Parse a whitelist or blacklist, which is either a matching list or a plain
+/// list of destination domain ids such as `[1, "137"]`. The domain ids of a
+/// plain list are returned as well so the two lists can be compared.
+fn parse_relay_list(p: ValueParser) -> ConfigResult<(MatchingList, Vec<u32>)> {
+    let list = match p.val {
+        Value::String(s) => serde_json::from_str::<Value>(s).ok(),
+        v => Some(v.clone()),
+    };
+    match list {
+        Some(Value::Array(items)) if items.iter().all(|i| !i.is_object()) => {
+            let mut err = ConfigParsingError::default();
+            let domains = items
+                .iter()
+                .enumerate()
+                .filter_map(|(i, item)| {
+                    ValueParser::new(p.cwp.join(i.to_string()), item)
+                        .parse_u32()
+                        .take_config_err(&mut err)
+                })
+                .collect_vec();
+            err.into_result((
+                MatchingList::with_destination_domains(domains.clone()),
+                domains,
+            ))
+        }
+        _ => parse_matching_list(p).map(|list| (list, Vec::new())),
+    }
+}
+
+/// A domain can not be both whitelisted and blacklisted.
+fn check_disjoint_domains(whitelist: &[u32], blacklist: &[u32]) -> eyre::Result<()> {
+    let both = whitelist
+        .iter()
+        .filter(|domain| blacklist.contains(domain))
+        .unique()
+        .join(", ");
+    if !both.is_empty() {
+        return Err(eyre!(
+            "Domains {both} are in both the whitelist and the blacklist"
+        ));
+    }
+    Ok(())
+}
+
 impl FromRawConf<DeprecatedRawRelayerSettings> for RelayerSettings {
     fn from_config_filtered(
         raw: DeprecatedRawRelayerSettings,
@@ -425,8 +640,13 @@ impl FromRawConf<DeprecatedRawRelayerSettings> for RelayerSettings {
         let gas_payment_enforcement = raw
             .gaspaymentenforcement
             .and_then(|j| {
-                serde_json::from_str::<Vec<RawGasPaymentEnforcementConf>>(&j)
-                    .take_err(&mut err, || cwp + "gaspaymentenforcement")
+                match j {
+                    Value::String(s) => {
+                        serde_json::from_str::<Vec<RawGasPaymentEnforcementConf>>(&s)
+                    }
+                    v => serde_json::from_value::<Vec<RawGasPaymentEnforcementConf>>(v),
+                }
+                .take_err(&mut err, || cwp + "gaspaymentenforcement")
             })
             .map(|rv| {
                 let cwp = cwp + "gaspaymentenforcement";
@@ -440,19 +660,23 @@ impl FromRawConf<DeprecatedRawRelayerSettings> for RelayerSettings {
             })
             .unwrap_or_else(|| vec![Default::default()]);
 
-        let whitelist = raw
+        let (whitelist, whitelist_domains) = raw
             .whitelist
             .and_then(|j| {
-                serde_json::from_str::<MatchingList>(&j).take_err(&mut err, || cwp + "whitelist")
+                parse_relay_list(ValueParser::new(cwp + "whitelist", &Value::String(j)))
+                    .take_config_err(&mut err)
             })
             .unwrap_or_default();
 
-        let blacklist = raw
+        let (blacklist, blacklist_domains) = raw
             .blacklist
             .and_then(|j| {
-                serde_json::from_str::<MatchingList>(&j).take_err(&mut err, || cwp + "blacklist")
+                parse_relay_list(ValueParser::new(cwp + "blacklist", &Value::String(j)))
+                    .take_config_err(&mut err)
             })
             .unwrap_or_default();
+        check_disjoint_domains(&whitelist_domains, &blacklist_domains)
+            .take_err(&mut err, || cwp + "blacklist");
 
         let transaction_gas_limit = raw.transactiongaslimit.and_then(|r| {
             r.try_into()
@@ -537,6 +761,28 @@ impl FromRawConf<DeprecatedRawRelayerSettings> for RelayerSettings {
             );
         }
 
+        let checkpoint_fetch_concurrency = raw
+            .checkpointfetchconcurrency
+            .and_then(|r| {
+                u64::try_from(r)
+                    .map_err(Into::into)
+                    .and_then(parse_checkpoint_fetch_concurrency)
+                    .take_err(&mut err, || cwp + "checkpointfetchconcurrency")
+            })
+            .unwrap_or(DEFAULT_CHECKPOINT_FETCH_CONCURRENCY);
+
+        let message_ttl = raw
+            .messagettl
+            .and_then(|ttl| parse_message_ttl(&ttl).take_err(&mut err, || cwp + "messagettl"));
+
+        let validator_syncers = raw
+            .validatorsyncers
+            .and_then(|syncers| {
+                parse_validator_syncers(ValueParser::new(cwp + "validatorsyncers", &syncers))
+                    .take_config_err(&mut err)
+            })
+            .unwrap_or_default();
+
         let db = raw
             .db
             .and_then(|r| r.parse().take_err(&mut err, || cwp + "db"))
@@ -555,7 +801,11 @@ impl FromRawConf<DeprecatedRawRelayerSettings> for RelayerSettings {
         let base = raw
             .base
             .parse_config_with_filter::<Settings>(cwp, Some(&chain_filter))
-            .take_config_err(&mut err);
+            .take_config_err(&mut err)
+            .map(|mut base| {
+                base.use_signer_role(SignerRole::Relay);
+                base
+            });
 
         let origin_chains = base
             .as_ref()
@@ -588,8 +838,15 @@ impl FromRawConf<DeprecatedRawRelayerSettings> for RelayerSettings {
 
         if let Some(base) = &base {
             for domain in &destination_chains {
-                base.chain_setup(domain)
-                    .unwrap()
+                let chain = base.chain_setup(domain).unwrap();
+                if chain.index_only {
+                    err.push(
+                        cwp + "chains" + domain.name() + "index_only",
+                        eyre!("Index only chains can not be destination chains"),
+                    );
+                    continue;
+                }
+                chain
                     .signer
                     .as_ref()
                     .ok_or_else(|| eyre!("Signer is required for destination chains"))
@@ -609,6 +866,10 @@ impl FromRawConf<DeprecatedRawRelayerSettings> for RelayerSettings {
             transaction_gas_limit,
             skip_transaction_gas_limit_for,
             allow_local_checkpoint_syncers: raw.allowlocalcheckpointsyncers,
+            checkpoint_fetch_concurrency,
+            verify_signatures: raw.verifysignatures.unwrap_or(true),
+            message_ttl,
+            validator_syncers,
         })
     }
 }
@@ -620,3 +881,152 @@ fn default_gasfraction() -> String {
 fn parse_chains(chains_str: String) -> Vec<String> {
     chains_str.split(',').map(str::to_ascii_lowercase).collect()
 }
+
+#[cfg(test)]
+mod test {
+    use hyperlane_core::HyperlaneMessage;
+
+    use super::*;
+
+    #[test]
+    fn parses_message_ttl() {
+        assert_eq!(
+            parse_message_ttl(&StrOrInt::Str("24h".into())).unwrap(),
+            Duration::from_secs(24 * 3600)
+        );
+        assert_eq!(
+            parse_message_ttl(&StrOrInt::Int(90)).unwrap(),
+            Duration::from_secs(90)
+        );
+        assert!(parse_message_ttl(&StrOrInt::Int(0)).is_err());
+        assert!(parse_message_ttl(&StrOrInt::Str("a while".into())).is_err());
+    }
+
+    #[test]
+    fn parses_destination_domain_lists() {
+        let parse = |list: Value| {
+            parse_relay_list(ValueParser::new(ConfigPath::default() + "whitelist", &list))
+        };
+
+        let (list, domains) = parse(serde_json::json!([1, "137"])).unwrap();
+        assert_eq!(domains, vec![1, 137]);
+        let msg = |destination| HyperlaneMessage {
+            destination,
+            ..Default::default()
+        };
+        assert!(list.msg_matches(&msg(137), false));
+        assert!(!list.msg_matches(&msg(5), false));
+
+        // an empty list is no list at all
+        let (list, _) = parse(serde_json::json!([])).unwrap();
+        assert!(list.msg_matches(&msg(5), true));
+
+        // matching lists are still supported
+        let (_, domains) = parse(serde_json::json!([{ "destinationDomain": 1 }])).unwrap();
+        assert!(domains.is_empty());
+
+        let err = parse(serde_json::json!([1, "mainnet"]))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("config_path: `whitelist.1`"));
+
+        assert!(check_disjoint_domains(&[1, 2], &[3]).is_ok());
+        assert!(check_disjoint_domains(&[1, 2], &[2, 3]).is_err());
+    }
+
+    #[test]
+    fn parses_gas_payment_enforcement() {
+        let parse = |policies: Value| {
+            parse_gas_payment_enforcement(ValueParser::new(
+                ConfigPath::default() + "gasPaymentEnforcement",
+                &policies,
+            ))
+        };
+
+        let policies = parse(serde_json::json!([
+            {
+                "type": "minimum",
+                "payment": "100",
+                "matchingList": [{ "destinationDomain": 1 }],
+            },
+            { "type": "none" },
+        ]))
+        .unwrap();
+        assert_eq!(policies.len(), 2);
+        assert!(matches!(
+            policies[0].policy,
+            GasPaymentEnforcementPolicy::Minimum { payment } if payment == U256::from(100)
+        ));
+        assert!(matches!(
+            policies[1].policy,
+            GasPaymentEnforcementPolicy::None
+        ));
+
+        // stringified JSON as it is given through env vars
+        let policies = parse(Value::String(r#"[{ "type": "none" }]"#.into())).unwrap();
+        assert_eq!(policies.len(), 1);
+
+        let err = parse(serde_json::json!([{ "type": "none" }, { "type": "maximum" }]))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("config_path: `gasPaymentEnforcement.1.type`"));
+        let err = parse(serde_json::json!([{ "type": "minimum" }]))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("config_path: `gasPaymentEnforcement.0.payment`"));
+    }
+
+    #[test]
+    fn parses_validator_syncers() {
+        let parse = |syncers: Value| {
+            parse_validator_syncers(ValueParser::new(
+                ConfigPath::default() + "validatorsyncers",
+                &syncers,
+            ))
+        };
+        let validator = "0x4c327ccb881a7542be77500b2833dc84c839e7b7";
+
+        let syncers = parse(serde_json::json!({
+            validator: { "type": "localStorage", "path": "/tmp/checkpoints" },
+            "0x13dfdeb827d4d7face707fadbfd4d651438b4ab3": {
+                "type": "s3",
+                "bucket": "checkpoints",
+                "region": "us-east-1",
+            },
+        }))
+        .unwrap();
+        assert_eq!(syncers.len(), 2);
+        assert!(matches!(
+            syncers[&H160::from_str(validator).unwrap()],
+            CheckpointSyncerConf::LocalStorage { .. }
+        ));
+
+        // stringified JSON as it is given through env vars
+        let syncers = parse(Value::String(format!(
+            r#"{{ "{validator}": {{ "type": "localStorage", "path": "/tmp" }} }}"#
+        )))
+        .unwrap();
+        assert_eq!(syncers.len(), 1);
+
+        let err = parse(serde_json::json!({
+            "0xnotanaddress": { "type": "localStorage", "path": "/tmp" },
+        }))
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("Invalid validator address"));
+
+        let err = parse(serde_json::json!({
+            validator: { "type": "localStorage", "path": "/tmp" },
+            "0x4C327CCB881A7542BE77500B2833DC84C839E7B7": { "type": "localStorage", "path": "/tmp" },
+        }))
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("more than one checkpoint syncer"));
+
+        let err = parse(serde_json::json!({ validator: { "type": "s3" } }))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("config_path: `validatorSyncers."));
+        assert!(err.contains("bucket`"));
+    }
+}
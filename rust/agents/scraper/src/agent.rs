@@ -3,12 +3,13 @@ use std::{collections::HashMap, sync::Arc};
 use async_trait::async_trait;
 use derive_more::AsRef;
 use hyperlane_base::{
-    run_all, settings::IndexSettings, BaseAgent, ContractSyncMetrics, CoreMetrics,
-    HyperlaneAgentCore,
+    run_all,
+    settings::{IndexFrom, IndexSettings},
+    BaseAgent, ContractSyncMetrics, CoreMetrics, HyperlaneAgentCore,
 };
 use hyperlane_core::HyperlaneDomain;
 use tokio::task::JoinHandle;
-use tracing::{info_span, instrument::Instrumented, trace, Instrument};
+use tracing::{info, info_span, instrument::Instrumented, trace, Instrument};
 
 use crate::{chain_scraper::HyperlaneSqlDb, db::ScraperDb, settings::ScraperSettings};
 
@@ -42,6 +43,7 @@ impl BaseAgent for Scraper {
     where
         Self: Sized,
     {
+        info!(config = %settings.to_effective_json(), "Loaded scraper settings");
         let db = ScraperDb::connect(&settings.db).await?;
         let core = settings.build_hyperlane_core(metrics.clone());
 
@@ -50,6 +52,13 @@ impl BaseAgent for Scraper {
 
         for domain in settings.chains_to_scrape.iter() {
             let chain_setup = settings.chain_setup(domain).expect("Missing chain config");
+            let index_from = match chain_setup.index.from {
+                IndexFrom::Height(height) => height,
+                from => {
+                    let indexer = chain_setup.build_message_indexer(&metrics).await?;
+                    from.resolve(indexer.as_ref()).await?
+                }
+            };
             let db = HyperlaneSqlDb::new(
                 db.clone(),
                 chain_setup.addresses.mailbox,
@@ -58,7 +67,7 @@ impl BaseAgent for Scraper {
                     .build_provider(domain, &metrics.clone())
                     .await?
                     .into(),
-                &chain_setup.index.clone(),
+                index_from,
             )
             .await?;
             scrapers.insert(
@@ -157,10 +166,10 @@ macro_rules! spawn_sync_task {
                 )
                 .await
                 .unwrap();
-            let cursor = sync
-                .$cursor(index_settings.clone())
-                .await;
                 tokio::spawn(async move {
+                    let cursor = sync
+                        .$cursor(index_settings)
+                        .await?;
                     sync
                         .sync($label, cursor)
                         .await
@@ -198,12 +207,13 @@ impl Scraper {
             .await
             .unwrap_or(None)
             .unwrap_or(0);
-        let cursor = sync
-            .forward_message_sync_cursor(index_settings.clone(), latest_nonce.saturating_sub(1))
-            .await;
-        tokio::spawn(async move { sync.sync("message_dispatch", cursor).await }).instrument(
-            info_span!("ChainContractSync", chain=%domain.name(), event="message_dispatch"),
-        )
+        tokio::spawn(async move {
+            let cursor = sync
+                .forward_message_sync_cursor(index_settings, latest_nonce.saturating_sub(1))
+                .await?;
+            sync.sync("message_dispatch", cursor).await
+        })
+        .instrument(info_span!("ChainContractSync", chain=%domain.name(), event="message_dispatch"))
     }
 
     spawn_sync_task!(
@@ -5,7 +5,6 @@ use std::{collections::HashMap, sync::Arc};
 
 use async_trait::async_trait;
 use eyre::Result;
-use hyperlane_base::settings::IndexSettings;
 use hyperlane_core::{
     BlockInfo, Delivery, HyperlaneDomain, HyperlaneLogStore, HyperlaneMessage,
     HyperlaneMessageStore, HyperlaneProvider, HyperlaneWatermarkedLogStore, InterchainGasPayment,
@@ -43,12 +42,9 @@ impl HyperlaneSqlDb {
         mailbox_address: H256,
         domain: HyperlaneDomain,
         provider: Arc<dyn HyperlaneProvider>,
-        index_settings: &IndexSettings,
+        index_from: u32,
     ) -> Result<Self> {
-        let cursor = Arc::new(
-            db.block_cursor(domain.id(), index_settings.from as u64)
-                .await?,
-        );
+        let cursor = Arc::new(db.block_cursor(domain.id(), index_from as u64).await?);
         Ok(Self {
             db,
             domain,
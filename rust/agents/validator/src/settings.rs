@@ -12,15 +12,19 @@ use hyperlane_base::{
     impl_loadable_from_settings,
     settings::{
         deprecated_parser::{
-            DeprecatedRawCheckpointSyncerConf, DeprecatedRawSettings, DeprecatedRawSignerConf,
+            DeprecatedRawCheckpointSyncers, DeprecatedRawSettings, DeprecatedRawSignerConf,
         },
+        parse_aws_profile,
         parser::{RawAgentConf, RawAgentSignerConf, ValueParser},
-        CheckpointSyncerConf, Settings, SignerConf,
+        CheckpointSyncerConf, Settings, SignerConf, SignerRole,
     },
 };
-use hyperlane_core::{cfg_unwrap_all, config::*, HyperlaneDomain, HyperlaneDomainProtocol};
+use hyperlane_core::{
+    cfg_unwrap_all, config::*, HyperlaneDomain, HyperlaneDomainProtocol, SignatureScheme,
+};
 use serde::Deserialize;
 use serde_json::Value;
+use tracing::warn;
 
 /// Settings for `Validator`
 #[derive(Debug, AsRef, AsMut, Deref, DerefMut)]
@@ -37,12 +41,66 @@ pub struct ValidatorSettings {
     pub origin_chain: HyperlaneDomain,
     /// The validator attestation signer
     pub validator: SignerConf,
+    /// How checkpoints are signed by the validator signer. Relayers only
+    /// deliver `Eip191` checkpoints, which is what multisig ISMs verify.
+    pub signature_scheme: SignatureScheme,
     /// The checkpoint syncer configuration
     pub checkpoint_syncer: CheckpointSyncerConf,
     /// The reorg_period in blocks
     pub reorg_period: u64,
     /// How frequently to check for new checkpoints
     pub interval: Duration,
+    /// How announcing the checkpoint storage location is retried
+    pub announcement_retry: AnnouncementRetryConf,
+}
+
+/// How announcing the checkpoint storage location on-chain is retried until
+/// the announcement is observed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AnnouncementRetryConf {
+    /// Number of times the announcement is checked and, if it is missing,
+    /// submitted before giving up. Retries forever if unset.
+    pub max_attempts: Option<u32>,
+    /// Wait between two attempts, defaults to the checkpoint `interval`
+    pub backoff: Option<Duration>,
+}
+
+impl ValidatorSettings {
+    /// Try to construct every configured signer and the checkpoint syncer
+    /// without starting the validator, reporting all of the failures at once.
+    pub async fn validate(&self) -> ConfigResult<()> {
+        let mut err = match self.base.validate().await {
+            Ok(()) => ConfigParsingError::default(),
+            Err(e) => e,
+        };
+        let cwp = ConfigPath::default();
+        self.validator
+            .build::<hyperlane_ethereum::Signers>()
+            .await
+            .take_err(&mut err, || &cwp + "validator");
+        self.checkpoint_syncer
+            .build(None)
+            .take_err(&mut err, || &cwp + "checkpointsyncer");
+        err.into_result(())
+    }
+
+    /// The fully merged configuration as JSON with secrets redacted, see
+    /// [`Settings::to_effective_json`].
+    pub fn to_effective_json(&self) -> serde_json::Value {
+        let mut json = self.base.to_effective_json();
+        json["db"] = self.db.display().to_string().into();
+        json["originChainName"] = self.origin_chain.name().into();
+        json["validator"] = self.validator.to_redacted_json();
+        json["signatureScheme"] = format!("{:?}", self.signature_scheme).into();
+        json["checkpointSyncer"] = self.checkpoint_syncer.to_redacted_json();
+        json["reorgPeriod"] = self.reorg_period.into();
+        json["interval"] = format!("{:?}", self.interval).into();
+        json["announcementRetry"] = serde_json::json!({
+            "maxAttempts": self.announcement_retry.max_attempts,
+            "backoff": self.announcement_retry.backoff.map(|b| format!("{b:?}")),
+        });
+        json
+    }
 }
 
 /// Raw settings for `Validator`
@@ -58,13 +116,34 @@ pub struct DeprecatedRawValidatorSettings {
     originchainname: Option<String>,
     /// The validator attestation signer
     #[serde(default)]
-    validator: DeprecatedRawSignerConf,
-    /// The checkpoint syncer configuration
-    checkpointsyncer: Option<DeprecatedRawCheckpointSyncerConf>,
+    validator: DeprecatedRawValidatorSignerConf,
+    /// The checkpoint syncer configuration, or a list of them which are all
+    /// written to
+    checkpointsyncer: Option<DeprecatedRawCheckpointSyncers>,
     /// The reorg_period in blocks
     reorgperiod: Option<StrOrInt>,
     /// How frequently to check for new checkpoints
     interval: Option<StrOrInt>,
+    /// How announcing the checkpoint storage location is retried
+    #[serde(alias = "announcementRetry")]
+    announcementretry: Option<DeprecatedRawAnnouncementRetryConf>,
+}
+
+/// Raw announcement retry policy
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DeprecatedRawAnnouncementRetryConf {
+    max_attempts: Option<StrOrInt>,
+    backoff_ms: Option<StrOrInt>,
+}
+
+/// Raw validator signer, which also selects how checkpoints are signed
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct DeprecatedRawValidatorSignerConf {
+    #[serde(flatten)]
+    signer: DeprecatedRawSignerConf,
+    signature_scheme: Option<String>,
 }
 
 impl_loadable_from_settings!(Validator, DeprecatedRawValidatorSettings -> ValidatorSettings);
@@ -115,6 +194,14 @@ impl FromRawConf<RawValidatorSettings> for ValidatorSettings {
             )
             .end();
 
+        let signature_scheme = p
+            .chain(&mut err)
+            .get_opt_key("validator")
+            .get_opt_key("signatureScheme")
+            .parse_from_str("Expected a signature scheme of `ecdsa` or `eip191`")
+            .unwrap_or_default();
+        warn_unrelayable_signature_scheme(signature_scheme);
+
         let db = p
             .chain(&mut err)
             .get_opt_key("db")
@@ -128,7 +215,7 @@ impl FromRawConf<RawValidatorSettings> for ValidatorSettings {
         let checkpoint_syncer = p
             .chain(&mut err)
             .get_key("checkpointSyncer")
-            .and_then(parse_checkpoint_syncer)
+            .and_then(|syncer| parse_checkpoint_syncers(syncer, origin_chain_name))
             .end();
 
         let interval = p
@@ -138,6 +225,26 @@ impl FromRawConf<RawValidatorSettings> for ValidatorSettings {
             .map(Duration::from_secs)
             .unwrap_or(Duration::from_secs(5));
 
+        let announcement_retry = AnnouncementRetryConf {
+            max_attempts: p
+                .chain(&mut err)
+                .get_opt_key("announcementRetry")
+                .get_opt_key("maxAttempts")
+                .parse_u32()
+                .and_then(|a| {
+                    parse_max_attempts(a)
+                        .into_config_result(|| cwp + "announcement_retry" + "max_attempts")
+                })
+                .end(),
+            backoff: p
+                .chain(&mut err)
+                .get_opt_key("announcementRetry")
+                .get_opt_key("backoffMs")
+                .parse_u64()
+                .map(Duration::from_millis)
+                .end(),
+        };
+
         cfg_unwrap_all!(cwp, err: [origin_chain_name]);
 
         let reorg_period = p
@@ -156,14 +263,62 @@ impl FromRawConf<RawValidatorSettings> for ValidatorSettings {
             db,
             origin_chain,
             validator,
+            signature_scheme,
             checkpoint_syncer,
             reorg_period,
             interval,
+            announcement_retry,
         })
     }
 }
 
-/// Expects ValidatorAgentConfig.checkpointSyncer
+/// Multisig ISMs only verify EIP-191 signatures, so relayers skip checkpoints
+/// signed with any other scheme.
+fn warn_unrelayable_signature_scheme(signature_scheme: SignatureScheme) {
+    if signature_scheme != SignatureScheme::Eip191 {
+        warn!(
+            ?signature_scheme,
+            "Checkpoints signed with this scheme can not be relayed to multisig ISMs, which only verify `eip191` signatures"
+        );
+    }
+}
+
+/// The announcement has to be attempted at least once.
+fn parse_max_attempts(max_attempts: u32) -> eyre::Result<u32> {
+    if max_attempts == 0 {
+        return Err(eyre!("Expected `maxAttempts` to be at least 1"));
+    }
+    Ok(max_attempts)
+}
+
+/// Expects ValidatorAgentConfig.checkpointSyncer, which is either a single
+/// checkpoint syncer or a list of them
+fn parse_checkpoint_syncers(
+    syncer: ValueParser,
+    chain: Option<&str>,
+) -> ConfigResult<CheckpointSyncerConf> {
+    let parse = |syncer: ValueParser| {
+        let folder_path = &syncer.cwp + "folder";
+        parse_checkpoint_syncer(syncer)?
+            .with_chain_name(chain)
+            .into_config_result(|| folder_path)
+    };
+    if !syncer.val.is_array() {
+        return parse(syncer);
+    }
+    let mut err = ConfigParsingError::default();
+    let cwp = syncer.cwp.clone();
+    let syncers = syncer
+        .into_array_iter()?
+        .filter_map(|s| parse(s).take_config_err(&mut err))
+        .collect::<Vec<_>>();
+    if syncers.is_empty() && err.is_ok() {
+        err.push(cwp, eyre!("Expected at least one checkpoint syncer"));
+    }
+    err.into_result(CheckpointSyncerConf::Mirrored(syncers))
+}
+
+/// Expects a single checkpoint syncer
 fn parse_checkpoint_syncer(syncer: ValueParser) -> ConfigResult<CheckpointSyncerConf> {
     let mut err = ConfigParsingError::default();
     let syncer_type = syncer.chain(&mut err).get_key("type").parse_string().end();
@@ -175,8 +330,23 @@ fn parse_checkpoint_syncer(syncer: ValueParser) -> ConfigResult<CheckpointSyncer
                 .get_key("path")
                 .parse_from_str("Expected checkpoint syncer file path")
                 .end();
+            let create_if_missing = syncer
+                .chain(&mut err)
+                .get_opt_key("createIfMissing")
+                .parse_bool()
+                .unwrap_or(true);
+            let retain = syncer
+                .chain(&mut err)
+                .get_opt_key("retain")
+                .parse_u32()
+                .end()
+                .filter(|&r| r > 0);
             cfg_unwrap_all!(&syncer.cwp, err: [path]);
-            err.into_result(CheckpointSyncerConf::LocalStorage { path })
+            err.into_result(CheckpointSyncerConf::LocalStorage {
+                path,
+                create_if_missing,
+                retain,
+            })
         }
         Some("s3") => {
             let bucket = syncer
@@ -185,23 +355,151 @@ fn parse_checkpoint_syncer(syncer: ValueParser) -> ConfigResult<CheckpointSyncer
                 .parse_string()
                 .end()
                 .map(str::to_owned);
+            let endpoint = syncer
+                .chain(&mut err)
+                .get_opt_key("endpoint")
+                .parse_string()
+                .and_then(|e| {
+                    CheckpointSyncerConf::parse_s3_endpoint(e)
+                        .into_config_result(|| &syncer.cwp + "endpoint")
+                })
+                .end();
+            let force_path_style = syncer
+                .chain(&mut err)
+                .get_opt_key("forcePathStyle")
+                .parse_bool()
+                .unwrap_or(false);
             let region = syncer
                 .chain(&mut err)
-                .get_key("region")
-                .parse_from_str("Expected aws region")
+                .get_opt_key("region")
+                .parse_string()
                 .end();
+            let region = CheckpointSyncerConf::s3_region(region, endpoint.as_ref())
+                .take_err(&mut err, || &syncer.cwp + "region");
             let folder = syncer
                 .chain(&mut err)
                 .get_opt_key("folder")
                 .parse_string()
                 .end()
                 .map(str::to_owned);
+            let sse_kms_key_id = syncer
+                .chain(&mut err)
+                .get_opt_key("sseKmsKeyId")
+                .parse_string()
+                .and_then(|k| {
+                    CheckpointSyncerConf::parse_s3_sse_kms_key_id(k)
+                        .into_config_result(|| &syncer.cwp + "sse_kms_key_id")
+                })
+                .end();
+            let profile = syncer
+                .chain(&mut err)
+                .get_opt_key("profile")
+                .parse_string()
+                .and_then(|p| parse_aws_profile(p).into_config_result(|| &syncer.cwp + "profile"))
+                .end();
 
             cfg_unwrap_all!(&syncer.cwp, err: [bucket, region]);
             err.into_result(CheckpointSyncerConf::S3 {
                 bucket,
                 region,
                 folder,
+                endpoint,
+                force_path_style,
+                sse_kms_key_id,
+                profile,
+            })
+        }
+        Some("gcs") => {
+            let bucket = syncer
+                .chain(&mut err)
+                .get_key("bucket")
+                .parse_string()
+                .end()
+                .map(str::to_owned);
+            let folder = syncer
+                .chain(&mut err)
+                .get_opt_key("folder")
+                .parse_string()
+                .end()
+                .map(str::to_owned);
+            let key = syncer
+                .chain(&mut err)
+                .get_opt_key("serviceAccountKey")
+                .parse_string()
+                .and_then(|key| {
+                    CheckpointSyncerConf::parse_gcs_service_account_key(key)
+                        .into_config_result(|| &syncer.cwp + "service_account_key")
+                })
+                .end();
+            let key_base64 = syncer
+                .chain(&mut err)
+                .get_opt_key("serviceAccountKeyBase64")
+                .parse_string()
+                .and_then(|encoded| {
+                    CheckpointSyncerConf::decode_gcs_service_account_key(encoded)
+                        .into_config_result(|| &syncer.cwp + "service_account_key_base64")
+                })
+                .end();
+            let service_account_key = match (key, key_base64) {
+                (Some(_), Some(_)) => {
+                    err.push(
+                        &syncer.cwp + "service_account_key_base64",
+                        eyre!(
+                            "Only one of `serviceAccountKey` and `serviceAccountKeyBase64` may be \
+                             set"
+                        ),
+                    );
+                    None
+                }
+                (key, key_base64) => key.or(key_base64),
+            };
+
+            cfg_unwrap_all!(&syncer.cwp, err: [bucket]);
+            err.into_result(CheckpointSyncerConf::Gcs {
+                bucket,
+                folder,
+                service_account_key,
+            })
+        }
+        Some("ipfs") => {
+            let api_url = syncer
+                .chain(&mut err)
+                .get_key("apiUrl")
+                .parse_from_str("Expected IPFS API url")
+                .end();
+            let gateway_url = syncer
+                .chain(&mut err)
+                .get_opt_key("gatewayUrl")
+                .parse_from_str("Expected IPFS gateway url")
+                .end();
+            let pin_service_token = syncer
+                .chain(&mut err)
+                .get_opt_key("pinServiceToken")
+                .parse_string()
+                .end()
+                .map(str::to_owned);
+            let ipns_name = syncer
+                .chain(&mut err)
+                .get_opt_key("ipnsName")
+                .parse_string()
+                .and_then(|n| {
+                    CheckpointSyncerConf::parse_ipns_name(n)
+                        .into_config_result(|| &syncer.cwp + "ipns_name")
+                })
+                .end();
+            if ipns_name.is_some() && gateway_url.is_none() {
+                err.push(
+                    &syncer.cwp + "gateway_url",
+                    eyre!("Missing public `gatewayUrl` to resolve the `ipnsName` through"),
+                );
+            }
+
+            cfg_unwrap_all!(&syncer.cwp, err: [api_url]);
+            err.into_result(CheckpointSyncerConf::Ipfs {
+                api_url,
+                gateway_url,
+                pin_service_token,
+                ipns_name,
             })
         }
         Some(_) => {
@@ -221,17 +519,20 @@ impl FromRawConf<DeprecatedRawValidatorSettings> for ValidatorSettings {
 
         let validator = raw
             .validator
+            .signer
             .parse_config::<SignerConf>(&cwp.join("validator"))
             .take_config_err(&mut err);
 
-        let checkpoint_syncer = raw
-            .checkpointsyncer
-            .ok_or_else(|| eyre!("Missing `checkpointsyncer`"))
-            .take_err(&mut err, || cwp + "checkpointsyncer")
-            .and_then(|r| {
-                r.parse_config(&cwp.join("checkpointsyncer"))
-                    .take_config_err(&mut err)
-            });
+        let signature_scheme = raw
+            .validator
+            .signature_scheme
+            .and_then(|s| {
+                s.parse::<SignatureScheme>()
+                    .context("Expected a signature scheme of `ecdsa` or `eip191`")
+                    .take_err(&mut err, || cwp + "validator" + "signature_scheme")
+            })
+            .unwrap_or_default();
+        warn_unrelayable_signature_scheme(signature_scheme);
 
         let reorg_period = raw
             .reorgperiod
@@ -248,6 +549,29 @@ impl FromRawConf<DeprecatedRawValidatorSettings> for ValidatorSettings {
             })
             .unwrap_or(Duration::from_secs(5));
 
+        let announcement_retry = raw
+            .announcementretry
+            .map(|r| {
+                let cwp = cwp + "announcementretry";
+                let max_attempts = r.max_attempts.and_then(|a| {
+                    u32::try_from(&a)
+                        .context("Invalid `maxAttempts`, expected integer")
+                        .and_then(parse_max_attempts)
+                        .take_err(&mut err, || &cwp + "max_attempts")
+                });
+                let backoff = r.backoff_ms.and_then(|b| {
+                    u64::try_from(&b)
+                        .map(Duration::from_millis)
+                        .context("Invalid `backoffMs`, expected integer")
+                        .take_err(&mut err, || &cwp + "backoff_ms")
+                });
+                AnnouncementRetryConf {
+                    max_attempts,
+                    backoff,
+                }
+            })
+            .unwrap_or_default();
+
         let Some(origin_chain_name) = raw
             .originchainname
             .ok_or_else(|| eyre!("Missing `originchainname`"))
@@ -255,6 +579,19 @@ impl FromRawConf<DeprecatedRawValidatorSettings> for ValidatorSettings {
             .map(|s| s.to_ascii_lowercase())
         else { return Err(err) };
 
+        let checkpoint_syncer = raw
+            .checkpointsyncer
+            .ok_or_else(|| eyre!("Missing `checkpointsyncer`"))
+            .take_err(&mut err, || cwp + "checkpointsyncer")
+            .and_then(|r| {
+                r.parse_config::<CheckpointSyncerConf>(&cwp.join("checkpointsyncer"))
+                    .take_config_err(&mut err)
+            })
+            .and_then(|c| {
+                c.with_chain_name(Some(&origin_chain_name))
+                    .take_err(&mut err, || cwp + "checkpointsyncer" + "folder")
+            });
+
         let db = raw
             .db
             .and_then(|r| r.parse().take_err(&mut err, || cwp + "db"))
@@ -280,6 +617,7 @@ impl FromRawConf<DeprecatedRawValidatorSettings> for ValidatorSettings {
 
         cfg_unwrap_all!(cwp, err: [base, origin_chain, validator, checkpoint_syncer, reorg_period]);
         let mut base = base;
+        base.use_signer_role(SignerRole::Validate);
 
         if origin_chain.domain_protocol() == HyperlaneDomainProtocol::Ethereum {
             // if an EVM chain we can assume the chain signer is the validator signer when not
@@ -294,9 +632,11 @@ impl FromRawConf<DeprecatedRawValidatorSettings> for ValidatorSettings {
             db,
             origin_chain,
             validator,
+            signature_scheme,
             checkpoint_syncer,
             reorg_period,
             interval,
+            announcement_retry,
         })
     }
 }
@@ -13,6 +13,7 @@ use hyperlane_base::{db::HyperlaneRocksDB, CheckpointSyncer, CoreMetrics};
 use hyperlane_core::{
     accumulator::incremental::IncrementalMerkle, Checkpoint, CheckpointWithMessageId,
     HyperlaneChain, HyperlaneContract, HyperlaneDomain, HyperlaneSignerExt, Mailbox,
+    SignatureScheme,
 };
 use hyperlane_ethereum::SingletonSignerHandle;
 
@@ -21,6 +22,7 @@ pub(crate) struct ValidatorSubmitter {
     interval: Duration,
     reorg_period: Option<NonZeroU64>,
     signer: SingletonSignerHandle,
+    signature_scheme: SignatureScheme,
     mailbox: Arc<dyn Mailbox>,
     checkpoint_syncer: Arc<dyn CheckpointSyncer>,
     message_db: HyperlaneRocksDB,
@@ -33,6 +35,7 @@ impl ValidatorSubmitter {
         reorg_period: u64,
         mailbox: Arc<dyn Mailbox>,
         signer: SingletonSignerHandle,
+        signature_scheme: SignatureScheme,
         checkpoint_syncer: Arc<dyn CheckpointSyncer>,
         message_db: HyperlaneRocksDB,
         metrics: ValidatorSubmitterMetrics,
@@ -42,6 +45,7 @@ impl ValidatorSubmitter {
             interval,
             mailbox,
             signer,
+            signature_scheme,
             checkpoint_syncer,
             message_db,
             metrics,
@@ -113,7 +117,10 @@ impl ValidatorSubmitter {
                             continue;
                         }
 
-                        let signed_checkpoint = self.signer.sign(queued_checkpoint).await?;
+                        let signed_checkpoint = self
+                            .signer
+                            .sign_with_scheme(queued_checkpoint, self.signature_scheme)
+                            .await?;
                         self.checkpoint_syncer
                             .write_checkpoint(&signed_checkpoint)
                             .await?;
@@ -207,7 +214,10 @@ impl ValidatorSubmitter {
                 .map(|i| i < latest_checkpoint.index)
                 .unwrap_or(true)
             {
-                let signed_checkpoint = self.signer.sign(latest_checkpoint).await?;
+                let signed_checkpoint = self
+                    .signer
+                    .sign_with_scheme(latest_checkpoint, self.signature_scheme)
+                    .await?;
 
                 info!(signed_checkpoint = ?signed_checkpoint, signer=?self.signer, "Signed new latest checkpoint");
                 current_index = Some(latest_checkpoint.index);
@@ -2,7 +2,7 @@ use std::{num::NonZeroU64, sync::Arc, time::Duration};
 
 use async_trait::async_trait;
 use derive_more::AsRef;
-use eyre::Result;
+use eyre::{bail, Result};
 use hyperlane_base::{
     db::{HyperlaneRocksDB, DB},
     run_all, BaseAgent, CheckpointSyncer, ContractSyncMetrics, CoreMetrics, HyperlaneAgentCore,
@@ -10,15 +10,15 @@ use hyperlane_base::{
 };
 use hyperlane_core::{
     accumulator::incremental::IncrementalMerkle, Announcement, ChainResult, HyperlaneChain,
-    HyperlaneContract, HyperlaneDomain, HyperlaneSigner, HyperlaneSignerExt, Mailbox, TxOutcome,
-    ValidatorAnnounce, H256, U256,
+    HyperlaneContract, HyperlaneDomain, HyperlaneSigner, HyperlaneSignerExt, Mailbox,
+    SignatureScheme, TxOutcome, ValidatorAnnounce, H256, U256,
 };
 use hyperlane_ethereum::{SingletonSigner, SingletonSignerHandle};
 use tokio::{task::JoinHandle, time::sleep};
 use tracing::{error, info, info_span, instrument::Instrumented, warn, Instrument};
 
 use crate::{
-    settings::ValidatorSettings,
+    settings::{AnnouncementRetryConf, ValidatorSettings},
     submit::{ValidatorSubmitter, ValidatorSubmitterMetrics},
 };
 
@@ -35,8 +35,10 @@ pub struct Validator {
     signer: SingletonSignerHandle,
     // temporary holder until `run` is called
     signer_instance: Option<Box<SingletonSigner>>,
+    signature_scheme: SignatureScheme,
     reorg_period: u64,
     interval: Duration,
+    announcement_retry: AnnouncementRetryConf,
     checkpoint_syncer: Arc<dyn CheckpointSyncer>,
 }
 #[async_trait]
@@ -49,6 +51,7 @@ impl BaseAgent for Validator {
     where
         Self: Sized,
     {
+        info!(config = %settings.to_effective_json(), "Loaded validator settings");
         let db = DB::from_path(&settings.db)?;
         let msg_db = HyperlaneRocksDB::new(&settings.origin_chain, db);
 
@@ -87,8 +90,10 @@ impl BaseAgent for Validator {
             validator_announce: validator_announce.into(),
             signer,
             signer_instance: Some(Box::new(signer_instance)),
+            signature_scheme: settings.signature_scheme,
             reorg_period: settings.reorg_period,
             interval: settings.interval,
+            announcement_retry: settings.announcement_retry,
             checkpoint_syncer,
         })
     }
@@ -136,13 +141,13 @@ impl BaseAgent for Validator {
 
 impl Validator {
     async fn run_message_sync(&self) -> Instrumented<JoinHandle<Result<()>>> {
-        let index_settings =
-            self.as_ref().settings.chains[self.origin_chain.name()].index_settings();
+        let index_settings = self.as_ref().settings.chains[self.origin_chain.name()]
+            .contract_index_settings("mailbox");
         let contract_sync = self.message_sync.clone();
-        let cursor = contract_sync
-            .forward_backward_message_sync_cursor(index_settings)
-            .await;
         tokio::spawn(async move {
+            let cursor = contract_sync
+                .forward_backward_message_sync_cursor(index_settings)
+                .await?;
             contract_sync
                 .clone()
                 .sync("dispatched_messages", cursor)
@@ -157,6 +162,7 @@ impl Validator {
             self.reorg_period,
             self.mailbox.clone(),
             self.signer.clone(),
+            self.signature_scheme,
             self.checkpoint_syncer.clone(),
             self.db.clone(),
             ValidatorSubmitterMetrics::new(&self.core.metrics, &self.origin_chain),
@@ -236,8 +242,18 @@ impl Validator {
         // which the validator is signing checkpoints but has not announced
         // their locations, which makes them functionally unusable.
         let validators: [H256; 1] = [self.signer.eth_address().into()];
+        let backoff = self.announcement_retry.backoff.unwrap_or(self.interval);
+        let mut attempts = 0u32;
         loop {
-            info!("Checking for validator announcement");
+            if self
+                .announcement_retry
+                .max_attempts
+                .map_or(false, |max| attempts >= max)
+            {
+                bail!("Validator storage location was not announced after {attempts} attempts");
+            }
+            attempts += 1;
+            info!(attempts, "Checking for validator announcement");
             if let Some(locations) = self
                 .validator_announce
                 .get_announced_storage_locations(&validators)
@@ -279,7 +295,7 @@ impl Validator {
                     warn!(origin_chain=%self.origin_chain, "Cannot announce validator without a signer; make sure a signer is set for the origin chain");
                 }
 
-                sleep(self.interval).await;
+                sleep(backoff).await;
             }
         }
         Ok(())
@@ -0,0 +1,10 @@
+//! Implementation of hyperlane for cosmos.
+
+#![forbid(unsafe_code)]
+#![warn(missing_docs)]
+
+pub use subscription::*;
+pub use trait_builder::*;
+
+mod subscription;
+mod trait_builder;
@@ -0,0 +1,219 @@
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use tokio::{sync::mpsc, time::sleep};
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tracing::{debug, warn};
+use url::Url;
+
+use crate::{ConnectionConf, RetryConf};
+
+/// Query of the CometBFT `subscribe` method for new blocks.
+const NEW_BLOCK_QUERY: &str = "tm.event='NewBlock'";
+
+/// An error type for block subscriptions.
+#[derive(thiserror::Error, Debug)]
+pub enum SubscriptionError {
+    /// The websocket failed more often in a row than the retry config allows
+    #[error("Block subscription to `{url}` failed {failures} times in a row: {last_error}")]
+    RetriesExhausted {
+        /// The websocket url subscribed to
+        url: Url,
+        /// Number of consecutive failures
+        failures: u32,
+        /// The last failure
+        last_error: String,
+    },
+}
+
+/// Subscription to new blocks over the CometBFT websocket of a cosmos chain,
+/// which reconnects according to its retry config when the websocket fails.
+#[derive(Debug, Clone)]
+pub struct BlockSubscription {
+    url: Url,
+    retry: RetryConf,
+}
+
+impl BlockSubscription {
+    /// The subscription of a connection, if it has `subscribe` set.
+    pub fn new(conf: &ConnectionConf) -> Option<Self> {
+        conf.websocket_url().map(|url| Self {
+            url,
+            retry: conf.retry,
+        })
+    }
+
+    /// Send the height of every new block to `heights` until the receiver is
+    /// dropped. Blocks produced while reconnecting are not reported, so
+    /// heights may be skipped.
+    pub async fn run(self, heights: mpsc::Sender<u64>) -> Result<(), SubscriptionError> {
+        let mut failures = 0;
+        loop {
+            let last_error = match self.subscribe(&heights, &mut failures).await {
+                Ok(()) => return Ok(()),
+                Err(e) => e,
+            };
+            failures += 1;
+            if self.retry.max_retries.map_or(false, |max| failures > max) {
+                return Err(SubscriptionError::RetriesExhausted {
+                    url: self.url,
+                    failures,
+                    last_error,
+                });
+            }
+            let backoff = self.retry.backoff(failures);
+            warn!(
+                url = %self.url,
+                error = %last_error,
+                ?backoff,
+                "Block subscription failed, reconnecting"
+            );
+            sleep(backoff).await;
+        }
+    }
+
+    /// Connect and forward the heights of new blocks, resetting `failures`
+    /// once the subscription delivers. Returns once the receiver is dropped
+    /// and errors when the websocket fails or is closed.
+    async fn subscribe(
+        &self,
+        heights: &mpsc::Sender<u64>,
+        failures: &mut u32,
+    ) -> Result<(), String> {
+        let (mut ws, _) = connect_async(self.url.as_str())
+            .await
+            .map_err(|e| format!("failed to connect ({e})"))?;
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 0,
+            "method": "subscribe",
+            "params": { "query": NEW_BLOCK_QUERY },
+        });
+        ws.send(Message::Text(request.to_string()))
+            .await
+            .map_err(|e| format!("failed to subscribe ({e})"))?;
+        debug!(url = %self.url, "Subscribed to new blocks");
+
+        while let Some(message) = ws.next().await {
+            let text = match message.map_err(|e| format!("connection failed ({e})"))? {
+                Message::Text(text) => text,
+                Message::Close(_) => break,
+                _ => continue,
+            };
+            let Some(height) = new_block_height(&text)? else {
+                continue;
+            };
+            *failures = 0;
+            if heights.send(height).await.is_err() {
+                return Ok(());
+            }
+        }
+        Err("connection closed".to_owned())
+    }
+}
+
+/// The height of the block of a `NewBlock` event, or `None` for other
+/// messages, e.g. the confirmation of the subscription.
+fn new_block_height(message: &str) -> Result<Option<u64>, String> {
+    let message: Value =
+        serde_json::from_str(message).map_err(|e| format!("invalid message ({e})"))?;
+    if let Some(error) = message.get("error") {
+        return Err(format!("subscription rejected ({error})"));
+    }
+    Ok(message
+        .pointer("/result/data/value/block/header/height")
+        .and_then(Value::as_str)
+        .and_then(|height| height.parse().ok()))
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::accept_async;
+
+    use super::*;
+
+    fn new_block(height: u64) -> Message {
+        Message::Text(
+            json!({
+                "jsonrpc": "2.0",
+                "id": 0,
+                "result": {
+                    "query": NEW_BLOCK_QUERY,
+                    "data": {
+                        "type": "tendermint/event/NewBlock",
+                        "value": { "block": { "header": { "height": height.to_string() } } },
+                    },
+                },
+            })
+            .to_string(),
+        )
+    }
+
+    fn retry(max_retries: Option<u32>) -> RetryConf {
+        RetryConf {
+            max_retries,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(5),
+        }
+    }
+
+    #[tokio::test]
+    async fn reconnects_when_the_websocket_closes() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}/websocket", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            // every connection delivers a single block and is then closed
+            for height in [5, 6] {
+                let (stream, _) = listener.accept().await.unwrap();
+                let mut ws = accept_async(stream).await.unwrap();
+                let request = ws.next().await.unwrap().unwrap().into_text().unwrap();
+                assert!(request.contains(NEW_BLOCK_QUERY));
+                ws.send(Message::Text(
+                    r#"{"jsonrpc":"2.0","id":0,"result":{}}"#.into(),
+                ))
+                .await
+                .unwrap();
+                ws.send(new_block(height)).await.unwrap();
+                ws.close(None).await.unwrap();
+            }
+        });
+
+        let subscription = BlockSubscription {
+            url: url.parse().unwrap(),
+            retry: retry(Some(1)),
+        };
+        let (sender, mut receiver) = mpsc::channel(1);
+        let task = tokio::spawn(subscription.run(sender));
+        assert_eq!(receiver.recv().await, Some(5));
+        assert_eq!(receiver.recv().await, Some(6));
+        task.abort();
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_retries() {
+        // nothing listens on port 1 so connecting is refused
+        let subscription = BlockSubscription {
+            url: "ws://127.0.0.1:1/websocket".parse().unwrap(),
+            retry: retry(Some(2)),
+        };
+        let (sender, _receiver) = mpsc::channel(1);
+        let err = subscription.run(sender).await.unwrap_err();
+        assert!(matches!(
+            err,
+            SubscriptionError::RetriesExhausted { failures: 3, .. }
+        ));
+    }
+
+    #[test]
+    fn doubles_the_backoff_up_to_the_maximum() {
+        let retry = RetryConf::new(None, Some(100), Some(250)).unwrap();
+        assert_eq!(retry.backoff(1), Duration::from_millis(100));
+        assert_eq!(retry.backoff(2), Duration::from_millis(200));
+        assert_eq!(retry.backoff(3), Duration::from_millis(250));
+        assert_eq!(retry.backoff(40), Duration::from_millis(250));
+        assert!(RetryConf::new(None, Some(100), Some(50)).is_err());
+        assert!(RetryConf::new(None, Some(0), None).is_err());
+    }
+}
@@ -0,0 +1,269 @@
+use std::time::Duration;
+
+use hyperlane_core::config::*;
+use url::Url;
+
+/// Cosmos connection configuration
+#[derive(Debug, Clone)]
+pub struct ConnectionConf {
+    /// The gRPC url to connect to
+    pub grpc_url: Url,
+    /// The Tendermint RPC url to connect to
+    pub rpc_url: Url,
+    /// The chain ID
+    pub chain_id: String,
+    /// The bech32 prefix of account addresses
+    pub prefix: String,
+    /// The gas price to pay for transactions
+    pub gas_price: GasPrice,
+    /// Subscribe to block events over the CometBFT websocket of `rpc_url`
+    /// instead of polling `block_results`
+    pub subscribe: bool,
+    /// How the subscription reconnects when its websocket fails
+    pub retry: RetryConf,
+}
+
+/// How a websocket reconnects after it failed. The backoff doubles after every
+/// consecutive failure, up to `max_backoff`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryConf {
+    /// Number of consecutive failures to reconnect after before giving up,
+    /// reconnects forever if `None`
+    pub max_retries: Option<u32>,
+    /// Backoff before the first reconnect
+    pub initial_backoff: Duration,
+    /// Upper bound for the backoff between two reconnects
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryConf {
+    fn default() -> Self {
+        Self {
+            max_retries: None,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryConf {
+    /// Retry settings with the given overrides of the defaults, checking that
+    /// the backoff bounds are consistent.
+    pub fn new(
+        max_retries: Option<u32>,
+        initial_backoff_ms: Option<u64>,
+        max_backoff_ms: Option<u64>,
+    ) -> Result<Self, ConnectionConfError> {
+        let default = Self::default();
+        let retry = Self {
+            max_retries: max_retries.or(default.max_retries),
+            initial_backoff: initial_backoff_ms
+                .map_or(default.initial_backoff, Duration::from_millis),
+            max_backoff: max_backoff_ms.map_or(default.max_backoff, Duration::from_millis),
+        };
+        if retry.initial_backoff.is_zero() || retry.initial_backoff > retry.max_backoff {
+            return Err(ConnectionConfError::InvalidRetryBackoff);
+        }
+        Ok(retry)
+    }
+
+    /// The backoff before reconnecting after `failures` consecutive failures.
+    pub fn backoff(&self, failures: u32) -> Duration {
+        let factor = 2u32.saturating_pow(failures.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+impl ConnectionConf {
+    /// The CometBFT websocket to subscribe to block events on, if `subscribe`
+    /// is set.
+    pub fn websocket_url(&self) -> Option<Url> {
+        self.subscribe
+            .then(|| to_websocket_url(&self.rpc_url).expect("websocket url is checked when parsed"))
+    }
+}
+
+/// Upgrade a Tendermint RPC url to its websocket endpoint, e.g.
+/// `https://rpc.example.com` to `wss://rpc.example.com/websocket`.
+pub fn to_websocket_url(rpc_url: &Url) -> Result<Url, ConnectionConfError> {
+    let scheme = match rpc_url.scheme() {
+        "http" | "ws" => "ws",
+        "https" | "wss" => "wss",
+        _ => {
+            return Err(ConnectionConfError::InvalidWebsocketUrl(
+                rpc_url.to_string(),
+            ))
+        }
+    };
+    let mut url = rpc_url.clone();
+    url.set_scheme(scheme)
+        .map_err(|_| ConnectionConfError::InvalidWebsocketUrl(rpc_url.to_string()))?;
+    if !url.path().trim_end_matches('/').ends_with("/websocket") {
+        let path = format!("{}/websocket", url.path().trim_end_matches('/'));
+        url.set_path(&path);
+    }
+    Ok(url)
+}
+
+/// A gas price for a cosmos chain, e.g. `0.025uatom`
+#[derive(Debug, Clone, PartialEq)]
+pub struct GasPrice {
+    /// The amount of `denom` to pay per unit of gas
+    pub amount: f64,
+    /// The denomination of the fee token
+    pub denom: String,
+}
+
+impl std::str::FromStr for GasPrice {
+    type Err = ConnectionConfError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let split = s
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .ok_or_else(|| ConnectionConfError::InvalidGasPrice(s.into()))?;
+        let (amount, denom) = s.split_at(split);
+        Ok(Self {
+            amount: amount
+                .parse()
+                .map_err(|_| ConnectionConfError::InvalidGasPrice(s.into()))?,
+            denom: denom.into(),
+        })
+    }
+}
+
+/// Raw cosmos connection configuration used for better deserialization errors.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeprecatedRawConnectionConf {
+    grpc_url: Option<String>,
+    rpc_url: Option<String>,
+    chain_id: Option<String>,
+    prefix: Option<String>,
+    gas_price: Option<String>,
+    subscribe: Option<bool>,
+    retry: Option<DeprecatedRawRetryConf>,
+}
+
+/// Raw reconnect settings of the block subscription
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeprecatedRawRetryConf {
+    max_retries: Option<StrOrInt>,
+    initial_backoff_ms: Option<StrOrInt>,
+    max_backoff_ms: Option<StrOrInt>,
+}
+
+/// An error type when parsing a connection configuration.
+#[derive(thiserror::Error, Debug)]
+pub enum ConnectionConfError {
+    /// Missing `grpcUrl` for connection configuration
+    #[error("Missing `grpcUrl` for connection configuration")]
+    MissingGrpcUrl,
+    /// Missing `rpcUrl` for connection configuration
+    #[error("Missing `rpcUrl` for connection configuration")]
+    MissingRpcUrl,
+    /// Missing `chainId` for connection configuration
+    #[error("Missing `chainId` for connection configuration")]
+    MissingChainId,
+    /// Missing `prefix` for connection configuration
+    #[error("Missing `prefix` for connection configuration")]
+    MissingPrefix,
+    /// Missing `gasPrice` for connection configuration
+    #[error("Missing `gasPrice` for connection configuration")]
+    MissingGasPrice,
+    /// Invalid url for connection configuration
+    #[error("Invalid url for connection configuration: `{0}` ({1})")]
+    InvalidConnectionUrl(String, url::ParseError),
+    /// The `rpcUrl` can not be upgraded to a websocket for `subscribe`
+    #[error("Unable to subscribe over a websocket to `{0}`, expected an http(s) or ws(s) url")]
+    InvalidWebsocketUrl(String),
+    /// Invalid `gasPrice` for connection configuration
+    #[error("Invalid `gasPrice` for connection configuration: `{0}`, expected e.g. `0.025uatom`")]
+    InvalidGasPrice(String),
+    /// A retry setting was not an integer
+    #[error("Invalid `{0}`, expected integer")]
+    InvalidRetryValue(&'static str),
+    /// The retry backoff bounds were inconsistent
+    #[error("Invalid `retry` backoff, expected positive values with `initialBackoffMs` at most `maxBackoffMs`")]
+    InvalidRetryBackoff,
+}
+
+impl FromRawConf<DeprecatedRawConnectionConf> for ConnectionConf {
+    fn from_config_filtered(
+        raw: DeprecatedRawConnectionConf,
+        cwp: &ConfigPath,
+        _filter: (),
+    ) -> ConfigResult<Self> {
+        use ConnectionConfError::*;
+
+        let mut err = ConfigParsingError::default();
+
+        let parse_url = |url: String| url.parse().map_err(|e| InvalidConnectionUrl(url, e));
+
+        let grpc_url = raw
+            .grpc_url
+            .ok_or(MissingGrpcUrl)
+            .and_then(parse_url)
+            .take_err(&mut err, || cwp + "grpc_url");
+        let rpc_url = raw
+            .rpc_url
+            .ok_or(MissingRpcUrl)
+            .and_then(parse_url)
+            .take_err(&mut err, || cwp + "rpc_url");
+        let chain_id = raw
+            .chain_id
+            .ok_or(MissingChainId)
+            .take_err(&mut err, || cwp + "chain_id");
+        let prefix = raw
+            .prefix
+            .ok_or(MissingPrefix)
+            .take_err(&mut err, || cwp + "prefix");
+        let gas_price = raw
+            .gas_price
+            .ok_or(MissingGasPrice)
+            .and_then(|p| p.parse())
+            .take_err(&mut err, || cwp + "gas_price");
+        let subscribe = raw.subscribe.unwrap_or(false);
+        if let (true, Some(rpc_url)) = (subscribe, &rpc_url) {
+            to_websocket_url(rpc_url).take_err(&mut err, || cwp + "subscribe");
+        }
+        let retry = match raw.retry {
+            Some(raw) => {
+                let cwp = cwp + "retry";
+                let max_retries = raw.max_retries.and_then(|v| {
+                    u32::try_from(v)
+                        .map_err(|_| InvalidRetryValue("maxRetries"))
+                        .take_err(&mut err, || &cwp + "max_retries")
+                });
+                let initial_backoff_ms = raw.initial_backoff_ms.and_then(|v| {
+                    u64::try_from(v)
+                        .map_err(|_| InvalidRetryValue("initialBackoffMs"))
+                        .take_err(&mut err, || &cwp + "initial_backoff_ms")
+                });
+                let max_backoff_ms = raw.max_backoff_ms.and_then(|v| {
+                    u64::try_from(v)
+                        .map_err(|_| InvalidRetryValue("maxBackoffMs"))
+                        .take_err(&mut err, || &cwp + "max_backoff_ms")
+                });
+                RetryConf::new(max_retries, initial_backoff_ms, max_backoff_ms)
+                    .take_err(&mut err, || &cwp + "initial_backoff_ms")
+            }
+            None => Some(RetryConf::default()),
+        };
+
+        cfg_unwrap_all!(cwp, err: [grpc_url, rpc_url, chain_id, prefix, gas_price, retry]);
+
+        err.into_result(Self {
+            grpc_url,
+            rpc_url,
+            chain_id,
+            prefix,
+            gas_price,
+            subscribe,
+            retry,
+        })
+    }
+}
@@ -1,7 +1,20 @@
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
 use hyperlane_core::config::*;
+use hyperlane_core::U256;
+use reqwest::{Certificate, Identity};
 use serde::Deserialize;
 use url::Url;
 
+/// How long to wait for a transaction to be confirmed by default before it is
+/// resubmitted with a higher gas price.
+pub const DEFAULT_CONFIRMATION_TIMEOUT: Duration = Duration::from_secs(300);
+
 /// Ethereum connection configuration
 #[derive(Debug, Clone)]
 pub enum ConnectionConf {
@@ -9,24 +22,366 @@ pub enum ConnectionConf {
     HttpQuorum {
         /// List of urls to connect to
         urls: Vec<Url>,
+        /// Number of providers which must agree on a response, defaults to a
+        /// majority of them
+        threshold: Option<usize>,
+        /// Extra headers to send with every request
+        headers: HashMap<String, String>,
+        /// Client certificate for mutual TLS with the providers
+        tls: Option<TlsConfig>,
+        /// How long a single request may take, defaults to 60 seconds
+        request_timeout: Option<Duration>,
+        /// Maximum number of idle connections kept open to each provider,
+        /// defaults to 32
+        max_connections: Option<usize>,
+        /// How to retry failed requests to each of the providers
+        retry: RetryConfig,
     },
-    /// An HTTP-only fallback set.
+    /// A fallback set, where each url may use either an HTTP or a websocket
+    /// transport.
     HttpFallback {
         /// List of urls to connect to in order of priority
         urls: Vec<Url>,
+        /// Extra headers to send with every request
+        headers: HashMap<String, String>,
+        /// Client certificate for mutual TLS with the HTTP providers
+        tls: Option<TlsConfig>,
+        /// How long a single HTTP request may take before the next url is
+        /// tried, defaults to 60 seconds
+        request_timeout: Option<Duration>,
+        /// Maximum number of idle connections kept open to each HTTP
+        /// provider, defaults to 32
+        max_connections: Option<usize>,
+        /// How to retry when every provider failed
+        retry: RetryConfig,
     },
     /// HTTP connection details
     Http {
         /// Url to connect to
         url: Url,
+        /// Extra headers to send with every request
+        headers: HashMap<String, String>,
+        /// Client certificate for mutual TLS with the provider
+        tls: Option<TlsConfig>,
+        /// How long a single request may take, defaults to 60 seconds
+        request_timeout: Option<Duration>,
+        /// Maximum number of idle connections kept open to the provider,
+        /// defaults to 32
+        max_connections: Option<usize>,
+        /// How to retry failed requests
+        retry: RetryConfig,
     },
-    /// Websocket connection details
+    /// Websocket connection details. The HTTP only options (`headers`,
+    /// `userAgent`, `tls`, `requestTimeout` and `maxConnections`) can not be
+    /// used with it.
     Ws {
         /// Url to connect to
         url: Url,
     },
 }
 
+/// The connections to an ethereum chain, which may index through different
+/// RPCs than it submits transactions to.
+#[derive(Debug, Clone)]
+pub struct ChainConnections {
+    /// Connection used by indexers
+    pub index: ConnectionConf,
+    /// Connection used to submit transactions and for all other calls
+    pub submit: ConnectionConf,
+}
+
+impl ChainConnections {
+    /// Use the same connection for indexing and submitting.
+    pub fn shared(conf: ConnectionConf) -> Self {
+        Self {
+            index: conf.clone(),
+            submit: conf,
+        }
+    }
+}
+
+/// How the gas price of submitted transactions is set
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TxType {
+    /// EIP-1559 fees are used if the chain can estimate them, otherwise a
+    /// legacy gas price
+    #[default]
+    Auto,
+    /// A legacy `gasPrice`, for chains which advertise EIP-1559 but do not
+    /// handle it well
+    Legacy,
+    /// `maxFeePerGas` and `maxPriorityFeePerGas`, failing if the chain can not
+    /// estimate them
+    Eip1559,
+}
+
+/// Overrides applied to the gas price estimated for transactions on a chain
+#[derive(Clone, Debug, PartialEq)]
+pub struct GasOracleConf {
+    /// The lowest gas price to use
+    pub min_gas_price: Option<U256>,
+    /// The highest gas price to use
+    pub max_gas_price: Option<U256>,
+    /// Factor the estimated gas price is multiplied by before clamping
+    pub gas_price_multiplier: f64,
+}
+
+impl Default for GasOracleConf {
+    fn default() -> Self {
+        Self {
+            min_gas_price: None,
+            max_gas_price: None,
+            gas_price_multiplier: 1.0,
+        }
+    }
+}
+
+impl GasOracleConf {
+    /// Check that the overrides are consistent with each other.
+    pub fn validate(&self) -> Result<(), GasOracleConfError> {
+        if !self.gas_price_multiplier.is_finite() || self.gas_price_multiplier <= 0. {
+            return Err(GasOracleConfError::InvalidMultiplier);
+        }
+        if let (Some(min), Some(max)) = (self.min_gas_price, self.max_gas_price) {
+            if min > max {
+                return Err(GasOracleConfError::MinAboveMax(min, max));
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply the multiplier and bounds to an estimated gas price.
+    pub fn apply(&self, estimated: U256) -> U256 {
+        // scale by a fixed point multiplier to avoid converting the price to a float
+        const PRECISION: u64 = 1_000_000;
+        let multiplier = U256::from((self.gas_price_multiplier * PRECISION as f64) as u64);
+        let mut price = estimated.saturating_mul(multiplier) / PRECISION;
+        if let Some(min) = self.min_gas_price {
+            price = price.max(min);
+        }
+        if let Some(max) = self.max_gas_price {
+            price = price.min(max);
+        }
+        price
+    }
+}
+
+/// Errors in the gas price overrides of a chain
+#[derive(Debug, thiserror::Error)]
+pub enum GasOracleConfError {
+    /// The multiplier was zero, negative or not a number
+    #[error("`gasPriceMultiplier` must be a positive number")]
+    InvalidMultiplier,
+    /// The lower bound was above the upper bound
+    #[error("`minGasPrice` ({0}) must not be greater than `maxGasPrice` ({1})")]
+    MinAboveMax(U256, U256),
+}
+
+/// A block tag which the chain reports finality with, used in place of a
+/// fixed number of blocks below the tip
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FinalityTag {
+    /// The latest block which is unlikely to be reorged
+    Safe,
+    /// The latest block which has been finalized
+    Finalized,
+}
+
+impl FinalityTag {
+    /// The name of the tag, as used in the JSON-RPC API and the config
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Safe => "safe",
+            Self::Finalized => "finalized",
+        }
+    }
+}
+
+/// Retry and backoff settings for HTTP providers. Anything left unset falls
+/// back to the defaults of the connection type.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RetryConfig {
+    /// Number of times a failed request is retried
+    pub max_retries: Option<u32>,
+    /// Backoff before the first retry, doubled for every retry after it
+    pub initial_backoff_ms: Option<u64>,
+    /// Upper bound for the backoff between two retries
+    pub max_backoff_ms: Option<u64>,
+}
+
+impl RetryConfig {
+    /// Check that the backoff bounds are consistent.
+    pub fn validate(&self) -> Result<(), ConnectionConfError> {
+        if self.initial_backoff_ms == Some(0) || self.max_backoff_ms == Some(0) {
+            return Err(ConnectionConfError::InvalidRetryBackoff);
+        }
+        if let (Some(initial), Some(max)) = (self.initial_backoff_ms, self.max_backoff_ms) {
+            if initial > max {
+                return Err(ConnectionConfError::InvalidRetryBackoff);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A client certificate and key presented to RPCs which require mutual TLS,
+/// optionally with a CA certificate to trust in addition to the system roots.
+/// The files are read while parsing so missing ones are reported early.
+#[derive(Clone)]
+pub struct TlsConfig {
+    /// Path to the PEM encoded client certificate
+    pub client_cert_path: PathBuf,
+    /// Path to the PEM encoded private key of the client certificate
+    pub client_key_path: PathBuf,
+    /// Path to a PEM encoded CA certificate for the RPC
+    pub ca_cert_path: Option<PathBuf>,
+    identity: Identity,
+    ca_cert: Option<Certificate>,
+}
+
+impl Debug for TlsConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TlsConfig")
+            .field("client_cert_path", &self.client_cert_path)
+            .field("client_key_path", &self.client_key_path)
+            .field("ca_cert_path", &self.ca_cert_path)
+            .finish()
+    }
+}
+
+impl TlsConfig {
+    /// The client identity built from the certificate and key.
+    pub fn identity(&self) -> &Identity {
+        &self.identity
+    }
+
+    /// The additional CA certificate to trust, if any.
+    pub fn ca_cert(&self) -> Option<&Certificate> {
+        self.ca_cert.as_ref()
+    }
+}
+
+fn read_tls_file(path: &Path) -> Result<Vec<u8>, ConnectionConfError> {
+    std::fs::read(path)
+        .map_err(|e| ConnectionConfError::UnreadableTlsFile(path.into(), e.to_string()))
+}
+
+/// Raw mutual TLS settings
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RawTlsConfig {
+    client_cert_path: Option<String>,
+    client_key_path: Option<String>,
+    ca_cert_path: Option<String>,
+}
+
+impl FromRawConf<RawTlsConfig> for TlsConfig {
+    fn from_config_filtered(
+        raw: RawTlsConfig,
+        cwp: &ConfigPath,
+        _filter: (),
+    ) -> ConfigResult<Self> {
+        use ConnectionConfError::*;
+
+        let mut err = ConfigParsingError::default();
+
+        let client_cert_path = raw
+            .client_cert_path
+            .map(PathBuf::from)
+            .ok_or(MissingTlsFile("clientCertPath"))
+            .take_err(&mut err, || cwp + "client_cert_path");
+        let client_key_path = raw
+            .client_key_path
+            .map(PathBuf::from)
+            .ok_or(MissingTlsFile("clientKeyPath"))
+            .take_err(&mut err, || cwp + "client_key_path");
+        let ca_cert_path = raw.ca_cert_path.map(PathBuf::from);
+
+        let client_cert = client_cert_path
+            .as_ref()
+            .and_then(|path| read_tls_file(path).take_err(&mut err, || cwp + "client_cert_path"));
+        let client_key = client_key_path
+            .as_ref()
+            .and_then(|path| read_tls_file(path).take_err(&mut err, || cwp + "client_key_path"));
+        let ca_cert = ca_cert_path.as_ref().and_then(|path| {
+            read_tls_file(path)
+                .and_then(|pem| {
+                    Certificate::from_pem(&pem)
+                        .map_err(|e| InvalidTlsFile(path.clone(), e.to_string()))
+                })
+                .take_err(&mut err, || cwp + "ca_cert_path")
+        });
+        // the identity is read from a single PEM bundle holding both the key
+        // and the certificate chain
+        let identity = client_cert.zip(client_key).and_then(|(mut pem, key)| {
+            pem.push(b'\n');
+            pem.extend(key);
+            Identity::from_pem(&pem)
+                .map_err(|e| InvalidTlsFile(client_key_path.clone().unwrap(), e.to_string()))
+                .take_err(&mut err, || cwp + "client_key_path")
+        });
+
+        // the identity is only missing if one of the files had an error
+        match (client_cert_path, client_key_path, identity) {
+            (Some(client_cert_path), Some(client_key_path), Some(identity)) => {
+                err.into_result(Self {
+                    client_cert_path,
+                    client_key_path,
+                    ca_cert_path,
+                    identity,
+                    ca_cert,
+                })
+            }
+            _ => Err(err),
+        }
+    }
+}
+
+/// Raw retry and backoff settings
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RawRetryConfig {
+    max_retries: Option<StrOrInt>,
+    initial_backoff_ms: Option<StrOrInt>,
+    max_backoff_ms: Option<StrOrInt>,
+}
+
+impl FromRawConf<RawRetryConfig> for RetryConfig {
+    fn from_config_filtered(
+        raw: RawRetryConfig,
+        cwp: &ConfigPath,
+        _filter: (),
+    ) -> ConfigResult<Self> {
+        let mut err = ConfigParsingError::default();
+
+        let max_retries = raw.max_retries.and_then(|v| {
+            (&v).try_into()
+                .map_err(|_| ConnectionConfError::InvalidRetryValue("maxRetries"))
+                .take_err(&mut err, || cwp + "max_retries")
+        });
+        let initial_backoff_ms = raw.initial_backoff_ms.and_then(|v| {
+            (&v).try_into()
+                .map_err(|_| ConnectionConfError::InvalidRetryValue("initialBackoffMs"))
+                .take_err(&mut err, || cwp + "initial_backoff_ms")
+        });
+        let max_backoff_ms = raw.max_backoff_ms.and_then(|v| {
+            (&v).try_into()
+                .map_err(|_| ConnectionConfError::InvalidRetryValue("maxBackoffMs"))
+                .take_err(&mut err, || cwp + "max_backoff_ms")
+        });
+
+        let retry = Self {
+            max_retries,
+            initial_backoff_ms,
+            max_backoff_ms,
+        };
+        retry
+            .validate()
+            .take_err(&mut err, || cwp + "initial_backoff_ms");
+        err.into_result(retry)
+    }
+}
+
 /// Ethereum connection configuration
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -38,6 +393,34 @@ pub struct RawConnectionConf {
     url: Option<String>,
     /// A comma separated list of urls to connect to
     urls: Option<String>,
+    /// A comma separated list of urls to index from instead of `urls`, e.g.
+    /// read replicas
+    index_urls: Option<String>,
+    /// A comma separated list of urls to submit transactions to instead of
+    /// `urls`
+    submit_urls: Option<String>,
+    /// How to reach consensus between multiple urls, one of `single`,
+    /// `fallback` or `quorum`. Takes precedence over `type` when set.
+    rpc_consensus_type: Option<String>,
+    /// Number of providers which must agree when using `quorum` consensus
+    quorum_threshold: Option<StrOrInt>,
+    /// Extra headers to send with every HTTP request, e.g. for an API key
+    headers: Option<HashMap<String, String>>,
+    /// Overrides the user agent of every HTTP request, for providers which
+    /// allow-list clients by it
+    user_agent: Option<String>,
+    /// How to retry failed requests, not used by `ws` connections
+    retry: Option<RawRetryConfig>,
+    /// Client certificate for RPCs which require mutual TLS
+    tls: Option<RawTlsConfig>,
+    /// How long a single HTTP request may take, e.g. `10s`
+    request_timeout: Option<StrOrInt>,
+    /// Maximum number of idle HTTP connections kept open to each url
+    max_connections: Option<StrOrInt>,
+    /// Check that every url resolves and accepts connections while parsing.
+    /// Off by default so configs can be validated offline.
+    #[serde(default)]
+    validate_connectivity: bool,
 }
 
 /// Error type when parsing a connection configuration.
@@ -64,6 +447,342 @@ pub enum ConnectionConfError {
     /// The urls were empty
     #[error("The `urls` value is empty")]
     EmptyUrls,
+    /// Unknown rpc consensus type was specified
+    #[error(
+        "Unsupported rpc consensus type '{0}', expected one of `single`, `fallback` or `quorum`"
+    )]
+    UnsupportedRpcConsensusType(String),
+    /// Several urls were given for a `single` connection
+    #[error("Rpc consensus type `single` takes one url, got {0}")]
+    MultipleSingleUrls(usize),
+    /// The quorum threshold was invalid
+    #[error("Invalid `quorumThreshold`, expected an integer between 1 and the number of urls")]
+    InvalidQuorumThreshold,
+    /// A header had an empty value
+    #[error("The value of header `{0}` is empty")]
+    EmptyHeaderValue(String),
+    /// A header name or value can not be sent over HTTP
+    #[error("Invalid header `{0}`")]
+    InvalidHeader(String),
+    /// The user agent was empty
+    #[error("The `userAgent` value is empty")]
+    EmptyUserAgent,
+    /// The user agent can not be sent as an HTTP header value
+    #[error("Invalid `userAgent` `{0}`")]
+    InvalidUserAgent(String),
+    /// The user agent was set both directly and as a header
+    #[error("The user agent can not be set by both `userAgent` and `headers`")]
+    ConflictingUserAgent,
+    /// A retry setting was not an integer
+    #[error("Invalid `{0}`, expected integer")]
+    InvalidRetryValue(&'static str),
+    /// A url used a scheme which is neither HTTP nor websocket
+    #[error("Unsupported url scheme `{0}`, expected one of `http`, `https`, `ws` or `wss`")]
+    UnsupportedUrlScheme(String),
+    /// A websocket url was given for a quorum connection
+    #[error("Websocket urls are not supported by quorum connections")]
+    UnsupportedQuorumWsUrl,
+    /// A url could not be resolved or connected to
+    #[error("Unable to reach `{0}`: {1}")]
+    UnreachableUrl(Url, String),
+    /// The retry backoff bounds were inconsistent
+    #[error("Invalid `retry` backoff, expected positive values with `initialBackoffMs` at most `maxBackoffMs`")]
+    InvalidRetryBackoff,
+    /// A file of the `tls` config was not specified
+    #[error("Missing `{0}` for `tls` configuration")]
+    MissingTlsFile(&'static str),
+    /// A file of the `tls` config could not be read
+    #[error("Unable to read `{}`: {1}", .0.display())]
+    UnreadableTlsFile(PathBuf, String),
+    /// A file of the `tls` config did not hold a valid PEM certificate or key
+    #[error("Invalid TLS certificate or key in `{}`: {1}", .0.display())]
+    InvalidTlsFile(PathBuf, String),
+    /// The request timeout was not a positive duration
+    #[error("Invalid `requestTimeout`, expected a positive duration")]
+    InvalidRequestTimeout,
+    /// The connection pool size was not a positive integer
+    #[error("Invalid `maxConnections`, expected an integer of at least 1")]
+    InvalidMaxConnections,
+    /// An HTTP only option was set for a websocket connection
+    #[error("`{0}` is not supported by websocket connections, use an http url or remove it")]
+    UnsupportedWsOption(&'static str),
+}
+
+impl ConnectionConf {
+    /// A connection to a single url, using a websocket transport for `ws` and
+    /// `wss` urls. Errors if HTTP only options are set for a websocket url.
+    pub fn single(
+        url: Url,
+        headers: HashMap<String, String>,
+        tls: Option<TlsConfig>,
+        request_timeout: Option<Duration>,
+        max_connections: Option<usize>,
+        retry: RetryConfig,
+    ) -> Result<Self, ConnectionConfError> {
+        Ok(if is_ws_url(&url) {
+            check_ws_options(&headers, &tls, request_timeout, max_connections)?;
+            Self::Ws { url }
+        } else {
+            Self::Http {
+                url,
+                headers,
+                tls,
+                request_timeout,
+                max_connections,
+                retry,
+            }
+        })
+    }
+
+    /// The same connection reaching the chain through `urls` instead. Single
+    /// url connections become fallback connections if given multiple urls.
+    pub fn with_urls(&self, urls: Vec<Url>) -> Result<Self, ConnectionConfError> {
+        let single_or_fallback = |mut urls: Vec<Url>,
+                                  headers: &HashMap<String, String>,
+                                  tls: &Option<TlsConfig>,
+                                  request_timeout,
+                                  max_connections,
+                                  retry| {
+            if urls.len() == 1 {
+                Self::single(
+                    urls.remove(0),
+                    headers.clone(),
+                    tls.clone(),
+                    request_timeout,
+                    max_connections,
+                    retry,
+                )
+            } else {
+                Ok(Self::HttpFallback {
+                    urls,
+                    headers: headers.clone(),
+                    tls: tls.clone(),
+                    request_timeout,
+                    max_connections,
+                    retry,
+                })
+            }
+        };
+        Ok(match self {
+            Self::HttpQuorum {
+                threshold,
+                headers,
+                tls,
+                request_timeout,
+                max_connections,
+                retry,
+                ..
+            } => {
+                if urls.iter().any(is_ws_url) {
+                    return Err(ConnectionConfError::UnsupportedQuorumWsUrl);
+                }
+                if threshold.map_or(false, |t| t > urls.len()) {
+                    return Err(ConnectionConfError::InvalidQuorumThreshold);
+                }
+                Self::HttpQuorum {
+                    urls,
+                    threshold: *threshold,
+                    headers: headers.clone(),
+                    tls: tls.clone(),
+                    request_timeout: *request_timeout,
+                    max_connections: *max_connections,
+                    retry: *retry,
+                }
+            }
+            Self::HttpFallback {
+                headers,
+                tls,
+                request_timeout,
+                max_connections,
+                retry,
+                ..
+            } => Self::HttpFallback {
+                urls,
+                headers: headers.clone(),
+                tls: tls.clone(),
+                request_timeout: *request_timeout,
+                max_connections: *max_connections,
+                retry: *retry,
+            },
+            Self::Http {
+                headers,
+                tls,
+                request_timeout,
+                max_connections,
+                retry,
+                ..
+            } => single_or_fallback(
+                urls,
+                headers,
+                tls,
+                *request_timeout,
+                *max_connections,
+                *retry,
+            )?,
+            Self::Ws { .. } => single_or_fallback(
+                urls,
+                &HashMap::new(),
+                &None,
+                None,
+                None,
+                RetryConfig::default(),
+            )?,
+        })
+    }
+}
+
+/// Send `user_agent` as the user agent of every HTTP request instead of the
+/// default one.
+pub fn add_user_agent(
+    headers: &mut HashMap<String, String>,
+    user_agent: &str,
+) -> Result<(), ConnectionConfError> {
+    if user_agent.trim().is_empty() {
+        return Err(ConnectionConfError::EmptyUserAgent);
+    }
+    if reqwest::header::HeaderValue::from_str(user_agent).is_err() {
+        return Err(ConnectionConfError::InvalidUserAgent(user_agent.into()));
+    }
+    if headers
+        .keys()
+        .any(|name| name.eq_ignore_ascii_case(reqwest::header::USER_AGENT.as_str()))
+    {
+        return Err(ConnectionConfError::ConflictingUserAgent);
+    }
+    headers.insert(
+        reqwest::header::USER_AGENT.as_str().to_owned(),
+        user_agent.to_owned(),
+    );
+    Ok(())
+}
+
+fn is_ws_url(url: &Url) -> bool {
+    matches!(url.scheme(), "ws" | "wss")
+}
+
+/// Check that none of the HTTP only options are set for a websocket
+/// connection, which would silently ignore them.
+fn check_ws_options(
+    headers: &HashMap<String, String>,
+    tls: &Option<TlsConfig>,
+    request_timeout: Option<Duration>,
+    max_connections: Option<usize>,
+) -> Result<(), ConnectionConfError> {
+    let user_agent = reqwest::header::USER_AGENT.as_str();
+    if headers
+        .keys()
+        .any(|name| !name.eq_ignore_ascii_case(user_agent))
+    {
+        return Err(ConnectionConfError::UnsupportedWsOption("headers"));
+    }
+    if !headers.is_empty() {
+        return Err(ConnectionConfError::UnsupportedWsOption("userAgent"));
+    }
+    if tls.is_some() {
+        return Err(ConnectionConfError::UnsupportedWsOption("tls"));
+    }
+    if request_timeout.is_some() {
+        return Err(ConnectionConfError::UnsupportedWsOption("requestTimeout"));
+    }
+    if max_connections.is_some() {
+        return Err(ConnectionConfError::UnsupportedWsOption("maxConnections"));
+    }
+    Ok(())
+}
+
+/// Parse the number of idle HTTP connections kept open to each url, which
+/// must be at least 1.
+pub fn parse_max_connections(v: &StrOrInt) -> Result<usize, ConnectionConfError> {
+    u32::try_from(v)
+        .ok()
+        .filter(|c| *c >= 1)
+        .map(|c| c as usize)
+        .ok_or(ConnectionConfError::InvalidMaxConnections)
+}
+
+/// Parse the timeout of a single HTTP request, which must be positive.
+pub fn parse_request_timeout(v: &StrOrInt) -> Result<Duration, ConnectionConfError> {
+    Duration::try_from(v)
+        .ok()
+        .filter(|t| !t.is_zero())
+        .ok_or(ConnectionConfError::InvalidRequestTimeout)
+}
+
+fn check_url_scheme(url: &Url) -> Result<(), ConnectionConfError> {
+    match url.scheme() {
+        "http" | "https" | "ws" | "wss" => Ok(()),
+        scheme => Err(ConnectionConfError::UnsupportedUrlScheme(scheme.into())),
+    }
+}
+
+/// Parse a comma separated list of http or websocket urls.
+fn parse_url_list(urls: &str) -> Result<Vec<Url>, ConnectionConfError> {
+    if urls.trim().is_empty() {
+        return Err(ConnectionConfError::EmptyUrls);
+    }
+    let urls = urls
+        .split(',')
+        .map(|s| s.parse())
+        .collect::<Result<Vec<Url>, _>>()
+        .map_err(|e| ConnectionConfError::InvalidConnectionUrls(urls.to_owned(), e))?;
+    urls.iter().try_for_each(check_url_scheme)?;
+    Ok(urls)
+}
+
+/// How long checking that a single url is reachable may take.
+const CONNECTIVITY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Check that the host of `url` resolves and accepts TCP connections within
+/// `CONNECTIVITY_TIMEOUT`.
+fn check_connectivity(url: &Url) -> Result<(), ConnectionConfError> {
+    let unreachable =
+        |reason: &str| ConnectionConfError::UnreachableUrl(url.clone(), reason.into());
+
+    // the system resolver has no timeout of its own, so resolve on another
+    // thread and stop waiting for it once the time is up
+    let deadline = Instant::now() + CONNECTIVITY_TIMEOUT;
+    let (sender, receiver) = mpsc::channel();
+    let target = url.clone();
+    std::thread::spawn(move || {
+        let _ = sender.send(target.socket_addrs(|| None));
+    });
+    let addrs = match receiver.recv_timeout(CONNECTIVITY_TIMEOUT) {
+        Ok(Ok(addrs)) => addrs,
+        Ok(Err(e)) => return Err(unreachable(&format!("failed to resolve host ({e})"))),
+        Err(_) => return Err(unreachable("DNS lookup timed out")),
+    };
+
+    let mut last_err = unreachable("host did not resolve to any address");
+    for addr in addrs {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(unreachable("connection timed out"));
+        }
+        match TcpStream::connect_timeout(&addr, remaining) {
+            Ok(_) => return Ok(()),
+            Err(e) => last_err = unreachable(&format!("connection to {addr} failed ({e})")),
+        }
+    }
+    Err(last_err)
+}
+
+/// Check the connectivity of every url at once, so checking takes at most
+/// `CONNECTIVITY_TIMEOUT` however many urls there are. Every unreachable url
+/// is reported at the config path given with it.
+fn check_all_connectivity(urls: Vec<(&Url, ConfigPath)>) -> ConfigResult<()> {
+    let mut err = ConfigParsingError::default();
+    let results = std::thread::scope(|scope| {
+        urls.into_iter()
+            .map(|(url, path)| (path, scope.spawn(move || check_connectivity(url))))
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|(path, check)| (path, check.join().expect("connectivity check panicked")))
+            .collect::<Vec<_>>()
+    });
+    for (path, result) in results {
+        result.take_err(&mut err, || path);
+    }
+    err.into_result(())
 }
 
 impl FromRawConf<RawConnectionConf> for ConnectionConf {
@@ -98,24 +817,220 @@ impl FromRawConf<RawConnectionConf> for ConnectionConf {
                 .into_config_result(|| cwp + "url")
         })();
 
-        macro_rules! make_with_urls {
-            ($variant:ident) => {
-                if let Ok(urls) = urls {
-                    Ok(Self::$variant { urls })
-                } else if let Ok(url) = url {
-                    Ok(Self::$variant { urls: vec![url] })
-                } else {
-                    Err(urls.unwrap_err())
+        let urls = urls.and_then(|urls| {
+            urls.iter()
+                .try_for_each(check_url_scheme)
+                .into_config_result(|| cwp + "urls")?;
+            Ok(urls)
+        });
+        let url = url.and_then(|url| {
+            check_url_scheme(&url).into_config_result(|| cwp + "url")?;
+            Ok(url)
+        });
+
+        if raw.validate_connectivity {
+            // `url` is usually also the first of `urls`, so it is only checked
+            // once
+            let url = url
+                .iter()
+                .filter(|url| !urls.iter().flatten().any(|u| u == *url))
+                .map(|url| (url, cwp + "url"));
+            let urls = urls.iter().flatten().map(|url| (url, cwp + "urls"));
+            check_all_connectivity(urls.chain(url).collect())?;
+        }
+
+        let urls = if let Ok(urls) = urls {
+            Ok(urls)
+        } else if let Ok(url) = &url {
+            Ok(vec![url.clone()])
+        } else {
+            urls
+        };
+
+        let mut headers = raw.headers.unwrap_or_default();
+        for (name, value) in &headers {
+            if value.is_empty() {
+                return Err(EmptyHeaderValue(name.clone())).into_config_result(|| cwp + "headers");
+            }
+            if reqwest::header::HeaderName::from_bytes(name.as_bytes()).is_err()
+                || reqwest::header::HeaderValue::from_str(value).is_err()
+            {
+                return Err(InvalidHeader(name.clone())).into_config_result(|| cwp + "headers");
+            }
+        }
+        if let Some(user_agent) = &raw.user_agent {
+            add_user_agent(&mut headers, user_agent).into_config_result(|| cwp + "user_agent")?;
+        }
+
+        let retry = raw
+            .retry
+            .map(|r| r.parse_config(&cwp.join("retry")))
+            .transpose()?
+            .unwrap_or_default();
+
+        let tls = raw
+            .tls
+            .map(|t| t.parse_config(&cwp.join("tls")))
+            .transpose()?;
+
+        let request_timeout = raw
+            .request_timeout
+            .map(|t| parse_request_timeout(&t))
+            .transpose()
+            .into_config_result(|| cwp + "request_timeout")?;
+
+        let max_connections = raw
+            .max_connections
+            .map(|c| parse_max_connections(&c))
+            .transpose()
+            .into_config_result(|| cwp + "max_connections")?;
+
+        let quorum = |urls: Vec<Url>, headers, tls| -> ConfigResult<Self> {
+            if urls.iter().any(is_ws_url) {
+                return Err(UnsupportedQuorumWsUrl).into_config_result(|| cwp + "urls");
+            }
+            let threshold = raw
+                .quorum_threshold
+                .as_ref()
+                .map(|t| {
+                    u32::try_from(t)
+                        .ok()
+                        .map(|t| t as usize)
+                        .filter(|t| (1..=urls.len()).contains(t))
+                        .ok_or(InvalidQuorumThreshold)
+                })
+                .transpose()
+                .into_config_result(|| cwp + "quorum_threshold")?;
+            Ok(Self::HttpQuorum {
+                urls,
+                threshold,
+                headers,
+                tls,
+                request_timeout,
+                max_connections,
+                retry,
+            })
+        };
+
+        if let Some(consensus_type) = raw.rpc_consensus_type.as_deref() {
+            return match consensus_type {
+                "single" => {
+                    let mut urls = urls?;
+                    if urls.len() > 1 {
+                        return Err(MultipleSingleUrls(urls.len()))
+                            .into_config_result(|| cwp + "urls");
+                    }
+                    Self::single(
+                        url.unwrap_or_else(|_| urls.remove(0)),
+                        headers,
+                        tls,
+                        request_timeout,
+                        max_connections,
+                        retry,
+                    )
+                    .into_config_result(|| cwp.clone())
                 }
+                "fallback" => Ok(Self::HttpFallback {
+                    urls: urls?,
+                    headers,
+                    tls,
+                    request_timeout,
+                    max_connections,
+                    retry,
+                }),
+                "quorum" => quorum(urls?, headers, tls),
+                t => Err(UnsupportedRpcConsensusType(t.into()))
+                    .into_config_result(|| cwp + "rpc_consensus_type"),
             };
         }
 
         match connection_type {
-            "httpQuorum" => make_with_urls!(HttpQuorum),
-            "httpFallback" => make_with_urls!(HttpFallback),
-            "http" => Ok(Self::Http { url: url? }),
-            "ws" => Ok(Self::Ws { url: url? }),
+            "httpQuorum" => quorum(urls?, headers, tls),
+            "httpFallback" => Ok(Self::HttpFallback {
+                urls: urls?,
+                headers,
+                tls,
+                request_timeout,
+                max_connections,
+                retry,
+            }),
+            "http" => Self::single(url?, headers, tls, request_timeout, max_connections, retry)
+                .into_config_result(|| cwp.clone()),
+            "ws" => {
+                check_ws_options(&headers, &tls, request_timeout, max_connections)
+                    .into_config_result(|| cwp.clone())?;
+                Ok(Self::Ws { url: url? })
+            }
             t => Err(UnsupportedConnectionType(t.into())).into_config_result(|| cwp.join("type")),
         }
     }
 }
+
+impl FromRawConf<RawConnectionConf> for ChainConnections {
+    fn from_config_filtered(
+        mut raw: RawConnectionConf,
+        cwp: &ConfigPath,
+        _filter: (),
+    ) -> ConfigResult<Self> {
+        let mut err = ConfigParsingError::default();
+        let mut parse_urls = |urls: Option<String>, key: &str| -> Option<Vec<Url>> {
+            urls.and_then(|urls| {
+                parse_url_list(&urls)
+                    .into_config_result(|| cwp + key)
+                    .take_config_err(&mut err)
+            })
+        };
+        let index_urls = parse_urls(raw.index_urls.take(), "index_urls");
+        let submit_urls = parse_urls(raw.submit_urls.take(), "submit_urls");
+        if !err.is_ok() {
+            return Err(err);
+        }
+
+        // the submit urls can stand in for the shared ones, which are checked
+        // when parsing them
+        let stand_in = raw.url.is_none() && raw.urls.is_none();
+        if raw.validate_connectivity {
+            let index = index_urls
+                .iter()
+                .flatten()
+                .map(|url| (url, cwp + "index_urls"));
+            let submit = submit_urls
+                .iter()
+                .filter(|_| !stand_in)
+                .flatten()
+                .map(|url| (url, cwp + "submit_urls"));
+            check_all_connectivity(index.chain(submit).collect()).take_config_err(&mut err);
+        }
+        if stand_in {
+            if let Some(submit_urls) = &submit_urls {
+                raw.url = Some(submit_urls[0].to_string());
+                raw.urls = Some(
+                    submit_urls
+                        .iter()
+                        .map(Url::to_string)
+                        .collect::<Vec<_>>()
+                        .join(","),
+                );
+            }
+        }
+        let conf = match ConnectionConf::from_config(raw, cwp) {
+            Ok(conf) => conf,
+            Err(e) => {
+                err.merge(e);
+                return Err(err);
+            }
+        };
+        if !err.is_ok() {
+            return Err(err);
+        }
+
+        let with_urls = |urls: Option<Vec<Url>>, key: &str| match urls {
+            Some(urls) => conf.with_urls(urls).into_config_result(|| cwp + key),
+            None => Ok(conf.clone()),
+        };
+        Ok(Self {
+            index: with_urls(index_urls, "index_urls")?,
+            submit: with_urls(submit_urls, "submit_urls")?,
+        })
+    }
+}
@@ -0,0 +1,184 @@
+use std::fmt::{Debug, Formatter};
+use std::process::Stdio;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::transaction::eip712::Eip712;
+use ethers::types::{Address, Signature, H256};
+use ethers::utils::{hash_message, keccak256};
+use ethers_signers::Signer;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+/// Digest signed once at startup to learn the address of the external key.
+const ADDRESS_PROBE: &[u8] = b"hyperlane external signer address probe";
+
+/// Error types for the external signer
+#[derive(Debug, thiserror::Error)]
+pub enum ExternalSignerError {
+    /// The signer command could not be run
+    #[error("Failed to run external signer `{0}`: {1}")]
+    Spawn(String, std::io::Error),
+    /// The signer command did not finish in time
+    #[error("External signer `{0}` timed out after {1:?}")]
+    Timeout(String, Duration),
+    /// The signer command exited with a non-zero status
+    #[error("External signer `{0}` failed with {1}: {2}")]
+    Failed(String, std::process::ExitStatus, String),
+    /// The signer command printed something other than a signature
+    #[error("Invalid signature from external signer: {0}")]
+    InvalidSignature(String),
+    /// Failed to encode EIP-712 typed data
+    #[error("Failed to encode typed data: {0}")]
+    Eip712(String),
+}
+
+/// A signer which delegates to an external executable, e.g. to reach an HSM
+/// which has no native support.
+///
+/// For every signing request the command is spawned with the 32-byte digest
+/// written to its stdin as a `0x` prefixed hex string. It must print the 65
+/// byte `r || s || v` signature of the digest as hex to stdout and exit with
+/// status zero.
+#[derive(Clone)]
+pub struct ExternalSigner {
+    command: String,
+    timeout: Duration,
+    address: Address,
+    chain_id: u64,
+}
+
+impl Debug for ExternalSigner {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExternalSigner")
+            .field("command", &self.command)
+            .field("timeout", &self.timeout)
+            .field("address", &self.address)
+            .field("chain_id", &self.chain_id)
+            .finish()
+    }
+}
+
+impl ExternalSigner {
+    /// Create a new signer for the given command. The command is run once to
+    /// sign a probe digest from which the signer address is recovered.
+    pub async fn new(
+        command: &str,
+        timeout: Duration,
+        chain_id: u64,
+    ) -> Result<Self, ExternalSignerError> {
+        let mut signer = Self {
+            command: command.to_owned(),
+            timeout,
+            address: Address::zero(),
+            chain_id,
+        };
+        let probe = H256::from(keccak256(ADDRESS_PROBE));
+        signer.address = signer
+            .run(probe)
+            .await?
+            .recover(probe)
+            .map_err(|e| ExternalSignerError::InvalidSignature(e.to_string()))?;
+        Ok(signer)
+    }
+
+    /// Run the command to sign `digest`, normalizing `v` to 27 or 28.
+    async fn run(&self, digest: H256) -> Result<Signature, ExternalSignerError> {
+        let spawn_err = |e| ExternalSignerError::Spawn(self.command.clone(), e);
+        let mut child = Command::new(&self.command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(spawn_err)?;
+
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        stdin
+            .write_all(format!("{digest:?}\n").as_bytes())
+            .await
+            .map_err(spawn_err)?;
+        drop(stdin);
+
+        let output = tokio::time::timeout(self.timeout, child.wait_with_output())
+            .await
+            .map_err(|_| ExternalSignerError::Timeout(self.command.clone(), self.timeout))?
+            .map_err(spawn_err)?;
+        if !output.status.success() {
+            return Err(ExternalSignerError::Failed(
+                self.command.clone(),
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+            ));
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let mut signature = stdout
+            .trim()
+            .parse::<Signature>()
+            .map_err(|e| ExternalSignerError::InvalidSignature(e.to_string()))?;
+        if signature.v < 27 {
+            signature.v += 27;
+        }
+        Ok(signature)
+    }
+
+    /// Sign a 32-byte digest with the external key, checking that the
+    /// signature recovers to the signer address.
+    pub(crate) async fn sign_digest(&self, digest: H256) -> Result<Signature, ExternalSignerError> {
+        let signature = self.run(digest).await?;
+        if signature.recover(digest).ok() != Some(self.address) {
+            return Err(ExternalSignerError::InvalidSignature(
+                "signature does not recover to the external signer address".into(),
+            ));
+        }
+        Ok(signature)
+    }
+}
+
+#[async_trait]
+impl Signer for ExternalSigner {
+    type Error = ExternalSignerError;
+
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(
+        &self,
+        message: S,
+    ) -> Result<Signature, Self::Error> {
+        self.sign_digest(hash_message(message)).await
+    }
+
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature, Self::Error> {
+        let mut tx = tx.clone();
+        let chain_id = tx.chain_id().map(|id| id.as_u64()).unwrap_or(self.chain_id);
+        tx.set_chain_id(chain_id);
+
+        let mut signature = self.sign_digest(tx.sighash()).await?;
+        // EIP-155
+        signature.v = (signature.v - 27) + 35 + chain_id * 2;
+        Ok(signature)
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        payload: &T,
+    ) -> Result<Signature, Self::Error> {
+        let digest = payload
+            .encode_eip712()
+            .map_err(|e| ExternalSignerError::Eip712(e.to_string()))?;
+        self.sign_digest(digest.into()).await
+    }
+
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    fn with_chain_id<T: Into<u64>>(mut self, chain_id: T) -> Self {
+        self.chain_id = chain_id.into();
+        self
+    }
+}
@@ -0,0 +1,359 @@
+use std::fmt::{Debug, Formatter};
+
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::transaction::eip712::Eip712;
+use ethers::types::{Address, Signature, H256, U256};
+use ethers::utils::{hash_message, keccak256};
+use ethers_signers::Signer;
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::json;
+
+const GCP_KMS_API_URL: &str = "https://cloudkms.googleapis.com/v1";
+const GCP_METADATA_TOKEN_URL: &str =
+    "http://metadata.google.internal/computeMetadata/v1/instance/service-accounts/default/token";
+/// Env var which can be used to provide an OAuth access token directly rather
+/// than fetching one from the GCE metadata server.
+const GCP_ACCESS_TOKEN_ENV: &str = "GOOGLE_OAUTH_ACCESS_TOKEN";
+
+/// Order of the secp256k1 curve, used to normalize signatures to low-s form.
+pub(crate) const SECP256K1_N: [u8; 32] = [
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe,
+    0xba, 0xae, 0xdc, 0xe6, 0xaf, 0x48, 0xa0, 0x3b, 0xbf, 0xd2, 0x5e, 0x8c, 0xd0, 0x36, 0x41, 0x41,
+];
+
+/// Error types for the GCP KMS signer
+#[derive(Debug, thiserror::Error)]
+pub enum GcpKmsSignerError {
+    /// Error communicating with the KMS or metadata APIs
+    #[error(transparent)]
+    Reqwest(#[from] reqwest::Error),
+    /// The KMS API returned an unexpected response
+    #[error("Unexpected response from GCP KMS: {0}")]
+    UnexpectedResponse(String),
+    /// The public key of the KMS key could not be decoded
+    #[error("Invalid GCP KMS public key: {0}")]
+    InvalidPublicKey(String),
+    /// The signature returned by KMS could not be decoded
+    #[error("Invalid GCP KMS signature: {0}")]
+    InvalidSignature(String),
+    /// Failed to encode EIP-712 typed data
+    #[error("Failed to encode typed data: {0}")]
+    Eip712(String),
+}
+
+/// A signer using a secp256k1 key stored in GCP Cloud KMS.
+///
+/// Credentials are taken from the `GOOGLE_OAUTH_ACCESS_TOKEN` env var when set
+/// and otherwise fetched from the GCE metadata server.
+#[derive(Clone)]
+pub struct GcpKmsSigner {
+    client: Client,
+    /// Fully qualified name of the crypto key version
+    key_version_name: String,
+    address: Address,
+    chain_id: u64,
+}
+
+impl Debug for GcpKmsSigner {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GcpKmsSigner")
+            .field("key_version_name", &self.key_version_name)
+            .field("address", &self.address)
+            .field("chain_id", &self.chain_id)
+            .finish()
+    }
+}
+
+#[derive(Deserialize)]
+struct AccessTokenResponse {
+    access_token: String,
+}
+
+#[derive(Deserialize)]
+struct PublicKeyResponse {
+    pem: String,
+}
+
+#[derive(Deserialize)]
+struct AsymmetricSignResponse {
+    signature: String,
+}
+
+impl GcpKmsSigner {
+    /// Create a new signer for the given key version. This fetches the public
+    /// key from KMS to derive the signer address.
+    pub async fn new(
+        project_id: &str,
+        location: &str,
+        key_ring: &str,
+        key_name: &str,
+        version: u32,
+        chain_id: u64,
+    ) -> Result<Self, GcpKmsSignerError> {
+        let key_version_name = format!(
+            "projects/{project_id}/locations/{location}/keyRings/{key_ring}/cryptoKeys/{key_name}/cryptoKeyVersions/{version}"
+        );
+        let mut signer = Self {
+            client: Client::new(),
+            key_version_name,
+            address: Address::zero(),
+            chain_id,
+        };
+        signer.address = signer.fetch_address().await?;
+        Ok(signer)
+    }
+
+    async fn access_token(&self) -> Result<String, GcpKmsSignerError> {
+        if let Ok(token) = std::env::var(GCP_ACCESS_TOKEN_ENV) {
+            return Ok(token);
+        }
+        let res = self
+            .client
+            .get(GCP_METADATA_TOKEN_URL)
+            .header("Metadata-Flavor", "Google")
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+        let res: AccessTokenResponse = serde_json::from_slice(&res)
+            .map_err(|e| GcpKmsSignerError::UnexpectedResponse(e.to_string()))?;
+        Ok(res.access_token)
+    }
+
+    async fn fetch_address(&self) -> Result<Address, GcpKmsSignerError> {
+        let res = self
+            .client
+            .get(format!(
+                "{GCP_KMS_API_URL}/{}/publicKey",
+                self.key_version_name
+            ))
+            .bearer_auth(self.access_token().await?)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+        let res: PublicKeyResponse = serde_json::from_slice(&res)
+            .map_err(|e| GcpKmsSignerError::UnexpectedResponse(e.to_string()))?;
+        address_from_pem(&res.pem).map_err(GcpKmsSignerError::InvalidPublicKey)
+    }
+
+    /// Sign a 32-byte digest with the KMS key, returning a signature with a
+    /// `v` value of 27 or 28.
+    pub(crate) async fn sign_digest(&self, digest: H256) -> Result<Signature, GcpKmsSignerError> {
+        let body = json!({ "digest": { "sha256": BASE64.encode(digest.as_bytes()) } });
+        let res = self
+            .client
+            .post(format!(
+                "{GCP_KMS_API_URL}/{}:asymmetricSign",
+                self.key_version_name
+            ))
+            .bearer_auth(self.access_token().await?)
+            .header("Content-Type", "application/json")
+            .body(body.to_string())
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+        let res: AsymmetricSignResponse = serde_json::from_slice(&res)
+            .map_err(|e| GcpKmsSignerError::UnexpectedResponse(e.to_string()))?;
+        let der = BASE64
+            .decode(res.signature)
+            .map_err(|e| GcpKmsSignerError::InvalidSignature(e.to_string()))?;
+        signature_from_der(&der, digest, self.address)
+    }
+}
+
+/// Turn a DER encoded signature of `digest` into a low-s signature with the
+/// `v` value of 27 or 28 which recovers to `address`.
+fn signature_from_der(
+    der: &[u8],
+    digest: H256,
+    address: Address,
+) -> Result<Signature, GcpKmsSignerError> {
+    let (r, s) = decode_der_signature(der)?;
+    let n = U256::from_big_endian(&SECP256K1_N);
+    let s = if s > n / 2 { n - s } else { s };
+
+    [27, 28]
+        .into_iter()
+        .map(|v| Signature { r, s, v })
+        .find(|sig| sig.recover(digest).ok() == Some(address))
+        .ok_or_else(|| {
+            GcpKmsSignerError::InvalidSignature(
+                "signature does not recover to the KMS key address".into(),
+            )
+        })
+}
+
+/// Derive the address of a PEM encoded secp256k1 public key.
+pub(crate) fn address_from_pem(pem: &str) -> Result<Address, String> {
+    let der = BASE64
+        .decode(
+            pem.lines()
+                .filter(|l| !l.starts_with("-----"))
+                .collect::<String>(),
+        )
+        .map_err(|e| e.to_string())?;
+    // The SPKI encoding of a secp256k1 key ends with the uncompressed point.
+    if der.len() < 65 || der[der.len() - 65] != 0x04 {
+        return Err("expected an uncompressed secp256k1 public key".into());
+    }
+    let point = &der[der.len() - 64..];
+    Ok(Address::from_slice(&keccak256(point)[12..]))
+}
+
+/// Decode an ASN.1 DER encoded ECDSA signature into its `(r, s)` components.
+pub(crate) fn decode_der_signature(der: &[u8]) -> Result<(U256, U256), GcpKmsSignerError> {
+    let invalid = || GcpKmsSignerError::InvalidSignature("malformed DER signature".into());
+    let read_int = |bytes: &[u8]| -> Result<(U256, usize), GcpKmsSignerError> {
+        if bytes.len() < 2 || bytes[0] != 0x02 {
+            return Err(invalid());
+        }
+        let len = bytes[1] as usize;
+        let int = bytes.get(2..2 + len).ok_or_else(invalid)?;
+        // strip the sign padding byte
+        let int = if int.len() == 33 && int[0] == 0 {
+            &int[1..]
+        } else {
+            int
+        };
+        if int.len() > 32 {
+            return Err(invalid());
+        }
+        Ok((U256::from_big_endian(int), 2 + len))
+    };
+
+    if der.len() < 2 || der[0] != 0x30 {
+        return Err(invalid());
+    }
+    let (r, r_len) = read_int(&der[2..])?;
+    let (s, _) = read_int(der.get(2 + r_len..).ok_or_else(invalid)?)?;
+    Ok((r, s))
+}
+
+#[async_trait]
+impl Signer for GcpKmsSigner {
+    type Error = GcpKmsSignerError;
+
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(
+        &self,
+        message: S,
+    ) -> Result<Signature, Self::Error> {
+        self.sign_digest(hash_message(message)).await
+    }
+
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature, Self::Error> {
+        let mut tx = tx.clone();
+        let chain_id = tx.chain_id().map(|id| id.as_u64()).unwrap_or(self.chain_id);
+        tx.set_chain_id(chain_id);
+
+        let mut signature = self.sign_digest(tx.sighash()).await?;
+        // EIP-155
+        signature.v = (signature.v - 27) + 35 + chain_id * 2;
+        Ok(signature)
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        payload: &T,
+    ) -> Result<Signature, Self::Error> {
+        let digest = payload
+            .encode_eip712()
+            .map_err(|e| GcpKmsSignerError::Eip712(e.to_string()))?;
+        self.sign_digest(digest.into()).await
+    }
+
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    fn with_chain_id<T: Into<u64>>(mut self, chain_id: T) -> Self {
+        self.chain_id = chain_id.into();
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ethers::core::k256::{elliptic_curve::sec1::ToEncodedPoint, PublicKey};
+    use ethers_signers::LocalWallet;
+
+    use super::*;
+
+    /// DER encode a signature the way KMS returns it.
+    fn encode_der(r: U256, s: U256) -> Vec<u8> {
+        let encode_int = |int: U256| {
+            let mut bytes = [0u8; 32];
+            int.to_big_endian(&mut bytes);
+            let zeros = bytes.iter().take_while(|b| **b == 0).count();
+            let mut int = bytes[zeros..].to_vec();
+            // integers are signed, so a set high bit needs a padding byte
+            if int.first().map_or(true, |b| b & 0x80 != 0) {
+                int.insert(0, 0);
+            }
+            let mut encoded = vec![0x02, int.len() as u8];
+            encoded.extend(int);
+            encoded
+        };
+        let mut body = encode_int(r);
+        body.extend(encode_int(s));
+        let mut der = vec![0x30, body.len() as u8];
+        der.extend(body);
+        der
+    }
+
+    #[test]
+    fn recovers_signatures_of_a_known_key() {
+        let wallet: LocalWallet =
+            "1111111111111111111111111111111111111111111111111111111111111111"
+                .parse()
+                .unwrap();
+
+        // the SPKI prefix of a secp256k1 public key, followed by the point
+        let point = PublicKey::from(&wallet.signer().verifying_key()).to_encoded_point(false);
+        let mut spki = hex::decode("3056301006072a8648ce3d020106052b8104000a034200").unwrap();
+        spki.extend_from_slice(point.as_bytes());
+        let pem = format!(
+            "-----BEGIN PUBLIC KEY-----\n{}\n-----END PUBLIC KEY-----\n",
+            BASE64.encode(&spki)
+        );
+        assert_eq!(address_from_pem(&pem).unwrap(), wallet.address());
+
+        let digest = hash_message("hello");
+        let expected = wallet.sign_hash(digest);
+        // KMS may return the high s form of the signature
+        let n = U256::from_big_endian(&SECP256K1_N);
+        for s in [expected.s, n - expected.s] {
+            let signature =
+                signature_from_der(&encode_der(expected.r, s), digest, wallet.address()).unwrap();
+            assert_eq!(signature, expected);
+            assert_eq!(signature.recover(digest).unwrap(), wallet.address());
+        }
+
+        let der = encode_der(expected.r, expected.s);
+        assert!(signature_from_der(&der, digest, Address::repeat_byte(1)).is_err());
+    }
+
+    #[test]
+    fn decodes_der_signature() {
+        let mut der = vec![0x30, 0x45, 0x02, 0x21, 0x00];
+        der.extend([0x80; 32]);
+        der.extend([0x02, 0x20]);
+        der.extend([0x01; 32]);
+
+        let (r, s) = decode_der_signature(&der).unwrap();
+        assert_eq!(r, U256::from_big_endian(&[0x80; 32]));
+        assert_eq!(s, U256::from_big_endian(&[0x01; 32]));
+        assert!(decode_der_signature(&der[..10]).is_err());
+    }
+}
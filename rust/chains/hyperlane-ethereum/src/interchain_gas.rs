@@ -8,17 +8,18 @@ use std::sync::Arc;
 use async_trait::async_trait;
 use ethers::prelude::Middleware;
 use hyperlane_core::{
-    ChainCommunicationError, ChainResult, ContractLocator, HyperlaneAbi, HyperlaneChain,
-    HyperlaneContract, HyperlaneDomain, HyperlaneProvider, Indexer, InterchainGasPaymaster,
-    InterchainGasPayment, LogMeta, SequenceIndexer, H160, H256,
+    ChainResult, ContractLocator, HyperlaneAbi, HyperlaneChain, HyperlaneContract, HyperlaneDomain,
+    HyperlaneProvider, Indexer, InterchainGasPaymaster, InterchainGasPayment, LogMeta,
+    SequenceIndexer, H160, H256,
 };
 use tracing::instrument;
 
 use crate::contracts::i_interchain_gas_paymaster::{
     IInterchainGasPaymaster as EthereumInterchainGasPaymasterInternal, IINTERCHAINGASPAYMASTER_ABI,
 };
+use crate::provider::get_finalized_block_number;
 use crate::trait_builder::BuildableWithProvider;
-use crate::EthereumProvider;
+use crate::{EthereumProvider, FinalityTag};
 
 impl<M> Display for EthereumInterchainGasPaymasterInternal<M>
 where
@@ -32,6 +33,7 @@ where
 pub struct InterchainGasPaymasterIndexerBuilder {
     pub mailbox_address: H160,
     pub finality_blocks: u32,
+    pub finality_tag: Option<FinalityTag>,
 }
 
 #[async_trait]
@@ -47,6 +49,7 @@ impl BuildableWithProvider for InterchainGasPaymasterIndexerBuilder {
             Arc::new(provider),
             locator,
             self.finality_blocks,
+            self.finality_tag,
         ))
     }
 }
@@ -60,6 +63,7 @@ where
     contract: Arc<EthereumInterchainGasPaymasterInternal<M>>,
     provider: Arc<M>,
     finality_blocks: u32,
+    finality_tag: Option<FinalityTag>,
 }
 
 impl<M> EthereumInterchainGasPaymasterIndexer<M>
@@ -67,7 +71,12 @@ where
     M: Middleware + 'static,
 {
     /// Create new EthereumInterchainGasPaymasterIndexer
-    pub fn new(provider: Arc<M>, locator: &ContractLocator, finality_blocks: u32) -> Self {
+    pub fn new(
+        provider: Arc<M>,
+        locator: &ContractLocator,
+        finality_blocks: u32,
+        finality_tag: Option<FinalityTag>,
+    ) -> Self {
         Self {
             contract: Arc::new(EthereumInterchainGasPaymasterInternal::new(
                 locator.address,
@@ -75,6 +84,7 @@ where
             )),
             provider,
             finality_blocks,
+            finality_tag,
         }
     }
 }
@@ -114,13 +124,7 @@ where
 
     #[instrument(level = "debug", err, ret, skip(self))]
     async fn get_finalized_block_number(&self) -> ChainResult<u32> {
-        Ok(self
-            .provider
-            .get_block_number()
-            .await
-            .map_err(ChainCommunicationError::from_other)?
-            .as_u32()
-            .saturating_sub(self.finality_blocks))
+        get_finalized_block_number(&*self.provider, self.finality_blocks, self.finality_tag).await
     }
 }
 
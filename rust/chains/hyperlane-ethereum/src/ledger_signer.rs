@@ -0,0 +1,415 @@
+use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::transaction::eip712::Eip712;
+use ethers::types::{Address, Signature, U256};
+use ethers_signers::Signer;
+use hidapi::{HidApi, HidDevice};
+
+/// Error types for the Ledger signer
+#[derive(Debug, thiserror::Error)]
+pub enum LedgerSignerError {
+    /// Error communicating with the USB HID device
+    #[error(transparent)]
+    Hid(#[from] hidapi::HidError),
+    /// There is no Ledger device with the configured index
+    #[error("No Ledger device with index {index} found, {found} connected")]
+    DeviceNotFound {
+        /// The index of the device to use
+        index: u32,
+        /// Number of connected Ledger devices
+        found: usize,
+    },
+    /// The HD derivation path could not be parsed
+    #[error("Invalid HD path `{0}`")]
+    InvalidPath(String),
+    /// The Ethereum app rejected the request
+    #[error("Ledger returned status {0:#06x}, is it unlocked with the Ethereum app open?")]
+    Status(u16),
+    /// The Ledger returned an unexpected response
+    #[error("Unexpected response from Ledger: {0}")]
+    UnexpectedResponse(String),
+    /// Failed to encode EIP-712 typed data
+    #[error("Failed to encode typed data: {0}")]
+    Eip712(String),
+    /// The blocking device task panicked or was cancelled
+    #[error("Ledger task failed: {0}")]
+    Task(String),
+}
+
+/// USB vendor id of Ledger devices.
+const LEDGER_VENDOR_ID: u16 = 0x2c97;
+/// HID usage page of the APDU interface of Ledger devices.
+const LEDGER_USAGE_PAGE: u16 = 0xffa0;
+/// Size of a HID packet exchanged with the device.
+const HID_PACKET_SIZE: usize = 64;
+/// Channel and tag every HID packet of the APDU transport starts with.
+const HID_CHANNEL: u16 = 0x0101;
+const HID_TAG_APDU: u8 = 0x05;
+/// How long to wait for each response packet, in milliseconds. Signing waits
+/// for the user to confirm on the device, so this is generous.
+const HID_READ_TIMEOUT_MS: i32 = 5 * 60 * 1000;
+/// Status word of a successful APDU.
+const STATUS_OK: u16 = 0x9000;
+
+/// Class and instructions of the Ledger Ethereum app.
+const CLA: u8 = 0xe0;
+const INS_GET_PUBLIC_KEY: u8 = 0x02;
+const INS_SIGN: u8 = 0x04;
+const INS_SIGN_PERSONAL_MESSAGE: u8 = 0x08;
+const INS_SIGN_EIP712: u8 = 0x0c;
+/// `p1` of the first and following chunks of a payload.
+const P1_FIRST: u8 = 0x00;
+const P1_MORE: u8 = 0x80;
+
+/// A signer using a key stored on a Ledger hardware wallet running the
+/// Ethereum app.
+///
+/// The device is selected by its index among the connected Ledger devices,
+/// ordered by their USB path, so several devices can be used side by side.
+/// Every signing request must be confirmed on the device. The device can't be
+/// opened twice, so clones of the signer share it.
+#[derive(Clone)]
+pub struct LedgerSigner {
+    device: Arc<Mutex<HidDevice>>,
+    hd_path: String,
+    path: Vec<u32>,
+    device_index: u32,
+    address: Address,
+    chain_id: u64,
+}
+
+impl Debug for LedgerSigner {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LedgerSigner")
+            .field("hd_path", &self.hd_path)
+            .field("device_index", &self.device_index)
+            .field("address", &self.address)
+            .field("chain_id", &self.chain_id)
+            .finish()
+    }
+}
+
+impl LedgerSigner {
+    /// Open the Ledger device with the given index and fetch the address of
+    /// the key at `hd_path`, e.g. `m/44'/60'/0'/0/0`.
+    pub async fn new(
+        hd_path: &str,
+        device_index: u32,
+        chain_id: u64,
+    ) -> Result<Self, LedgerSignerError> {
+        let path = parse_hd_path(hd_path)?;
+        let device = run_blocking(move || open_device(device_index)).await?;
+        let mut signer = Self {
+            device: Arc::new(Mutex::new(device)),
+            hd_path: hd_path.to_owned(),
+            path,
+            device_index,
+            address: Address::zero(),
+            chain_id,
+        };
+        signer.address = signer.fetch_address().await?;
+        Ok(signer)
+    }
+
+    /// Send an APDU to the device and return the data of its response.
+    async fn exchange(&self, ins: u8, p1: u8, data: Vec<u8>) -> Result<Vec<u8>, LedgerSignerError> {
+        let device = self.device.clone();
+        run_blocking(move || {
+            let device = device
+                .lock()
+                .map_err(|e| LedgerSignerError::Task(e.to_string()))?;
+            let mut apdu = vec![CLA, ins, p1, 0x00, data.len() as u8];
+            apdu.extend_from_slice(&data);
+            exchange_apdu(&device, &apdu)
+        })
+        .await
+    }
+
+    async fn fetch_address(&self) -> Result<Address, LedgerSignerError> {
+        let res = self
+            .exchange(INS_GET_PUBLIC_KEY, P1_FIRST, encode_path(&self.path))
+            .await?;
+        // the public key is followed by the address as hex characters
+        let unexpected = || LedgerSignerError::UnexpectedResponse("invalid address".into());
+        let key_len = *res.first().ok_or_else(unexpected)? as usize;
+        let address_len = *res.get(1 + key_len).ok_or_else(unexpected)? as usize;
+        let address = res
+            .get(2 + key_len..2 + key_len + address_len)
+            .ok_or_else(unexpected)?;
+        std::str::from_utf8(address)
+            .ok()
+            .and_then(|a| a.parse().ok())
+            .ok_or_else(unexpected)
+    }
+
+    /// Sign `data` with the key at the derivation path, sending both in as
+    /// many chunks as necessary.
+    async fn sign_payload(&self, ins: u8, data: &[u8]) -> Result<Signature, LedgerSignerError> {
+        let mut payload = encode_path(&self.path);
+        payload.extend_from_slice(data);
+        let mut res = Vec::new();
+        for (i, chunk) in payload.chunks(255).enumerate() {
+            let p1 = if i == 0 { P1_FIRST } else { P1_MORE };
+            res = self.exchange(ins, p1, chunk.to_vec()).await?;
+        }
+        if res.len() != 65 {
+            return Err(LedgerSignerError::UnexpectedResponse(format!(
+                "expected a 65 byte signature, got {} bytes",
+                res.len()
+            )));
+        }
+        Ok(Signature {
+            r: U256::from_big_endian(&res[1..33]),
+            s: U256::from_big_endian(&res[33..65]),
+            v: res[0] as u64,
+        })
+    }
+}
+
+#[async_trait]
+impl Signer for LedgerSigner {
+    type Error = LedgerSignerError;
+
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(
+        &self,
+        message: S,
+    ) -> Result<Signature, Self::Error> {
+        let message = message.as_ref();
+        let mut payload = (message.len() as u32).to_be_bytes().to_vec();
+        payload.extend_from_slice(message);
+        self.sign_payload(INS_SIGN_PERSONAL_MESSAGE, &payload).await
+    }
+
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature, Self::Error> {
+        let mut tx = tx.clone();
+        if tx.chain_id().is_none() {
+            tx.set_chain_id(self.chain_id);
+        }
+        let chain_id = tx.chain_id().map(|id| id.as_u64()).unwrap_or(self.chain_id);
+        let mut signature = self.sign_payload(INS_SIGN, &tx.rlp()).await?;
+        // the app only returns the lowest byte of an EIP-155 `v`
+        let eip155_v = chain_id * 2 + 35;
+        if eip155_v + 1 > 255 {
+            let truncated = eip155_v % 256;
+            let parity = signature.v.abs_diff(truncated);
+            signature.v = match tx {
+                TypedTransaction::Legacy(_) => eip155_v + parity,
+                _ => parity % 2,
+            };
+        }
+        Ok(signature)
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        payload: &T,
+    ) -> Result<Signature, Self::Error> {
+        let domain_separator = payload
+            .domain_separator()
+            .map_err(|e| LedgerSignerError::Eip712(e.to_string()))?;
+        let struct_hash = payload
+            .struct_hash()
+            .map_err(|e| LedgerSignerError::Eip712(e.to_string()))?;
+        let mut hashes = domain_separator.to_vec();
+        hashes.extend_from_slice(&struct_hash);
+        self.sign_payload(INS_SIGN_EIP712, &hashes).await
+    }
+
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    fn with_chain_id<T: Into<u64>>(mut self, chain_id: T) -> Self {
+        self.chain_id = chain_id.into();
+        self
+    }
+}
+
+/// Run blocking device IO without blocking the async runtime.
+async fn run_blocking<T: Send + 'static>(
+    f: impl FnOnce() -> Result<T, LedgerSignerError> + Send + 'static,
+) -> Result<T, LedgerSignerError> {
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| LedgerSignerError::Task(e.to_string()))?
+}
+
+/// Open the Ledger device with the given index, ordering the connected
+/// devices by their USB path so the index is stable.
+fn open_device(index: u32) -> Result<HidDevice, LedgerSignerError> {
+    let api = HidApi::new()?;
+    let mut devices = api
+        .device_list()
+        .filter(|d| {
+            // the usage page is not reported on linux, where the APDU
+            // interface is the first one
+            d.vendor_id() == LEDGER_VENDOR_ID
+                && (d.usage_page() == LEDGER_USAGE_PAGE || d.interface_number() == 0)
+        })
+        .collect::<Vec<_>>();
+    devices.sort_by(|a, b| a.path().cmp(b.path()));
+    devices.dedup_by(|a, b| a.path() == b.path());
+    let device = devices
+        .get(index as usize)
+        .ok_or(LedgerSignerError::DeviceNotFound {
+            index,
+            found: devices.len(),
+        })?;
+    Ok(device.open_device(&api)?)
+}
+
+/// Exchange an APDU with the device, returning the response data once the
+/// status word reports success.
+fn exchange_apdu(device: &HidDevice, apdu: &[u8]) -> Result<Vec<u8>, LedgerSignerError> {
+    for packet in encode_packets(apdu) {
+        // the first byte is the report id, which Ledger devices don't use
+        let mut report = vec![0x00];
+        report.extend_from_slice(&packet);
+        device.write(&report)?;
+    }
+
+    let mut packets = Vec::new();
+    loop {
+        let mut packet = [0u8; HID_PACKET_SIZE];
+        let read = device.read_timeout(&mut packet, HID_READ_TIMEOUT_MS)?;
+        if read == 0 {
+            return Err(LedgerSignerError::UnexpectedResponse(
+                "timed out waiting for the device".into(),
+            ));
+        }
+        packets.push(packet);
+        if let Some(mut res) = decode_packets(&packets)? {
+            let status = res
+                .len()
+                .checked_sub(2)
+                .map(|i| u16::from_be_bytes([res[i], res[i + 1]]))
+                .ok_or_else(|| LedgerSignerError::UnexpectedResponse("missing status".into()))?;
+            if status != STATUS_OK {
+                return Err(LedgerSignerError::Status(status));
+            }
+            res.truncate(res.len() - 2);
+            return Ok(res);
+        }
+    }
+}
+
+/// Split an APDU into HID packets. Every packet starts with the channel, tag
+/// and its sequence number, and the first one also with the APDU length.
+fn encode_packets(apdu: &[u8]) -> Vec<[u8; HID_PACKET_SIZE]> {
+    let mut data = (apdu.len() as u16).to_be_bytes().to_vec();
+    data.extend_from_slice(apdu);
+    data.chunks(HID_PACKET_SIZE - 5)
+        .enumerate()
+        .map(|(seq, chunk)| {
+            let mut packet = [0u8; HID_PACKET_SIZE];
+            packet[..2].copy_from_slice(&HID_CHANNEL.to_be_bytes());
+            packet[2] = HID_TAG_APDU;
+            packet[3..5].copy_from_slice(&(seq as u16).to_be_bytes());
+            packet[5..5 + chunk.len()].copy_from_slice(chunk);
+            packet
+        })
+        .collect()
+}
+
+/// Reassemble a response from HID packets, returning `None` while packets are
+/// still missing.
+fn decode_packets(packets: &[[u8; HID_PACKET_SIZE]]) -> Result<Option<Vec<u8>>, LedgerSignerError> {
+    let mut data = Vec::new();
+    for (seq, packet) in packets.iter().enumerate() {
+        if packet[..2] != HID_CHANNEL.to_be_bytes()
+            || packet[2] != HID_TAG_APDU
+            || packet[3..5] != (seq as u16).to_be_bytes()
+        {
+            return Err(LedgerSignerError::UnexpectedResponse(
+                "invalid HID packet".into(),
+            ));
+        }
+        data.extend_from_slice(&packet[5..]);
+    }
+    let Some(len) = data.get(..2).map(|l| u16::from_be_bytes([l[0], l[1]]) as usize) else {
+        return Ok(None);
+    };
+    if data.len() < 2 + len {
+        return Ok(None);
+    }
+    Ok(Some(data[2..2 + len].to_vec()))
+}
+
+/// Parse an HD derivation path such as `m/44'/60'/0'/0/0`, where `'` marks
+/// hardened indices.
+fn parse_hd_path(path: &str) -> Result<Vec<u32>, LedgerSignerError> {
+    let invalid = || LedgerSignerError::InvalidPath(path.to_owned());
+    let mut parts = path.trim().split('/');
+    if parts.next() != Some("m") {
+        return Err(invalid());
+    }
+    let indices = parts
+        .map(|part| {
+            let (index, hardened) = match part.strip_suffix('\'') {
+                Some(index) => (index, true),
+                None => (part, false),
+            };
+            let index: u32 = index.parse().map_err(|_| invalid())?;
+            if index >= 1 << 31 {
+                return Err(invalid());
+            }
+            Ok(if hardened { index | 1 << 31 } else { index })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    // the app accepts at most 10 indices
+    if indices.is_empty() || indices.len() > 10 {
+        return Err(invalid());
+    }
+    Ok(indices)
+}
+
+/// Encode a derivation path as the app expects it, the number of indices
+/// followed by each of them.
+fn encode_path(path: &[u32]) -> Vec<u8> {
+    let mut encoded = vec![path.len() as u8];
+    for index in path {
+        encoded.extend_from_slice(&index.to_be_bytes());
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_hd_paths() {
+        assert_eq!(
+            parse_hd_path("m/44'/60'/1'/0/3").unwrap(),
+            vec![44 | 1 << 31, 60 | 1 << 31, 1 | 1 << 31, 0, 3]
+        );
+        for path in ["44'/60'", "m", "m/44'/x", "m/2147483648"] {
+            assert!(parse_hd_path(path).is_err(), "{path}");
+        }
+        assert_eq!(
+            encode_path(&[44 | 1 << 31, 0]),
+            vec![2, 0x80, 0, 0, 44, 0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn frames_apdus_in_hid_packets() {
+        let apdu: Vec<u8> = (0..150).map(|i| i as u8).collect();
+        let packets = encode_packets(&apdu);
+        assert_eq!(packets.len(), 3);
+        assert_eq!(&packets[1][..5], &[0x01, 0x01, 0x05, 0x00, 0x01]);
+        assert_eq!(decode_packets(&packets[..2]).unwrap(), None);
+        assert_eq!(decode_packets(&packets).unwrap(), Some(apdu));
+
+        let mut out_of_order = packets.clone();
+        out_of_order.swap(1, 2);
+        assert!(decode_packets(&out_of_order).is_err());
+    }
+}
@@ -10,10 +10,11 @@ use ethers::prelude::{abi, Lazy, Middleware};
 
 #[cfg(not(doctest))]
 pub use self::{
-    aggregation_ism::*, ccip_read_ism::*, config::*, config::*, interchain_gas::*,
-    interchain_gas::*, interchain_security_module::*, interchain_security_module::*, mailbox::*,
-    mailbox::*, multisig_ism::*, provider::*, routing_ism::*, rpc_clients::*, signers::*,
-    singleton_signer::*, trait_builder::*, validator_announce::*,
+    aggregation_ism::*, ccip_read_ism::*, config::*, config::*, external_signer::*,
+    gcp_kms_signer::*, interchain_gas::*, interchain_gas::*, interchain_security_module::*,
+    interchain_security_module::*, ledger_signer::*, mailbox::*, mailbox::*, multisig_ism::*,
+    nonce::*, provider::*, relay_service::*, routing_ism::*, rpc_clients::*, signers::*,
+    singleton_signer::*, trait_builder::*, validator_announce::*, vault_signer::*,
 };
 
 #[cfg(not(doctest))]
@@ -67,6 +68,18 @@ mod rpc_clients;
 
 mod signers;
 
+mod gcp_kms_signer;
+
+mod external_signer;
+
+mod ledger_signer;
+
+mod vault_signer;
+
+mod relay_service;
+
+mod nonce;
+
 #[cfg(not(doctest))]
 mod singleton_signer;
 
@@ -5,27 +5,33 @@ use std::collections::HashMap;
 use std::num::NonZeroU64;
 use std::ops::RangeInclusive;
 use std::sync::Arc;
+use std::time::Duration;
 
 use async_trait::async_trait;
 use ethers::abi::AbiEncode;
 use ethers::prelude::Middleware;
-use ethers_contract::builders::ContractCall;
+use ethers::types::{Address, Bytes};
+use ethers_contract::{builders::ContractCall, EthEvent};
 use tracing::instrument;
 
 use hyperlane_core::accumulator::incremental::IncrementalMerkle;
 use hyperlane_core::accumulator::TREE_DEPTH;
 use hyperlane_core::{
-    utils::fmt_bytes, ChainCommunicationError, ChainResult, Checkpoint, ContractLocator,
-    HyperlaneAbi, HyperlaneChain, HyperlaneContract, HyperlaneDomain, HyperlaneMessage,
-    HyperlaneProtocolError, HyperlaneProvider, Indexer, LogMeta, Mailbox, RawHyperlaneMessage,
-    SequenceIndexer, TxCostEstimate, TxOutcome, H160, H256, U256,
+    utils::fmt_bytes, BatchItem, BatchResult, ChainCommunicationError, ChainResult, Checkpoint,
+    ContractLocator, HyperlaneAbi, HyperlaneChain, HyperlaneContract, HyperlaneDomain,
+    HyperlaneMessage, HyperlaneProtocolError, HyperlaneProvider, Indexer, LogMeta, Mailbox,
+    RawHyperlaneMessage, SequenceIndexer, TxCostEstimate, TxOutcome, H160, H256, U256,
 };
 
 use crate::contracts::arbitrum_node_interface::ArbitrumNodeInterface;
-use crate::contracts::i_mailbox::{IMailbox as EthereumMailboxInternal, ProcessCall, IMAILBOX_ABI};
+use crate::contracts::i_mailbox::{
+    DispatchFilter, IMailbox as EthereumMailboxInternal, ProcessCall, ProcessIdFilter, IMAILBOX_ABI,
+};
+use crate::contracts::multicall::Multicall;
+use crate::provider::get_finalized_block_number;
 use crate::trait_builder::BuildableWithProvider;
 use crate::tx::{fill_tx_gas_params, report_tx};
-use crate::EthereumProvider;
+use crate::{EthereumProvider, FinalityTag, GasOracleConf, TxType, DEFAULT_CONFIRMATION_TIMEOUT};
 
 /// derived from `forge inspect Mailbox storage --pretty`
 const MERKLE_TREE_CONTRACT_SLOT: u32 = 152;
@@ -41,6 +47,8 @@ where
 
 pub struct SequenceIndexerBuilder {
     pub finality_blocks: u32,
+    pub finality_tag: Option<FinalityTag>,
+    pub event_topics: Vec<H256>,
 }
 
 #[async_trait]
@@ -56,12 +64,16 @@ impl BuildableWithProvider for SequenceIndexerBuilder {
             Arc::new(provider),
             locator,
             self.finality_blocks,
+            self.finality_tag,
+            self.event_topics.clone(),
         ))
     }
 }
 
 pub struct DeliveryIndexerBuilder {
     pub finality_blocks: u32,
+    pub finality_tag: Option<FinalityTag>,
+    pub event_topics: Vec<H256>,
 }
 
 #[async_trait]
@@ -77,6 +89,8 @@ impl BuildableWithProvider for DeliveryIndexerBuilder {
             Arc::new(provider),
             locator,
             self.finality_blocks,
+            self.finality_tag,
+            self.event_topics.clone(),
         ))
     }
 }
@@ -90,14 +104,25 @@ where
     contract: Arc<EthereumMailboxInternal<M>>,
     provider: Arc<M>,
     finality_blocks: u32,
+    finality_tag: Option<FinalityTag>,
+    event_topics: Vec<H256>,
 }
 
 impl<M> EthereumMailboxIndexer<M>
 where
     M: Middleware + 'static,
 {
-    /// Create new EthereumMailboxIndexer
-    pub fn new(provider: Arc<M>, locator: &ContractLocator, finality_blocks: u32) -> Self {
+    /// Create new EthereumMailboxIndexer. If `event_topics` is not empty only
+    /// events whose signature hash is one of them are indexed. With a
+    /// `finality_tag` the tagged block is final rather than the one
+    /// `finality_blocks` below the tip.
+    pub fn new(
+        provider: Arc<M>,
+        locator: &ContractLocator,
+        finality_blocks: u32,
+        finality_tag: Option<FinalityTag>,
+        event_topics: Vec<H256>,
+    ) -> Self {
         let contract = Arc::new(EthereumMailboxInternal::new(
             locator.address,
             provider.clone(),
@@ -106,18 +131,19 @@ where
             contract,
             provider,
             finality_blocks,
+            finality_tag,
+            event_topics,
         }
     }
 
+    /// Whether events of the given type should be fetched.
+    fn indexes_event<E: EthEvent>(&self) -> bool {
+        self.event_topics.is_empty() || self.event_topics.contains(&H256::from(E::signature().0))
+    }
+
     #[instrument(level = "debug", err, ret, skip(self))]
     async fn get_finalized_block_number(&self) -> ChainResult<u32> {
-        Ok(self
-            .provider
-            .get_block_number()
-            .await
-            .map_err(ChainCommunicationError::from_other)?
-            .as_u32()
-            .saturating_sub(self.finality_blocks))
+        get_finalized_block_number(&*self.provider, self.finality_blocks, self.finality_tag).await
     }
 }
 
@@ -135,6 +161,9 @@ where
         &self,
         range: RangeInclusive<u32>,
     ) -> ChainResult<Vec<(HyperlaneMessage, LogMeta)>> {
+        if !self.indexes_event::<DispatchFilter>() {
+            return Ok(vec![]);
+        }
         let mut events: Vec<(HyperlaneMessage, LogMeta)> = self
             .contract
             .dispatch_filter()
@@ -177,6 +206,9 @@ where
 
     #[instrument(err, skip(self))]
     async fn fetch_logs(&self, range: RangeInclusive<u32>) -> ChainResult<Vec<(H256, LogMeta)>> {
+        if !self.indexes_event::<ProcessIdFilter>() {
+            return Ok(vec![]);
+        }
         Ok(self
             .contract
             .process_id_filter()
@@ -203,7 +235,18 @@ where
     }
 }
 
-pub struct MailboxBuilder {}
+pub struct MailboxBuilder {
+    /// How long to wait for a `process` transaction to be confirmed before
+    /// resubmitting it with a higher gas price
+    pub confirmation_timeout: Duration,
+    /// How the gas price of `process` transactions is set
+    pub tx_type: TxType,
+    /// Overrides applied to the estimated gas price of `process` transactions
+    pub gas_oracle: GasOracleConf,
+    /// Multicall3 contract used to process batches of messages in one
+    /// transaction
+    pub multicall_address: Option<H256>,
+}
 
 #[async_trait]
 impl BuildableWithProvider for MailboxBuilder {
@@ -214,7 +257,14 @@ impl BuildableWithProvider for MailboxBuilder {
         provider: M,
         locator: &ContractLocator,
     ) -> Self::Output {
-        Box::new(EthereumMailbox::new(Arc::new(provider), locator))
+        let mut mailbox = EthereumMailbox::new(Arc::new(provider), locator)
+            .with_confirmation_timeout(self.confirmation_timeout)
+            .with_tx_type(self.tx_type)
+            .with_gas_oracle(self.gas_oracle.clone());
+        if let Some(address) = self.multicall_address {
+            mailbox = mailbox.with_multicall_address(address);
+        }
+        Box::new(mailbox)
     }
 }
 
@@ -228,6 +278,10 @@ where
     domain: HyperlaneDomain,
     provider: Arc<M>,
     arbitrum_node_interface: Option<Arc<ArbitrumNodeInterface<M>>>,
+    multicall: Option<Arc<Multicall<M>>>,
+    confirmation_timeout: Duration,
+    tx_type: TxType,
+    gas_oracle: GasOracleConf,
 }
 
 impl<M> EthereumMailbox<M>
@@ -256,9 +310,40 @@ where
             domain: locator.domain.clone(),
             provider,
             arbitrum_node_interface,
+            multicall: None,
+            confirmation_timeout: DEFAULT_CONFIRMATION_TIMEOUT,
+            tx_type: TxType::default(),
+            gas_oracle: GasOracleConf::default(),
         }
     }
 
+    /// Resubmit `process` transactions with a higher gas price if they are
+    /// not confirmed within `timeout`.
+    pub fn with_confirmation_timeout(mut self, timeout: Duration) -> Self {
+        self.confirmation_timeout = timeout;
+        self
+    }
+
+    /// Set the gas price of `process` transactions as configured by `tx_type`.
+    pub fn with_tx_type(mut self, tx_type: TxType) -> Self {
+        self.tx_type = tx_type;
+        self
+    }
+
+    /// Adjust the estimated gas price of `process` transactions with
+    /// `gas_oracle`.
+    pub fn with_gas_oracle(mut self, gas_oracle: GasOracleConf) -> Self {
+        self.gas_oracle = gas_oracle;
+        self
+    }
+
+    /// Process batches of messages through the Multicall3 contract at
+    /// `address`.
+    pub fn with_multicall_address(mut self, address: H256) -> Self {
+        self.multicall = Some(Arc::new(Multicall::new(address, self.provider.clone())));
+        self
+    }
+
     /// Returns a ContractCall that processes the provided message.
     /// If the provided tx_gas_limit is None, gas estimation occurs.
     async fn process_contract_call(
@@ -271,7 +356,15 @@ where
             metadata.to_vec().into(),
             RawHyperlaneMessage::from(message).to_vec().into(),
         );
-        fill_tx_gas_params(tx, tx_gas_limit, self.provider.clone(), message.destination).await
+        fill_tx_gas_params(
+            tx,
+            tx_gas_limit,
+            self.provider.clone(),
+            message.destination,
+            self.tx_type,
+            &self.gas_oracle,
+        )
+        .await
     }
 }
 
@@ -439,10 +532,67 @@ where
         let contract_call = self
             .process_contract_call(message, metadata, tx_gas_limit)
             .await?;
-        let receipt = report_tx(contract_call).await?;
+        let receipt = report_tx(contract_call, &self.provider, self.confirmation_timeout).await?;
         Ok(receipt.into())
     }
 
+    #[instrument(skip(self, batch), fields(batch_size = batch.len()))]
+    async fn process_batch(&self, batch: &[BatchItem]) -> ChainResult<BatchResult> {
+        let multicall = self.multicall.as_ref().ok_or_else(|| {
+            ChainCommunicationError::from_other_str("No multicall address is configured")
+        })?;
+        // every call may fail on its own so one bad message does not revert
+        // the rest of the batch
+        let calls = batch
+            .iter()
+            .map(|item| {
+                (
+                    self.contract.address(),
+                    true,
+                    self.process_calldata(&item.message, &item.metadata).into(),
+                )
+            })
+            .collect::<Vec<(Address, bool, Bytes)>>();
+        let gas_limit = batch
+            .iter()
+            .fold(U256::zero(), |sum, item| sum.saturating_add(item.gas_limit));
+        let tx = multicall
+            .method::<_, ()>("aggregate3", calls)
+            .map_err(ChainCommunicationError::from_other)?;
+        let contract_call = fill_tx_gas_params(
+            tx,
+            Some(gas_limit),
+            self.provider.clone(),
+            self.domain.id(),
+            self.tx_type,
+            &self.gas_oracle,
+        )
+        .await?;
+        let receipt = report_tx(contract_call, &self.provider, self.confirmation_timeout).await?;
+
+        // aggregate3 succeeds even if some of the calls failed, the messages
+        // which were delivered are the ones the mailbox emitted `ProcessId` for
+        let processed = receipt
+            .logs
+            .iter()
+            .filter(|log| {
+                log.address == self.contract.address()
+                    && log.topics.first() == Some(&ProcessIdFilter::signature())
+            })
+            .filter_map(|log| log.topics.get(1).copied().map(H256::from))
+            .collect::<Vec<_>>();
+        let failed_indexes = batch
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| !processed.contains(&item.message.id()))
+            .map(|(i, _)| i)
+            .collect();
+        Ok(BatchResult {
+            outcome: receipt.into(),
+            failed_indexes,
+        })
+    }
+
     #[instrument(skip(self), fields(msg=%message, metadata=%fmt_bytes(metadata)))]
     async fn process_estimate_costs(
         &self,
@@ -485,7 +635,7 @@ where
 
         Ok(TxCostEstimate {
             gas_limit: gas_limit.into(),
-            gas_price: gas_price.into(),
+            gas_price: self.gas_oracle.apply(gas_price.into()),
             l2_gas_limit: l2_gas_limit.map(|v| v.into()),
         })
     }
@@ -520,11 +670,11 @@ mod test {
     };
 
     use hyperlane_core::{
-        ContractLocator, HyperlaneDomain, HyperlaneMessage, KnownHyperlaneDomain, Mailbox,
-        TxCostEstimate, H160, H256, U256,
+        BatchItem, ContractLocator, HyperlaneDomain, HyperlaneMessage, KnownHyperlaneDomain,
+        Mailbox, TxCostEstimate, H160, H256, U256,
     };
 
-    use crate::EthereumMailbox;
+    use crate::{EthereumMailbox, GasOracleConf, TxType};
 
     /// An amount of gas to add to the estimated gas
     const GAS_ESTIMATE_BUFFER: u32 = 50000;
@@ -558,16 +708,20 @@ mod test {
         // order, so we start with the final RPCs and work toward the first
         // RPCs
 
-        // RPC 4: eth_gasPrice by process_estimate_costs
+        // RPC 5: eth_gasPrice by process_estimate_costs
         // Return 15 gwei
         let gas_price: U256 =
             EthersU256::from(ethers::utils::parse_units("15", "gwei").unwrap()).into();
         mock_provider.push(gas_price).unwrap();
 
-        // RPC 3: eth_estimateGas to the ArbitrumNodeInterface's estimateRetryableTicket function by process_estimate_costs
+        // RPC 4: eth_estimateGas to the ArbitrumNodeInterface's estimateRetryableTicket function by process_estimate_costs
         let l2_gas_limit = U256::from(200000); // 200k gas
         mock_provider.push(l2_gas_limit).unwrap();
 
+        // RPC 3: eth_gasPrice from process_contract_call, the block has no base
+        // fee so the transaction falls back to a legacy gas price
+        mock_provider.push(gas_price).unwrap();
+
         // RPC 2: eth_getBlockByNumber from the estimate_eip1559_fees call in process_contract_call
         mock_provider.push(Block::<Transaction>::default()).unwrap();
 
@@ -593,4 +747,52 @@ mod test {
             },
         );
     }
+
+    #[tokio::test]
+    async fn process_contract_call_applies_gas_oracle() {
+        let mock_provider = Arc::new(MockProvider::new());
+        let provider = Arc::new(Provider::new(mock_provider.clone()));
+        let mailbox = EthereumMailbox::new(
+            provider,
+            &ContractLocator {
+                domain: &HyperlaneDomain::Known(KnownHyperlaneDomain::Ethereum),
+                address: H256::default(),
+            },
+        )
+        .with_tx_type(TxType::Legacy)
+        .with_gas_oracle(GasOracleConf {
+            min_gas_price: None,
+            max_gas_price: Some(U256::from(150)),
+            gas_price_multiplier: 2.0,
+        });
+
+        // RPC 1: eth_gasPrice, doubled to 200 and then capped at 150
+        mock_provider.push(U256::from(100)).unwrap();
+
+        let contract_call = mailbox
+            .process_contract_call(&HyperlaneMessage::default(), &[], Some(U256::from(100_000)))
+            .await
+            .unwrap();
+
+        assert_eq!(contract_call.tx.gas_price(), Some(EthersU256::from(150)));
+    }
+
+    #[tokio::test]
+    async fn process_batch_requires_multicall_address() {
+        let provider = Arc::new(Provider::new(MockProvider::new()));
+        let mailbox = EthereumMailbox::new(
+            provider,
+            &ContractLocator {
+                domain: &HyperlaneDomain::Known(KnownHyperlaneDomain::Ethereum),
+                address: H256::default(),
+            },
+        );
+
+        let batch = [BatchItem {
+            message: HyperlaneMessage::default(),
+            metadata: vec![],
+            gas_limit: U256::from(100_000),
+        }];
+        assert!(mailbox.process_batch(&batch).await.is_err());
+    }
 }
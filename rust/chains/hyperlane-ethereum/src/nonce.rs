@@ -0,0 +1,88 @@
+use async_trait::async_trait;
+use ethers::prelude::{BlockId, BlockNumber, FromErr, Middleware, NameOrAddress, U256};
+use ethers::types::transaction::eip2718::TypedTransaction;
+
+/// How the nonces of transactions submitted to a chain are chosen
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NonceStrategy {
+    /// Nonces are counted up locally from the transaction count of the signer
+    /// when the first transaction is sent. Only safe if nothing else submits
+    /// transactions with the same key.
+    #[default]
+    Sequential,
+    /// The pending nonce of the signer, including transactions in the
+    /// mempool, is queried from the chain before each submission so other
+    /// instances using the same key are accounted for.
+    External,
+    /// The nonce of every transaction is resolved by the provider from the
+    /// signer's confirmed transaction count, replacing any transaction still
+    /// stuck in the mempool.
+    PerTx,
+}
+
+/// Error type for the [`PendingNonceMiddleware`]
+#[derive(Debug, thiserror::Error)]
+#[error("{0}")]
+pub struct PendingNonceMiddlewareError<E: std::error::Error>(E);
+
+impl<E: std::error::Error> FromErr<E> for PendingNonceMiddlewareError<E> {
+    fn from(src: E) -> Self {
+        Self(src)
+    }
+}
+
+/// A middleware which sets the nonce of every transaction to the pending
+/// transaction count of its sender right before it is sent. Everything else is
+/// delegated.
+#[derive(Debug)]
+pub struct PendingNonceMiddleware<M> {
+    inner: M,
+}
+
+impl<M> PendingNonceMiddleware<M> {
+    /// Wrap `inner` to query the pending nonce for every transaction.
+    pub fn new(inner: M) -> Self {
+        Self { inner }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<M: Middleware> Middleware for PendingNonceMiddleware<M> {
+    type Error = PendingNonceMiddlewareError<M::Error>;
+    type Provider = M::Provider;
+    type Inner = M;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    async fn fill_transaction(
+        &self,
+        tx: &mut TypedTransaction,
+        block: Option<BlockId>,
+    ) -> Result<(), Self::Error> {
+        if let (None, Some(&from)) = (tx.nonce(), tx.from()) {
+            let nonce = self.get_transaction_count(from, None).await?;
+            tx.set_nonce(nonce);
+        }
+        self.inner
+            .fill_transaction(tx, block)
+            .await
+            .map_err(PendingNonceMiddlewareError)
+    }
+
+    /// Counts pending transactions unless a block is given, since this is
+    /// also how the signer middleware looks up the nonce.
+    async fn get_transaction_count<T: Into<NameOrAddress> + Send + Sync>(
+        &self,
+        from: T,
+        block: Option<BlockId>,
+    ) -> Result<U256, Self::Error> {
+        let block = block.unwrap_or_else(|| BlockNumber::Pending.into());
+        self.inner
+            .get_transaction_count(from, Some(block))
+            .await
+            .map_err(PendingNonceMiddlewareError)
+    }
+}
@@ -6,6 +6,7 @@ use std::time::Duration;
 use async_trait::async_trait;
 use derive_new::new;
 use ethers::prelude::Middleware;
+use ethers::types::BlockNumber;
 use hyperlane_core::ethers_core_types;
 use tokio::time::sleep;
 use tracing::instrument;
@@ -15,7 +16,7 @@ use hyperlane_core::{
     HyperlaneDomain, HyperlaneProvider, HyperlaneProviderError, TxnInfo, TxnReceiptInfo, H256,
 };
 
-use crate::BuildableWithProvider;
+use crate::{BuildableWithProvider, FinalityTag};
 
 /// Connection to an ethereum provider. Useful for querying information about
 /// the blockchain.
@@ -105,6 +106,33 @@ where
             .map_err(ChainCommunicationError::from_other)?;
         Ok(!code.is_empty())
     }
+
+    #[instrument(err, skip(self))]
+    async fn average_block_time(&self, sample_blocks: u32) -> ChainResult<Option<Duration>> {
+        let tip = self
+            .provider
+            .get_block_number()
+            .await
+            .map_err(ChainCommunicationError::from_other)?
+            .as_u64();
+        let start = tip.saturating_sub(sample_blocks as u64);
+        if start == tip {
+            return Ok(None);
+        }
+        let timestamp = |number: u64| async move {
+            self.provider
+                .get_block(number)
+                .await
+                .map_err(ChainCommunicationError::from_other)
+                .map(|block| block.map(|b| b.timestamp.as_u64()))
+        };
+        let (Some(start_time), Some(tip_time)) = (timestamp(start).await?, timestamp(tip).await?)
+        else {
+            return Ok(None);
+        };
+        let elapsed = Duration::from_secs(tip_time.saturating_sub(start_time));
+        Ok(Some(elapsed / (tip - start) as u32))
+    }
 }
 
 impl<M> EthereumProvider<M>
@@ -145,6 +173,73 @@ impl BuildableWithProvider for HyperlaneProviderBuilder {
     }
 }
 
+/// Builder which resolves an ENS name into an address using the provider.
+pub struct EnsNameResolverBuilder {
+    /// The ENS name to resolve, e.g. `mailbox.hyperlane.eth`
+    pub name: String,
+}
+
+#[async_trait]
+impl BuildableWithProvider for EnsNameResolverBuilder {
+    type Output = ChainResult<H256>;
+
+    async fn build_with_provider<M: Middleware + 'static>(
+        &self,
+        provider: M,
+        _locator: &ContractLocator,
+    ) -> Self::Output {
+        let address = provider
+            .resolve_name(&self.name)
+            .await
+            .map_err(ChainCommunicationError::from_other)?;
+        Ok(address.into())
+    }
+}
+
+/// The chain did not report the block with a finality tag, usually because it
+/// does not support the tag.
+#[derive(Debug, thiserror::Error)]
+#[error("Unable to get the `{tag}` block, does the chain support this block tag? {reason}")]
+pub struct FinalityTagError {
+    tag: &'static str,
+    reason: String,
+}
+
+/// The number of the latest final block. This is the block with
+/// `finality_tag` if one is set and otherwise `finality_blocks` below the tip.
+pub(crate) async fn get_finalized_block_number<M: Middleware>(
+    provider: &M,
+    finality_blocks: u32,
+    finality_tag: Option<FinalityTag>,
+) -> ChainResult<u32> {
+    let Some(tag) = finality_tag else {
+        return Ok(provider
+            .get_block_number()
+            .await
+            .map_err(ChainCommunicationError::from_other)?
+            .as_u32()
+            .saturating_sub(finality_blocks));
+    };
+    let error = |reason: String| {
+        ChainCommunicationError::from_other(FinalityTagError {
+            tag: tag.as_str(),
+            reason,
+        })
+    };
+    let block_number = match tag {
+        FinalityTag::Safe => BlockNumber::Safe,
+        FinalityTag::Finalized => BlockNumber::Finalized,
+    };
+    provider
+        .get_block(block_number)
+        .await
+        .map_err(|e| error(e.to_string()))?
+        .ok_or_else(|| error("no block was returned".to_owned()))?
+        .number
+        .map(|number| number.as_u32())
+        .ok_or_else(|| error("the block has no number".to_owned()))
+}
+
 /// Call a get function that returns a Result<Option<T>> and retry if the inner
 /// option is None. This can happen because the provider has not discovered the
 /// object we are looking for yet.
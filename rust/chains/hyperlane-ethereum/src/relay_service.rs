@@ -0,0 +1,306 @@
+use std::fmt::{Debug, Formatter};
+
+use async_trait::async_trait;
+use ethers::prelude::{BlockId, FromErr, Middleware, PendingTransaction};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::transaction::eip712::Eip712;
+use ethers::types::{Address, Bytes, Signature, H256, U256};
+use ethers::utils::keccak256;
+use ethers_signers::Signer;
+use reqwest::{Client, Url};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::{Signers, SignersError};
+
+/// Error types for the relay service
+#[derive(Debug, thiserror::Error)]
+pub enum RelayServiceError {
+    /// Error communicating with the relay service
+    #[error(transparent)]
+    Reqwest(#[from] reqwest::Error),
+    /// The env var holding the API key is not set
+    #[error("Relay service API key env var `{0}` is not set")]
+    MissingApiKey(String),
+    /// The relay service returned an unexpected response
+    #[error("Unexpected response from relay service: {0}")]
+    UnexpectedResponse(String),
+    /// The transaction can not be relayed
+    #[error("Invalid transaction for relay service: {0}")]
+    InvalidTransaction(&'static str),
+    /// The configured signer failed to sign
+    #[error("Relay service signer failed: {0}")]
+    Signer(Box<SignersError>),
+}
+
+impl From<SignersError> for RelayServiceError {
+    fn from(e: SignersError) -> Self {
+        Self::Signer(Box::new(e))
+    }
+}
+
+/// Submits transactions through a meta-transaction relay service rather than
+/// signing and broadcasting them locally.
+///
+/// Every transaction is posted as JSON with its `chainId`, `target`, `data`
+/// and optional `gasLimit` to the endpoint, which must respond with the
+/// `txHash` of the transaction it broadcast. The request also carries the
+/// `user` address of the configured signer and its EIP-191 `signature` over
+/// `keccak256(chainId (uint256) ++ target ++ data)`, so the relay service
+/// can authenticate who the transaction is relayed for. The API key is read
+/// from the configured env var on every request and sent in the `X-Api-Key`
+/// header.
+#[derive(Clone)]
+pub struct RelayService {
+    client: Client,
+    endpoint: Url,
+    api_key_env: String,
+    chain_id: u64,
+    signer: Box<Signers>,
+}
+
+impl Debug for RelayService {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RelayService")
+            .field("endpoint", &self.endpoint)
+            .field("api_key_env", &self.api_key_env)
+            .field("chain_id", &self.chain_id)
+            .field("signer", &self.signer.address())
+            .finish()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RelayResponse {
+    tx_hash: H256,
+}
+
+impl RelayService {
+    /// Create a new relay service client signing payloads with `signer`,
+    /// checking that the API key env var is set.
+    pub fn new(
+        endpoint: Url,
+        api_key_env: &str,
+        chain_id: u64,
+        signer: Signers,
+    ) -> Result<Self, RelayServiceError> {
+        let relay = Self {
+            client: Client::new(),
+            endpoint,
+            api_key_env: api_key_env.to_owned(),
+            chain_id,
+            signer: Box::new(signer.with_chain_id(chain_id)),
+        };
+        relay.api_key()?;
+        Ok(relay)
+    }
+
+    fn api_key(&self) -> Result<String, RelayServiceError> {
+        std::env::var(&self.api_key_env)
+            .map_err(|_| RelayServiceError::MissingApiKey(self.api_key_env.clone()))
+    }
+
+    /// The signer payloads are signed with.
+    pub fn signer(&self) -> &Signers {
+        &self.signer
+    }
+
+    /// Build the signed request body for the transaction.
+    async fn request(&self, tx: &TypedTransaction) -> Result<Value, RelayServiceError> {
+        let missing_target = RelayServiceError::InvalidTransaction("missing target address");
+        let target = tx
+            .to()
+            .and_then(|to| to.as_address())
+            .ok_or(missing_target)?;
+        let chain_id = tx.chain_id().map(|id| id.as_u64()).unwrap_or(self.chain_id);
+        let data = tx.data().cloned().unwrap_or_default();
+
+        let mut payload = vec![0u8; 32];
+        U256::from(chain_id).to_big_endian(&mut payload);
+        payload.extend_from_slice(target.as_bytes());
+        payload.extend_from_slice(&data);
+        let signature = self.signer.sign_message(keccak256(payload)).await?;
+
+        Ok(json!({
+            "chainId": chain_id,
+            "target": target,
+            "data": data,
+            "gasLimit": tx.gas().map(|g| g.to_string()),
+            "user": self.signer.address(),
+            "signature": Bytes::from(signature.to_vec()),
+        }))
+    }
+
+    /// Hand the transaction to the relay service, returning the hash of the
+    /// transaction it broadcast.
+    pub async fn submit(&self, tx: &TypedTransaction) -> Result<H256, RelayServiceError> {
+        let body = self.request(tx).await?;
+        let res = self
+            .client
+            .post(self.endpoint.clone())
+            .header("X-Api-Key", self.api_key()?)
+            .header("Content-Type", "application/json")
+            .body(body.to_string())
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+        let res: RelayResponse = serde_json::from_slice(&res)
+            .map_err(|e| RelayServiceError::UnexpectedResponse(e.to_string()))?;
+        Ok(res.tx_hash)
+    }
+}
+
+#[async_trait]
+impl Signer for RelayService {
+    type Error = RelayServiceError;
+
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(
+        &self,
+        message: S,
+    ) -> Result<Signature, Self::Error> {
+        Ok(self.signer.sign_message(message).await?)
+    }
+
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature, Self::Error> {
+        Ok(self.signer.sign_transaction(tx).await?)
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        payload: &T,
+    ) -> Result<Signature, Self::Error> {
+        Ok(self.signer.sign_typed_data(payload).await?)
+    }
+
+    /// The address of the signer transactions are relayed for.
+    fn address(&self) -> Address {
+        self.signer.address()
+    }
+
+    fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    fn with_chain_id<T: Into<u64>>(mut self, chain_id: T) -> Self {
+        self.chain_id = chain_id.into();
+        self.signer = Box::new(self.signer.with_chain_id(self.chain_id));
+        self
+    }
+}
+
+/// Error type for the [`RelayServiceMiddleware`]
+#[derive(Debug, thiserror::Error)]
+pub enum RelayServiceMiddlewareError<E: std::error::Error> {
+    /// Error from the inner middleware
+    #[error("{0}")]
+    Middleware(E),
+    /// Error submitting to the relay service
+    #[error("{0}")]
+    Relay(RelayServiceError),
+}
+
+impl<E: std::error::Error> FromErr<E> for RelayServiceMiddlewareError<E> {
+    fn from(src: E) -> Self {
+        Self::Middleware(src)
+    }
+}
+
+/// A middleware which sends transactions through a [`RelayService`] instead of
+/// broadcasting them with the inner middleware. Everything else is delegated.
+#[derive(Debug)]
+pub struct RelayServiceMiddleware<M> {
+    inner: M,
+    relay: RelayService,
+}
+
+impl<M> RelayServiceMiddleware<M> {
+    /// Wrap `inner` to submit transactions through `relay`.
+    pub fn new(inner: M, relay: RelayService) -> Self {
+        Self { inner, relay }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<M: Middleware> Middleware for RelayServiceMiddleware<M> {
+    type Error = RelayServiceMiddlewareError<M::Error>;
+    type Provider = M::Provider;
+    type Inner = M;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    async fn send_transaction<T: Into<TypedTransaction> + Send + Sync>(
+        &self,
+        tx: T,
+        block: Option<BlockId>,
+    ) -> Result<PendingTransaction<'_, Self::Provider>, Self::Error> {
+        let mut tx = tx.into();
+        self.inner
+            .fill_transaction(&mut tx, block)
+            .await
+            .map_err(RelayServiceMiddlewareError::Middleware)?;
+        let hash = self
+            .relay
+            .submit(&tx)
+            .await
+            .map_err(RelayServiceMiddlewareError::Relay)?;
+        Ok(PendingTransaction::new(hash, self.provider()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ethers::types::transaction::eip2718::TypedTransaction;
+    use ethers::types::{Address, Signature, TransactionRequest, U256};
+    use ethers::utils::keccak256;
+    use ethers_signers::{LocalWallet, Signer};
+    use reqwest::Client;
+
+    use super::RelayService;
+
+    #[tokio::test]
+    async fn signs_payloads_with_the_configured_signer() {
+        let wallet: LocalWallet =
+            "1111111111111111111111111111111111111111111111111111111111111111"
+                .parse()
+                .unwrap();
+        let relay = RelayService {
+            client: Client::new(),
+            endpoint: "https://relay.example.com".parse().unwrap(),
+            api_key_env: "RELAY_SERVICE_API_KEY".to_owned(),
+            chain_id: 0,
+            signer: Box::new(wallet.clone().into()),
+        }
+        .with_chain_id(5u64);
+        assert_eq!(relay.address(), wallet.address());
+        assert_eq!(relay.signer().chain_id(), 5);
+
+        let target: Address = "0x000000000000000000000000000000000000dEaD"
+            .parse()
+            .unwrap();
+        let tx: TypedTransaction = TransactionRequest::new()
+            .to(target)
+            .data(vec![1, 2, 3])
+            .into();
+        let body = relay.request(&tx).await.unwrap();
+        assert_eq!(body["chainId"], 5);
+        assert_eq!(
+            body["user"].as_str().unwrap(),
+            format!("{:?}", wallet.address())
+        );
+
+        let mut payload = vec![0u8; 32];
+        U256::from(5).to_big_endian(&mut payload);
+        payload.extend_from_slice(target.as_bytes());
+        payload.extend_from_slice(&[1, 2, 3]);
+        let signature: Signature = body["signature"].as_str().unwrap()[2..].parse().unwrap();
+        signature
+            .verify(keccak256(payload).to_vec(), wallet.address())
+            .unwrap();
+    }
+}
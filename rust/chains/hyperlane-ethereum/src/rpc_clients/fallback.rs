@@ -19,6 +19,10 @@ use crate::rpc_clients::{categorize_client_response, CategorizedResponse};
 
 const MAX_BLOCK_TIME: Duration = Duration::from_secs(2 * 60);
 const BLOCK_NUMBER_RPC: &str = "eth_blockNumber";
+/// Failed requests allowed across all providers before giving up
+const DEFAULT_MAX_RETRIES: usize = 3;
+/// Backoff between two rounds through the providers
+const DEFAULT_RETRY_BACKOFF_MS: u64 = 100;
 
 #[derive(Clone, Copy, new)]
 struct PrioritizedProviderInner {
@@ -50,6 +54,9 @@ struct PrioritizedProviders<T> {
 pub struct FallbackProvider<T> {
     inner: Arc<PrioritizedProviders<T>>,
     max_block_time: Duration,
+    max_retries: usize,
+    base_retry_ms: u64,
+    max_retry_ms: u64,
 }
 
 impl<T> Clone for FallbackProvider<T> {
@@ -57,6 +64,9 @@ impl<T> Clone for FallbackProvider<T> {
         Self {
             inner: self.inner.clone(),
             max_block_time: self.max_block_time,
+            max_retries: self.max_retries,
+            base_retry_ms: self.base_retry_ms,
+            max_retry_ms: self.max_retry_ms,
         }
     }
 }
@@ -168,6 +178,9 @@ where
 pub struct FallbackProviderBuilder<T> {
     providers: Vec<T>,
     max_block_time: Duration,
+    max_retries: usize,
+    base_retry_ms: u64,
+    max_retry_ms: Option<u64>,
 }
 
 impl<T> Default for FallbackProviderBuilder<T> {
@@ -175,6 +188,9 @@ impl<T> Default for FallbackProviderBuilder<T> {
         Self {
             providers: Vec::new(),
             max_block_time: MAX_BLOCK_TIME,
+            max_retries: DEFAULT_MAX_RETRIES,
+            base_retry_ms: DEFAULT_RETRY_BACKOFF_MS,
+            max_retry_ms: None,
         }
     }
 }
@@ -193,6 +209,29 @@ impl<T> FallbackProviderBuilder<T> {
         self
     }
 
+    /// Set how many failed requests are allowed across all providers before
+    /// the request fails.
+    pub fn with_max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Set the backoff between the first two rounds through the providers.
+    /// It doubles every round after that, up to the max backoff.
+    pub fn with_base_retry_ms(mut self, base_retry_ms: u64) -> Self {
+        assert!(base_retry_ms >= 1);
+        self.base_retry_ms = base_retry_ms;
+        self
+    }
+
+    /// Set an upper bound for the backoff between two rounds. Defaults to the
+    /// base backoff, so the backoff stays fixed unless a bound is given.
+    pub fn with_max_retry_ms(mut self, max_retry_ms: u64) -> Self {
+        assert!(max_retry_ms >= 1);
+        self.max_retry_ms = Some(max_retry_ms);
+        self
+    }
+
     #[cfg(test)]
     pub fn with_max_block_time(mut self, max_block_time: Duration) -> Self {
         self.max_block_time = max_block_time;
@@ -214,6 +253,9 @@ impl<T> FallbackProviderBuilder<T> {
         FallbackProvider {
             inner: Arc::new(prioritized_providers),
             max_block_time: self.max_block_time,
+            max_retries: self.max_retries,
+            base_retry_ms: self.base_retry_ms,
+            max_retry_ms: self.max_retry_ms.unwrap_or(self.base_retry_ms),
         }
     }
 }
@@ -250,11 +292,17 @@ where
         let params = serde_json::to_value(params).expect("valid");
 
         let mut errors = vec![];
-        // make sure we do at least 4 total retries.
-        while errors.len() <= 3 {
+        let mut round: u32 = 0;
+        // keep going through the providers until `max_retries` requests failed
+        while errors.len() <= self.max_retries {
             if !errors.is_empty() {
-                sleep(Duration::from_millis(100)).await;
+                let backoff_ms = self
+                    .base_retry_ms
+                    .saturating_mul(2u64.saturating_pow(round - 1))
+                    .min(self.max_retry_ms);
+                sleep(Duration::from_millis(backoff_ms)).await;
             }
+            round += 1;
             let priorities_snapshot = self.take_priorities_snapshot().await;
             for (idx, priority) in priorities_snapshot.iter().enumerate() {
                 let provider = &self.inner.providers[priority.index];
@@ -290,12 +338,22 @@ mod tests {
         // Even if the tests were single-threaded, need the arc-mutex
         // for interior mutability in `JsonRpcClient::request`
         requests: Arc<Mutex<Vec<(String, String)>>>,
+        // Whether every request fails with a retryable error
+        failing: bool,
     }
 
     impl ProviderMock {
         fn new() -> Self {
             Self {
                 requests: Arc::new(Mutex::new(vec![])),
+                failing: false,
+            }
+        }
+
+        fn failing() -> Self {
+            Self {
+                failing: true,
+                ..Self::new()
             }
         }
 
@@ -331,6 +389,12 @@ mod tests {
         ) -> Result<R, Self::Error> {
             self.push(method, params);
             sleep(Duration::from_millis(10)).await;
+            if self.failing {
+                return serde_json::from_str("").map_err(|e| HttpClientError::SerdeJson {
+                    err: e,
+                    text: "".to_owned(),
+                });
+            }
             dummy_return_value()
         }
     }
@@ -397,6 +461,22 @@ mod tests {
         assert_eq!(provider_call_count, vec![0, 0, 2]);
     }
 
+    #[tokio::test]
+    async fn test_max_retries_is_respected() {
+        let fallback_provider = FallbackProviderBuilder::default()
+            .add_provider(ProviderMock::failing())
+            .with_max_retries(2)
+            .with_base_retry_ms(1)
+            .build();
+        assert!(fallback_provider
+            .request::<_, u64>(BLOCK_NUMBER_RPC, ())
+            .await
+            .is_err());
+
+        let provider_call_count: Vec<_> = get_call_counts(&fallback_provider).await;
+        assert_eq!(provider_call_count, vec![3]);
+    }
+
     // TODO: make `categorize_client_response` generic over `ProviderError` to allow testing
     // two stalled providers (so that the for loop in `request` doesn't stop after the first provider)
 }
@@ -1,10 +1,11 @@
 use ethers::providers::HttpClientError;
 use tracing::{info, trace, warn};
 
-pub use self::{fallback::*, retrying::*};
+pub use self::{fallback::*, retrying::*, transport::*};
 
 mod fallback;
 mod retrying;
+mod transport;
 
 enum CategorizedResponse<R> {
     IsOk(R),
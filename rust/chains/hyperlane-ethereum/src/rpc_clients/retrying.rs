@@ -17,6 +17,7 @@ use tracing::{debug, error, instrument, trace, warn_span};
 pub struct RetryingProvider<P> {
     max_requests: u32,
     base_retry_ms: u64,
+    max_retry_ms: u64,
     inner: P,
 }
 
@@ -27,6 +28,7 @@ impl<P> RetryingProvider<P> {
             inner,
             max_requests: max_requests.unwrap_or(6),
             base_retry_ms: base_retry_ms.unwrap_or(50),
+            max_retry_ms: u64::MAX,
         }
     }
 
@@ -43,6 +45,12 @@ impl<P> RetryingProvider<P> {
         self.base_retry_ms = base_retry_ms;
     }
 
+    /// Set an upper bound for the backoff time between two requests.
+    pub fn set_max_retry_ms(&mut self, max_retry_ms: u64) {
+        assert!(max_retry_ms >= 1);
+        self.max_retry_ms = max_retry_ms;
+    }
+
     /// Get the max_requests
     pub fn max_requests(&self) -> u32 {
         self.max_requests
@@ -93,7 +101,10 @@ where
         let mut i = 1;
         loop {
             let mut rate_limited = false;
-            let backoff_ms = self.base_retry_ms * 2u64.pow(i - 1);
+            let backoff_ms = self
+                .base_retry_ms
+                .saturating_mul(2u64.saturating_pow(i - 1))
+                .min(self.max_retry_ms);
             trace!(params = %serde_json::to_string(&params).unwrap_or_default(), "Dispatching request with params");
             debug!(attempt = i, "Dispatching request");
 
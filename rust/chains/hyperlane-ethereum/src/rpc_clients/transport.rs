@@ -0,0 +1,50 @@
+use std::fmt::Debug;
+
+use async_trait::async_trait;
+use ethers::providers::{Http, HttpClientError, JsonRpcClient, Ws, WsClientError};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// A JSON-RPC client using either an HTTP or a websocket transport, so
+/// fallback providers can mix both. Websocket errors are reported as
+/// `HttpClientError`s so they are categorized the same way as HTTP errors.
+#[derive(Debug)]
+pub enum HttpOrWsClient {
+    /// An HTTP transport
+    Http(Http),
+    /// A websocket transport
+    Ws(Ws),
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl JsonRpcClient for HttpOrWsClient {
+    type Error = HttpClientError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned,
+    {
+        match self {
+            Self::Http(client) => client.request(method, params).await,
+            Self::Ws(client) => client
+                .request(method, params)
+                .await
+                .map_err(ws_to_http_error),
+        }
+    }
+}
+
+fn ws_to_http_error(err: WsClientError) -> HttpClientError {
+    match err {
+        WsClientError::JsonRpcError(e) => HttpClientError::JsonRpcError(e),
+        WsClientError::JsonError(err) => HttpClientError::SerdeJson {
+            err,
+            text: String::new(),
+        },
+        err => HttpClientError::SerdeJson {
+            err: serde::de::Error::custom(format!("websocket error: {err}")),
+            text: err.to_string(),
+        },
+    }
+}
@@ -8,6 +8,11 @@ use hyperlane_core::{
     HyperlaneSigner, HyperlaneSignerError, Signature as HyperlaneSignature, H160, H256,
 };
 
+use crate::{
+    ExternalSigner, ExternalSignerError, GcpKmsSigner, GcpKmsSignerError, LedgerSigner,
+    LedgerSignerError, RelayService, RelayServiceError, VaultSigner, VaultSignerError,
+};
+
 /// Ethereum-supported signer types
 #[derive(Debug, Clone)]
 pub enum Signers {
@@ -15,6 +20,17 @@ pub enum Signers {
     Local(LocalWallet),
     /// A signer using a key stored in aws kms
     Aws(AwsSigner),
+    /// A signer using a key stored in gcp cloud kms
+    GcpKms(GcpKmsSigner),
+    /// A signer using a key stored on a Ledger hardware wallet
+    Ledger(LedgerSigner),
+    /// A signer using a key stored in a hashicorp vault transit engine
+    Vault(VaultSigner),
+    /// A signer delegating to an external command
+    External(ExternalSigner),
+    /// Submits transactions through a relay service, signing their payloads
+    /// with the wrapped signer
+    RelayService(RelayService),
 }
 
 impl From<LocalWallet> for Signers {
@@ -29,6 +45,36 @@ impl From<AwsSigner> for Signers {
     }
 }
 
+impl From<GcpKmsSigner> for Signers {
+    fn from(s: GcpKmsSigner) -> Self {
+        Signers::GcpKms(s)
+    }
+}
+
+impl From<VaultSigner> for Signers {
+    fn from(s: VaultSigner) -> Self {
+        Signers::Vault(s)
+    }
+}
+
+impl From<ExternalSigner> for Signers {
+    fn from(s: ExternalSigner) -> Self {
+        Signers::External(s)
+    }
+}
+
+impl From<RelayService> for Signers {
+    fn from(s: RelayService) -> Self {
+        Signers::RelayService(s)
+    }
+}
+
+impl From<LedgerSigner> for Signers {
+    fn from(s: LedgerSigner) -> Self {
+        Signers::Ledger(s)
+    }
+}
+
 #[async_trait]
 impl Signer for Signers {
     type Error = SignersError;
@@ -40,6 +86,11 @@ impl Signer for Signers {
         match self {
             Signers::Local(signer) => Ok(signer.sign_message(message).await?),
             Signers::Aws(signer) => Ok(signer.sign_message(message).await?),
+            Signers::GcpKms(signer) => Ok(signer.sign_message(message).await?),
+            Signers::Ledger(signer) => Ok(signer.sign_message(message).await?),
+            Signers::Vault(signer) => Ok(signer.sign_message(message).await?),
+            Signers::External(signer) => Ok(signer.sign_message(message).await?),
+            Signers::RelayService(signer) => Ok(signer.sign_message(message).await?),
         }
     }
 
@@ -47,6 +98,11 @@ impl Signer for Signers {
         match self {
             Signers::Local(signer) => Ok(signer.sign_transaction(message).await?),
             Signers::Aws(signer) => Ok(signer.sign_transaction(message).await?),
+            Signers::GcpKms(signer) => Ok(signer.sign_transaction(message).await?),
+            Signers::Ledger(signer) => Ok(signer.sign_transaction(message).await?),
+            Signers::Vault(signer) => Ok(signer.sign_transaction(message).await?),
+            Signers::External(signer) => Ok(signer.sign_transaction(message).await?),
+            Signers::RelayService(signer) => Ok(signer.sign_transaction(message).await?),
         }
     }
 
@@ -57,6 +113,11 @@ impl Signer for Signers {
         match self {
             Signers::Local(signer) => Ok(signer.sign_typed_data(payload).await?),
             Signers::Aws(signer) => Ok(signer.sign_typed_data(payload).await?),
+            Signers::GcpKms(signer) => Ok(signer.sign_typed_data(payload).await?),
+            Signers::Ledger(signer) => Ok(signer.sign_typed_data(payload).await?),
+            Signers::Vault(signer) => Ok(signer.sign_typed_data(payload).await?),
+            Signers::External(signer) => Ok(signer.sign_typed_data(payload).await?),
+            Signers::RelayService(signer) => Ok(signer.sign_typed_data(payload).await?),
         }
     }
 
@@ -64,6 +125,11 @@ impl Signer for Signers {
         match self {
             Signers::Local(signer) => signer.address(),
             Signers::Aws(signer) => signer.address(),
+            Signers::GcpKms(signer) => signer.address(),
+            Signers::Ledger(signer) => signer.address(),
+            Signers::Vault(signer) => signer.address(),
+            Signers::External(signer) => signer.address(),
+            Signers::RelayService(signer) => signer.address(),
         }
     }
 
@@ -71,6 +137,11 @@ impl Signer for Signers {
         match self {
             Signers::Local(signer) => signer.chain_id(),
             Signers::Aws(signer) => signer.chain_id(),
+            Signers::GcpKms(signer) => signer.chain_id(),
+            Signers::Ledger(signer) => signer.chain_id(),
+            Signers::Vault(signer) => signer.chain_id(),
+            Signers::External(signer) => signer.chain_id(),
+            Signers::RelayService(signer) => signer.chain_id(),
         }
     }
 
@@ -78,6 +149,11 @@ impl Signer for Signers {
         match self {
             Signers::Local(signer) => signer.with_chain_id(chain_id).into(),
             Signers::Aws(signer) => signer.with_chain_id(chain_id).into(),
+            Signers::GcpKms(signer) => signer.with_chain_id(chain_id).into(),
+            Signers::Vault(signer) => signer.with_chain_id(chain_id).into(),
+            Signers::External(signer) => signer.with_chain_id(chain_id).into(),
+            Signers::RelayService(signer) => signer.with_chain_id(chain_id).into(),
+            Signers::Ledger(signer) => signer.with_chain_id(chain_id).into(),
         }
     }
 }
@@ -95,6 +171,33 @@ impl HyperlaneSigner for Signers {
         signature.v = 28 - (signature.v % 2);
         Ok(signature.into())
     }
+
+    async fn sign_digest(&self, digest: &H256) -> Result<HyperlaneSignature, HyperlaneSignerError> {
+        let eth_digest = ethers::types::H256::from(*digest);
+        let mut signature = match self {
+            Signers::Local(signer) => signer.sign_hash(eth_digest),
+            Signers::Aws(signer) => signer
+                .sign_digest(eth_digest.into())
+                .await
+                .map_err(SignersError::from)?,
+            Signers::GcpKms(signer) => signer
+                .sign_digest(eth_digest)
+                .await
+                .map_err(SignersError::from)?,
+            Signers::Vault(signer) => signer
+                .sign_digest(eth_digest)
+                .await
+                .map_err(SignersError::from)?,
+            Signers::External(signer) => signer
+                .sign_digest(eth_digest)
+                .await
+                .map_err(SignersError::from)?,
+            Signers::Ledger(_) => return Err(SignersError::UnsupportedDigestSigning.into()),
+            Signers::RelayService(relay) => return relay.signer().sign_digest(digest).await,
+        };
+        signature.v = 28 - (signature.v % 2);
+        Ok(signature.into())
+    }
 }
 
 /// Error types for Signers
@@ -106,6 +209,30 @@ pub enum SignersError {
     /// Wallet Signer Error
     #[error("{0}")]
     WalletError(#[from] WalletError),
+    /// GCP KMS Signer Error
+    #[error("{0}")]
+    GcpKmsSignerError(#[from] GcpKmsSignerError),
+    /// Ledger Signer Error
+    #[error("{0}")]
+    LedgerSignerError(#[from] LedgerSignerError),
+    /// Vault Signer Error
+    #[error("{0}")]
+    VaultSignerError(#[from] VaultSignerError),
+    /// External Signer Error
+    #[error("{0}")]
+    ExternalSignerError(#[from] ExternalSignerError),
+    /// Relay Service Error
+    #[error("{0}")]
+    RelayServiceError(#[from] RelayServiceError),
+    /// The signer can only sign EIP-191 messages
+    #[error("Ledger signers can not sign raw digests")]
+    UnsupportedDigestSigning,
+}
+
+impl From<SignersError> for HyperlaneSignerError {
+    fn from(e: SignersError) -> Self {
+        Self::from(Box::new(e) as Box<_>)
+    }
 }
 
 impl From<std::convert::Infallible> for SignersError {
@@ -116,7 +243,7 @@ impl From<std::convert::Infallible> for SignersError {
 
 #[cfg(test)]
 mod test {
-    use hyperlane_core::{Checkpoint, HyperlaneSigner, HyperlaneSignerExt, H256};
+    use hyperlane_core::{Checkpoint, HyperlaneSigner, HyperlaneSignerExt, SignatureScheme, H256};
 
     use crate::signers::Signers;
 
@@ -138,6 +265,13 @@ mod test {
             let signed = signer.sign(message).await.expect("!sign");
             assert!(signed.signature.v == 27 || signed.signature.v == 28);
             signed.verify(signer.eth_address()).expect("!verify");
+
+            let signed = signer
+                .sign_with_scheme(message, SignatureScheme::Ecdsa)
+                .await
+                .expect("!sign");
+            assert_eq!(signed.signature_scheme, SignatureScheme::Ecdsa);
+            signed.verify(signer.eth_address()).expect("!verify");
         };
         tokio::runtime::Builder::new_current_thread()
             .enable_all()
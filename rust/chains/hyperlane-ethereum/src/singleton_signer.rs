@@ -7,15 +7,17 @@ use tokio::sync::{mpsc, oneshot};
 use tracing::warn;
 
 use hyperlane_core::{
-    HyperlaneSigner, HyperlaneSignerError, Signature as HyperlaneSignature, H160, H256,
+    HyperlaneSigner, HyperlaneSignerError, Signature as HyperlaneSignature, SignatureScheme, H160,
+    H256,
 };
 
 use crate::Signers;
 
 /// A callback to send the result of a signing operation
 type Callback = oneshot::Sender<Result<Signature, HyperlaneSignerError>>;
-/// A hash that needs to be signed with a callback to send the result
-type SignTask = (H256, Callback);
+/// A hash that needs to be signed, the scheme to sign it with and a callback
+/// to send the result
+type SignTask = (H256, SignatureScheme, Callback);
 
 /// A wrapper around a signer that uses channels to ensure that only one call is
 /// made at a time. Mostly useful for the AWS signers.
@@ -53,8 +55,22 @@ impl HyperlaneSigner for SingletonSignerHandle {
     }
 
     async fn sign_hash(&self, hash: &H256) -> Result<HyperlaneSignature, HyperlaneSignerError> {
+        self.send_task(*hash, SignatureScheme::Eip191).await
+    }
+
+    async fn sign_digest(&self, digest: &H256) -> Result<HyperlaneSignature, HyperlaneSignerError> {
+        self.send_task(*digest, SignatureScheme::Ecdsa).await
+    }
+}
+
+impl SingletonSignerHandle {
+    async fn send_task(
+        &self,
+        hash: H256,
+        scheme: SignatureScheme,
+    ) -> Result<HyperlaneSignature, HyperlaneSignerError> {
         let (tx, rx) = oneshot::channel();
-        let task = (*hash, tx);
+        let task = (hash, scheme, tx);
         self.tx.send(task).map_err(SingletonSignerError::from)?;
         match rx.await {
             Ok(res) => res.map(Into::into),
@@ -85,10 +101,14 @@ impl SingletonSigner {
 
     /// Run this signer's event loop.
     pub async fn run(mut self) {
-        while let Some((hash, tx)) = self.rx.recv().await {
+        while let Some((hash, scheme, tx)) = self.rx.recv().await {
             let mut retries = self.retries;
             let res = loop {
-                match self.inner.sign_hash(&hash).await {
+                let res = match scheme {
+                    SignatureScheme::Eip191 => self.inner.sign_hash(&hash).await,
+                    SignatureScheme::Ecdsa => self.inner.sign_digest(&hash).await,
+                };
+                match res {
                     Ok(res) => break Ok(res),
                     Err(err) => {
                         warn!("Error signing hash: {}", err);
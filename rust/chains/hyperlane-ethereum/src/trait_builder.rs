@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::fmt::Write;
 use std::sync::Arc;
 use std::time::Duration;
@@ -7,6 +8,7 @@ use ethers::prelude::{
     Http, JsonRpcClient, Middleware, NonceManagerMiddleware, Provider, Quorum, QuorumProvider,
     SignerMiddleware, WeightedProvider, Ws, WsClientError,
 };
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
 use reqwest::{Client, Url};
 use thiserror::Error;
 
@@ -19,11 +21,16 @@ use ethers_prometheus::middleware::{
 };
 use hyperlane_core::{ChainCommunicationError, ChainResult, ContractLocator};
 
-use crate::{signers::Signers, ConnectionConf, FallbackProvider, RetryingProvider};
+use crate::{
+    signers::Signers, ConnectionConf, FallbackProvider, HttpOrWsClient, NonceStrategy,
+    PendingNonceMiddleware, RelayServiceMiddleware, RetryConfig, RetryingProvider, TlsConfig,
+};
 
 // This should be whatever the prometheus scrape interval is
 const METRICS_SCRAPE_INTERVAL: Duration = Duration::from_secs(60);
 const HTTP_CLIENT_TIMEOUT: Duration = Duration::from_secs(60);
+/// Idle connections kept open to each host if `max_connections` is not set
+const DEFAULT_MAX_CONNECTIONS: usize = 32;
 
 /// An error when connecting to an ethereum provider.
 #[derive(Error, Debug)]
@@ -34,6 +41,9 @@ pub enum EthereumProviderConnectionError {
     /// Underlying websocket library threw an error
     #[error(transparent)]
     WebsocketClientError(#[from] WsClientError),
+    /// A configured header could not be used
+    #[error("Invalid http header `{0}`")]
+    InvalidHeader(String),
 }
 
 impl From<EthereumProviderConnectionError> for ChainCommunicationError {
@@ -50,22 +60,33 @@ pub trait BuildableWithProvider {
 
     /// Construct a new instance of the associated trait using a connection
     /// config. This is the first step and will wrap the provider with
-    /// metrics and a signer as needed.
+    /// metrics and a signer as needed. The signer signs for `chain_id` if
+    /// given and for the chain id reported by the provider otherwise.
+    #[allow(clippy::too_many_arguments)]
     async fn build_with_connection_conf(
         &self,
         conn: &ConnectionConf,
         locator: &ContractLocator,
         signer: Option<Signers>,
+        chain_id: Option<u64>,
+        nonce_strategy: NonceStrategy,
         rpc_metrics: Option<JsonRpcClientMetrics>,
         middleware_metrics: Option<(MiddlewareMetrics, PrometheusMiddlewareConf)>,
     ) -> ChainResult<Self::Output> {
         Ok(match conn {
-            ConnectionConf::HttpQuorum { urls } => {
-                let mut builder = QuorumProvider::builder().quorum(Quorum::Majority);
-                let http_client = Client::builder()
-                    .timeout(HTTP_CLIENT_TIMEOUT)
-                    .build()
-                    .map_err(EthereumProviderConnectionError::from)?;
+            ConnectionConf::HttpQuorum {
+                urls,
+                threshold,
+                headers,
+                tls,
+                request_timeout,
+                max_connections,
+                retry,
+            } => {
+                let quorum = threshold.map_or(Quorum::Majority, Quorum::ProviderCount);
+                let mut builder = QuorumProvider::builder().quorum(quorum);
+                let http_client =
+                    build_http_client(headers, tls, *request_timeout, *max_connections)?;
                 for url in urls {
                     let http_provider = Http::new_with_client(url.clone(), http_client.clone());
                     // Wrap the inner providers as RetryingProviders rather than the QuorumProvider.
@@ -84,24 +105,57 @@ pub trait BuildableWithProvider {
                         &middleware_metrics,
                     );
                     let retrying_provider =
-                        RetryingProvider::new(metrics_provider, Some(5), Some(1000));
+                        build_retrying_provider(metrics_provider, retry, Some(4), Some(1000));
                     let weighted_provider = WeightedProvider::new(retrying_provider);
                     builder = builder.add_provider(weighted_provider);
                 }
                 let quorum_provider = builder.build();
-                self.wrap_with_metrics(quorum_provider, locator, signer, middleware_metrics)
-                    .await?
+                self.wrap_with_metrics(
+                    quorum_provider,
+                    locator,
+                    signer,
+                    chain_id,
+                    nonce_strategy,
+                    middleware_metrics,
+                )
+                .await?
             }
-            ConnectionConf::HttpFallback { urls } => {
+            ConnectionConf::HttpFallback {
+                urls,
+                headers,
+                tls,
+                request_timeout,
+                max_connections,
+                retry,
+            } => {
                 let mut builder = FallbackProvider::builder();
-                let http_client = Client::builder()
-                    .timeout(HTTP_CLIENT_TIMEOUT)
-                    .build()
-                    .map_err(EthereumProviderConnectionError::from)?;
+                if let Some(max_retries) = retry.max_retries {
+                    builder = builder.with_max_retries(max_retries as usize);
+                }
+                if let Some(initial_backoff_ms) = retry.initial_backoff_ms {
+                    builder = builder.with_base_retry_ms(initial_backoff_ms);
+                }
+                if let Some(max_backoff_ms) = retry.max_backoff_ms {
+                    builder = builder.with_max_retry_ms(max_backoff_ms);
+                }
+                let http_client =
+                    build_http_client(headers, tls, *request_timeout, *max_connections)?;
                 for url in urls {
-                    let http_provider = Http::new_with_client(url.clone(), http_client.clone());
+                    // the transport is chosen per url so http and websocket
+                    // providers can back each other up
+                    let client = if is_ws_url(url) {
+                        let ws = Ws::connect(url)
+                            .await
+                            .map_err(EthereumProviderConnectionError::from)?;
+                        HttpOrWsClient::Ws(ws)
+                    } else {
+                        HttpOrWsClient::Http(Http::new_with_client(
+                            url.clone(),
+                            http_client.clone(),
+                        ))
+                    };
                     let metrics_provider = self.wrap_rpc_with_metrics(
-                        http_provider,
+                        client,
                         url.clone(),
                         &rpc_metrics,
                         &middleware_metrics,
@@ -109,14 +163,26 @@ pub trait BuildableWithProvider {
                     builder = builder.add_provider(metrics_provider);
                 }
                 let fallback_provider = builder.build();
-                self.wrap_with_metrics(fallback_provider, locator, signer, middleware_metrics)
-                    .await?
+                self.wrap_with_metrics(
+                    fallback_provider,
+                    locator,
+                    signer,
+                    chain_id,
+                    nonce_strategy,
+                    middleware_metrics,
+                )
+                .await?
             }
-            ConnectionConf::Http { url } => {
-                let http_client = Client::builder()
-                    .timeout(HTTP_CLIENT_TIMEOUT)
-                    .build()
-                    .map_err(EthereumProviderConnectionError::from)?;
+            ConnectionConf::Http {
+                url,
+                headers,
+                tls,
+                request_timeout,
+                max_connections,
+                retry,
+            } => {
+                let http_client =
+                    build_http_client(headers, tls, *request_timeout, *max_connections)?;
                 let http_provider = Http::new_with_client(url.clone(), http_client);
                 let metrics_provider = self.wrap_rpc_with_metrics(
                     http_provider,
@@ -124,16 +190,31 @@ pub trait BuildableWithProvider {
                     &rpc_metrics,
                     &middleware_metrics,
                 );
-                let retrying_http_provider = RetryingProvider::new(metrics_provider, None, None);
-                self.wrap_with_metrics(retrying_http_provider, locator, signer, middleware_metrics)
-                    .await?
+                let retrying_http_provider =
+                    build_retrying_provider(metrics_provider, retry, None, None);
+                self.wrap_with_metrics(
+                    retrying_http_provider,
+                    locator,
+                    signer,
+                    chain_id,
+                    nonce_strategy,
+                    middleware_metrics,
+                )
+                .await?
             }
             ConnectionConf::Ws { url } => {
                 let ws = Ws::connect(url)
                     .await
                     .map_err(EthereumProviderConnectionError::from)?;
-                self.wrap_with_metrics(ws, locator, signer, middleware_metrics)
-                    .await?
+                self.wrap_with_metrics(
+                    ws,
+                    locator,
+                    signer,
+                    chain_id,
+                    nonce_strategy,
+                    middleware_metrics,
+                )
+                .await?
             }
         })
     }
@@ -181,6 +262,8 @@ pub trait BuildableWithProvider {
         client: P,
         locator: &ContractLocator,
         signer: Option<Signers>,
+        chain_id: Option<u64>,
+        nonce_strategy: NonceStrategy,
         metrics: Option<(MiddlewareMetrics, PrometheusMiddlewareConf)>,
     ) -> ChainResult<Self::Output>
     where
@@ -190,32 +273,68 @@ pub trait BuildableWithProvider {
         Ok(if let Some(metrics) = metrics {
             let provider = Arc::new(PrometheusMiddleware::new(provider, metrics.0, metrics.1));
             tokio::spawn(provider.start_updating_on_interval(METRICS_SCRAPE_INTERVAL));
-            self.wrap_with_signer(provider, locator, signer).await?
+            self.wrap_with_signer(provider, locator, signer, chain_id, nonce_strategy)
+                .await?
         } else {
-            self.wrap_with_signer(provider, locator, signer).await?
+            self.wrap_with_signer(provider, locator, signer, chain_id, nonce_strategy)
+                .await?
         })
     }
 
     /// Wrap the provider creation with a signing provider if signers were
-    /// provided; this is the third step.
+    /// provided, managing nonces as configured; this is the third step.
     async fn wrap_with_signer<M>(
         &self,
         provider: M,
         locator: &ContractLocator,
         signer: Option<Signers>,
+        chain_id: Option<u64>,
+        nonce_strategy: NonceStrategy,
     ) -> ChainResult<Self::Output>
     where
         M: Middleware + 'static,
     {
-        Ok(if let Some(signer) = signer {
-            let signing_provider = build_signing_provider(provider, signer)
-                .await
-                .map_err(ChainCommunicationError::from_other)?;
-            self.build_with_provider(signing_provider, locator)
-        } else {
-            self.build_with_provider(provider, locator)
-        }
-        .await)
+        Ok(match signer {
+            Some(Signers::RelayService(relay)) => {
+                let chain_id = match chain_id {
+                    Some(chain_id) => chain_id,
+                    None => provider
+                        .get_chainid()
+                        .await
+                        .map_err(ChainCommunicationError::from_other)?
+                        .as_u64(),
+                };
+                let relay = ethers::signers::Signer::with_chain_id(relay, chain_id);
+                let relaying_provider = RelayServiceMiddleware::new(provider, relay);
+                self.build_with_provider(relaying_provider, locator).await
+            }
+            Some(signer) => {
+                let signer = match chain_id {
+                    Some(chain_id) => ethers::signers::Signer::with_chain_id(signer, chain_id),
+                    None => with_provider_chain_id(&provider, signer)
+                        .await
+                        .map_err(ChainCommunicationError::from_other)?,
+                };
+                match nonce_strategy {
+                    NonceStrategy::Sequential => {
+                        let address = ethers::prelude::Signer::address(&signer);
+                        let provider = NonceManagerMiddleware::new(provider, address);
+                        let signing_provider = SignerMiddleware::new(provider, signer);
+                        self.build_with_provider(signing_provider, locator).await
+                    }
+                    NonceStrategy::External => {
+                        let provider = PendingNonceMiddleware::new(provider);
+                        let signing_provider = SignerMiddleware::new(provider, signer);
+                        self.build_with_provider(signing_provider, locator).await
+                    }
+                    NonceStrategy::PerTx => {
+                        let signing_provider = SignerMiddleware::new(provider, signer);
+                        self.build_with_provider(signing_provider, locator).await
+                    }
+                }
+            }
+            None => self.build_with_provider(provider, locator).await,
+        })
     }
 
     /// Construct a new instance of the associated trait using a provider.
@@ -224,16 +343,72 @@ pub trait BuildableWithProvider {
         M: Middleware + 'static;
 }
 
-async fn build_signing_provider<M: Middleware>(
-    provider: M,
-    signer: Signers,
-) -> Result<SignerMiddleware<NonceManagerMiddleware<M>, Signers>, M::Error> {
-    let provider_chain_id = provider.get_chainid().await?;
-    let signer = ethers::signers::Signer::with_chain_id(signer, provider_chain_id.as_u64());
+/// Whether the url uses a websocket transport.
+fn is_ws_url(url: &Url) -> bool {
+    matches!(url.scheme(), "ws" | "wss")
+}
 
-    let address = ethers::prelude::Signer::address(&signer);
-    let provider = NonceManagerMiddleware::new(provider, address);
+/// Build an http client which sends the given headers with every request and
+/// presents the client certificate if mutual TLS is configured. Requests time
+/// out after `request_timeout`, or `HTTP_CLIENT_TIMEOUT` if it is not set.
+/// At most `max_connections` idle connections are kept open to each host, or
+/// `DEFAULT_MAX_CONNECTIONS` if it is not set.
+fn build_http_client(
+    headers: &HashMap<String, String>,
+    tls: &Option<TlsConfig>,
+    request_timeout: Option<Duration>,
+    max_connections: Option<usize>,
+) -> Result<Client, EthereumProviderConnectionError> {
+    let mut header_map = HeaderMap::new();
+    for (name, value) in headers {
+        let invalid = || EthereumProviderConnectionError::InvalidHeader(name.clone());
+        header_map.insert(
+            HeaderName::from_bytes(name.as_bytes()).map_err(|_| invalid())?,
+            HeaderValue::from_str(value).map_err(|_| invalid())?,
+        );
+    }
+    let mut builder = Client::builder()
+        .timeout(request_timeout.unwrap_or(HTTP_CLIENT_TIMEOUT))
+        .pool_max_idle_per_host(max_connections.unwrap_or(DEFAULT_MAX_CONNECTIONS))
+        .default_headers(header_map);
+    if let Some(tls) = tls {
+        // client certificates loaded from PEM are only supported by rustls
+        builder = builder.use_rustls_tls().identity(tls.identity().clone());
+        if let Some(ca_cert) = tls.ca_cert() {
+            builder = builder.add_root_certificate(ca_cert.clone());
+        }
+    }
+    Ok(builder.build()?)
+}
+
+/// Wrap the provider with retries, using the given defaults for anything the
+/// retry config leaves unset.
+fn build_retrying_provider<P>(
+    provider: P,
+    retry: &RetryConfig,
+    default_max_retries: Option<u32>,
+    default_base_retry_ms: Option<u64>,
+) -> RetryingProvider<P> {
+    let max_requests = retry
+        .max_retries
+        .or(default_max_retries)
+        .map(|r| r.saturating_add(1));
+    let base_retry_ms = retry.initial_backoff_ms.or(default_base_retry_ms);
+    let mut provider = RetryingProvider::new(provider, max_requests, base_retry_ms);
+    if let Some(max_backoff_ms) = retry.max_backoff_ms {
+        provider.set_max_retry_ms(max_backoff_ms);
+    }
+    provider
+}
 
-    let signing_provider = SignerMiddleware::new(provider, signer);
-    Ok(signing_provider)
+/// Restrict the signer to the chain id reported by the provider.
+async fn with_provider_chain_id<M: Middleware>(
+    provider: &M,
+    signer: Signers,
+) -> Result<Signers, M::Error> {
+    let provider_chain_id = provider.get_chainid().await?;
+    Ok(ethers::signers::Signer::with_chain_id(
+        signer,
+        provider_chain_id.as_u64(),
+    ))
 }
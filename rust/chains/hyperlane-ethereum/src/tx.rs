@@ -2,21 +2,37 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use ethers::abi::Detokenize;
-use ethers::prelude::{NameOrAddress, TransactionReceipt};
-use ethers::types::Eip1559TransactionRequest;
+use ethers::prelude::{NameOrAddress, TransactionReceipt, TxHash};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Eip1559TransactionRequest, TransactionRequest, U256 as EthersU256};
 use ethers_contract::builders::ContractCall;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 use hyperlane_core::utils::fmt_bytes;
 use hyperlane_core::{ChainCommunicationError, ChainResult, KnownHyperlaneDomain, H256, U256};
 
-use crate::Middleware;
+use crate::{GasOracleConf, Middleware, TxType};
 
 /// An amount of gas to add to the estimated gas
 const GAS_ESTIMATE_BUFFER: u32 = 50000;
 
-/// Dispatches a transaction, logs the tx id, and returns the result
-pub(crate) async fn report_tx<M, D>(tx: ContractCall<M, D>) -> ChainResult<TransactionReceipt>
+/// How often a transaction is resubmitted with a higher gas price before
+/// giving up
+const MAX_RESUBMISSIONS: usize = 3;
+
+/// How much the gas price is raised on every resubmission, nodes only replace
+/// a pending transaction if the price is at least 10% higher
+const GAS_PRICE_BUMP_PERCENT: u64 = 15;
+
+/// Dispatches a transaction, logs the tx id, and returns the result. If the
+/// transaction is not confirmed within `confirmation_timeout` it is
+/// resubmitted with the same nonce and a higher gas price, unless one of the
+/// earlier submissions was mined in the meantime.
+pub(crate) async fn report_tx<M, D>(
+    mut tx: ContractCall<M, D>,
+    provider: &M,
+    confirmation_timeout: Duration,
+) -> ChainResult<TransactionReceipt>
 where
     M: Middleware + 'static,
     D: Detokenize,
@@ -33,43 +49,213 @@ where
         .cloned()
         .unwrap_or_else(|| NameOrAddress::Address(Default::default()));
 
-    info!(?to, %data, "Dispatching transaction");
-    // We can set the gas higher here!
-    let dispatch_fut = tx.send();
-    let dispatched = dispatch_fut.await?;
+    // fix the nonce up front so resubmissions replace the transaction
+    provider
+        .fill_transaction(&mut tx.tx, None)
+        .await
+        .map_err(ChainCommunicationError::from_other)?;
+
+    // all submissions share a nonce, so at most one of them can be mined
+    let mut dispatched_hashes: Vec<TxHash> = Vec::new();
+    for attempt in 0..=MAX_RESUBMISSIONS {
+        if attempt > 0 {
+            // an earlier submission may have been mined right as we timed out
+            if let Some(receipt) = mined_submission(provider, &tx.tx, &dispatched_hashes).await? {
+                return Ok(receipt);
+            }
+            bump_gas_price(&mut tx.tx);
+            warn!(
+                ?to,
+                attempt, "Resubmitting transaction with a higher gas price"
+            );
+        }
+        info!(?to, %data, "Dispatching transaction");
+        let dispatched = match tx.send().await {
+            Ok(dispatched) => dispatched,
+            // a resubmission fails with "nonce too low" once an earlier
+            // submission was mined, so check for it before giving up
+            Err(err) if attempt > 0 => {
+                warn!(?to, error = ?err, "Resubmitting transaction failed");
+                if let Some(receipt) =
+                    mined_submission(provider, &tx.tx, &dispatched_hashes).await?
+                {
+                    return Ok(receipt);
+                }
+                return Err(err.into());
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        dispatched_hashes.push(*dispatched);
+        let tx_hash: H256 = (*dispatched).into();
 
-    let tx_hash: H256 = (*dispatched).into();
+        info!(?to, %data, ?tx_hash, "Dispatched tx");
 
-    info!(?to, %data, ?tx_hash, "Dispatched tx");
+        match tokio::time::timeout(confirmation_timeout, dispatched).await {
+            // all good
+            Ok(Ok(Some(receipt))) => {
+                info!(?tx_hash, "confirmed transaction");
 
-    match tokio::time::timeout(Duration::from_secs(300), dispatched).await {
-        // all good
-        Ok(Ok(Some(receipt))) => {
-            info!(?tx_hash, "confirmed transaction");
+                return Ok(receipt);
+            }
+            // ethers-rs will return None if it can no longer poll for the tx
+            // in the mempool, which is also the case once it was replaced by
+            // an earlier submission being mined
+            Ok(Ok(None)) => {
+                return match mined_submission(provider, &tx.tx, &dispatched_hashes).await? {
+                    Some(receipt) => Ok(receipt),
+                    None => Err(ChainCommunicationError::TransactionDropped(tx_hash)),
+                }
+            }
+            // Received error, pass it through
+            Ok(Err(x)) => {
+                error!(?tx_hash, error = ?x, "encountered error when waiting for receipt");
+                return Err(x.into());
+            }
+            // Timed out
+            Err(x) => {
+                error!(?tx_hash, error = ?x, "waiting for receipt timed out");
+            }
+        }
+    }
+    match mined_submission(provider, &tx.tx, &dispatched_hashes).await? {
+        Some(receipt) => Ok(receipt),
+        None => Err(ChainCommunicationError::TransactionTimeout()),
+    }
+}
 
-            Ok(receipt)
+/// The receipt of whichever of the `dispatched` submissions of `tx` was
+/// mined, if any. Fails if the nonce of `tx` was used by a transaction which
+/// is not one of them, since resubmitting it can never succeed.
+async fn mined_submission<M>(
+    provider: &M,
+    tx: &TypedTransaction,
+    dispatched: &[TxHash],
+) -> ChainResult<Option<TransactionReceipt>>
+where
+    M: Middleware + 'static,
+{
+    // the nonce is read before the receipts, so a submission mined in between
+    // is still found
+    let nonce_used = match (tx.from(), tx.nonce()) {
+        (Some(from), Some(nonce)) => {
+            let mined_nonce = provider
+                .get_transaction_count(*from, None)
+                .await
+                .map_err(ChainCommunicationError::from_other)?;
+            mined_nonce > *nonce
         }
-        // ethers-rs will return None if it can no longer poll for the tx in the mempool
-        Ok(Ok(None)) => Err(ChainCommunicationError::TransactionDropped(tx_hash)),
-        // Received error, pass it through
-        Ok(Err(x)) => {
-            error!(?tx_hash, error = ?x, "encountered error when waiting for receipt");
-            Err(x.into())
+        _ => false,
+    };
+    for tx_hash in dispatched {
+        let receipt = provider
+            .get_transaction_receipt(*tx_hash)
+            .await
+            .map_err(ChainCommunicationError::from_other)?;
+        if let Some(receipt) = receipt {
+            info!(tx_hash = ?H256::from(*tx_hash), "Found receipt of an earlier submission");
+            return Ok(Some(receipt));
+        }
+    }
+    if nonce_used {
+        let tx_hash = dispatched.last().copied().unwrap_or_default();
+        warn!(
+            nonce = ?tx.nonce(),
+            "The nonce of the transaction was used by another transaction"
+        );
+        return Err(ChainCommunicationError::TransactionDropped(tx_hash.into()));
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod test {
+    use ethers::providers::{MockProvider, Provider};
+    use ethers::types::{Address, TransactionReceipt, TransactionRequest, TxHash, U256};
+
+    use super::*;
+
+    fn submitted_tx() -> TypedTransaction {
+        TransactionRequest::new()
+            .from(Address::repeat_byte(1))
+            .nonce(7)
+            .into()
+    }
+
+    #[tokio::test]
+    async fn finds_an_earlier_submission_once_its_nonce_is_used() {
+        let mock_provider = Arc::new(MockProvider::new());
+        let provider = Provider::new(mock_provider.clone());
+        let receipt = TransactionReceipt {
+            transaction_hash: TxHash::repeat_byte(2),
+            ..Default::default()
+        };
+
+        // The MockProvider responses we push are processed in LIFO order
+        // RPC 3: eth_getTransactionReceipt of the second submission
+        mock_provider.push(Some(receipt.clone())).unwrap();
+        // RPC 2: eth_getTransactionReceipt of the first submission
+        mock_provider
+            .push::<Option<TransactionReceipt>, _>(None)
+            .unwrap();
+        // RPC 1: eth_getTransactionCount, the nonce was used
+        mock_provider.push(U256::from(8)).unwrap();
+
+        let found = mined_submission(
+            &provider,
+            &submitted_tx(),
+            &[TxHash::repeat_byte(1), TxHash::repeat_byte(2)],
+        )
+        .await
+        .unwrap();
+        assert_eq!(found, Some(receipt));
+    }
+
+    #[tokio::test]
+    async fn fails_if_the_nonce_is_used_by_another_transaction() {
+        let mock_provider = Arc::new(MockProvider::new());
+        let provider = Provider::new(mock_provider.clone());
+
+        // RPC 2: eth_getTransactionReceipt of the only submission
+        mock_provider
+            .push::<Option<TransactionReceipt>, _>(None)
+            .unwrap();
+        // RPC 1: eth_getTransactionCount, the nonce was used
+        mock_provider.push(U256::from(8)).unwrap();
+
+        let result = mined_submission(&provider, &submitted_tx(), &[TxHash::repeat_byte(1)]).await;
+        assert!(matches!(
+            result,
+            Err(ChainCommunicationError::TransactionDropped(_))
+        ));
+    }
+}
+
+/// Raise the gas price of a transaction by `GAS_PRICE_BUMP_PERCENT`.
+fn bump_gas_price(tx: &mut TypedTransaction) {
+    let bump = |price: EthersU256| price + price * GAS_PRICE_BUMP_PERCENT / 100;
+    match tx {
+        TypedTransaction::Eip1559(inner) => {
+            inner.max_fee_per_gas = inner.max_fee_per_gas.map(bump);
+            inner.max_priority_fee_per_gas = inner.max_priority_fee_per_gas.map(bump);
         }
-        // Timed out
-        Err(x) => {
-            error!(?tx_hash, error = ?x, "waiting for receipt timed out");
-            Err(ChainCommunicationError::TransactionTimeout())
+        _ => {
+            if let Some(price) = tx.gas_price() {
+                tx.set_gas_price(bump(price));
+            }
         }
     }
 }
 
-/// Populates the gas limit and price for a transaction
+/// Populates the gas limit and price for a transaction, using the fields of
+/// `tx_type`. The estimated prices are adjusted by `gas_oracle`.
 pub(crate) async fn fill_tx_gas_params<M, D>(
     tx: ContractCall<M, D>,
     tx_gas_limit: Option<U256>,
     provider: Arc<M>,
     domain: u32,
+    tx_type: TxType,
+    gas_oracle: &GasOracleConf,
 ) -> ChainResult<ContractCall<M, D>>
 where
     M: Middleware + 'static,
@@ -83,9 +269,41 @@ where
             .saturating_add(U256::from(GAS_ESTIMATE_BUFFER).into())
             .into()
     };
-    let Ok((max_fee, max_priority_fee)) = provider.estimate_eip1559_fees(None).await else {
-        // Is not EIP 1559 chain
-        return Ok(tx.gas(gas_limit))
+    let fees = if tx_type == TxType::Legacy {
+        None
+    } else {
+        match provider.estimate_eip1559_fees(None).await {
+            Ok(fees) => Some(fees),
+            Err(err) if tx_type == TxType::Eip1559 => {
+                return Err(ChainCommunicationError::from_other(err))
+            }
+            // Is not EIP 1559 chain
+            Err(_) => None,
+        }
+    };
+    let Some((max_fee, max_priority_fee)) = fees else {
+        let gas_price = provider
+            .get_gas_price()
+            .await
+            .map_err(ChainCommunicationError::from_other)?;
+        let gas_price: EthersU256 = gas_oracle.apply(gas_price.into()).into();
+        let mut request = TransactionRequest::new();
+        if let Some(from) = tx.tx.from() {
+            request = request.from(*from);
+        }
+        if let Some(to) = tx.tx.to() {
+            request = request.to(to.clone());
+        }
+        if let Some(data) = tx.tx.data() {
+            request = request.data(data.clone());
+        }
+        if let Some(value) = tx.tx.value() {
+            request = request.value(*value);
+        }
+        request = request.gas_price(gas_price);
+        let mut legacy_tx = tx;
+        legacy_tx.tx = TypedTransaction::Legacy(request);
+        return Ok(legacy_tx.gas(gas_limit));
     };
     let max_priority_fee = if matches!(
         KnownHyperlaneDomain::try_from(domain),
@@ -97,6 +315,15 @@ where
     } else {
         max_priority_fee
     };
+    let max_fee: EthersU256 = gas_oracle.apply(max_fee.into()).into();
+    // the lower bound is meant for the total price, raising the tip to it would
+    // overpay, and the tip is part of the max fee so it can never be above it
+    let tip_oracle = GasOracleConf {
+        min_gas_price: None,
+        ..gas_oracle.clone()
+    };
+    let max_priority_fee: EthersU256 = tip_oracle.apply(max_priority_fee.into()).into();
+    let max_priority_fee = max_priority_fee.min(max_fee);
     // Is EIP 1559 chain
     let mut request = Eip1559TransactionRequest::new();
     if let Some(from) = tx.tx.from() {
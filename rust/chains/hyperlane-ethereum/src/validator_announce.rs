@@ -20,7 +20,7 @@ use crate::contracts::i_validator_announce::{
 };
 use crate::trait_builder::BuildableWithProvider;
 use crate::tx::{fill_tx_gas_params, report_tx};
-use crate::EthereumProvider;
+use crate::{EthereumProvider, GasOracleConf, TxType, DEFAULT_CONFIRMATION_TIMEOUT};
 
 impl<M> std::fmt::Display for EthereumValidatorAnnounceInternal<M>
 where
@@ -31,7 +31,12 @@ where
     }
 }
 
-pub struct ValidatorAnnounceBuilder {}
+pub struct ValidatorAnnounceBuilder {
+    /// How the gas price of announcements is set
+    pub tx_type: TxType,
+    /// Overrides applied to the estimated gas price of announcements
+    pub gas_oracle: GasOracleConf,
+}
 
 #[async_trait]
 impl BuildableWithProvider for ValidatorAnnounceBuilder {
@@ -42,7 +47,11 @@ impl BuildableWithProvider for ValidatorAnnounceBuilder {
         provider: M,
         locator: &ContractLocator,
     ) -> Self::Output {
-        Box::new(EthereumValidatorAnnounce::new(Arc::new(provider), locator))
+        Box::new(
+            EthereumValidatorAnnounce::new(Arc::new(provider), locator)
+                .with_tx_type(self.tx_type)
+                .with_gas_oracle(self.gas_oracle.clone()),
+        )
     }
 }
 
@@ -55,6 +64,8 @@ where
     contract: Arc<EthereumValidatorAnnounceInternal<M>>,
     domain: HyperlaneDomain,
     provider: Arc<M>,
+    tx_type: TxType,
+    gas_oracle: GasOracleConf,
 }
 
 impl<M> EthereumValidatorAnnounce<M>
@@ -71,9 +82,23 @@ where
             )),
             domain: locator.domain.clone(),
             provider,
+            tx_type: TxType::default(),
+            gas_oracle: GasOracleConf::default(),
         }
     }
 
+    /// Set the gas price of announcements as configured by `tx_type`.
+    pub fn with_tx_type(mut self, tx_type: TxType) -> Self {
+        self.tx_type = tx_type;
+        self
+    }
+
+    /// Adjust the estimated gas price of announcements with `gas_oracle`.
+    pub fn with_gas_oracle(mut self, gas_oracle: GasOracleConf) -> Self {
+        self.gas_oracle = gas_oracle;
+        self
+    }
+
     /// Returns a ContractCall that processes the provided message.
     /// If the provided tx_gas_limit is None, gas estimation occurs.
     async fn announce_contract_call(
@@ -87,7 +112,15 @@ where
             announcement.value.storage_location,
             serialized_signature.into(),
         );
-        fill_tx_gas_params(tx, tx_gas_limit, self.provider.clone(), self.domain.id()).await
+        fill_tx_gas_params(
+            tx,
+            tx_gas_limit,
+            self.provider.clone(),
+            self.domain.id(),
+            self.tx_type,
+            &self.gas_oracle,
+        )
+        .await
     }
 }
 
@@ -171,7 +204,8 @@ where
         let contract_call = self
             .announce_contract_call(announcement, tx_gas_limit)
             .await?;
-        let receipt = report_tx(contract_call).await?;
+        let receipt =
+            report_tx(contract_call, &self.provider, DEFAULT_CONFIRMATION_TIMEOUT).await?;
         Ok(receipt.into())
     }
 }
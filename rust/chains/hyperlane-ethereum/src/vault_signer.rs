@@ -0,0 +1,256 @@
+use std::fmt::{Debug, Formatter};
+
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::transaction::eip712::Eip712;
+use ethers::types::{Address, Signature, H256, U256};
+use ethers::utils::hash_message;
+use ethers_signers::Signer;
+use reqwest::{Client, Url};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::gcp_kms_signer::{address_from_pem, decode_der_signature, SECP256K1_N};
+
+/// Error types for the Vault signer
+#[derive(Debug, thiserror::Error)]
+pub enum VaultSignerError {
+    /// Error communicating with the Vault API
+    #[error(transparent)]
+    Reqwest(#[from] reqwest::Error),
+    /// The env var holding the Vault token is not set
+    #[error("Vault token env var `{0}` is not set")]
+    MissingToken(String),
+    /// The Vault API returned an unexpected response
+    #[error("Unexpected response from Vault: {0}")]
+    UnexpectedResponse(String),
+    /// The public key of the transit key could not be decoded
+    #[error("Invalid Vault public key: {0}")]
+    InvalidPublicKey(String),
+    /// The transit key is not a secp256k1 key
+    #[error("Unsupported Vault key type `{0}`, expected `{SECP256K1_KEY_TYPE}`")]
+    UnsupportedKeyType(String),
+    /// The signature returned by Vault could not be decoded
+    #[error("Invalid Vault signature: {0}")]
+    InvalidSignature(String),
+    /// Failed to encode EIP-712 typed data
+    #[error("Failed to encode typed data: {0}")]
+    Eip712(String),
+}
+
+/// Key type reported for secp256k1 keys by transit compatible engines.
+const SECP256K1_KEY_TYPE: &str = "ecdsa-secp256k1";
+
+/// A signer using a secp256k1 key held by a HashiCorp Vault transit secrets
+/// engine.
+///
+/// The builtin Vault transit engine does not support secp256k1, so the mount
+/// must be served by a transit compatible plugin which provides
+/// `ecdsa-secp256k1` keys. The key type is checked when the signer is created.
+///
+/// The Vault token is read from the configured env var on every request so it
+/// can be rotated without restarting the agent.
+#[derive(Clone)]
+pub struct VaultSigner {
+    client: Client,
+    /// Base url of the Vault server
+    address: Url,
+    /// Mount path of the transit secrets engine
+    mount_path: String,
+    /// Name of the transit key
+    key_name: String,
+    /// Name of the env var holding the Vault token
+    token_env: String,
+    signer_address: Address,
+    chain_id: u64,
+}
+
+impl Debug for VaultSigner {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VaultSigner")
+            .field("address", &self.address)
+            .field("mount_path", &self.mount_path)
+            .field("key_name", &self.key_name)
+            .field("signer_address", &self.signer_address)
+            .field("chain_id", &self.chain_id)
+            .finish()
+    }
+}
+
+#[derive(Deserialize)]
+struct VaultResponse<T> {
+    data: T,
+}
+
+#[derive(Deserialize)]
+struct ReadKeyResponse {
+    #[serde(rename = "type")]
+    key_type: String,
+    latest_version: u32,
+    keys: std::collections::HashMap<String, KeyVersion>,
+}
+
+#[derive(Deserialize)]
+struct KeyVersion {
+    public_key: String,
+}
+
+#[derive(Deserialize)]
+struct SignResponse {
+    signature: String,
+}
+
+impl VaultSigner {
+    /// Create a new signer for the given transit key. This fetches the public
+    /// key from Vault to derive the signer address and fails if the key is not
+    /// a secp256k1 key.
+    pub async fn new(
+        address: Url,
+        mount_path: &str,
+        key_name: &str,
+        token_env: &str,
+        chain_id: u64,
+    ) -> Result<Self, VaultSignerError> {
+        let mut signer = Self {
+            client: Client::new(),
+            address,
+            mount_path: mount_path.trim_matches('/').to_owned(),
+            key_name: key_name.to_owned(),
+            token_env: token_env.to_owned(),
+            signer_address: Address::zero(),
+            chain_id,
+        };
+        signer.signer_address = signer.fetch_address().await?;
+        Ok(signer)
+    }
+
+    fn token(&self) -> Result<String, VaultSignerError> {
+        std::env::var(&self.token_env)
+            .map_err(|_| VaultSignerError::MissingToken(self.token_env.clone()))
+    }
+
+    fn url(&self, action: &str) -> Result<Url, VaultSignerError> {
+        self.address
+            .join(&format!(
+                "v1/{}/{action}/{}",
+                self.mount_path, self.key_name
+            ))
+            .map_err(|e| VaultSignerError::UnexpectedResponse(e.to_string()))
+    }
+
+    async fn fetch_address(&self) -> Result<Address, VaultSignerError> {
+        let res = self
+            .client
+            .get(self.url("keys")?)
+            .header("X-Vault-Token", self.token()?)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+        let res: VaultResponse<ReadKeyResponse> = serde_json::from_slice(&res)
+            .map_err(|e| VaultSignerError::UnexpectedResponse(e.to_string()))?;
+        if res.data.key_type != SECP256K1_KEY_TYPE {
+            return Err(VaultSignerError::UnsupportedKeyType(res.data.key_type));
+        }
+        let key = res
+            .data
+            .keys
+            .get(&res.data.latest_version.to_string())
+            .ok_or_else(|| {
+                VaultSignerError::InvalidPublicKey("missing latest key version".into())
+            })?;
+        address_from_pem(&key.public_key).map_err(VaultSignerError::InvalidPublicKey)
+    }
+
+    /// Sign a 32-byte digest with the transit key, returning a signature with
+    /// a `v` value of 27 or 28.
+    pub(crate) async fn sign_digest(&self, digest: H256) -> Result<Signature, VaultSignerError> {
+        let body = json!({
+            "input": BASE64.encode(digest.as_bytes()),
+            "prehashed": true,
+            "marshaling_algorithm": "asn1",
+        });
+        let res = self
+            .client
+            .post(self.url("sign")?)
+            .header("X-Vault-Token", self.token()?)
+            .header("Content-Type", "application/json")
+            .body(body.to_string())
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+        let res: VaultResponse<SignResponse> = serde_json::from_slice(&res)
+            .map_err(|e| VaultSignerError::UnexpectedResponse(e.to_string()))?;
+
+        // Signatures are formatted as `vault:v<version>:<base64>`
+        let encoded = res.data.signature.rsplit(':').next().unwrap_or_default();
+        let der = BASE64
+            .decode(encoded)
+            .map_err(|e| VaultSignerError::InvalidSignature(e.to_string()))?;
+
+        let (r, s) = decode_der_signature(&der)
+            .map_err(|e| VaultSignerError::InvalidSignature(e.to_string()))?;
+        let n = U256::from_big_endian(&SECP256K1_N);
+        let s = if s > n / 2 { n - s } else { s };
+
+        [27, 28]
+            .into_iter()
+            .map(|v| Signature { r, s, v })
+            .find(|sig| sig.recover(digest).ok() == Some(self.signer_address))
+            .ok_or_else(|| {
+                VaultSignerError::InvalidSignature(
+                    "signature does not recover to the transit key address".into(),
+                )
+            })
+    }
+}
+
+#[async_trait]
+impl Signer for VaultSigner {
+    type Error = VaultSignerError;
+
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(
+        &self,
+        message: S,
+    ) -> Result<Signature, Self::Error> {
+        self.sign_digest(hash_message(message)).await
+    }
+
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature, Self::Error> {
+        let mut tx = tx.clone();
+        let chain_id = tx.chain_id().map(|id| id.as_u64()).unwrap_or(self.chain_id);
+        tx.set_chain_id(chain_id);
+
+        let mut signature = self.sign_digest(tx.sighash()).await?;
+        // EIP-155
+        signature.v = (signature.v - 27) + 35 + chain_id * 2;
+        Ok(signature)
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        payload: &T,
+    ) -> Result<Signature, Self::Error> {
+        let digest = payload
+            .encode_eip712()
+            .map_err(|e| VaultSignerError::Eip712(e.to_string()))?;
+        self.sign_digest(digest.into()).await
+    }
+
+    fn address(&self) -> Address {
+        self.signer_address
+    }
+
+    fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    fn with_chain_id<T: Into<u64>>(mut self, chain_id: T) -> Self {
+        self.chain_id = chain_id.into();
+        self
+    }
+}
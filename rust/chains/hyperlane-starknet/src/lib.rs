@@ -0,0 +1,8 @@
+//! Implementation of hyperlane for starknet.
+
+#![forbid(unsafe_code)]
+#![warn(missing_docs)]
+
+pub use trait_builder::*;
+
+mod trait_builder;
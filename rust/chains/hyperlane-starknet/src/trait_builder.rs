@@ -0,0 +1,137 @@
+use hyperlane_core::{config::*, H256};
+use url::Url;
+
+/// The prime defining the field starknet felts are elements of,
+/// `2^251 + 17 * 2^192 + 1`.
+const FELT_PRIME: [u8; 32] = [
+    0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x11, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+];
+
+/// Starknet connection configuration
+#[derive(Debug, Clone)]
+pub struct ConnectionConf {
+    /// The JSON-RPC url to connect to
+    pub url: Url,
+    /// The chain ID as a felt, e.g. the encoding of `SN_MAIN`
+    pub chain_id: H256,
+    /// The address of the account used to send transactions
+    pub account_address: H256,
+}
+
+/// Check whether a 32 byte value is a valid felt, i.e. less than the field
+/// prime.
+pub fn is_valid_felt(value: &H256) -> bool {
+    value.as_bytes() < FELT_PRIME.as_slice()
+}
+
+/// Parse a felt from a `0x` prefixed hex string, or from a short string of up
+/// to 31 ascii characters such as `SN_MAIN`.
+pub fn parse_felt(s: &str) -> Result<H256, ConnectionConfError> {
+    let invalid = || ConnectionConfError::InvalidFelt(s.into());
+    let mut bytes = [0u8; 32];
+    if let Some(hex_str) = s.strip_prefix("0x") {
+        if hex_str.is_empty() || hex_str.len() > 64 {
+            return Err(invalid());
+        }
+        let padded = format!("{hex_str:0>64}");
+        hex::decode_to_slice(padded, &mut bytes).map_err(|_| invalid())?;
+    } else {
+        if s.is_empty() || s.len() > 31 || !s.is_ascii() {
+            return Err(invalid());
+        }
+        bytes[32 - s.len()..].copy_from_slice(s.as_bytes());
+    }
+    let felt = H256::from(bytes);
+    if is_valid_felt(&felt) {
+        Ok(felt)
+    } else {
+        Err(invalid())
+    }
+}
+
+/// Raw starknet connection configuration used for better deserialization
+/// errors.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeprecatedRawConnectionConf {
+    url: Option<String>,
+    chain_id: Option<String>,
+    account_address: Option<String>,
+}
+
+/// An error type when parsing a connection configuration.
+#[derive(thiserror::Error, Debug)]
+pub enum ConnectionConfError {
+    /// Missing `url` for connection configuration
+    #[error("Missing `url` for connection configuration")]
+    MissingConnectionUrl,
+    /// Missing `chainId` for connection configuration
+    #[error("Missing `chainId` for connection configuration")]
+    MissingChainId,
+    /// Missing `accountAddress` for connection configuration
+    #[error("Missing `accountAddress` for connection configuration")]
+    MissingAccountAddress,
+    /// Invalid `url` for connection configuration
+    #[error("Invalid `url` for connection configuration: `{0}` ({1})")]
+    InvalidConnectionUrl(String, url::ParseError),
+    /// A value is not a valid felt
+    #[error("Invalid felt `{0}`, expected a 0x prefixed hex string below the field prime or a short string")]
+    InvalidFelt(String),
+}
+
+impl FromRawConf<DeprecatedRawConnectionConf> for ConnectionConf {
+    fn from_config_filtered(
+        raw: DeprecatedRawConnectionConf,
+        cwp: &ConfigPath,
+        _filter: (),
+    ) -> ConfigResult<Self> {
+        use ConnectionConfError::*;
+
+        let mut err = ConfigParsingError::default();
+
+        let url = raw
+            .url
+            .ok_or(MissingConnectionUrl)
+            .and_then(|url| url.parse().map_err(|e| InvalidConnectionUrl(url, e)))
+            .take_err(&mut err, || cwp + "url");
+        let chain_id = raw
+            .chain_id
+            .ok_or(MissingChainId)
+            .and_then(|id| parse_felt(&id))
+            .take_err(&mut err, || cwp + "chain_id");
+        let account_address = raw
+            .account_address
+            .ok_or(MissingAccountAddress)
+            .and_then(|addr| parse_felt(&addr))
+            .take_err(&mut err, || cwp + "account_address");
+
+        cfg_unwrap_all!(cwp, err: [url, chain_id, account_address]);
+
+        err.into_result(Self {
+            url,
+            chain_id,
+            account_address,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_felts() {
+        assert_eq!(parse_felt("0x1").unwrap(), H256::from_low_u64_be(1));
+        assert_eq!(
+            parse_felt("SN_MAIN").unwrap(),
+            H256::from_low_u64_be(0x534e5f4d41494e)
+        );
+        assert!(
+            parse_felt("0x0800000000000011000000000000000000000000000000000000000000000001")
+                .is_err()
+        );
+        assert!(parse_felt("0x").is_err());
+        assert!(parse_felt("0xzz").is_err());
+    }
+}
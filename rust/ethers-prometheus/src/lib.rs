@@ -3,6 +3,8 @@
 #![forbid(unsafe_code)]
 #![warn(missing_docs)]
 
+use std::collections::HashMap;
+
 use ethers::prelude::U256;
 
 mod contracts;
@@ -11,16 +13,24 @@ pub mod json_rpc_client;
 pub mod middleware;
 
 /// Some basic information about a chain.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize))]
 #[cfg_attr(feature = "serde", serde(tag = "type", rename_all = "camelCase"))]
 pub struct ChainInfo {
     /// A human-friendly name for the chain. This should be a short string like
     /// "kovan".
     pub name: Option<String>,
+    /// Extra labels added to the metrics of this chain, e.g. `region`. They
+    /// are only reported for the label names the metrics were created with.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub labels: HashMap<String, String>,
+    /// The native token of the chain, used to scale and label wallet
+    /// balances. Balances are scaled by 18 decimals if it is not set.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub native_token: Option<middleware::TokenInfo>,
 }
 
 /// Convert a u256 scaled integer value into the corresponding f64 value.
 fn u256_as_scaled_f64(value: U256, decimals: u8) -> f64 {
-    value.to_f64_lossy() / (10u64.pow(decimals as u32) as f64)
+    value.to_f64_lossy() / 10f64.powi(decimals as i32)
 }
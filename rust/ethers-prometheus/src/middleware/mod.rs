@@ -256,6 +256,12 @@ pub struct MiddlewareMetrics {
     /// - `token_name`: Full name of the token.
     #[builder(setter(into, strip_option), default)]
     wallet_balance: Option<GaugeVec>,
+
+    /// Names of the extra chain labels, see [`ChainInfo::labels`], which all
+    /// of the metrics were created with after the labels described above.
+    /// Chains which do not set one of them report it as empty.
+    #[builder(setter(into), default)]
+    chain_label_names: Vec<String>,
 }
 
 /// An ethers-rs middleware that instruments calls with prometheus metrics. To
@@ -311,9 +317,12 @@ impl<M: Middleware> Middleware for PrometheusMiddleware<M> {
         let start = Instant::now();
         let tx: TypedTransaction = tx.into();
 
-        let chain = {
+        let (chain, chain_labels) = {
             let data = self.conf.read().await;
-            chain_name(&data.chain).to_owned()
+            (
+                chain_name(&data.chain).to_owned(),
+                chain_labels(&self.metrics.chain_label_names, &data.chain),
+            )
         };
         let addr_from: String = tx
             .from()
@@ -328,12 +337,15 @@ impl<M: Middleware> Middleware for PrometheusMiddleware<M> {
             .unwrap_or_else(|| "none".into());
 
         if let Some(m) = &self.metrics.transaction_send_total {
-            m.with(&hashmap! {
-                "chain" => chain.as_str(),
-                "address_from" => addr_from.as_str(),
-                "address_to" => addr_to.as_str(),
-                "txn_status" => "dispatched"
-            })
+            m.with(&with_chain_labels(
+                hashmap! {
+                    "chain" => chain.as_str(),
+                    "address_from" => addr_from.as_str(),
+                    "address_to" => addr_to.as_str(),
+                    "txn_status" => "dispatched"
+                },
+                &chain_labels,
+            ))
             .inc()
         }
 
@@ -341,21 +353,27 @@ impl<M: Middleware> Middleware for PrometheusMiddleware<M> {
 
         if let Some(m) = &self.metrics.transaction_send_duration_seconds {
             let duration = (Instant::now() - start).as_secs_f64();
-            m.with(&hashmap! {
-                "chain" => chain.as_str(),
-                "address_from" => addr_from.as_str(),
-                "address_to" => addr_to.as_str(),
-                "txn_status" => if result.is_ok() { "completed" } else { "failed" }
-            })
+            m.with(&with_chain_labels(
+                hashmap! {
+                    "chain" => chain.as_str(),
+                    "address_from" => addr_from.as_str(),
+                    "address_to" => addr_to.as_str(),
+                    "txn_status" => if result.is_ok() { "completed" } else { "failed" }
+                },
+                &chain_labels,
+            ))
             .inc_by(duration);
         }
         if let Some(m) = &self.metrics.transaction_send_total {
-            m.with(&hashmap! {
-                "chain" => chain.as_str(),
-                "address_from" => addr_from.as_str(),
-                "address_to" => addr_to.as_str(),
-                "txn_status" => if result.is_ok() { "completed" } else { "failed" }
-            })
+            m.with(&with_chain_labels(
+                hashmap! {
+                    "chain" => chain.as_str(),
+                    "address_from" => addr_from.as_str(),
+                    "address_to" => addr_to.as_str(),
+                    "txn_status" => if result.is_ok() { "completed" } else { "failed" }
+                },
+                &chain_labels,
+            ))
             .inc()
         }
 
@@ -375,6 +393,7 @@ impl<M: Middleware> Middleware for PrometheusMiddleware<M> {
         {
             let data = self.conf.read().await;
             let chain = chain_name(&data.chain);
+            let chain_labels = chain_labels(&self.metrics.chain_label_names, &data.chain);
             let empty_hm = HashMap::default();
             let (contract_addr, contract_name, contract_fns) = tx
                 .to()
@@ -403,13 +422,16 @@ impl<M: Middleware> Middleware for PrometheusMiddleware<M> {
                 .map(|s| format!("{:02x}{:02x}{:02x}{:02x}", s[0], s[1], s[2], s[3]))
                 .unwrap_or_else(|| "unknown".into());
 
-            let labels = hashmap! {
-                "chain" => chain,
-                "contract_name" => contract_name.as_str(),
-                "contract_address" => contract_addr.as_str(),
-                "function_name" => fn_name,
-                "function_selector" => &fn_selector,
-            };
+            let labels = with_chain_labels(
+                hashmap! {
+                    "chain" => chain,
+                    "contract_name" => contract_name.as_str(),
+                    "contract_address" => contract_addr.as_str(),
+                    "function_name" => fn_name,
+                    "function_selector" => &fn_selector,
+                },
+                &chain_labels,
+            );
             if let Some(m) = &self.metrics.contract_call_count {
                 m.with(&labels).inc();
             }
@@ -442,6 +464,7 @@ impl<M: Middleware> Middleware for PrometheusMiddleware<M> {
                 acc
             };
             let chain = chain_name(&data.chain);
+            let chain_labels = chain_labels(&self.metrics.chain_label_names, &data.chain);
             let (address, contract_name) = filter
                 .address
                 .as_ref()
@@ -486,15 +509,18 @@ impl<M: Middleware> Middleware for PrometheusMiddleware<M> {
             let topic1 = topic_str(1);
             let topic2 = topic_str(2);
             let topic3 = topic_str(3);
-            let labels = hashmap! {
-                "chain" => chain,
-                "contract_name" => &contract_name,
-                "address" => &address,
-                "topic0" => &topic0,
-                "topic1" => &topic1,
-                "topic2" => &topic2,
-                "topic3" => &topic3
-            };
+            let labels = with_chain_labels(
+                hashmap! {
+                    "chain" => chain,
+                    "contract_name" => &contract_name,
+                    "address" => &address,
+                    "topic0" => &topic0,
+                    "topic1" => &topic1,
+                    "topic2" => &topic2,
+                    "topic3" => &topic3
+                },
+                &chain_labels,
+            );
             if let Some(m) = &self.metrics.logs_query_count {
                 m.with(&labels).inc();
             }
@@ -583,6 +609,7 @@ impl<M: Middleware + Send + Sync> PrometheusMiddleware<M> {
         let wallet_balance = self.metrics.wallet_balance.clone();
         let block_height = self.metrics.block_height.clone();
         let gas_price_gwei = self.metrics.gas_price_gwei.clone();
+        let chain_label_names = self.metrics.chain_label_names.clone();
 
         let data_ref = self.conf.clone();
         let client = self.inner.clone();
@@ -590,13 +617,28 @@ impl<M: Middleware + Send + Sync> PrometheusMiddleware<M> {
         async move {
             let data = data_ref.read().await;
             let chain = chain_name(&data.chain);
+            let chain_labels = chain_labels(&chain_label_names, &data.chain);
             debug!("Updating metrics for chain ({chain})");
 
             if block_height.is_some() || gas_price_gwei.is_some() {
-                Self::update_block_details(&*client, chain, block_height, gas_price_gwei).await;
+                Self::update_block_details(
+                    &*client,
+                    chain,
+                    &chain_labels,
+                    block_height,
+                    gas_price_gwei,
+                )
+                .await;
             }
             if let Some(wallet_balance) = wallet_balance {
-                Self::update_wallet_balances(client.clone(), &data, chain, wallet_balance).await;
+                Self::update_wallet_balances(
+                    client.clone(),
+                    &data,
+                    chain,
+                    &chain_labels,
+                    wallet_balance,
+                )
+                .await;
             }
 
             // more metrics to come...
@@ -606,6 +648,7 @@ impl<M: Middleware + Send + Sync> PrometheusMiddleware<M> {
     async fn update_block_details(
         client: &M,
         chain: &str,
+        chain_labels: &[(String, String)],
         block_height: Option<IntGaugeVec>,
         gas_price_gwei: Option<GaugeVec>,
     ) {
@@ -622,14 +665,22 @@ impl<M: Middleware + Send + Sync> PrometheusMiddleware<M> {
                 .as_u64() as i64;
             trace!("Block height for chain {chain} is {height}");
             block_height
-                .with(&hashmap! { "chain" => chain })
+                .with(&with_chain_labels(
+                    hashmap! { "chain" => chain },
+                    chain_labels,
+                ))
                 .set(height);
         }
         if let Some(gas_price_gwei) = gas_price_gwei {
             if let Some(london_fee) = current_block.base_fee_per_gas {
                 let gas = u256_as_scaled_f64(london_fee, 18) * 1e9;
                 trace!("Gas price for chain {chain} is {gas:.1}gwei");
-                gas_price_gwei.with(&hashmap! { "chain" => chain }).set(gas);
+                gas_price_gwei
+                    .with(&with_chain_labels(
+                        hashmap! { "chain" => chain },
+                        chain_labels,
+                    ))
+                    .set(gas);
             } else {
                 trace!("Gas price for chain {chain} unknown, chain is pre-london");
             }
@@ -640,8 +691,18 @@ impl<M: Middleware + Send + Sync> PrometheusMiddleware<M> {
         client: Arc<M>,
         data: &PrometheusMiddlewareConf,
         chain: &str,
+        chain_labels: &[(String, String)],
         wallet_balance_metric: GaugeVec,
     ) {
+        let native_token = data
+            .chain
+            .as_ref()
+            .and_then(|c| c.native_token.clone())
+            .unwrap_or_else(|| TokenInfo {
+                name: "Native".into(),
+                symbol: "Native".into(),
+                decimals: 18,
+            });
         for (wallet_addr, wallet_info) in data.wallets.iter() {
             let wallet_addr_str: String = wallet_addr.encode_hex();
             let wallet_name = wallet_info.name.as_deref().unwrap_or("none");
@@ -649,19 +710,17 @@ impl<M: Middleware + Send + Sync> PrometheusMiddleware<M> {
             match client.get_balance(*wallet_addr, None).await {
                 Ok(balance) => {
                     // Okay, so the native type is not a token, but whatever, close enough.
-                    // Note: This is ETH for many chains, but not all so that is why we use `N` and `Native`
-                    // TODO: can we get away with scaling as 18 in all cases here? I am guessing not.
-                    let balance = u256_as_scaled_f64(balance, 18);
+                    let balance = u256_as_scaled_f64(balance, native_token.decimals);
                     trace!("Wallet {wallet_name} ({wallet_addr_str}) on chain {chain} balance is {balance} of the native currency");
                     wallet_balance_metric
-                        .with(&hashmap! {
+                        .with(&with_chain_labels(hashmap! {
                         "chain" => chain,
                         "wallet_address" => wallet_addr_str.as_str(),
                         "wallet_name" => wallet_name,
                         "token_address" => "none",
-                        "token_symbol" => "Native",
-                        "token_name" => "Native"
-                    }).set(balance)
+                        "token_symbol" => native_token.symbol.as_str(),
+                        "token_name" => native_token.name.as_str()
+                    }, chain_labels)).set(balance)
                 },
                 Err(e) => warn!("Metric update failed for wallet {wallet_name} ({wallet_addr_str}) on chain {chain} balance for native currency; {e}")
             }
@@ -680,14 +739,17 @@ impl<M: Middleware + Send + Sync> PrometheusMiddleware<M> {
                 };
                 trace!("Wallet {wallet_name} ({wallet_addr_str}) on chain {chain} balance is {balance}{}", token.symbol);
                 wallet_balance_metric
-                    .with(&hashmap! {
-                        "chain" => chain,
-                        "wallet_address" => wallet_addr_str.as_str(),
-                        "wallet_name" => wallet_name,
-                        "token_address" => token_addr_str.as_str(),
-                        "token_symbol" => token.symbol.as_str(),
-                        "token_name" => token.symbol.as_str()
-                    })
+                    .with(&with_chain_labels(
+                        hashmap! {
+                            "chain" => chain,
+                            "wallet_address" => wallet_addr_str.as_str(),
+                            "wallet_name" => wallet_name,
+                            "token_address" => token_addr_str.as_str(),
+                            "token_symbol" => token.symbol.as_str(),
+                            "token_name" => token.symbol.as_str()
+                        },
+                        chain_labels,
+                    ))
                     .set(balance);
             }
         }
@@ -707,3 +769,32 @@ fn chain_name(chain: &Option<ChainInfo>) -> &str {
         .and_then(|c| c.name.as_deref())
         .unwrap_or("unknown")
 }
+
+/// The values of the extra chain labels the metrics were created with, empty
+/// for the ones the chain does not set.
+fn chain_labels(names: &[String], chain: &Option<ChainInfo>) -> Vec<(String, String)> {
+    names
+        .iter()
+        .map(|name| {
+            let value = chain
+                .as_ref()
+                .and_then(|c| c.labels.get(name))
+                .cloned()
+                .unwrap_or_default();
+            (name.clone(), value)
+        })
+        .collect()
+}
+
+/// Add the extra chain labels to the labels of a metric.
+fn with_chain_labels<'a>(
+    mut labels: HashMap<&'a str, &'a str>,
+    chain_labels: &'a [(String, String)],
+) -> HashMap<&'a str, &'a str> {
+    labels.extend(
+        chain_labels
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str())),
+    );
+    labels
+}
@@ -7,7 +7,7 @@ use hyperlane_core::config::*;
 use tokio::task::JoinHandle;
 use tracing::{debug_span, instrument::Instrumented, Instrument};
 
-use crate::{metrics::CoreMetrics, settings::Settings};
+use crate::{health::run_health_server, metrics::CoreMetrics, settings::Settings};
 
 /// Properties shared across all hyperlane agents
 #[derive(Debug)]
@@ -19,7 +19,7 @@ pub struct HyperlaneAgentCore {
 }
 
 /// Settings of an agent defined from configuration
-pub trait LoadableFromSettings: AsRef<Settings> + Sized {
+pub trait LoadableFromSettings: AsRef<Settings> + AsMut<Settings> + Sized {
     /// Create a new instance of these settings by reading the configs and env
     /// vars.
     fn load() -> ConfigResult<Self>;
@@ -63,11 +63,18 @@ pub async fn agent_main<A: BaseAgent>() -> Result<()> {
         color_eyre::install()?;
     }
 
-    let settings = A::Settings::load()?;
+    let mut settings = A::Settings::load()?;
     let core_settings: &Settings = settings.as_ref();
 
     let metrics = settings.as_ref().metrics(A::AGENT_NAME)?;
     core_settings.tracing.start_tracing(&metrics)?;
+    settings.as_mut().resolve_finality(&metrics).await?;
+    settings.as_mut().resolve_ens_names(&metrics).await?;
+    let core_settings: &Settings = settings.as_ref();
+    if let Some(health_addr) = core_settings.health_addr {
+        let chains = core_settings.chains.values().cloned().collect();
+        run_health_server(health_addr, chains, metrics.clone());
+    }
     let agent = A::from_settings(settings, metrics.clone()).await?;
     metrics.run_http_server();
 
@@ -0,0 +1,79 @@
+use std::ops::RangeInclusive;
+
+use hyperlane_core::ChainCommunicationError;
+
+/// Messages providers reject `eth_getLogs` ranges with when they would return
+/// too many results, lowercased.
+const TOO_MANY_RESULTS_ERRORS: [&str; 5] = [
+    "too many results",
+    "query returned more than",
+    "response size exceeded",
+    "log response size exceeded",
+    "block range is too wide",
+];
+
+/// The chunk size of an indexer which is halved whenever the provider rejects
+/// a range for returning too many results and grows back gradually as queries
+/// succeed.
+#[derive(Debug)]
+pub(crate) struct AdaptiveChunkSize {
+    bounds: RangeInclusive<u32>,
+    current: u32,
+}
+
+impl AdaptiveChunkSize {
+    /// Start at the largest chunk size within `bounds`.
+    pub fn new(bounds: RangeInclusive<u32>) -> Self {
+        let current = *bounds.end();
+        Self { bounds, current }
+    }
+
+    pub fn get(&self) -> u32 {
+        self.current
+    }
+
+    /// Halve the chunk size, returns false if it is already at the minimum.
+    pub fn shrink(&mut self) -> bool {
+        if self.current <= *self.bounds.start() {
+            return false;
+        }
+        self.current = (self.current / 2).max(*self.bounds.start());
+        true
+    }
+
+    /// Grow the chunk size by a quarter after a successful query.
+    pub fn grow(&mut self) {
+        let step = (self.current / 4).max(1);
+        self.current = self.current.saturating_add(step).min(*self.bounds.end());
+    }
+}
+
+/// Whether the provider rejected a query because it would return too many
+/// results.
+pub(crate) fn is_too_many_results(err: &ChainCommunicationError) -> bool {
+    let msg = err.to_string().to_ascii_lowercase();
+    TOO_MANY_RESULTS_ERRORS.iter().any(|e| msg.contains(e))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn shrinks_and_grows_within_bounds() {
+        let mut chunk_size = AdaptiveChunkSize::new(100..=1000);
+        assert_eq!(chunk_size.get(), 1000);
+        assert!(chunk_size.shrink());
+        assert_eq!(chunk_size.get(), 500);
+        assert!(chunk_size.shrink() && chunk_size.shrink() && chunk_size.shrink());
+        assert_eq!(chunk_size.get(), 100);
+        assert!(!chunk_size.shrink());
+
+        chunk_size.grow();
+        assert_eq!(chunk_size.get(), 125);
+        for _ in 0..20 {
+            chunk_size.grow();
+        }
+        assert_eq!(chunk_size.get(), 1000);
+    }
+}
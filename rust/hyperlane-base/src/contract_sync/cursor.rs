@@ -9,8 +9,9 @@ use std::{
 use async_trait::async_trait;
 use derive_new::new;
 use eyre::Result;
+use prometheus::IntCounter;
 use tokio::time::sleep;
-use tracing::{debug, warn};
+use tracing::{debug, error, warn};
 
 use hyperlane_core::{
     ChainCommunicationError, ChainResult, ContractSyncCursor, CursorAction, HyperlaneMessage,
@@ -19,12 +20,63 @@ use hyperlane_core::{
 };
 
 use crate::contract_sync::eta_calculator::SyncerEtaCalculator;
+use crate::settings::IndexSettings;
 
 /// Time window for the moving average used in the eta calculator in seconds.
 const ETA_TIME_WINDOW: f64 = 2. * 60.;
 
 const MAX_SEQUENCE_RANGE: u32 = 100;
 
+/// Watches the tip reported by an indexer and halts indexing once it moves
+/// back by more than `max_depth` blocks, rather than carrying on from the
+/// lower tip as if the blocks indexed above it were never there. A
+/// `max_depth` of 0 disables the check.
+#[derive(Debug, Clone)]
+pub(crate) struct ReorgGuard {
+    max_depth: u32,
+    highest_tip: u32,
+    halted: bool,
+    /// Counts the reorgs which halted indexing
+    deep_reorgs: IntCounter,
+}
+
+impl ReorgGuard {
+    pub fn new(max_depth: u32, deep_reorgs: IntCounter) -> Self {
+        Self {
+            max_depth,
+            highest_tip: 0,
+            halted: false,
+            deep_reorgs,
+        }
+    }
+
+    /// Record a newly observed tip, returns whether indexing may continue.
+    fn observe_tip(&mut self, tip: u32) -> bool {
+        if self.halted {
+            return false;
+        }
+        let depth = self.highest_tip.saturating_sub(tip);
+        if self.max_depth != 0 && depth > self.max_depth {
+            error!(
+                depth,
+                max_reorg_depth = self.max_depth,
+                highest_tip = self.highest_tip,
+                tip,
+                "CRITICAL: Observed a reorg deeper than the max reorg depth, halting indexing"
+            );
+            self.deep_reorgs.inc();
+            self.halted = true;
+            return false;
+        }
+        self.highest_tip = self.highest_tip.max(tip);
+        true
+    }
+
+    fn halted(&self) -> bool {
+        self.halted
+    }
+}
+
 /// A struct that holds the data needed for forwards and backwards
 /// message sync cursors.
 #[derive(Debug, new)]
@@ -34,9 +86,10 @@ pub(crate) struct MessageSyncCursor {
     sync_state: SyncState,
 }
 
-#[derive(Debug, new)]
+#[derive(Debug)]
 pub(crate) struct SyncState {
-    chunk_size: u32,
+    /// Bounds the block ranges which are indexed.
+    index: IndexSettings,
     /// The starting block for the cursor
     start_block: u32,
     /// The next block that should be indexed.
@@ -53,6 +106,29 @@ pub(crate) struct SyncState {
 }
 
 impl SyncState {
+    fn new(
+        index: IndexSettings,
+        start_block: u32,
+        next_block: u32,
+        mode: IndexMode,
+        next_sequence: u32,
+        direction: SyncDirection,
+    ) -> Self {
+        Self {
+            // the tip indexers report already leaves out the blocks which
+            // are not final yet
+            index: IndexSettings {
+                finality_blocks: 0,
+                ..index
+            },
+            start_block,
+            next_block,
+            mode,
+            next_sequence,
+            direction,
+        }
+    }
+
     async fn get_next_range(
         &mut self,
         max_sequence: Option<u32>,
@@ -60,7 +136,13 @@ impl SyncState {
     ) -> ChainResult<Option<RangeInclusive<u32>>> {
         // We attempt to index a range of blocks that is as large as possible.
         let range = match self.mode {
-            IndexMode::Block => self.block_range(tip),
+            IndexMode::Block => {
+                if let Some(range) = self.block_range(tip) {
+                    range
+                } else {
+                    return Ok(None);
+                }
+            }
             IndexMode::Sequence => {
                 let max_sequence = max_sequence.ok_or_else(|| {
                     ChainCommunicationError::from_other_str(
@@ -80,23 +162,32 @@ impl SyncState {
         Ok(Some(range))
     }
 
-    fn block_range(&mut self, tip: u32) -> RangeInclusive<u32> {
+    fn block_range(&mut self, tip: u32) -> Option<RangeInclusive<u32>> {
         let (from, to) = match self.direction {
             SyncDirection::Forward => {
-                let from = self.next_block;
-                let mut to = from + self.chunk_size;
-                to = u32::min(to, tip);
+                // block zero is the genesis block, which has no logs to index
+                let last_indexed = self.next_block.saturating_sub(1);
+                let range = self.index.next_range(tip.into(), last_indexed.into())?;
+                // the range ends at or below the tip so it fits a u32
+                let (mut from, to) = (*range.start() as u32, *range.end() as u32);
+                // once caught up with the tip, index the last `reorg_period`
+                // blocks again in case they were reorged since
+                if to == tip {
+                    from = from
+                        .saturating_sub(self.index.reorg_period)
+                        .max(self.start_block);
+                }
                 self.next_block = to + 1;
                 (from, to)
             }
             SyncDirection::Backward => {
                 let to = self.next_block;
-                let from = to.saturating_sub(self.chunk_size);
+                let from = to.saturating_sub(self.index.chunk_size);
                 self.next_block = from.saturating_sub(1);
                 (from, to)
             }
         };
-        from..=to
+        Some(from..=to)
     }
 
     /// Returns the next sequence range to index.
@@ -178,24 +269,27 @@ impl MessageSyncCursor {
 /// A MessageSyncCursor that syncs forwards in perpetuity.
 pub(crate) struct ForwardMessageSyncCursor {
     cursor: MessageSyncCursor,
+    reorg_guard: ReorgGuard,
 }
 
 impl ForwardMessageSyncCursor {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         indexer: Arc<dyn SequenceIndexer<HyperlaneMessage>>,
         db: Arc<dyn HyperlaneMessageStore>,
-        chunk_size: u32,
+        index: IndexSettings,
         start_block: u32,
         next_block: u32,
         mode: IndexMode,
         next_sequence: u32,
+        reorg_guard: ReorgGuard,
     ) -> Self {
         Self {
             cursor: MessageSyncCursor::new(
                 indexer,
                 db,
                 SyncState::new(
-                    chunk_size,
+                    index,
                     start_block,
                     next_block,
                     mode,
@@ -203,6 +297,7 @@ impl ForwardMessageSyncCursor {
                     SyncDirection::Forward,
                 ),
             ),
+            reorg_guard,
         }
     }
 
@@ -235,6 +330,9 @@ impl ForwardMessageSyncCursor {
             else {
                 return Ok(None);
             };
+        if !self.reorg_guard.observe_tip(tip) {
+            return Ok(None);
+        }
         let cursor_count = self.cursor.sync_state.next_sequence;
         Ok(match cursor_count.cmp(&mailbox_count) {
             Ordering::Equal => {
@@ -268,8 +366,10 @@ impl ContractSyncCursor<HyperlaneMessage> for ForwardMessageSyncCursor {
         if let Some(range) = self.get_next_range().await? {
             Ok((CursorAction::Query(range), eta))
         } else {
-            // TODO: Define the sleep time from interval flag
-            Ok((CursorAction::Sleep(Duration::from_secs(5)), eta))
+            Ok((
+                CursorAction::Sleep(self.cursor.sync_state.index.poll_interval),
+                eta,
+            ))
         }
     }
 
@@ -303,7 +403,7 @@ impl BackwardMessageSyncCursor {
     pub fn new(
         indexer: Arc<dyn SequenceIndexer<HyperlaneMessage>>,
         db: Arc<dyn HyperlaneMessageStore>,
-        chunk_size: u32,
+        index: IndexSettings,
         start_block: u32,
         next_block: u32,
         mode: IndexMode,
@@ -315,7 +415,7 @@ impl BackwardMessageSyncCursor {
                 indexer,
                 db,
                 SyncState::new(
-                    chunk_size,
+                    index,
                     start_block,
                     next_block,
                     mode,
@@ -399,8 +499,9 @@ impl ForwardBackwardMessageSyncCursor {
     pub async fn new(
         indexer: Arc<dyn SequenceIndexer<HyperlaneMessage>>,
         db: Arc<dyn HyperlaneMessageStore>,
-        chunk_size: u32,
+        index: IndexSettings,
         mode: IndexMode,
+        reorg_guard: ReorgGuard,
     ) -> Result<Self> {
         let (count, tip) = indexer.sequence_and_tip().await?;
         let count = count.ok_or(ChainCommunicationError::from_other_str(
@@ -409,16 +510,17 @@ impl ForwardBackwardMessageSyncCursor {
         let forward_cursor = ForwardMessageSyncCursor::new(
             indexer.clone(),
             db.clone(),
-            chunk_size,
+            index.clone(),
             tip,
             tip,
             mode,
             count,
+            reorg_guard,
         );
         let backward_cursor = BackwardMessageSyncCursor::new(
             indexer.clone(),
             db.clone(),
-            chunk_size,
+            index,
             tip,
             tip,
             mode,
@@ -443,13 +545,17 @@ impl ContractSyncCursor<HyperlaneMessage> for ForwardBackwardMessageSyncCursor {
             self.direction = SyncDirection::Forward;
             return Ok((CursorAction::Query(forward_range), eta));
         }
+        let poll_interval = self.forward.cursor.sync_state.index.poll_interval;
+        // Messages below a deep reorg can't be trusted either.
+        if self.forward.reorg_guard.halted() {
+            return Ok((CursorAction::Sleep(poll_interval), eta));
+        }
 
         if let Some(backward_range) = self.backward.get_next_range().await? {
             self.direction = SyncDirection::Backward;
             return Ok((CursorAction::Query(backward_range), eta));
         }
-        // TODO: Define the sleep time from interval flag
-        return Ok((CursorAction::Sleep(Duration::from_secs(5)), eta));
+        Ok((CursorAction::Sleep(poll_interval), eta))
     }
 
     fn latest_block(&self) -> u32 {
@@ -475,6 +581,7 @@ pub(crate) struct RateLimitedContractSyncCursor<T> {
     last_tip_update: Instant,
     eta_calculator: SyncerEtaCalculator,
     sync_state: SyncState,
+    reorg_guard: ReorgGuard,
 }
 
 impl<T> RateLimitedContractSyncCursor<T> {
@@ -482,9 +589,10 @@ impl<T> RateLimitedContractSyncCursor<T> {
     pub async fn new(
         indexer: Arc<dyn SequenceIndexer<T>>,
         db: Arc<dyn HyperlaneWatermarkedLogStore<T>>,
-        chunk_size: u32,
+        index: IndexSettings,
         initial_height: u32,
         mode: IndexMode,
+        reorg_guard: ReorgGuard,
     ) -> Result<Self> {
         let (max_sequence, tip) = indexer.sequence_and_tip().await?;
         Ok(Self {
@@ -495,7 +603,7 @@ impl<T> RateLimitedContractSyncCursor<T> {
             last_tip_update: Instant::now(),
             eta_calculator: SyncerEtaCalculator::new(initial_height, tip, ETA_TIME_WINDOW),
             sync_state: SyncState::new(
-                chunk_size,
+                index,
                 initial_height,
                 initial_height,
                 mode,
@@ -503,13 +611,14 @@ impl<T> RateLimitedContractSyncCursor<T> {
                 // The rate limited cursor currently only syncs in the forward direction.
                 SyncDirection::Forward,
             ),
+            reorg_guard,
         })
     }
 
     /// Wait based on how close we are to the tip and update the tip,
     /// i.e. the highest block we may scrape.
     async fn get_rate_limit(&mut self) -> ChainResult<Option<Duration>> {
-        if self.sync_state.next_block + self.sync_state.chunk_size < self.tip {
+        if self.sync_state.next_block + self.sync_state.index.chunk_size < self.tip {
             // If doing the full chunk wouldn't exceed the already known tip we do not need to rate limit.
             Ok(None)
         } else {
@@ -522,6 +631,9 @@ impl<T> RateLimitedContractSyncCursor<T> {
             }
             match self.indexer.get_finalized_block_number().await {
                 Ok(tip) => {
+                    if !self.reorg_guard.observe_tip(tip) {
+                        return Ok(Some(self.sync_state.index.poll_interval));
+                    }
                     // we retrieved a new tip value, go ahead and update.
                     self.last_tip_update = Instant::now();
                     self.tip = tip;
@@ -558,7 +670,7 @@ impl<T> RateLimitedContractSyncCursor<T> {
 
     fn sync_step(&self) -> u32 {
         match self.sync_state.mode {
-            IndexMode::Block => self.sync_state.chunk_size,
+            IndexMode::Block => self.sync_state.index.chunk_size,
             IndexMode::Sequence => MAX_SEQUENCE_RANGE,
         }
     }
@@ -584,14 +696,22 @@ where
             return Ok((CursorAction::Sleep(rate_limit), eta));
         }
         let (max_sequence, tip) = self.indexer.sequence_and_tip().await?;
+        if !self.reorg_guard.observe_tip(tip) {
+            return Ok((
+                CursorAction::Sleep(self.sync_state.index.poll_interval),
+                eta,
+            ));
+        }
         self.tip = tip;
         self.max_sequence = max_sequence;
         if let Some(range) = self.sync_state.get_next_range(max_sequence, tip).await? {
             return Ok((CursorAction::Query(range), eta));
         }
 
-        // TODO: Define the sleep time from interval flag
-        Ok((CursorAction::Sleep(Duration::from_secs(5)), eta))
+        Ok((
+            CursorAction::Sleep(self.sync_state.index.poll_interval),
+            eta,
+        ))
     }
 
     fn latest_block(&self) -> u32 {
@@ -606,9 +726,33 @@ where
                 self.sync_state.start_block,
                 self.sync_state
                     .next_block
-                    .saturating_sub(self.sync_state.chunk_size),
+                    .saturating_sub(self.sync_state.index.chunk_size),
             ))
             .await?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rescans_reorg_period_once_caught_up() {
+        let index = IndexSettings {
+            chunk_size: 10,
+            reorg_period: 3,
+            ..Default::default()
+        };
+        let mut state =
+            SyncState::new(index, 100, 100, IndexMode::Block, 0, SyncDirection::Forward);
+
+        // chunks don't overlap while catching up
+        assert_eq!(state.block_range(125), Some(100..=109));
+        assert_eq!(state.block_range(125), Some(110..=119));
+        // a range reaching the tip starts `reorg_period` blocks early
+        assert_eq!(state.block_range(125), Some(117..=125));
+        assert_eq!(state.block_range(127), Some(123..=127));
+        assert_eq!(state.block_range(127), None);
+    }
+}
@@ -20,6 +20,12 @@ pub struct ContractSyncMetrics {
 
     /// See `last_known_message_nonce` in CoreMetrics.
     pub message_nonce: IntGaugeVec,
+
+    /// Reorgs deeper than the max reorg depth which halted indexing
+    ///
+    /// Labels:
+    /// - `chain`: Chain the indexer is collecting data from.
+    pub deep_reorgs: IntCounterVec,
 }
 
 impl ContractSyncMetrics {
@@ -43,10 +49,19 @@ impl ContractSyncMetrics {
 
         let message_nonce = metrics.last_known_message_nonce();
 
+        let deep_reorgs = metrics
+            .new_int_counter(
+                "contract_sync_deep_reorgs",
+                "Number of reorgs deeper than the max reorg depth, each of which halted indexing",
+                &["chain"],
+            )
+            .expect("failed to register deep_reorgs metric");
+
         ContractSyncMetrics {
             indexed_height,
             stored_events,
             message_nonce,
+            deep_reorgs,
         }
     }
 }
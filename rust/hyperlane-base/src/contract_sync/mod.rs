@@ -1,18 +1,21 @@
-use std::{fmt::Debug, marker::PhantomData, sync::Arc};
+use std::{fmt::Debug, marker::PhantomData, ops::RangeInclusive, sync::Arc};
 
+use adaptive_chunking::*;
 use cursor::*;
 use derive_new::new;
+use eyre::Context;
 use hyperlane_core::{
-    utils::fmt_sync_time, ContractSyncCursor, CursorAction, HyperlaneDomain, HyperlaneLogStore,
-    HyperlaneMessage, HyperlaneMessageStore, HyperlaneWatermarkedLogStore, Indexer,
-    SequenceIndexer,
+    utils::fmt_sync_time, ChainResult, ContractSyncCursor, CursorAction, HyperlaneDomain,
+    HyperlaneLogStore, HyperlaneMessage, HyperlaneMessageStore, HyperlaneWatermarkedLogStore,
+    IndexMode, Indexer, LogMeta, SequenceIndexer,
 };
 pub use metrics::ContractSyncMetrics;
 use tokio::time::sleep;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 use crate::settings::IndexSettings;
 
+mod adaptive_chunking;
 mod cursor;
 mod eta_calculator;
 mod metrics;
@@ -26,6 +29,9 @@ pub struct ContractSync<T, D: HyperlaneLogStore<T>, I: Indexer<T>> {
     db: D,
     indexer: I,
     metrics: ContractSyncMetrics,
+    /// Bounds of the chunk size if ranges are split adaptively, see
+    /// [`IndexSettings::adaptive_chunk_bounds`]
+    adaptive_chunk_bounds: Option<RangeInclusive<u32>>,
     _phantom: PhantomData<T>,
 }
 
@@ -40,6 +46,15 @@ where
         &self.domain
     }
 
+    fn reorg_guard(&self, index_settings: &IndexSettings) -> ReorgGuard {
+        ReorgGuard::new(
+            index_settings.max_reorg_depth,
+            self.metrics
+                .deep_reorgs
+                .with_label_values(&[self.domain.as_ref()]),
+        )
+    }
+
     /// Sync logs and write them to the LogStore
     #[tracing::instrument(name = "ContractSync", fields(domain=self.domain().name()), skip(self, cursor))]
     pub async fn sync(
@@ -56,6 +71,10 @@ where
             .metrics
             .stored_events
             .with_label_values(&[label, chain_name]);
+        let mut chunk_size = self
+            .adaptive_chunk_bounds
+            .clone()
+            .map(AdaptiveChunkSize::new);
 
         loop {
             indexed_height.set(cursor.latest_block() as i64);
@@ -64,7 +83,13 @@ where
                 CursorAction::Query(range) => {
                     debug!(?range, "Looking for for events in index range");
 
-                    let logs = self.indexer.fetch_logs(range.clone()).await?;
+                    let logs = match &mut chunk_size {
+                        Some(chunk_size) => {
+                            self.fetch_logs_adaptively(range.clone(), chunk_size)
+                                .await?
+                        }
+                        None => self.indexer.fetch_logs(range.clone()).await?,
+                    };
 
                     info!(
                         ?range,
@@ -85,6 +110,39 @@ where
             }
         }
     }
+
+    /// Fetch the logs of `range` in chunks of at most `chunk_size`, which is
+    /// halved whenever the provider reports too many results.
+    async fn fetch_logs_adaptively(
+        &self,
+        range: RangeInclusive<u32>,
+        chunk_size: &mut AdaptiveChunkSize,
+    ) -> ChainResult<Vec<(T, LogMeta)>> {
+        let mut logs = Vec::new();
+        let mut from = *range.start();
+        while from <= *range.end() {
+            let to = from.saturating_add(chunk_size.get() - 1).min(*range.end());
+            match self.indexer.fetch_logs(from..=to).await {
+                Ok(chunk) => {
+                    logs.extend(chunk);
+                    chunk_size.grow();
+                    from = match to.checked_add(1) {
+                        Some(from) => from,
+                        None => break,
+                    };
+                }
+                Err(err) if is_too_many_results(&err) && chunk_size.shrink() => {
+                    warn!(
+                        error = %err,
+                        chunk_size = chunk_size.get(),
+                        "Too many results in index range, shrinking the chunk size"
+                    );
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(logs)
+    }
 }
 
 /// A ContractSync for syncing events using a RateLimitedContractSyncCursor
@@ -98,24 +156,32 @@ where
     pub async fn rate_limited_cursor(
         &self,
         index_settings: IndexSettings,
-    ) -> Box<dyn ContractSyncCursor<T>> {
-        let watermark = self.db.retrieve_high_watermark().await.unwrap();
-        let index_settings = IndexSettings {
-            from: watermark.unwrap_or(index_settings.from),
-            chunk_size: index_settings.chunk_size,
-            mode: index_settings.mode,
+    ) -> eyre::Result<Box<dyn ContractSyncCursor<T>>> {
+        let watermark = self
+            .db
+            .retrieve_high_watermark()
+            .await
+            .context("Retrieving the high watermark")?;
+        let from = match watermark {
+            Some(watermark) => watermark,
+            None => index_settings
+                .from
+                .resolve(self.indexer.as_ref())
+                .await
+                .context("Resolving the `from` block")?,
         };
-        Box::new(
+        Ok(Box::new(
             RateLimitedContractSyncCursor::new(
                 Arc::new(self.indexer.clone()),
                 self.db.clone(),
-                index_settings.chunk_size,
-                index_settings.from,
-                index_settings.mode,
+                index_settings.clone(),
+                from,
+                index_settings.mode(IndexMode::Block),
+                self.reorg_guard(&index_settings),
             )
             .await
-            .unwrap(),
-        )
+            .context("Creating the rate limited cursor")?,
+        ))
     }
 }
 
@@ -131,32 +197,39 @@ impl MessageContractSync {
         &self,
         index_settings: IndexSettings,
         next_nonce: u32,
-    ) -> Box<dyn ContractSyncCursor<HyperlaneMessage>> {
-        Box::new(ForwardMessageSyncCursor::new(
+    ) -> eyre::Result<Box<dyn ContractSyncCursor<HyperlaneMessage>>> {
+        let from = index_settings
+            .from
+            .resolve(self.indexer.as_ref())
+            .await
+            .context("Resolving the `from` block")?;
+        Ok(Box::new(ForwardMessageSyncCursor::new(
             self.indexer.clone(),
             self.db.clone(),
-            index_settings.chunk_size,
-            index_settings.from,
-            index_settings.from,
-            index_settings.mode,
+            index_settings.clone(),
+            from,
+            from,
+            index_settings.mode(IndexMode::Sequence),
             next_nonce,
-        ))
+            self.reorg_guard(&index_settings),
+        )))
     }
 
     /// Returns a new cursor to be used for syncing dispatched messages from the indexer
     pub async fn forward_backward_message_sync_cursor(
         &self,
         index_settings: IndexSettings,
-    ) -> Box<dyn ContractSyncCursor<HyperlaneMessage>> {
-        Box::new(
+    ) -> eyre::Result<Box<dyn ContractSyncCursor<HyperlaneMessage>>> {
+        Ok(Box::new(
             ForwardBackwardMessageSyncCursor::new(
                 self.indexer.clone(),
                 self.db.clone(),
-                index_settings.chunk_size,
-                index_settings.mode,
+                index_settings.clone(),
+                index_settings.mode(IndexMode::Sequence),
+                self.reorg_guard(&index_settings),
             )
             .await
-            .unwrap(),
-        )
+            .context("Creating the forward-backward message cursor")?,
+        ))
     }
 }
@@ -23,6 +23,7 @@ use super::{
 
 const MESSAGE_ID: &str = "message_id_";
 const MESSAGE_DISPATCHED_BLOCK_NUMBER: &str = "message_dispatched_block_number_";
+const MESSAGE_DISPATCHED_BLOCK_HASH: &str = "message_dispatched_block_hash_";
 const MESSAGE: &str = "message_";
 const NONCE_PROCESSED: &str = "nonce_processed_";
 const GAS_PAYMENT_FOR_MESSAGE_ID: &str = "gas_payment_for_message_id_v2_";
@@ -221,6 +222,9 @@ impl HyperlaneLogStore<HyperlaneMessage> for HyperlaneRocksDB {
         for (message, meta) in messages {
             let stored_message = self.store_message(message, meta.block_number)?;
             if stored_message {
+                // - `nonce` --> `dispatched block hash`, used to look up when
+                // the message was dispatched
+                self.store_dispatched_block_hash_by_nonce(&message.nonce, &meta.block_hash)?;
                 stored += 1;
             }
         }
@@ -312,6 +316,13 @@ macro_rules! make_store_and_retrieve {
 make_store_and_retrieve!(pub, message_id_by_nonce, MESSAGE_ID, u32, H256);
 make_store_and_retrieve!(pub(self), message_by_id, MESSAGE, H256, HyperlaneMessage);
 make_store_and_retrieve!(pub(self), dispatched_block_number_by_nonce, MESSAGE_DISPATCHED_BLOCK_NUMBER, u32, u64);
+make_store_and_retrieve!(
+    pub,
+    dispatched_block_hash_by_nonce,
+    MESSAGE_DISPATCHED_BLOCK_HASH,
+    u32,
+    H256
+);
 make_store_and_retrieve!(pub, processed_by_nonce, NONCE_PROCESSED, u32, bool);
 make_store_and_retrieve!(pub(self), processed_by_gas_payment_meta, GAS_PAYMENT_META_PROCESSED, InterchainGasPaymentMeta, bool);
 make_store_and_retrieve!(pub(self), interchain_gas_expenditure_data_by_message_id, GAS_EXPENDITURE_FOR_MESSAGE_ID, H256, InterchainGasExpenditureData);
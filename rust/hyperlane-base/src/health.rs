@@ -0,0 +1,117 @@
+use std::{
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use eyre::{eyre, Result};
+use futures_util::future::join_all;
+use hyperlane_core::HyperlaneProvider;
+use tokio::{
+    task::JoinHandle,
+    time::{sleep, timeout},
+};
+use tracing::{info, warn};
+
+use crate::{settings::ChainConf, CoreMetrics};
+
+/// How often the connections to the chains are checked.
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+/// How long checking the connection to a single chain may take.
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Serve `/health` on `addr` for liveness and readiness probes. It returns 200
+/// while every chain in `chains` can be reached and 503 otherwise. The chains
+/// are checked again every few seconds, so an agent which loses its
+/// connection to a chain becomes unhealthy until it can reach it again.
+pub fn run_health_server(
+    addr: SocketAddr,
+    chains: Vec<ChainConf>,
+    metrics: Arc<CoreMetrics>,
+) -> JoinHandle<()> {
+    use warp::{http::StatusCode, Filter};
+
+    let healthy = Arc::new(AtomicBool::new(false));
+    tokio::spawn(check_chains(chains, metrics, healthy.clone()));
+
+    info!(%addr, "starting health server");
+    tokio::spawn(async move {
+        warp::serve(warp::path!("health").map(move || {
+            if healthy.load(Ordering::Relaxed) {
+                warp::reply::with_status("ok", StatusCode::OK)
+            } else {
+                warp::reply::with_status("chains unreachable", StatusCode::SERVICE_UNAVAILABLE)
+            }
+        }))
+        .try_bind(addr)
+        .await;
+        warn!("Health server could not be started or exited early");
+    })
+}
+
+/// Check every chain on an interval, marking the agent as healthy only while
+/// all of them can be reached.
+async fn check_chains(chains: Vec<ChainConf>, metrics: Arc<CoreMetrics>, healthy: Arc<AtomicBool>) {
+    let mut chains: Vec<ChainHealth> = chains.into_iter().map(ChainHealth::new).collect();
+    loop {
+        let results = join_all(chains.iter_mut().map(|chain| chain.check(&metrics))).await;
+        let all_healthy = results.into_iter().all(|ok| ok);
+        if healthy.swap(all_healthy, Ordering::Relaxed) != all_healthy {
+            if all_healthy {
+                info!("All chains are reachable, reporting healthy");
+            } else {
+                warn!("Not all chains are reachable, reporting unhealthy");
+            }
+        }
+        sleep(HEALTH_CHECK_INTERVAL).await;
+    }
+}
+
+/// The connection to a chain, reusing its provider between checks.
+struct ChainHealth {
+    chain: ChainConf,
+    provider: Option<Box<dyn HyperlaneProvider>>,
+    healthy: bool,
+}
+
+impl ChainHealth {
+    fn new(chain: ChainConf) -> Self {
+        Self {
+            chain,
+            provider: None,
+            healthy: false,
+        }
+    }
+
+    /// Check the chain can be reached. The provider is rebuilt after a
+    /// failure.
+    async fn check(&mut self, metrics: &CoreMetrics) -> bool {
+        let result = match timeout(HEALTH_CHECK_TIMEOUT, self.try_check(metrics)).await {
+            Ok(result) => result,
+            Err(_) => Err(eyre!("Timed out after {HEALTH_CHECK_TIMEOUT:?}")),
+        };
+        let chain = self.chain.domain.name();
+        match result {
+            Ok(()) if !self.healthy => info!(chain, "Connected to chain"),
+            Ok(()) => {}
+            Err(err) => {
+                warn!(chain, error = %err, "Failed to connect to chain");
+                self.provider = None;
+            }
+        }
+        self.healthy = self.provider.is_some();
+        self.healthy
+    }
+
+    async fn try_check(&mut self, metrics: &CoreMetrics) -> Result<()> {
+        if self.provider.is_none() {
+            self.provider = Some(self.chain.build_provider(metrics).await?);
+        }
+        let provider = self.provider.as_ref().unwrap();
+        provider.is_contract(&self.chain.addresses.mailbox).await?;
+        Ok(())
+    }
+}
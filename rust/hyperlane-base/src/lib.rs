@@ -15,6 +15,10 @@ pub use agent::*;
 mod metrics;
 pub use metrics::*;
 
+/// Health endpoint for liveness and readiness probes
+mod health;
+pub use health::*;
+
 mod contract_sync;
 pub use contract_sync::*;
 
@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 use std::fmt::{Debug, Formatter};
+use std::net::SocketAddr;
 use std::sync::{Arc, OnceLock};
 
 use eyre::Result;
@@ -30,7 +31,7 @@ pub struct CoreMetrics {
     /// Metrics registry for adding new metrics and gathering reports
     registry: Registry,
     const_labels: HashMap<String, String>,
-    listen_port: u16,
+    listen_addr: SocketAddr,
     agent_name: String,
 
     span_durations: CounterVec,
@@ -38,6 +39,7 @@ pub struct CoreMetrics {
     span_events: IntCounterVec,
     last_known_message_nonce: IntGaugeVec,
     validator_checkpoint_index: IntGaugeVec,
+    checkpoint_signature_mismatches: IntCounterVec,
     submitter_queue_length: IntGaugeVec,
 
     operations_processed_count: IntCounterVec,
@@ -51,15 +53,21 @@ pub struct CoreMetrics {
 
     /// Set of provider-specific metrics. These only need to get created once.
     provider_metrics: OnceLock<MiddlewareMetrics>,
+    /// Names of the extra labels chains add to their provider metrics.
+    chain_label_names: Vec<String>,
 }
 
 impl CoreMetrics {
     /// Track metrics for a particular agent name.
     ///
     /// - `for_agent` name of the agent these metrics are tracking.
-    /// - `listen_port` port to start the HTTP server on.
+    /// - `listen_addr` address to start the HTTP server on.
     /// - `registry` prometheus registry to attach the metrics to
-    pub fn new(for_agent: &str, listen_port: u16, registry: Registry) -> prometheus::Result<Self> {
+    pub fn new(
+        for_agent: &str,
+        listen_addr: SocketAddr,
+        registry: Registry,
+    ) -> prometheus::Result<Self> {
         let const_labels: HashMap<String, String> = labels! {
             namespaced!("baselib_version") => env!("CARGO_PKG_VERSION").into(),
             "agent".into() => for_agent.into(),
@@ -119,6 +127,16 @@ impl CoreMetrics {
             registry
         )?;
 
+        let checkpoint_signature_mismatches = register_int_counter_vec_with_registry!(
+            opts!(
+                namespaced!("checkpoint_signature_mismatches"),
+                "Fetched checkpoints rejected because they were not signed by their validator",
+                const_labels_ref
+            ),
+            &["validator"],
+            registry
+        )?;
+
         let submitter_queue_length = register_int_gauge_vec_with_registry!(
             opts!(
                 namespaced!("submitter_queue_length"),
@@ -162,7 +180,7 @@ impl CoreMetrics {
         Ok(Self {
             agent_name: for_agent.into(),
             registry,
-            listen_port,
+            listen_addr,
             const_labels,
 
             span_durations,
@@ -170,6 +188,7 @@ impl CoreMetrics {
             span_events,
             last_known_message_nonce,
             validator_checkpoint_index,
+            checkpoint_signature_mismatches,
 
             submitter_queue_length,
 
@@ -180,9 +199,18 @@ impl CoreMetrics {
 
             json_rpc_client_metrics: OnceLock::new(),
             provider_metrics: OnceLock::new(),
+            chain_label_names: Vec::new(),
         })
     }
 
+    /// Create the provider metrics with these extra labels, which chains set
+    /// with `metricsLabels`. This has no effect once the provider metrics were
+    /// created.
+    pub fn with_chain_label_names(mut self, names: Vec<String>) -> Self {
+        self.chain_label_names = names;
+        self
+    }
+
     /// Create the provider metrics attached to this core metrics instance.
     pub fn provider_metrics(&self) -> MiddlewareMetrics {
         self.provider_metrics
@@ -306,6 +334,14 @@ impl CoreMetrics {
         self.validator_checkpoint_index.clone()
     }
 
+    /// Counter for fetched checkpoints which were rejected because they were
+    /// not signed by the validator whose syncer they were read from
+    /// Labels:
+    /// - `validator`: Address of the validator
+    pub fn checkpoint_signature_mismatches(&self) -> IntCounterVec {
+        self.checkpoint_signature_mismatches.clone()
+    }
+
     /// Latest message nonce in the validator.
     ///
     /// Phase:
@@ -417,8 +453,8 @@ impl CoreMetrics {
     /// scrape me!
     pub fn run_http_server(self: Arc<Self>) -> JoinHandle<()> {
         use warp::Filter;
-        let port = self.listen_port;
-        tracing::info!(port, "starting prometheus server on 0.0.0.0");
+        let addr = self.listen_addr;
+        tracing::info!(%addr, "starting prometheus server");
         tokio::spawn(async move {
             warp::serve(
                 warp::path!("metrics")
@@ -440,7 +476,7 @@ impl CoreMetrics {
                         )
                     })),
             )
-            .try_bind(([0, 0, 0, 0], port))
+            .try_bind(addr)
             .await;
             warn!("Prometheus server could not be started or exited early");
         })
@@ -451,6 +487,11 @@ impl CoreMetrics {
         &self.agent_name
     }
 
+    /// Names of the extra labels chains add to their provider metrics.
+    pub(crate) fn chain_label_names(&self) -> &[String] {
+        &self.chain_label_names
+    }
+
     fn const_labels_str(&self) -> HashMap<&str, &str> {
         self.const_labels
             .iter()
@@ -463,8 +504,8 @@ impl Debug for CoreMetrics {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "CoreMetrics {{ agent_name: {}, listen_port: {:?} }}",
-            self.agent_name, self.listen_port
+            "CoreMetrics {{ agent_name: {}, listen_addr: {:?} }}",
+            self.agent_name, self.listen_addr
         )
     }
 }
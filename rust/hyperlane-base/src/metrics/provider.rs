@@ -9,47 +9,57 @@ pub(crate) fn create_provider_metrics(metrics: &CoreMetrics) -> Result<Middlewar
         .block_height(metrics.new_int_gauge(
             "block_height",
             BLOCK_HEIGHT_HELP,
-            BLOCK_HEIGHT_LABELS,
+            &with_chain_labels(BLOCK_HEIGHT_LABELS, metrics),
         )?)
         .gas_price_gwei(metrics.new_gauge(
             "gas_price_gwei",
             GAS_PRICE_GWEI_HELP,
-            GAS_PRICE_GWEI_LABELS,
+            &with_chain_labels(GAS_PRICE_GWEI_LABELS, metrics),
         )?)
         .contract_call_duration_seconds(metrics.new_counter(
             "contract_call_duration_seconds",
             CONTRACT_CALL_DURATION_SECONDS_HELP,
-            CONTRACT_CALL_DURATION_SECONDS_LABELS,
+            &with_chain_labels(CONTRACT_CALL_DURATION_SECONDS_LABELS, metrics),
         )?)
         .contract_call_count(metrics.new_int_counter(
             "contract_call_count",
             CONTRACT_CALL_COUNT_HELP,
-            CONTRACT_CALL_COUNT_LABELS,
+            &with_chain_labels(CONTRACT_CALL_COUNT_LABELS, metrics),
         )?)
         .logs_query_duration_seconds(metrics.new_counter(
             "logs_query_duration_seconds",
             LOGS_QUERY_DURATION_SECONDS_HELP,
-            LOGS_QUERY_DURATION_SECONDS_LABELS,
+            &with_chain_labels(LOGS_QUERY_DURATION_SECONDS_LABELS, metrics),
         )?)
         .logs_query_count(metrics.new_int_counter(
             "logs_query_count",
             LOG_QUERY_COUNT_HELP,
-            LOGS_QUERY_COUNT_LABELS,
+            &with_chain_labels(LOGS_QUERY_COUNT_LABELS, metrics),
         )?)
         .transaction_send_duration_seconds(metrics.new_counter(
             "transaction_send_duration_seconds",
             TRANSACTION_SEND_DURATION_SECONDS_HELP,
-            TRANSACTION_SEND_DURATION_SECONDS_LABELS,
+            &with_chain_labels(TRANSACTION_SEND_DURATION_SECONDS_LABELS, metrics),
         )?)
         .transaction_send_total(metrics.new_int_counter(
             "transaction_send_total",
             TRANSACTION_SEND_TOTAL_HELP,
-            TRANSACTION_SEND_TOTAL_LABELS,
+            &with_chain_labels(TRANSACTION_SEND_TOTAL_LABELS, metrics),
         )?)
         .wallet_balance(metrics.new_gauge(
             "wallet_balance",
             WALLET_BALANCE_HELP,
-            WALLET_BALANCE_LABELS,
+            &with_chain_labels(WALLET_BALANCE_LABELS, metrics),
         )?)
+        .chain_label_names(metrics.chain_label_names().to_vec())
         .build()?)
 }
+
+/// The label names of a metric followed by the extra labels of the chains.
+fn with_chain_labels<'a>(names: &[&'a str], metrics: &'a CoreMetrics) -> Vec<&'a str> {
+    names
+        .iter()
+        .copied()
+        .chain(metrics.chain_label_names().iter().map(String::as_str))
+        .collect()
+}
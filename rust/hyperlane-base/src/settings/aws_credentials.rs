@@ -1,6 +1,6 @@
 use async_trait::async_trait;
 use rusoto_core::credential::{
-    AutoRefreshingProvider, AwsCredentials, CredentialsError, EnvironmentProvider,
+    AutoRefreshingProvider, AwsCredentials, CredentialsError, EnvironmentProvider, ProfileProvider,
     ProvideAwsCredentials,
 };
 use rusoto_sts::WebIdentityProvider;
@@ -14,9 +14,13 @@ use rusoto_sts::WebIdentityProvider;
 /// The primary use case is running Hyperlane agents in AWS Kubernetes cluster (EKS) configured
 /// with [IAM Roles for Service Accounts (IRSA)](https://aws.amazon.com/blogs/containers/diving-into-iam-roles-for-service-accounts/).
 /// The IRSA approach follows security best practices and allows for key rotation.
+///
+/// When built `with_profile`, credentials are only read from that named profile in the shared
+/// credentials file (`~/.aws/credentials` unless `AWS_SHARED_CREDENTIALS_FILE` is set) instead.
 pub(crate) struct AwsChainCredentialsProvider {
     environment_provider: EnvironmentProvider,
     web_identity_provider: AutoRefreshingProvider<WebIdentityProvider>,
+    profile: Option<String>,
 }
 
 impl AwsChainCredentialsProvider {
@@ -30,6 +34,16 @@ impl AwsChainCredentialsProvider {
         AwsChainCredentialsProvider {
             environment_provider: EnvironmentProvider::default(),
             web_identity_provider: auto_refreshing_provider,
+            profile: None,
+        }
+    }
+
+    /// Read credentials from the named `profile`, or from the default chain
+    /// when it is `None`.
+    pub fn with_profile(profile: Option<String>) -> Self {
+        AwsChainCredentialsProvider {
+            profile,
+            ..Self::new()
         }
     }
 }
@@ -37,6 +51,16 @@ impl AwsChainCredentialsProvider {
 #[async_trait]
 impl ProvideAwsCredentials for AwsChainCredentialsProvider {
     async fn credentials(&self) -> Result<AwsCredentials, CredentialsError> {
+        if let Some(profile) = &self.profile {
+            // a missing profile is an error rather than silently falling back to
+            // the other sources
+            let provider = ProfileProvider::with_default_credentials(profile.as_str())
+                .map_err(|e| profile_error(profile, e))?;
+            return provider
+                .credentials()
+                .await
+                .map_err(|e| profile_error(profile, e));
+        }
         if let Ok(creds) = self.environment_provider.credentials().await {
             Ok(creds)
         } else {
@@ -45,3 +69,10 @@ impl ProvideAwsCredentials for AwsChainCredentialsProvider {
         }
     }
 }
+
+fn profile_error(profile: &str, err: CredentialsError) -> CredentialsError {
+    CredentialsError::new(format!(
+        "Could not load AWS credentials from profile `{profile}`, is it in the shared \
+         credentials file? {err}"
+    ))
+}
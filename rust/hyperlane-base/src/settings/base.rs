@@ -1,18 +1,25 @@
-use std::{collections::HashMap, fmt::Debug, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Debug,
+    net::SocketAddr,
+    sync::Arc,
+};
 
-use eyre::{eyre, Context, Result};
+use eyre::{bail, eyre, Context, Result};
 use futures_util::future::try_join_all;
 use hyperlane_core::{
-    Delivery, HyperlaneChain, HyperlaneDomain, HyperlaneMessageStore, HyperlaneProvider,
+    config::*, Delivery, HyperlaneChain, HyperlaneDomain, HyperlaneMessageStore, HyperlaneProvider,
     HyperlaneWatermarkedLogStore, InterchainGasPaymaster, InterchainGasPayment, Mailbox,
     MultisigIsm, ValidatorAnnounce, H256,
 };
+use itertools::Itertools;
 
 use crate::{
-    settings::{chains::ChainConf, trace::TracingConfig},
+    settings::{chains::ChainConf, signers::SignerRole, trace::TracingConfig},
     ContractSync, ContractSyncMetrics, CoreMetrics, HyperlaneAgentCore, MessageContractSync,
     WatermarkContractSync,
 };
+use tracing::warn;
 
 /// Settings. Usually this should be treated as a base config and used as
 /// follows:
@@ -38,17 +45,94 @@ use crate::{
 ///     }
 /// }
 /// ```
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct Settings {
     /// Configuration for contracts on each chain
     pub chains: HashMap<String, ChainConf>,
-    /// Port to listen for prometheus scrape requests
-    pub metrics_port: u16,
+    /// Address to listen for prometheus scrape requests on
+    pub metrics_addr: SocketAddr,
+    /// Prefix prepended to the name of every metric family
+    pub metrics_prefix: Option<String>,
+    /// Address to serve the `/health` endpoint on, if enabled
+    pub health_addr: Option<SocketAddr>,
     /// The tracing configuration
     pub tracing: TracingConfig,
 }
 
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            chains: Default::default(),
+            metrics_addr: ([0, 0, 0, 0], 9090).into(),
+            metrics_prefix: None,
+            health_addr: None,
+            tracing: Default::default(),
+        }
+    }
+}
+
 impl Settings {
+    /// Make every chain sign with its signer for `role`, see
+    /// [`ChainConf::use_signer_role`].
+    pub fn use_signer_role(&mut self, role: SignerRole) {
+        for chain in self.chains.values_mut() {
+            chain.use_signer_role(role);
+        }
+    }
+
+    /// Try to construct every configured signer without starting an agent,
+    /// reporting all of the failures at once.
+    pub async fn validate(&self) -> ConfigResult<()> {
+        let mut err = ConfigParsingError::default();
+        let chains_path = ConfigPath::default().join("chains");
+        for (name, chain) in &self.chains {
+            chain
+                .validate_signer()
+                .await
+                .take_err(&mut err, || &chains_path + name + "signer");
+        }
+        err.into_result(())
+    }
+
+    /// Resolve time based finality on every chain into a number of blocks.
+    pub async fn resolve_finality(&mut self, metrics: &CoreMetrics) -> Result<()> {
+        for (name, chain) in &mut self.chains {
+            if chain.testnet {
+                warn!(
+                    chain = %name,
+                    testnet = true,
+                    "Chain is marked as a testnet, unsafe mainnet configurations are not \
+                    warned about. Never use this flag in production!"
+                );
+            }
+            if chain.assume_instant_finality {
+                warn!(
+                    chain = %name,
+                    "Assuming instant finality, blocks are acted on at the chain tip without \
+                    waiting for confirmations. Reorgs on this chain may cause invalid \
+                    checkpoints or missed messages!"
+                );
+            }
+            chain
+                .resolve_finality(metrics)
+                .await
+                .with_context(|| format!("Resolving finality for chain `{name}`"))?;
+        }
+        Ok(())
+    }
+
+    /// Resolve the core contract addresses given as ENS names on every chain,
+    /// reporting all of the failures at once.
+    pub async fn resolve_ens_names(&mut self, metrics: &CoreMetrics) -> ConfigResult<()> {
+        let mut err = ConfigParsingError::default();
+        for chain in self.chains.values_mut() {
+            if let Err(e) = chain.resolve_ens_names(metrics).await {
+                err.merge(e);
+            }
+        }
+        err.into_result(())
+    }
+
     /// Generate an agent core
     pub fn build_hyperlane_core(&self, metrics: Arc<CoreMetrics>) -> HyperlaneAgentCore {
         HyperlaneAgentCore {
@@ -87,11 +171,22 @@ impl Settings {
 
     /// Create the core metrics from the settings given the name of the agent.
     pub fn metrics(&self, name: &str) -> Result<Arc<CoreMetrics>> {
-        Ok(Arc::new(CoreMetrics::new(
-            name,
-            self.metrics_port,
-            prometheus::Registry::new(),
-        )?))
+        let registry = match &self.metrics_prefix {
+            Some(prefix) => prometheus::Registry::new_custom(Some(prefix.clone()), None)?,
+            None => prometheus::Registry::new(),
+        };
+        let chain_label_names = self
+            .chains
+            .values()
+            .filter_map(|chain| chain.metrics_conf.chain.as_ref())
+            .flat_map(|chain| chain.labels.keys().cloned())
+            .sorted()
+            .dedup()
+            .collect();
+        Ok(Arc::new(
+            CoreMetrics::new(name, self.metrics_addr, registry)?
+                .with_chain_label_names(chain_label_names),
+        ))
     }
 
     /// Private to preserve linearity of AgentCore::from_settings -- creating an
@@ -99,12 +194,53 @@ impl Settings {
     fn clone(&self) -> Self {
         Self {
             chains: self.chains.clone(),
-            metrics_port: self.metrics_port,
+            metrics_addr: self.metrics_addr,
+            metrics_prefix: self.metrics_prefix.clone(),
+            health_addr: self.health_addr,
             tracing: self.tracing.clone(),
         }
     }
 }
 
+/// Parse a prefix for metric names, an empty prefix meaning none.
+pub(crate) fn parse_metrics_prefix(prefix: &str) -> Result<Option<String>> {
+    if prefix.is_empty() {
+        return Ok(None);
+    }
+    let mut chars = prefix.chars();
+    let valid_start = chars
+        .next()
+        .map_or(false, |c| c.is_ascii_alphabetic() || c == '_' || c == ':');
+    if !valid_start || !chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':') {
+        bail!("Invalid `metricsPrefix` `{prefix}`, must match `[a-zA-Z_:][a-zA-Z0-9_:]*`");
+    }
+    Ok(Some(prefix.to_owned()))
+}
+
+/// Check that no two chains resolve to the same domain id, since only one of
+/// them could be used for that domain.
+pub(crate) fn check_unique_domain_ids(chains: &HashMap<String, ChainConf>) -> Result<()> {
+    let mut names_by_id: BTreeMap<u32, Vec<&str>> = BTreeMap::new();
+    for (name, chain) in chains {
+        names_by_id.entry(chain.domain.id()).or_default().push(name);
+    }
+    let collisions = names_by_id
+        .into_iter()
+        .filter(|(_, names)| names.len() > 1)
+        .map(|(id, mut names)| {
+            names.sort_unstable();
+            format!("{} (domain {id})", names.join(", "))
+        })
+        .collect::<Vec<_>>();
+    if !collisions.is_empty() {
+        bail!(
+            "Multiple chains resolve to the same domain id: {}",
+            collisions.join("; ")
+        );
+    }
+    Ok(())
+}
+
 /// Generate a call to ChainSetup for the given builder
 macro_rules! build_contract_fns {
     ($singular:ident, $plural:ident -> $ret:ty) => {
@@ -151,6 +287,7 @@ macro_rules! build_indexer_fns {
                 db.clone(),
                 indexer.into(),
                 sync_metrics.clone(),
+                setup.index.adaptive_chunk_bounds(),
             );
 
             Ok(Box::new(sync))
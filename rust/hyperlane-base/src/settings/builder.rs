@@ -0,0 +1,148 @@
+use std::{collections::HashMap, net::SocketAddr};
+
+use eyre::eyre;
+use hyperlane_core::config::*;
+
+use crate::settings::{
+    base::{check_unique_domain_ids, parse_metrics_prefix},
+    ChainConf, Settings, TracingConfig,
+};
+
+/// Builds [`Settings`] from already parsed chain configs, for tools which
+/// generate agent configs and would otherwise have to round trip them through
+/// JSON. The same checks as the config parsers are run by
+/// [`SettingsBuilder::build`].
+#[derive(Debug, Default)]
+pub struct SettingsBuilder {
+    chains: Vec<(String, ChainConf)>,
+    metrics_port: Option<u16>,
+    metrics_prefix: Option<String>,
+    health_port: Option<u16>,
+    tracing: TracingConfig,
+}
+
+impl SettingsBuilder {
+    /// Add the configuration of a chain. The name must be the name of the
+    /// chain's domain.
+    pub fn add_chain(mut self, name: impl Into<String>, chain: ChainConf) -> Self {
+        self.chains.push((name.into(), chain));
+        self
+    }
+
+    /// Port to serve prometheus metrics on, defaults to 9090.
+    pub fn metrics_port(mut self, port: u16) -> Self {
+        self.metrics_port = Some(port);
+        self
+    }
+
+    /// Prefix prepended to the name of every metric family.
+    pub fn metrics_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.metrics_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Port to serve the `/health` endpoint on.
+    pub fn health_port(mut self, port: u16) -> Self {
+        self.health_port = Some(port);
+        self
+    }
+
+    /// The tracing configuration.
+    pub fn tracing(mut self, tracing: TracingConfig) -> Self {
+        self.tracing = tracing;
+        self
+    }
+
+    /// Validate the configuration and build the settings, reporting all of
+    /// the problems at once like the parsers do.
+    pub fn build(self) -> ConfigResult<Settings> {
+        let mut err = ConfigParsingError::default();
+        let cwp = ConfigPath::default();
+        let chains_path = cwp.join("chains");
+
+        let mut chains = HashMap::with_capacity(self.chains.len());
+        for (name, chain) in self.chains {
+            if chains.contains_key(&name) {
+                err.push(
+                    &chains_path + &name,
+                    eyre!("Chain `{name}` was added more than once"),
+                );
+                continue;
+            }
+            // chains are looked up by the name of their domain
+            if chain.domain.name() != name {
+                err.push(
+                    &chains_path + &name + "name",
+                    eyre!(
+                        "Chain `{name}` has the domain name `{}`, expected them to match",
+                        chain.domain.name()
+                    ),
+                );
+            }
+            chains.insert(name, chain);
+        }
+        check_unique_domain_ids(&chains).take_err(&mut err, || &cwp + "chains");
+
+        let metrics_prefix = self.metrics_prefix.and_then(|prefix| {
+            parse_metrics_prefix(&prefix)
+                .take_err(&mut err, || &cwp + "metrics_prefix")
+                .flatten()
+        });
+
+        err.into_result(Settings {
+            chains,
+            metrics_addr: SocketAddr::from(([0, 0, 0, 0], self.metrics_port.unwrap_or(9090))),
+            metrics_prefix,
+            health_addr: self
+                .health_port
+                .map(|port| SocketAddr::from(([0, 0, 0, 0], port))),
+            tracing: self.tracing,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+    use crate::settings::{deprecated_parser::DeprecatedRawChainConf, test_utils::test_chain};
+
+    fn chain(name: &str, domain: u32) -> ChainConf {
+        serde_json::from_value::<DeprecatedRawChainConf>(test_chain(json!({
+            "name": name,
+            "domain": domain,
+        })))
+        .unwrap()
+        .parse_config(&ConfigPath::default())
+        .unwrap()
+    }
+
+    #[test]
+    fn builds_settings_programmatically() {
+        let settings = SettingsBuilder::default()
+            .add_chain("alpha", chain("alpha", 99991))
+            .add_chain("beta", chain("beta", 99992))
+            .metrics_port(9091)
+            .metrics_prefix("hyperlane_")
+            .tracing(TracingConfig::default())
+            .build()
+            .unwrap();
+        assert_eq!(settings.chains.len(), 2);
+        assert_eq!(settings.metrics_addr.port(), 9091);
+        assert_eq!(settings.metrics_prefix.as_deref(), Some("hyperlane_"));
+        assert_eq!(settings.health_addr, None);
+
+        let err = SettingsBuilder::default()
+            .add_chain("alpha", chain("alpha", 99991))
+            .add_chain("beta", chain("beta", 99991))
+            .add_chain("gamma", chain("delta", 99993))
+            .metrics_prefix("not a prefix")
+            .build()
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("alpha, beta (domain 99991)"));
+        assert!(err.contains("config_path: `chains.gamma.name`"));
+        assert!(err.contains("config_path: `metricsPrefix`"));
+    }
+}
@@ -1,38 +1,135 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, ops::RangeInclusive, time::Duration};
 
 use ethers::prelude::Selector;
 use ethers_prometheus::middleware::{
-    ChainInfo, ContractInfo, PrometheusMiddlewareConf, WalletInfo,
+    ChainInfo, ContractInfo, PrometheusMiddlewareConf, TokenInfo, WalletInfo, BLOCK_HEIGHT_LABELS,
+    CONTRACT_CALL_COUNT_LABELS, GAS_PRICE_GWEI_LABELS, LOGS_QUERY_COUNT_LABELS,
+    TRANSACTION_SEND_TOTAL_LABELS, WALLET_BALANCE_LABELS,
 };
-use eyre::{eyre, Context, Result};
+use eyre::{bail, eyre, Context, Result};
 use hyperlane_core::{
-    AggregationIsm, CcipReadIsm, ContractLocator, HyperlaneAbi, HyperlaneDomain,
-    HyperlaneDomainProtocol, HyperlaneMessage, HyperlaneProvider, HyperlaneSigner, IndexMode,
-    InterchainGasPaymaster, InterchainGasPayment, InterchainSecurityModule, Mailbox, MultisigIsm,
-    RoutingIsm, SequenceIndexer, ValidatorAnnounce, H256,
+    config::{
+        parse_duration, ConfigErrResultExt, ConfigParsingError, ConfigPath, ConfigResult, StrOrInt,
+    },
+    utils::hex_or_base58_to_h256,
+    AggregationIsm, CcipReadIsm, ChainResult, ContractLocator, HyperlaneAbi, HyperlaneDomain,
+    HyperlaneDomainProtocol, HyperlaneDomainType, HyperlaneMessage, HyperlaneProvider,
+    HyperlaneSigner, IndexMode, InterchainGasPaymaster, InterchainGasPayment,
+    InterchainSecurityModule, KnownHyperlaneDomain, Mailbox, MultisigIsm, RoutingIsm,
+    SequenceIndexer, ValidatorAnnounce, H256, U256,
 };
+use hyperlane_cosmos as h_cosmos;
 use hyperlane_ethereum::{
     self as h_eth, BuildableWithProvider, EthereumInterchainGasPaymasterAbi, EthereumMailboxAbi,
     EthereumValidatorAnnounceAbi,
 };
 use hyperlane_fuel as h_fuel;
 use hyperlane_sealevel as h_sealevel;
+use hyperlane_starknet as h_starknet;
 
 use crate::{
-    settings::signers::{BuildableWithSignerConf, SignerConf},
+    metrics::NAMESPACE,
+    settings::signers::{BuildableWithSignerConf, RoleSigners, SignerConf, SignerRole},
     CoreMetrics,
 };
 
+pub use h_eth::DEFAULT_CONFIRMATION_TIMEOUT;
+
+/// The default cap on how many messages are bundled into one submission.
+pub const DEFAULT_MAX_BATCH_SIZE: u32 = 32;
+
+/// The largest accepted `maxBatchSize`. The submitter preallocates batches and
+/// channels of this size, so it is bounded to keep them reasonable.
+pub const MAX_BATCH_SIZE_LIMIT: u32 = 1024;
+
+/// The default cap on how many transactions submitted to a chain may be
+/// waiting for confirmation at once.
+pub const DEFAULT_MAX_IN_FLIGHT_TRANSACTIONS: u32 = 8;
+
+/// How long a partial batch waits for more messages by default before it is
+/// submitted anyway.
+pub const DEFAULT_BATCH_FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long indexers wait before polling a chain again by default once they
+/// have caught up with its tip.
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The smallest chunk size adaptive chunking shrinks to by default.
+pub const DEFAULT_MIN_CHUNK_SIZE: u32 = 1;
+
+/// How many blocks the tip may move back by default before indexing is
+/// halted.
+pub const DEFAULT_MAX_REORG_DEPTH: u32 = 256;
+
+/// The address Multicall3 is deployed at on most EVM chains.
+const CANONICAL_MULTICALL3_ADDRESS: &str = "0xcA11bde05977b3631167028862bE2a173976CA11";
+
+/// Number of recent blocks sampled to estimate the block time when resolving
+/// time based finality.
+const FINALITY_BLOCK_TIME_SAMPLE: u32 = 100;
+
 /// A chain setup is a domain ID, an address on that chain (where the mailbox is
 /// deployed) and details for connecting to the chain API.
 #[derive(Clone, Debug)]
 pub struct ChainConf {
     /// The domain
     pub domain: HyperlaneDomain,
+    /// The EVM chain id transactions are signed for, if it differs from the
+    /// domain id. Otherwise the chain id reported by the provider is used.
+    pub chain_id: Option<u64>,
+    /// Whether this chain is only indexed and never submitted to. The relayer
+    /// rejects configs which make it a destination.
+    pub index_only: bool,
     /// Signer configuration for this chain
     pub signer: Option<SignerConf>,
-    /// Number of blocks until finality
+    /// Signers used in place of `signer` for a single role
+    pub role_signers: RoleSigners,
+    /// Act on the chain tip immediately, for rollups with instant soft
+    /// finality. This overrides the configured finality with zero blocks.
+    pub assume_instant_finality: bool,
+    /// Whether this chain is a test network, which is not warned about
+    /// configurations that are unsafe on a mainnet
+    pub testnet: bool,
+    /// How finality was configured for this chain
+    pub finality: Finality,
+    /// Number of blocks until finality. For time based finality this is only
+    /// known once resolved with [`ChainConf::resolve_finality`].
     pub finality_blocks: u32,
+    /// Number of blocks to re-scan on every poll to account for reorgs,
+    /// defaults to `finality_blocks` when finality is a block count
+    pub reorg_period: u32,
+    /// The expected time between blocks, if configured
+    pub block_time: Option<Duration>,
+    /// Maximum number of messages submitted to this chain in a single batch
+    pub max_batch_size: u32,
+    /// Maximum number of submitted transactions waiting for confirmation on
+    /// this chain, new ones are only submitted once this drops below it
+    pub max_in_flight_transactions: u32,
+    /// How long a partial batch waits to fill up before it is submitted
+    /// anyway
+    pub batch_flush_interval: Duration,
+    /// How operations submitted to this chain are grouped into transactions
+    pub batch_strategy: BatchStrategy,
+    /// How the nonces of transactions submitted to this chain are chosen
+    pub nonce_strategy: h_eth::NonceStrategy,
+    /// How the gas price of transactions submitted to this chain is set
+    pub tx_type: h_eth::TxType,
+    /// How long indexers wait before polling this chain again once they have
+    /// caught up with its tip
+    pub poll_interval: Duration,
+    /// How long to wait for a transaction to be confirmed before it is
+    /// resubmitted with a higher gas price
+    pub confirmation_timeout: Duration,
+    /// Overrides applied to estimated gas prices
+    pub gas_oracle: h_eth::GasOracleConf,
+    /// Transactions with a higher estimated gas limit are not submitted to
+    /// this chain
+    pub max_gas_limit: Option<U256>,
+    /// Factor estimated gas limits are multiplied by before submission, for
+    /// chains which systematically under-estimate gas
+    pub gas_limit_multiplier: f64,
+    /// The native token of the chain which gas is paid in
+    pub native_token: NativeToken,
     /// Addresses of contracts on the chain
     pub addresses: CoreContractAddresses,
     /// The chain connection details
@@ -45,15 +142,50 @@ pub struct ChainConf {
     pub index: IndexSettings,
 }
 
+/// How long it takes for a block to be considered final
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Finality {
+    /// A number of blocks
+    Blocks(u32),
+    /// A span of time which is translated into a number of blocks using the
+    /// chain's observed block time
+    Time(Duration),
+    /// The block the chain reports with a block tag, e.g. `safe`. Only
+    /// ethereum chains support this.
+    Tag(h_eth::FinalityTag),
+}
+
+impl Default for Finality {
+    fn default() -> Self {
+        Self::Blocks(0)
+    }
+}
+
+/// How operations submitted to a chain are grouped into transactions
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum BatchStrategy {
+    /// Every operation is submitted in a transaction of its own
+    #[default]
+    None,
+    /// Operations are grouped into batches of up to `maxBatchSize` for the
+    /// Multicall3 contract at the given address
+    Multicall3(H256),
+}
+
 /// A connection to _some_ blockchain.
 #[derive(Clone, Debug)]
 pub enum ChainConnectionConf {
-    /// Ethereum configuration
-    Ethereum(h_eth::ConnectionConf),
+    /// Ethereum configuration, which may index through different RPCs than
+    /// it submits transactions to
+    Ethereum(h_eth::ChainConnections),
     /// Fuel configuration
     Fuel(h_fuel::ConnectionConf),
     /// Sealevel configuration.
     Sealevel(h_sealevel::ConnectionConf),
+    /// Cosmos configuration.
+    Cosmos(h_cosmos::ConnectionConf),
+    /// Starknet configuration.
+    Starknet(h_starknet::ConnectionConf),
 }
 
 impl ChainConnectionConf {
@@ -63,6 +195,8 @@ impl ChainConnectionConf {
             Self::Ethereum(_) => HyperlaneDomainProtocol::Ethereum,
             Self::Fuel(_) => HyperlaneDomainProtocol::Fuel,
             Self::Sealevel(_) => HyperlaneDomainProtocol::Sealevel,
+            Self::Cosmos(_) => HyperlaneDomainProtocol::Cosmos,
+            Self::Starknet(_) => HyperlaneDomainProtocol::Starknet,
         }
     }
 }
@@ -76,26 +210,700 @@ pub struct CoreContractAddresses {
     pub interchain_gas_paymaster: H256,
     /// Address of the ValidatorAnnounce contract
     pub validator_announce: H256,
+    /// Contracts configured by ENS name, their addresses are only known once
+    /// resolved with [`ChainConf::resolve_ens_names`]
+    pub ens_names: Vec<EnsName>,
+    /// The blocks contracts were deployed at, keyed by `mailbox`,
+    /// `interchain_gas_paymaster` or `validator_announce`. Indexing a contract
+    /// starts at its block instead of `index.from` when set.
+    pub deployed_blocks: HashMap<&'static str, u32>,
+}
+
+/// A core contract address given as an ENS name
+#[derive(Clone, Debug)]
+pub struct EnsName {
+    /// The core contract, one of `mailbox`, `interchain_gas_paymaster` or
+    /// `validator_announce`
+    pub contract: &'static str,
+    /// The ENS name, e.g. `mailbox.hyperlane.eth`
+    pub name: String,
+    /// Where the name was configured
+    pub cwp: ConfigPath,
+}
+
+/// Whether an address looks like an ENS name, i.e. it contains a dot and is
+/// not a valid hex address.
+pub(crate) fn is_ens_name(addr: &str) -> bool {
+    addr.contains('.') && hex_or_base58_to_h256(addr).is_err()
+}
+
+/// Normalize an address copied from e.g. a block explorer: surrounding
+/// whitespace is removed and hex addresses are lowercased, with a `0x` prefix
+/// added if it is missing. Anything else, such as base58, is case sensitive
+/// and kept as is.
+pub(crate) fn normalize_address(addr: &str) -> String {
+    let addr = addr.trim();
+    let hex = addr
+        .strip_prefix("0x")
+        .or_else(|| addr.strip_prefix("0X"))
+        .or_else(|| {
+            // 32 byte base58 addresses are 32 to 44 characters long, so a 40
+            // character one could be either. It is taken to be hex because
+            // every character is a hex digit, which is vanishingly unlikely
+            // for base58 given its 58 character alphabet.
+            (matches!(addr.len(), 40 | 64) && addr.chars().all(|c| c.is_ascii_hexdigit()))
+                .then_some(addr)
+        });
+    match hex {
+        Some(hex) => format!("0x{}", hex.to_ascii_lowercase()),
+        None => addr.to_owned(),
+    }
+}
+
+/// Reject ENS names on chains which can not resolve them.
+pub(crate) fn validate_ens_names(
+    ens_names: &[EnsName],
+    protocol: HyperlaneDomainProtocol,
+    err: &mut ConfigParsingError,
+) {
+    if protocol == HyperlaneDomainProtocol::Ethereum {
+        return;
+    }
+    for ens_name in ens_names {
+        err.push(
+            ens_name.cwp.clone(),
+            eyre!(
+                "`{}` looks like an ENS name, which are only supported on ethereum chains",
+                ens_name.name
+            ),
+        );
+    }
+}
+
+/// The maximum number of decimals a native token may have
+pub const MAX_NATIVE_TOKEN_DECIMALS: u32 = 36;
+
+/// Metadata of the native token of a chain, used to scale and label the
+/// native wallet balances in the metrics.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NativeToken {
+    /// The symbol of the token, e.g. `ETH`. Empty if unknown.
+    pub symbol: String,
+    /// The number of decimals of the token
+    pub decimals: u32,
+}
+
+impl Default for NativeToken {
+    fn default() -> Self {
+        Self {
+            symbol: String::new(),
+            decimals: 18,
+        }
+    }
+}
+
+impl NativeToken {
+    /// The token as reported in the wallet balance metrics, where it is
+    /// labelled `Native` if the symbol is unknown.
+    pub(crate) fn token_info(&self) -> TokenInfo {
+        let symbol = if self.symbol.is_empty() {
+            "Native".to_owned()
+        } else {
+            self.symbol.clone()
+        };
+        TokenInfo {
+            name: symbol.clone(),
+            symbol,
+            // at most `MAX_NATIVE_TOKEN_DECIMALS`
+            decimals: self.decimals as u8,
+        }
+    }
 }
 
 /// Indexing settings
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Clone)]
 pub struct IndexSettings {
     /// The height at which to start indexing contracts.
-    pub from: u32,
+    pub from: IndexFrom,
     /// The number of blocks to query at once when indexing contracts.
     pub chunk_size: u32,
-    /// The indexing mode.
-    pub mode: IndexMode,
+    /// The unit the chunk size was configured in.
+    pub chunk_unit: IndexChunkUnit,
+    /// The indexing modes, see [`IndexSettings::mode`].
+    pub modes: Vec<IndexMode>,
+    /// The number of blocks below the tip which are not yet final, filled in
+    /// from the chain by [`ChainConf::index_settings`].
+    pub finality_blocks: u32,
+    /// The number of blocks below the last indexed one which are indexed
+    /// again on every poll in case they were reorged, filled in from the
+    /// chain by [`ChainConf::index_settings`].
+    pub reorg_period: u32,
+    /// How long to wait before polling again once caught up with the tip,
+    /// filled in from the chain by [`ChainConf::index_settings`].
+    pub poll_interval: Duration,
+    /// Event signature hashes the mailbox indexers are restricted to. Empty
+    /// means all mailbox events are indexed.
+    pub event_topics: Vec<H256>,
+    /// Indexing of the chain is halted if its tip moves back by more than
+    /// this many blocks, 0 disables the check.
+    pub max_reorg_depth: u32,
+    /// Halve the chunk size whenever the provider reports too many results
+    /// for a range and grow it back as queries succeed.
+    pub adaptive_chunking: bool,
+    /// The smallest chunk size adaptive chunking shrinks to.
+    pub min_chunk_size: u32,
+    /// The largest chunk size adaptive chunking grows to, `chunk_size` if
+    /// unset.
+    pub max_chunk_size: Option<u32>,
+}
+
+impl Default for IndexSettings {
+    fn default() -> Self {
+        Self {
+            from: IndexFrom::default(),
+            chunk_size: 1999,
+            chunk_unit: IndexChunkUnit::Blocks,
+            modes: vec![IndexMode::default()],
+            finality_blocks: 0,
+            reorg_period: 0,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            event_topics: vec![],
+            max_reorg_depth: DEFAULT_MAX_REORG_DEPTH,
+            adaptive_chunking: false,
+            min_chunk_size: DEFAULT_MIN_CHUNK_SIZE,
+            max_chunk_size: None,
+        }
+    }
+}
+
+impl IndexSettings {
+    /// The bounds adaptive chunking keeps the chunk size within, None if it
+    /// is disabled.
+    pub fn adaptive_chunk_bounds(&self) -> Option<RangeInclusive<u32>> {
+        let max = self.max_chunk_size.unwrap_or(self.chunk_size);
+        self.adaptive_chunking
+            .then(|| self.min_chunk_size..=max.max(self.min_chunk_size))
+    }
+
+    /// The mode to index with, `preferred` if it is one of the configured
+    /// modes and otherwise the first configured mode. This lets a chain index
+    /// data with a sequence, like dispatched messages, and data without one,
+    /// like gas payments, using different strategies.
+    pub fn mode(&self, preferred: IndexMode) -> IndexMode {
+        if self.modes.contains(&preferred) {
+            preferred
+        } else {
+            self.modes.first().copied().unwrap_or_default()
+        }
+    }
+
+    /// The next block range to index after `last_indexed`, at most
+    /// `chunk_size` blocks long, starting no earlier than a configured `from`
+    /// height and ending at or below `tip - finality_blocks`. Returns None
+    /// when there is nothing new to index.
+    pub fn next_range(&self, tip: u64, last_indexed: u64) -> Option<RangeInclusive<u64>> {
+        let end = tip.checked_sub(self.finality_blocks as u64)?;
+        let mut start = last_indexed.checked_add(1)?;
+        if let IndexFrom::Height(from) = self.from {
+            start = start.max(from as u64);
+        }
+        if start > end {
+            return None;
+        }
+        let last = start
+            .saturating_add((self.chunk_size as u64).max(1) - 1)
+            .min(end);
+        Some(start..=last)
+    }
+}
+
+/// The unit an index chunk size was configured in
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum IndexChunkUnit {
+    /// A number of blocks
+    #[default]
+    Blocks,
+    /// A span of time which is translated into a number of blocks using the
+    /// chain's block time
+    Time(Duration),
+}
+
+/// Where to start indexing from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexFrom {
+    /// A specific block height
+    Height(u32),
+    /// The chain tip at the time the indexer starts
+    Latest,
+    /// The latest finalized block at the time the indexer starts
+    Finalized,
+}
+
+impl Default for IndexFrom {
+    fn default() -> Self {
+        Self::Height(0)
+    }
+}
+
+impl IndexFrom {
+    /// Resolve this to a concrete block height, querying the indexer for
+    /// block tags.
+    pub async fn resolve<T>(&self, indexer: &dyn SequenceIndexer<T>) -> ChainResult<u32> {
+        match self {
+            Self::Height(height) => Ok(*height),
+            Self::Latest => Ok(indexer.sequence_and_tip().await?.1),
+            Self::Finalized => indexer.get_finalized_block_number().await,
+        }
+    }
+}
+
+/// Parse the indexing modes, given either as a single mode or as a list of
+/// distinct modes.
+pub(crate) fn parse_index_modes(modes: serde_json::Value) -> Result<Vec<IndexMode>> {
+    let modes: Vec<IndexMode> = if modes.is_array() {
+        serde_json::from_value(modes)
+    } else {
+        serde_json::from_value(modes).map(|m| vec![m])
+    }
+    .context("Invalid mode, expected `block`, `sequence` or a list of them")?;
+    if modes.is_empty() {
+        bail!("Invalid mode, expected at least one mode");
+    }
+    for (i, mode) in modes.iter().enumerate() {
+        if modes[..i].contains(mode) {
+            bail!("Invalid mode, `{mode:?}` is listed more than once");
+        }
+    }
+    Ok(modes)
+}
+
+/// Parse an event signature hash to restrict indexing to.
+pub(crate) fn parse_event_topic(topic: &str) -> Result<H256> {
+    hex_or_base58_to_h256(topic)
+        .with_context(|| format!("Invalid event topic `{topic}`, expected a 32 byte hash"))
+}
+
+/// Parse the block to start indexing from, given either as a height or as one
+/// of the tags `"latest"` or `"finalized"`.
+pub(crate) fn parse_index_from(from: &StrOrInt) -> Result<IndexFrom> {
+    match from {
+        StrOrInt::Str(s) if s.trim() == "latest" => Ok(IndexFrom::Latest),
+        StrOrInt::Str(s) if s.trim() == "finalized" => Ok(IndexFrom::Finalized),
+        _ => u32::try_from(from).map(IndexFrom::Height).map_err(|_| {
+            eyre!("Invalid `from` block, expected a block height, `latest` or `finalized`")
+        }),
+    }
+}
+
+/// Parse finality given either as a number of blocks, e.g. `20` or `"20"`, as
+/// a span of time, e.g. `"15min"`, or as one of the block tags `"safe"` or
+/// `"finalized"`. Time based finality must be resolved with
+/// `ChainConf::resolve_finality`.
+pub(crate) fn parse_finality(finality: &StrOrInt) -> Result<Finality> {
+    if let Ok(blocks) = u32::try_from(finality) {
+        return Ok(Finality::Blocks(blocks));
+    }
+    match finality {
+        StrOrInt::Str(s) if s.trim() == "safe" => Ok(Finality::Tag(h_eth::FinalityTag::Safe)),
+        StrOrInt::Str(s) if s.trim() == "finalized" => {
+            Ok(Finality::Tag(h_eth::FinalityTag::Finalized))
+        }
+        StrOrInt::Str(s) => parse_duration(s).map(Finality::Time).map_err(|_| {
+            eyre!(
+                "Invalid finality `{s}`, expected a number of blocks, a duration, `safe` or \
+                 `finalized`"
+            )
+        }),
+        StrOrInt::Int(_) => bail!("Invalid finality, expected a number of blocks or a duration"),
+    }
+}
+
+/// Check finality is safe to run on a mainnet, i.e. it waits for at least
+/// one block. Chains with instant finality should opt in with
+/// `assumeInstantFinality` to not be warned about it.
+pub(crate) fn check_mainnet_finality(finality: &Finality) -> Result<()> {
+    if *finality == Finality::Blocks(0) {
+        bail!(
+            "Finality of 0 blocks acts on blocks which may still be reorged; set \
+             `assumeInstantFinality` if the chain has instant finality"
+        );
+    }
+    Ok(())
+}
+
+/// Parse a polling interval given either as a number of milliseconds, e.g.
+/// `500` or `"500"`, or as a duration, e.g. `"2s"`.
+pub(crate) fn parse_poll_interval(interval: &StrOrInt) -> Result<Duration> {
+    let interval = match u64::try_from(interval) {
+        Ok(ms) => Duration::from_millis(ms),
+        Err(_) => match interval {
+            StrOrInt::Str(s) => parse_duration(s).map_err(|_| {
+                eyre!("Invalid `pollInterval` `{s}`, expected milliseconds or a duration")
+            })?,
+            StrOrInt::Int(_) => {
+                bail!("Invalid `pollInterval`, expected milliseconds or a duration")
+            }
+        },
+    };
+    if interval.is_zero() {
+        bail!("Invalid `pollInterval`, must be greater than 0");
+    }
+    Ok(interval)
+}
+
+/// Parse how long a partial batch waits for more messages, where integers are
+/// seconds.
+pub(crate) fn parse_batch_flush_interval(interval: &StrOrInt) -> Result<Duration> {
+    let interval = Duration::try_from(interval)
+        .context("Invalid `batchFlushInterval`, expected a duration")?;
+    if interval.is_zero() {
+        bail!("Invalid `batchFlushInterval`, must be greater than 0");
+    }
+    Ok(interval)
+}
+
+/// Parse the bounds of adaptive chunking, each a number of blocks.
+pub(crate) fn parse_chunk_bound(bound: &StrOrInt, name: &str) -> Result<u32> {
+    let bound = u32::try_from(bound)
+        .with_context(|| format!("Invalid `{name}`, expected a number of blocks"))?;
+    if bound == 0 {
+        bail!("Invalid `{name}`, must be greater than 0");
+    }
+    Ok(bound)
+}
+
+/// Check the factor gas limit estimates are padded by is at least 1, a lower
+/// one would make transactions run out of gas.
+pub(crate) fn parse_gas_limit_multiplier(multiplier: f64) -> Result<f64> {
+    if !multiplier.is_finite() || multiplier < 1. {
+        bail!("Invalid `gasLimitMultiplier` {multiplier}, must be a number of at least 1.0");
+    }
+    Ok(multiplier)
+}
+
+/// Check the extra labels for the metrics of a chain have valid prometheus
+/// label names which do not collide with the labels the metrics already have.
+pub(crate) fn parse_metrics_labels(
+    labels: HashMap<String, String>,
+) -> Result<HashMap<String, String>> {
+    let const_labels = ["agent".to_owned(), format!("{NAMESPACE}_baselib_version")];
+    for name in labels.keys() {
+        let mut chars = name.chars();
+        let valid = chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid {
+            bail!("Invalid metrics label name `{name}`, must match `[a-zA-Z_][a-zA-Z0-9_]*`");
+        }
+        let reserved = name.starts_with("__")
+            || const_labels.contains(name)
+            || [
+                BLOCK_HEIGHT_LABELS,
+                GAS_PRICE_GWEI_LABELS,
+                CONTRACT_CALL_COUNT_LABELS,
+                LOGS_QUERY_COUNT_LABELS,
+                TRANSACTION_SEND_TOTAL_LABELS,
+                WALLET_BALANCE_LABELS,
+            ]
+            .iter()
+            .any(|names| names.contains(&name.as_str()));
+        if reserved {
+            bail!("Metrics label name `{name}` is reserved");
+        }
+    }
+    Ok(labels)
+}
+
+/// Multiply an estimated gas limit by `multiplier`, rounding up.
+pub fn pad_gas_limit(estimated: U256, multiplier: f64) -> U256 {
+    // scale by a fixed point multiplier to avoid converting the limit to a float
+    const PRECISION: u64 = 1_000_000;
+    let multiplier = U256::from((multiplier * PRECISION as f64) as u64);
+    let (padded, rem) = estimated
+        .saturating_mul(multiplier)
+        .div_mod(PRECISION.into());
+    if rem.is_zero() {
+        padded
+    } else {
+        padded.saturating_add(1.into())
+    }
+}
+
+/// Parse the number of decimals of a native token.
+pub(crate) fn parse_native_token_decimals(decimals: &StrOrInt) -> Result<u32> {
+    let decimals = u32::try_from(decimals).context("Invalid `decimals`, expected an integer")?;
+    if decimals > MAX_NATIVE_TOKEN_DECIMALS {
+        bail!("Invalid `decimals`, must be at most {MAX_NATIVE_TOKEN_DECIMALS}");
+    }
+    Ok(decimals)
+}
+
+/// Parse the number of blocks the tip may move back before indexing is
+/// halted, where 0 disables the check.
+pub(crate) fn parse_max_reorg_depth(depth: &StrOrInt) -> Result<u32> {
+    u32::try_from(depth).context("Invalid `maxReorgDepth`, expected a number of blocks")
+}
+
+/// Parse how long to wait for a transaction to be confirmed, where integers
+/// are seconds. Waiting less than a block would resubmit every transaction.
+pub(crate) fn parse_confirmation_timeout(
+    timeout: &StrOrInt,
+    block_time: Option<Duration>,
+) -> Result<Duration> {
+    let timeout = Duration::try_from(timeout)
+        .context("Invalid `confirmationTimeout`, expected a duration")?;
+    if timeout.is_zero() {
+        bail!("Invalid `confirmationTimeout`, must be greater than 0");
+    }
+    if let Some(block_time) = block_time {
+        if timeout < block_time {
+            bail!(
+                "Invalid `confirmationTimeout`, must be at least the block time of {block_time:?}"
+            );
+        }
+    }
+    Ok(timeout)
+}
+
+/// Parse the batching strategy of a chain, `"none"` or `"multicall3"`. The
+/// multicall address falls back to the canonical deployment on known EVM
+/// chains which have it.
+pub(crate) fn parse_batch_strategy(
+    strategy: Option<&str>,
+    multicall_address: Option<H256>,
+    domain: &HyperlaneDomain,
+) -> Result<BatchStrategy> {
+    match strategy.unwrap_or("none") {
+        "none" => {
+            if multicall_address.is_some() {
+                bail!("`multicallAddress` is only used with the `multicall3` batch strategy");
+            }
+            Ok(BatchStrategy::None)
+        }
+        "multicall3" => {
+            if domain.domain_protocol() != HyperlaneDomainProtocol::Ethereum {
+                bail!("The `multicall3` batch strategy is only supported on ethereum chains");
+            }
+            multicall_address
+                .or_else(|| default_multicall_address(domain))
+                .map(BatchStrategy::Multicall3)
+                .ok_or_else(|| {
+                    eyre!(
+                        "No known Multicall3 deployment on `{}`, `multicallAddress` must be set",
+                        domain.name()
+                    )
+                })
+        }
+        s => bail!("Invalid `batchStrategy` `{s}`, expected `none` or `multicall3`"),
+    }
+}
+
+/// Parse how nonces are chosen for transactions, one of `"sequential"`,
+/// `"external"` or `"perTx"`.
+pub(crate) fn parse_nonce_strategy(strategy: &str) -> Result<h_eth::NonceStrategy> {
+    Ok(match strategy {
+        "sequential" => h_eth::NonceStrategy::Sequential,
+        "external" => h_eth::NonceStrategy::External,
+        "perTx" => h_eth::NonceStrategy::PerTx,
+        s => bail!(
+            "Invalid `nonceStrategy` `{s}`, expected one of `sequential`, `external` or `perTx`"
+        ),
+    })
+}
+
+/// Parse how the gas price of transactions is set, one of `"legacy"`,
+/// `"eip1559"` or `"auto"`.
+pub(crate) fn parse_tx_type(tx_type: &str) -> Result<h_eth::TxType> {
+    Ok(match tx_type {
+        "auto" => h_eth::TxType::Auto,
+        "legacy" => h_eth::TxType::Legacy,
+        "eip1559" => h_eth::TxType::Eip1559,
+        s => bail!("Invalid `txType` `{s}`, expected one of `legacy`, `eip1559` or `auto`"),
+    })
+}
+
+/// The canonical Multicall3 address for known EVM chains it is deployed on.
+fn default_multicall_address(domain: &HyperlaneDomain) -> Option<H256> {
+    let HyperlaneDomain::Known(known) = domain else {
+        return None;
+    };
+    // zksync deploys contracts to different addresses and the local test
+    // chains only have the hyperlane contracts
+    let deployed = known.domain_protocol() == HyperlaneDomainProtocol::Ethereum
+        && known.domain_type() != HyperlaneDomainType::LocalTestChain
+        && *known != KnownHyperlaneDomain::Zksync2Testnet;
+    deployed.then(|| hex_or_base58_to_h256(CANONICAL_MULTICALL3_ADDRESS).unwrap())
+}
+
+/// Parse an index chunk size given either as a number of blocks, e.g. `1999`
+/// or `"50000blocks"`, or as a span of time, e.g. `"2min"`. Time based chunk
+/// sizes must be resolved with `IndexSettings::resolve_chunk_size`.
+pub(crate) fn parse_index_chunk(chunk: &StrOrInt) -> Result<(u32, IndexChunkUnit)> {
+    if let StrOrInt::Str(s) = chunk {
+        let s = s.trim();
+        if let Some(blocks) = s.strip_suffix("blocks").or_else(|| s.strip_suffix("block")) {
+            let blocks = blocks.trim().parse().context("Invalid number of blocks")?;
+            return Ok((blocks, IndexChunkUnit::Blocks));
+        }
+        if s.parse::<u32>().is_err() {
+            return Ok((0, IndexChunkUnit::Time(parse_duration(s)?)));
+        }
+    }
+    Ok((chunk.try_into()?, IndexChunkUnit::Blocks))
+}
+
+impl IndexSettings {
+    /// Translate a time based chunk size into a number of blocks using the
+    /// chain's block time. This is a no-op for block based chunk sizes.
+    pub fn resolve_chunk_size(&mut self, block_time: Option<Duration>) -> Result<()> {
+        if let IndexChunkUnit::Time(span) = self.chunk_unit {
+            let block_time = block_time
+                .filter(|t| !t.is_zero())
+                .ok_or_else(|| eyre!("A time based chunk size requires `blockTime` to be set"))?;
+            let blocks = span.as_nanos() / block_time.as_nanos();
+            self.chunk_size = blocks.clamp(1, u32::MAX as u128) as u32;
+        }
+        Ok(())
+    }
 }
 
 impl ChainConf {
+    /// Whether transactions can be submitted to this chain, i.e. it is not
+    /// `index_only` and has a signer other than the node itself.
+    pub fn is_submitter(&self) -> bool {
+        !self.index_only && !matches!(self.signer, None | Some(SignerConf::Node))
+    }
+
+    /// Whether this chain can be indexed, i.e. its mailbox address is known.
+    /// This is not the case while it is an ENS name which is not resolved
+    /// yet.
+    pub fn is_indexer(&self) -> bool {
+        !self.addresses.mailbox.is_zero()
+    }
+
+    /// The signer to use for `role`, falling back to the default signer of
+    /// the chain.
+    pub fn signer_for(&self, role: SignerRole) -> Option<&SignerConf> {
+        self.role_signers.get(role, self.signer.as_ref())
+    }
+
+    /// Make the signer for `role` the one the chain signs with.
+    pub fn use_signer_role(&mut self, role: SignerRole) {
+        self.signer = self.signer_for(role).cloned();
+    }
+
     /// Fetch the index settings and index mode, since they are often used together.
     pub fn index_settings(&self) -> IndexSettings {
-        self.index.clone()
+        IndexSettings {
+            finality_blocks: self.finality_blocks,
+            reorg_period: self.reorg_period,
+            poll_interval: self.poll_interval,
+            ..self.index.clone()
+        }
+    }
+
+    /// The index settings for one of the core contracts, e.g.
+    /// `interchain_gas_paymaster`, which start at the block the contract was
+    /// deployed at if it is configured.
+    pub fn contract_index_settings(&self, contract: &str) -> IndexSettings {
+        let mut index = self.index_settings();
+        if let Some(&block) = self.addresses.deployed_blocks.get(contract) {
+            index.from = IndexFrom::Height(block);
+        }
+        index
+    }
+
+    /// The block tag indexers treat as final, if finality is tag based.
+    pub fn finality_tag(&self) -> Option<h_eth::FinalityTag> {
+        match self.finality {
+            Finality::Tag(tag) => Some(tag),
+            _ => None,
+        }
+    }
+
+    /// Translate time based finality into a number of blocks using the block
+    /// time observed from the chain, falling back to the configured
+    /// `block_time` if the chain can not report one. This is a no-op for block
+    /// based finality and only checks the chain can use tag based finality.
+    pub async fn resolve_finality(&mut self, metrics: &CoreMetrics) -> Result<()> {
+        let span = match self.finality {
+            Finality::Blocks(_) => return Ok(()),
+            Finality::Tag(tag) => {
+                if !matches!(self.connection, ChainConnectionConf::Ethereum(_)) {
+                    bail!(
+                        "Finality by the `{}` block tag is only supported on ethereum chains",
+                        tag.as_str()
+                    );
+                }
+                return Ok(());
+            }
+            Finality::Time(span) => span,
+        };
+        let observed = self
+            .build_provider(metrics)
+            .await?
+            .average_block_time(FINALITY_BLOCK_TIME_SAMPLE)
+            .await
+            .context("Estimating block time to resolve finality")?;
+        let block_time = observed
+            .or(self.block_time)
+            .filter(|t| !t.is_zero())
+            .ok_or_else(|| {
+                eyre!("Unable to determine the block time to resolve time based finality")
+            })?;
+        let block_time = block_time.as_nanos();
+        let blocks = (span.as_nanos() + block_time - 1) / block_time;
+        self.finality_blocks = blocks.min(u32::MAX as u128) as u32;
+        Ok(())
     }
 
-    /// Try to convert the chain settings into an HyperlaneProvider.
+    /// Resolve the core contract addresses configured as ENS names using the
+    /// index connection of the chain. Failures are reported at the path of
+    /// the name.
+    pub async fn resolve_ens_names(&mut self, metrics: &CoreMetrics) -> ConfigResult<()> {
+        let mut err = ConfigParsingError::default();
+        let ChainConnectionConf::Ethereum(conf) = &self.connection else {
+            validate_ens_names(
+                &self.addresses.ens_names,
+                self.connection.protocol(),
+                &mut err,
+            );
+            return err.into_result(());
+        };
+        let locator = self.locator(H256::zero());
+        let mut resolved = Vec::with_capacity(self.addresses.ens_names.len());
+        for ens_name in &self.addresses.ens_names {
+            let builder = h_eth::EnsNameResolverBuilder {
+                name: ens_name.name.clone(),
+            };
+            let address = self
+                .build_ethereum(&conf.index, &locator, metrics, builder)
+                .await
+                .and_then(|res| res.map_err(Into::into))
+                .with_context(|| format!("Resolving ENS name `{}`", ens_name.name))
+                .take_err(&mut err, || ens_name.cwp.clone());
+            if let Some(address) = address {
+                resolved.push((ens_name.contract, address));
+            }
+        }
+        for (contract, address) in resolved {
+            match contract {
+                "mailbox" => self.addresses.mailbox = address,
+                "interchain_gas_paymaster" => self.addresses.interchain_gas_paymaster = address,
+                "validator_announce" => self.addresses.validator_announce = address,
+                contract => unreachable!("unknown core contract `{contract}`"),
+            }
+        }
+        if err.is_ok() {
+            self.addresses.ens_names.clear();
+        }
+        err.into_result(())
+    }
+
+    /// Try to convert the chain settings into an HyperlaneProvider. It is only
+    /// used to read from the chain, so ethereum chains use their index
+    /// connection.
     pub async fn build_provider(
         &self,
         metrics: &CoreMetrics,
@@ -104,11 +912,20 @@ impl ChainConf {
         match &self.connection {
             ChainConnectionConf::Ethereum(conf) => {
                 let locator = self.locator(H256::zero());
-                self.build_ethereum(conf, &locator, metrics, h_eth::HyperlaneProviderBuilder {})
-                    .await
+                self.build_ethereum(
+                    &conf.index,
+                    &locator,
+                    metrics,
+                    h_eth::HyperlaneProviderBuilder {},
+                )
+                .await
             }
             ChainConnectionConf::Fuel(_) => todo!(),
             ChainConnectionConf::Sealevel(_) => todo!(),
+            ChainConnectionConf::Cosmos(_) => Err(eyre!("Cosmos does not support providers yet")),
+            ChainConnectionConf::Starknet(_) => {
+                Err(eyre!("Starknet does not support providers yet"))
+            }
         }
         .context(ctx)
     }
@@ -120,8 +937,21 @@ impl ChainConf {
 
         match &self.connection {
             ChainConnectionConf::Ethereum(conf) => {
-                self.build_ethereum(conf, &locator, metrics, h_eth::MailboxBuilder {})
-                    .await
+                self.build_ethereum(
+                    &conf.submit,
+                    &locator,
+                    metrics,
+                    h_eth::MailboxBuilder {
+                        confirmation_timeout: self.confirmation_timeout,
+                        tx_type: self.tx_type,
+                        gas_oracle: self.gas_oracle.clone(),
+                        multicall_address: match self.batch_strategy {
+                            BatchStrategy::None => None,
+                            BatchStrategy::Multicall3(address) => Some(address),
+                        },
+                    },
+                )
+                .await
             }
 
             ChainConnectionConf::Fuel(conf) => {
@@ -136,6 +966,10 @@ impl ChainConf {
                     .map(|m| Box::new(m) as Box<dyn Mailbox>)
                     .map_err(Into::into)
             }
+            ChainConnectionConf::Cosmos(_) => Err(eyre!("Cosmos does not support mailboxes yet")),
+            ChainConnectionConf::Starknet(_) => {
+                Err(eyre!("Starknet does not support mailboxes yet"))
+            }
         }
         .context(ctx)
     }
@@ -151,11 +985,13 @@ impl ChainConf {
         match &self.connection {
             ChainConnectionConf::Ethereum(conf) => {
                 self.build_ethereum(
-                    conf,
+                    &conf.index,
                     &locator,
                     metrics,
                     h_eth::SequenceIndexerBuilder {
                         finality_blocks: self.finality_blocks,
+                        finality_tag: self.finality_tag(),
+                        event_topics: self.index.event_topics.clone(),
                     },
                 )
                 .await
@@ -166,6 +1002,12 @@ impl ChainConf {
                 let indexer = Box::new(h_sealevel::SealevelMailboxIndexer::new(conf, locator)?);
                 Ok(indexer as Box<dyn SequenceIndexer<HyperlaneMessage>>)
             }
+            ChainConnectionConf::Cosmos(_) => {
+                Err(eyre!("Cosmos does not support message indexers yet"))
+            }
+            ChainConnectionConf::Starknet(_) => {
+                Err(eyre!("Starknet does not support message indexers yet"))
+            }
         }
         .context(ctx)
     }
@@ -181,11 +1023,13 @@ impl ChainConf {
         match &self.connection {
             ChainConnectionConf::Ethereum(conf) => {
                 self.build_ethereum(
-                    conf,
+                    &conf.index,
                     &locator,
                     metrics,
                     h_eth::DeliveryIndexerBuilder {
                         finality_blocks: self.finality_blocks,
+                        finality_tag: self.finality_tag(),
+                        event_topics: self.index.event_topics.clone(),
                     },
                 )
                 .await
@@ -196,6 +1040,12 @@ impl ChainConf {
                 let indexer = Box::new(h_sealevel::SealevelMailboxIndexer::new(conf, locator)?);
                 Ok(indexer as Box<dyn SequenceIndexer<H256>>)
             }
+            ChainConnectionConf::Cosmos(_) => {
+                Err(eyre!("Cosmos does not support delivery indexers yet"))
+            }
+            ChainConnectionConf::Starknet(_) => {
+                Err(eyre!("Starknet does not support delivery indexers yet"))
+            }
         }
         .context(ctx)
     }
@@ -212,7 +1062,7 @@ impl ChainConf {
         match &self.connection {
             ChainConnectionConf::Ethereum(conf) => {
                 self.build_ethereum(
-                    conf,
+                    &conf.submit,
                     &locator,
                     metrics,
                     h_eth::InterchainGasPaymasterBuilder {},
@@ -227,6 +1077,8 @@ impl ChainConf {
                 );
                 Ok(paymaster as Box<dyn InterchainGasPaymaster>)
             }
+            ChainConnectionConf::Cosmos(_) => Err(eyre!("Cosmos does not support IGPs yet")),
+            ChainConnectionConf::Starknet(_) => Err(eyre!("Starknet does not support IGPs yet")),
         }
         .context(ctx)
     }
@@ -242,12 +1094,13 @@ impl ChainConf {
         match &self.connection {
             ChainConnectionConf::Ethereum(conf) => {
                 self.build_ethereum(
-                    conf,
+                    &conf.index,
                     &locator,
                     metrics,
                     h_eth::InterchainGasPaymasterIndexerBuilder {
                         mailbox_address: self.addresses.mailbox.into(),
                         finality_blocks: self.finality_blocks,
+                        finality_tag: self.finality_tag(),
                     },
                 )
                 .await
@@ -260,6 +1113,12 @@ impl ChainConf {
                 );
                 Ok(indexer as Box<dyn SequenceIndexer<InterchainGasPayment>>)
             }
+            ChainConnectionConf::Cosmos(_) => {
+                Err(eyre!("Cosmos does not support IGP indexers yet"))
+            }
+            ChainConnectionConf::Starknet(_) => {
+                Err(eyre!("Starknet does not support IGP indexers yet"))
+            }
         }
         .context(ctx)
     }
@@ -272,8 +1131,16 @@ impl ChainConf {
         let locator = self.locator(self.addresses.validator_announce);
         match &self.connection {
             ChainConnectionConf::Ethereum(conf) => {
-                self.build_ethereum(conf, &locator, metrics, h_eth::ValidatorAnnounceBuilder {})
-                    .await
+                self.build_ethereum(
+                    &conf.submit,
+                    &locator,
+                    metrics,
+                    h_eth::ValidatorAnnounceBuilder {
+                        tx_type: self.tx_type,
+                        gas_oracle: self.gas_oracle.clone(),
+                    },
+                )
+                .await
             }
 
             ChainConnectionConf::Fuel(_) => todo!(),
@@ -281,6 +1148,12 @@ impl ChainConf {
                 let va = Box::new(h_sealevel::SealevelValidatorAnnounce::new(conf, locator));
                 Ok(va as Box<dyn ValidatorAnnounce>)
             }
+            ChainConnectionConf::Cosmos(_) => {
+                Err(eyre!("Cosmos does not support validator announce yet"))
+            }
+            ChainConnectionConf::Starknet(_) => {
+                Err(eyre!("Starknet does not support validator announce yet"))
+            }
         }
         .context("Building ValidatorAnnounce")
     }
@@ -298,7 +1171,7 @@ impl ChainConf {
         match &self.connection {
             ChainConnectionConf::Ethereum(conf) => {
                 self.build_ethereum(
-                    conf,
+                    &conf.submit,
                     &locator,
                     metrics,
                     h_eth::InterchainSecurityModuleBuilder {},
@@ -314,6 +1187,8 @@ impl ChainConf {
                 ));
                 Ok(ism as Box<dyn InterchainSecurityModule>)
             }
+            ChainConnectionConf::Cosmos(_) => Err(eyre!("Cosmos does not support ISMs yet")),
+            ChainConnectionConf::Starknet(_) => Err(eyre!("Starknet does not support ISMs yet")),
         }
         .context(ctx)
     }
@@ -329,8 +1204,13 @@ impl ChainConf {
 
         match &self.connection {
             ChainConnectionConf::Ethereum(conf) => {
-                self.build_ethereum(conf, &locator, metrics, h_eth::MultisigIsmBuilder {})
-                    .await
+                self.build_ethereum(
+                    &conf.submit,
+                    &locator,
+                    metrics,
+                    h_eth::MultisigIsmBuilder {},
+                )
+                .await
             }
 
             ChainConnectionConf::Fuel(_) => todo!(),
@@ -339,6 +1219,12 @@ impl ChainConf {
                 let ism = Box::new(h_sealevel::SealevelMultisigIsm::new(conf, locator, keypair));
                 Ok(ism as Box<dyn MultisigIsm>)
             }
+            ChainConnectionConf::Cosmos(_) => {
+                Err(eyre!("Cosmos does not support multisig ISM yet"))
+            }
+            ChainConnectionConf::Starknet(_) => {
+                Err(eyre!("Starknet does not support multisig ISM yet"))
+            }
         }
         .context(ctx)
     }
@@ -357,7 +1243,7 @@ impl ChainConf {
 
         match &self.connection {
             ChainConnectionConf::Ethereum(conf) => {
-                self.build_ethereum(conf, &locator, metrics, h_eth::RoutingIsmBuilder {})
+                self.build_ethereum(&conf.submit, &locator, metrics, h_eth::RoutingIsmBuilder {})
                     .await
             }
 
@@ -365,6 +1251,10 @@ impl ChainConf {
             ChainConnectionConf::Sealevel(_) => {
                 Err(eyre!("Sealevel does not support routing ISM yet")).context(ctx)
             }
+            ChainConnectionConf::Cosmos(_) => Err(eyre!("Cosmos does not support routing ISM yet")),
+            ChainConnectionConf::Starknet(_) => {
+                Err(eyre!("Starknet does not support routing ISM yet"))
+            }
         }
         .context(ctx)
     }
@@ -383,14 +1273,25 @@ impl ChainConf {
 
         match &self.connection {
             ChainConnectionConf::Ethereum(conf) => {
-                self.build_ethereum(conf, &locator, metrics, h_eth::AggregationIsmBuilder {})
-                    .await
+                self.build_ethereum(
+                    &conf.submit,
+                    &locator,
+                    metrics,
+                    h_eth::AggregationIsmBuilder {},
+                )
+                .await
             }
 
             ChainConnectionConf::Fuel(_) => todo!(),
             ChainConnectionConf::Sealevel(_) => {
                 Err(eyre!("Sealevel does not support aggregation ISM yet")).context(ctx)
             }
+            ChainConnectionConf::Cosmos(_) => {
+                Err(eyre!("Cosmos does not support aggregation ISM yet"))
+            }
+            ChainConnectionConf::Starknet(_) => {
+                Err(eyre!("Starknet does not support aggregation ISM yet"))
+            }
         }
         .context(ctx)
     }
@@ -409,23 +1310,48 @@ impl ChainConf {
 
         match &self.connection {
             ChainConnectionConf::Ethereum(conf) => {
-                self.build_ethereum(conf, &locator, metrics, h_eth::CcipReadIsmBuilder {})
-                    .await
+                self.build_ethereum(
+                    &conf.submit,
+                    &locator,
+                    metrics,
+                    h_eth::CcipReadIsmBuilder {},
+                )
+                .await
             }
 
             ChainConnectionConf::Fuel(_) => todo!(),
             ChainConnectionConf::Sealevel(_) => {
                 Err(eyre!("Sealevel does not support CCIP read ISM yet")).context(ctx)
             }
+            ChainConnectionConf::Cosmos(_) => {
+                Err(eyre!("Cosmos does not support CCIP read ISM yet"))
+            }
+            ChainConnectionConf::Starknet(_) => {
+                Err(eyre!("Starknet does not support CCIP read ISM yet"))
+            }
         }
         .context(ctx)
     }
 
     async fn signer<S: BuildableWithSignerConf>(&self) -> Result<Option<S>> {
-        if let Some(conf) = &self.signer {
-            Ok(Some(conf.build::<S>().await?))
-        } else {
-            Ok(None)
+        match &self.signer {
+            // the node signs for itself so there is nothing to build
+            None | Some(SignerConf::Node) => Ok(None),
+            Some(conf) => Ok(Some(conf.build::<S>().await?)),
+        }
+    }
+
+    /// Try to construct the configured signer for this chain's protocol.
+    pub async fn validate_signer(&self) -> Result<()> {
+        if matches!(self.signer, None | Some(SignerConf::Node)) {
+            return Ok(());
+        }
+        match &self.connection {
+            ChainConnectionConf::Ethereum(_) => self.ethereum_signer().await.map(|_| ()),
+            ChainConnectionConf::Fuel(_) => self.fuel_signer().await.map(|_| ()),
+            ChainConnectionConf::Sealevel(_) => self.sealevel_signer().await.map(|_| ()),
+            ChainConnectionConf::Cosmos(_) => Err(eyre!("Cosmos does not support signers yet")),
+            ChainConnectionConf::Starknet(_) => Err(eyre!("Starknet does not support signers yet")),
         }
     }
 
@@ -452,10 +1378,12 @@ impl ChainConf {
     ) -> PrometheusMiddlewareConf {
         let mut cfg = self.metrics_conf.clone();
 
-        if cfg.chain.is_none() {
-            cfg.chain = Some(ChainInfo {
-                name: Some(self.domain.name().into()),
-            });
+        let chain = cfg.chain.get_or_insert_with(ChainInfo::default);
+        if chain.name.is_none() {
+            chain.name = Some(self.domain.name().into());
+        }
+        if chain.native_token.is_none() {
+            chain.native_token = Some(self.native_token.token_info());
         }
 
         if let Some(signer) = signer {
@@ -519,8 +1447,86 @@ impl ChainConf {
         let rpc_metrics = Some(metrics.json_rpc_client_metrics());
         let middleware_metrics = Some((metrics.provider_metrics(), metrics_conf));
         let res = builder
-            .build_with_connection_conf(conf, locator, signer, rpc_metrics, middleware_metrics)
+            .build_with_connection_conf(
+                conf,
+                locator,
+                signer,
+                self.chain_id,
+                self.nonce_strategy,
+                rpc_metrics,
+                middleware_metrics,
+            )
             .await;
         Ok(res?)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use hyperlane_core::config::IntoParsedConf;
+    use serde_json::json;
+
+    use super::*;
+    use crate::settings::{deprecated_parser::DeprecatedRawChainConf, test_utils::test_chain};
+
+    #[test]
+    fn computes_next_index_range() {
+        let index = IndexSettings {
+            from: IndexFrom::Height(100),
+            chunk_size: 10,
+            finality_blocks: 5,
+            ..Default::default()
+        };
+        assert_eq!(index.next_range(200, 0), Some(100..=109));
+        assert_eq!(index.next_range(200, 150), Some(151..=160));
+        assert_eq!(index.next_range(200, 190), Some(191..=195));
+        assert_eq!(index.next_range(200, 195), None);
+        assert_eq!(index.next_range(3, 0), None);
+    }
+
+    #[test]
+    fn classifies_submitter_and_indexer_chains() {
+        let key = format!("0x{}", "01".repeat(32));
+        let parse = |signer: serde_json::Value, index_only: bool, mailbox: &str| -> ChainConf {
+            serde_json::from_value::<DeprecatedRawChainConf>(test_chain(json!({
+                "signer": signer,
+                "indexOnly": index_only,
+                "addresses": {
+                    "mailbox": mailbox,
+                    "interchainGasPaymaster": "0x2222222222222222222222222222222222222222",
+                    "validatorAnnounce": "0x3333333333333333333333333333333333333333",
+                },
+            })))
+            .unwrap()
+            .parse_config(&ConfigPath::default())
+            .unwrap()
+        };
+        let mailbox = "0x1111111111111111111111111111111111111111";
+        let zero = "0x0000000000000000000000000000000000000000";
+        let hex_key = json!({ "type": "hexKey", "key": key });
+        // a signer without any fields is the node itself
+        let node = json!({});
+
+        for (signer, index_only, is_submitter) in [
+            (hex_key.clone(), false, true),
+            (hex_key.clone(), true, false),
+            (node.clone(), false, false),
+            (node.clone(), true, false),
+            (serde_json::Value::Null, false, false),
+            (serde_json::Value::Null, true, false),
+        ] {
+            for (mailbox, is_indexer) in [(mailbox, true), (zero, false)] {
+                let chain = parse(signer.clone(), index_only, mailbox);
+                assert_eq!(chain.is_submitter(), is_submitter, "{signer} {index_only}");
+                assert_eq!(chain.is_indexer(), is_indexer, "{mailbox}");
+            }
+        }
+    }
+
+    #[test]
+    fn pads_gas_limit() {
+        assert_eq!(pad_gas_limit(100_000.into(), 1.0), 100_000.into());
+        assert_eq!(pad_gas_limit(100_000.into(), 1.25), 125_000.into());
+        assert_eq!(pad_gas_limit(3.into(), 1.5), 5.into());
+    }
+}
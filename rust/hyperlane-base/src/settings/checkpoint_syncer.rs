@@ -1,12 +1,49 @@
 use core::str::FromStr;
 use std::{collections::HashMap, path::PathBuf};
 
-use eyre::{eyre, Context, Report, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use eyre::{bail, eyre, Context, Report, Result};
+use gcp_auth::CustomServiceAccount;
 use hyperlane_core::H160;
 use prometheus::{IntGauge, IntGaugeVec};
+use reqwest::Url;
 use rusoto_core::Region;
+use tracing::debug;
 
-use crate::{CheckpointSyncer, LocalStorage, MultisigCheckpointSyncer, S3Storage};
+use crate::{
+    CheckpointSyncer, GcsServiceAccountKey, GcsStorage, IpfsStorage, LocalStorage, MirroredStorage,
+    MultisigCheckpointSyncer, S3Storage, DEFAULT_CHECKPOINT_FETCH_CONCURRENCY,
+};
+
+/// The AWS regions an S3 checkpoint syncer can be in when no custom
+/// `endpoint` is set.
+pub const AWS_REGIONS: [&str; 25] = [
+    "af-south-1",
+    "ap-east-1",
+    "ap-northeast-1",
+    "ap-northeast-2",
+    "ap-northeast-3",
+    "ap-south-1",
+    "ap-southeast-1",
+    "ap-southeast-2",
+    "ca-central-1",
+    "cn-north-1",
+    "cn-northwest-1",
+    "eu-central-1",
+    "eu-north-1",
+    "eu-south-1",
+    "eu-west-1",
+    "eu-west-2",
+    "eu-west-3",
+    "me-south-1",
+    "sa-east-1",
+    "us-east-1",
+    "us-east-2",
+    "us-gov-east-1",
+    "us-gov-west-1",
+    "us-west-1",
+    "us-west-2",
+];
 
 /// Checkpoint Syncer types
 #[derive(Debug, Clone)]
@@ -15,6 +52,13 @@ pub enum CheckpointSyncerConf {
     LocalStorage {
         /// Path
         path: PathBuf,
+        /// Create the directory if it does not exist. When off a missing
+        /// directory is an error, e.g. to catch a volume which failed to
+        /// mount.
+        create_if_missing: bool,
+        /// Number of the most recent checkpoints to keep, older ones are
+        /// deleted after each write. Everything is kept when `None`.
+        retain: Option<u32>,
     },
     /// A checkpoint syncer on S3
     S3 {
@@ -24,17 +68,57 @@ pub enum CheckpointSyncerConf {
         folder: Option<String>,
         /// S3 Region
         region: Region,
+        /// Url of an S3 compatible store (e.g. MinIO or Wasabi) to use instead
+        /// of AWS
+        endpoint: Option<Url>,
+        /// Address the bucket in the request path rather than the host name.
+        /// Rusoto always addresses buckets by path, so this is accepted for
+        /// compatibility with configs shared with other S3 clients.
+        force_path_style: bool,
+        /// KMS key to encrypt written checkpoints with (SSE-KMS), for buckets
+        /// which enforce it. Reads are unaffected.
+        sse_kms_key_id: Option<String>,
+        /// A named profile in the shared AWS credentials file to write with
+        /// instead of the default credential chain
+        profile: Option<String>,
+    },
+    /// A checkpoint syncer on Google Cloud Storage
+    Gcs {
+        /// Bucket name
+        bucket: String,
+        /// Folder name inside bucket - defaults to the root of the bucket
+        folder: Option<String>,
+        /// Service account key file or inline JSON key - defaults to
+        /// application default credentials
+        service_account_key: Option<GcsServiceAccountKey>,
+    },
+    /// A checkpoint syncer which pins checkpoints to IPFS
+    Ipfs {
+        /// Url of the IPFS HTTP API used to pin and index checkpoints
+        api_url: Url,
+        /// Url of a gateway used to fetch checkpoints - defaults to the API
+        gateway_url: Option<Url>,
+        /// Bearer token for the pinning service API
+        pin_service_token: Option<String>,
+        /// IPNS name the checkpoint index is published under, i.e. the id
+        /// (`k51...`) of a key of the node. Relayers resolve the index through
+        /// it, so validators must set it together with a public `gateway_url`
+        /// for their checkpoints to be relayed.
+        ipns_name: Option<String>,
     },
+    /// Several checkpoint syncers, e.g. a primary and a warm standby.
+    /// Checkpoints are written to all of them and read from the first one
+    /// which has them.
+    Mirrored(Vec<CheckpointSyncerConf>),
 }
 
 impl FromStr for CheckpointSyncerConf {
     type Err = Report;
 
     fn from_str(s: &str) -> Result<Self> {
-        let [prefix, suffix]: [&str; 2] =
-            s.split("://").collect::<Vec<_>>().try_into().map_err(|_| {
-                eyre!("Error parsing storage location; could not split prefix and suffix ({s})")
-            })?;
+        let (prefix, suffix) = s.split_once("://").ok_or_else(|| {
+            eyre!("Error parsing storage location; could not split prefix and suffix ({s})")
+        })?;
 
         match prefix {
             "s3" => {
@@ -50,36 +134,251 @@ impl FromStr for CheckpointSyncerConf {
                     region: region
                         .parse()
                         .context("Invalid region when parsing storage location")?,
+                    endpoint: None,
+                    force_path_style: false,
+                    sse_kms_key_id: None,
+                    profile: None,
                 })
             }
+            "gs" => {
+                let (bucket, folder) = match suffix.split_once('/') {
+                    Some((bucket, folder)) => (bucket, Some(folder.to_owned())),
+                    None => (suffix, None),
+                };
+                if bucket.is_empty() {
+                    return Err(eyre!(
+                        "Error parsing storage location; missing bucket ({suffix})"
+                    ));
+                }
+                Ok(CheckpointSyncerConf::Gcs {
+                    bucket: bucket.into(),
+                    folder,
+                    service_account_key: None,
+                })
+            }
+            "ipfs" => Self::parse_ipfs_location(suffix),
             "file" => Ok(CheckpointSyncerConf::LocalStorage {
                 path: suffix.into(),
+                create_if_missing: true,
+                retain: None,
             }),
             _ => Err(eyre!("Unknown storage location prefix `{prefix}`")),
         }
     }
 }
 
+/// Placeholder in a checkpoint syncer `folder` which is replaced with the name
+/// of the chain being validated.
+pub const CHAIN_PLACEHOLDER: &str = "{chain}";
+
+/// Prefix marking a GCS `serviceAccountKey` as an inline base64 encoded JSON
+/// key rather than the path to a key file.
+pub const GCS_SERVICE_ACCOUNT_KEY_BASE64_PREFIX: &str = "base64:";
+
 impl CheckpointSyncerConf {
+    /// Parse the suffix of an `ipfs://` storage location. Announcements are
+    /// `<gateway url>/ipns/<name>` for a published index, which is read
+    /// through that gateway, or the url of the API. Legacy locations without
+    /// a scheme are https.
+    fn parse_ipfs_location(suffix: &str) -> Result<Self> {
+        let url = if suffix.contains("://") {
+            suffix.to_owned()
+        } else {
+            format!("https://{suffix}")
+        };
+        let (url, ipns_name) = match url.rsplit_once("/ipns/") {
+            Some((base, name)) => (base.to_owned(), Some(name.trim_end_matches('/').to_owned())),
+            None => (url, None),
+        };
+        let url: Url = format!("{}/", url.trim_end_matches('/'))
+            .parse()
+            .context("Invalid IPFS url when parsing storage location")?;
+        Ok(match ipns_name {
+            Some(name) => CheckpointSyncerConf::Ipfs {
+                api_url: url.clone(),
+                gateway_url: Some(url),
+                pin_service_token: None,
+                ipns_name: Some(Self::parse_ipns_name(&name)?),
+            },
+            None => CheckpointSyncerConf::Ipfs {
+                api_url: url,
+                gateway_url: None,
+                pin_service_token: None,
+                ipns_name: None,
+            },
+        })
+    }
+
+    /// Check the IPNS name of an IPFS checkpoint syncer is a single non-empty
+    /// path segment.
+    pub fn parse_ipns_name(name: &str) -> Result<String> {
+        let name = name.trim();
+        if name.is_empty() || name.contains('/') {
+            bail!("Invalid IPFS `ipnsName` `{name}`, expected the id of an IPNS key");
+        }
+        Ok(name.to_owned())
+    }
+
+    /// Parse the url of an S3 compatible endpoint.
+    pub fn parse_s3_endpoint(endpoint: &str) -> Result<Url> {
+        let url: Url = endpoint
+            .parse()
+            .with_context(|| format!("Invalid S3 `endpoint` `{endpoint}`, expected a url"))?;
+        if !matches!(url.scheme(), "http" | "https") || url.host().is_none() {
+            bail!("Invalid S3 `endpoint` `{endpoint}`, expected an http(s) url with a host");
+        }
+        Ok(url)
+    }
+
+    /// Check the SSE-KMS key id of an S3 checkpoint syncer is not empty.
+    pub fn parse_s3_sse_kms_key_id(key_id: &str) -> Result<String> {
+        let key_id = key_id.trim();
+        if key_id.is_empty() {
+            bail!("Invalid S3 `sseKmsKeyId`, expected a non-empty KMS key id");
+        }
+        Ok(key_id.to_owned())
+    }
+
+    /// Resolve the region of an S3 checkpoint syncer. Stores behind a custom
+    /// `endpoint` often use placeholder regions, so any name is accepted for
+    /// them and it defaults to `us-east-1`. Otherwise it must be one of the
+    /// `AWS_REGIONS`.
+    pub fn s3_region(region: Option<&str>, endpoint: Option<&Url>) -> Result<Region> {
+        match endpoint {
+            Some(endpoint) => Ok(Region::Custom {
+                name: region.unwrap_or("us-east-1").to_owned(),
+                endpoint: endpoint.as_str().trim_end_matches('/').to_owned(),
+            }),
+            None => {
+                let region =
+                    region.ok_or_else(|| eyre!("Missing `region` for S3 checkpoint syncer"))?;
+                if !AWS_REGIONS.contains(&region) {
+                    bail!(
+                        "Unknown `region` `{region}` for S3 checkpoint syncer, expected one of {}",
+                        AWS_REGIONS.join(", ")
+                    );
+                }
+                region
+                    .parse()
+                    .context("Invalid `region` for S3 checkpoint syncer")
+            }
+        }
+    }
+
+    /// Parse the `serviceAccountKey` of a GCS checkpoint syncer, which is
+    /// either the path to a key file or a `base64:` prefixed inline key.
+    pub fn parse_gcs_service_account_key(key: &str) -> Result<GcsServiceAccountKey> {
+        match key.strip_prefix(GCS_SERVICE_ACCOUNT_KEY_BASE64_PREFIX) {
+            Some(encoded) => Self::decode_gcs_service_account_key(encoded),
+            None => Ok(GcsServiceAccountKey::File(key.into())),
+        }
+    }
+
+    /// Decode an inline base64 encoded GCS service account key, checking that
+    /// it is a valid JSON key so mistakes are caught before the first write.
+    pub fn decode_gcs_service_account_key(encoded: &str) -> Result<GcsServiceAccountKey> {
+        let json = STANDARD
+            .decode(encoded.trim())
+            .context("Invalid base64 for GCS service account key")?;
+        let json = String::from_utf8(json).context("GCS service account key is not valid utf-8")?;
+        CustomServiceAccount::from_json(&json)
+            .map_err(|e| eyre!("Invalid GCS service account key JSON: {e}"))?;
+        Ok(GcsServiceAccountKey::Json(json))
+    }
+
+    /// Replace the `{chain}` placeholder in the S3 or GCS folder with the chain
+    /// name. Errors if the folder uses the placeholder but no chain name is
+    /// given.
+    pub fn with_chain_name(mut self, chain: Option<&str>) -> Result<Self> {
+        if let CheckpointSyncerConf::Mirrored(syncers) = self {
+            return syncers
+                .into_iter()
+                .map(|s| s.with_chain_name(chain))
+                .collect::<Result<_>>()
+                .map(CheckpointSyncerConf::Mirrored);
+        }
+        if let CheckpointSyncerConf::S3 {
+            folder: Some(folder),
+            ..
+        }
+        | CheckpointSyncerConf::Gcs {
+            folder: Some(folder),
+            ..
+        } = &mut self
+        {
+            if folder.contains(CHAIN_PLACEHOLDER) {
+                let Some(chain) = chain else {
+                    bail!(
+                        "`folder` uses the `{CHAIN_PLACEHOLDER}` placeholder but no chain name \
+                         is known"
+                    );
+                };
+                *folder = folder.replace(CHAIN_PLACEHOLDER, chain);
+                debug!(%folder, "Resolved checkpoint syncer folder");
+            }
+        }
+        Ok(self)
+    }
+
     /// Turn conf info a Checkpoint Syncer
     pub fn build(
         &self,
         latest_index_gauge: Option<IntGauge>,
     ) -> Result<Box<dyn CheckpointSyncer>, Report> {
         Ok(match self {
-            CheckpointSyncerConf::LocalStorage { path } => {
-                Box::new(LocalStorage::new(path.clone(), latest_index_gauge)?)
-            }
+            CheckpointSyncerConf::LocalStorage {
+                path,
+                create_if_missing,
+                retain,
+            } => Box::new(LocalStorage::new(
+                path.clone(),
+                *create_if_missing,
+                *retain,
+                latest_index_gauge,
+            )?),
             CheckpointSyncerConf::S3 {
                 bucket,
                 folder,
                 region,
+                sse_kms_key_id,
+                profile,
+                ..
             } => Box::new(S3Storage::new(
                 bucket.clone(),
                 folder.clone(),
                 region.clone(),
+                sse_kms_key_id.clone(),
+                profile.clone(),
+                latest_index_gauge,
+            )),
+            CheckpointSyncerConf::Gcs {
+                bucket,
+                folder,
+                service_account_key,
+            } => Box::new(GcsStorage::new(
+                bucket.clone(),
+                folder.clone(),
+                service_account_key.clone(),
+                latest_index_gauge,
+            )),
+            CheckpointSyncerConf::Ipfs {
+                api_url,
+                gateway_url,
+                pin_service_token,
+                ipns_name,
+            } => Box::new(IpfsStorage::new(
+                api_url.clone(),
+                gateway_url.clone(),
+                pin_service_token.clone(),
+                ipns_name.clone(),
                 latest_index_gauge,
             )),
+            CheckpointSyncerConf::Mirrored(syncers) => Box::new(MirroredStorage::new(
+                syncers
+                    .iter()
+                    .map(|s| s.build(latest_index_gauge.clone()))
+                    .collect::<Result<_>>()?,
+            )?),
         })
     }
 }
@@ -108,6 +407,9 @@ impl MultisigCheckpointSyncerConf {
                 continue;
             }
         }
-        Ok(MultisigCheckpointSyncer::new(checkpoint_syncers))
+        Ok(MultisigCheckpointSyncer::new(
+            checkpoint_syncers,
+            DEFAULT_CHECKPOINT_FETCH_CONCURRENCY,
+        ))
     }
 }
@@ -4,18 +4,41 @@
 
 use std::{
     collections::{HashMap, HashSet},
+    net::SocketAddr,
     path::PathBuf,
 };
 
-use ethers_prometheus::middleware::PrometheusMiddlewareConf;
-use eyre::{eyre, Context};
-use hyperlane_core::{cfg_unwrap_all, config::*, utils::hex_or_base58_to_h256, HyperlaneDomain};
+use ethers_prometheus::middleware::{ChainInfo, PrometheusMiddlewareConf};
+use eyre::{bail, eyre, Context};
+use hyperlane_core::{
+    cfg_unwrap_all, config::*, utils::hex_or_base58_to_h256, HyperlaneDomain,
+    HyperlaneDomainProtocol, IndexMode, KnownHyperlaneDomain, H256, U256,
+};
 use serde::Deserialize;
+use tracing::{info, warn};
 
 use super::envs::*;
 use crate::settings::{
-    chains::IndexSettings, trace::TracingConfig, ChainConf, ChainConnectionConf,
-    CheckpointSyncerConf, CoreContractAddresses, Settings, SignerConf,
+    base::{check_unique_domain_ids, parse_metrics_prefix},
+    chains::{
+        check_mainnet_finality, is_ens_name, normalize_address, parse_batch_flush_interval,
+        parse_batch_strategy, parse_chunk_bound, parse_confirmation_timeout, parse_event_topic,
+        parse_finality, parse_gas_limit_multiplier, parse_index_chunk, parse_index_from,
+        parse_index_modes, parse_max_reorg_depth, parse_metrics_labels,
+        parse_native_token_decimals, parse_nonce_strategy, parse_poll_interval, parse_tx_type,
+        validate_ens_names, EnsName, Finality, IndexChunkUnit, IndexFrom, IndexSettings,
+        NativeToken, DEFAULT_BATCH_FLUSH_INTERVAL, DEFAULT_CONFIRMATION_TIMEOUT,
+        DEFAULT_MAX_BATCH_SIZE, DEFAULT_MAX_IN_FLIGHT_TRANSACTIONS, DEFAULT_MAX_REORG_DEPTH,
+        DEFAULT_MIN_CHUNK_SIZE, DEFAULT_POLL_INTERVAL, MAX_BATCH_SIZE_LIMIT,
+    },
+    signers::{
+        check_relay_service_signer, parse_aws_profile, parse_aws_region,
+        parse_external_signer_timeout, parse_relay_service_endpoint, parse_role_arn,
+        parse_signer_role, RoleSigners, SignerRole, DEFAULT_EXTERNAL_SIGNER_TIMEOUT,
+    },
+    trace::{parse_log_format, parse_log_targets, parse_sampling_rate, Level, TracingConfig},
+    ChainConf, ChainConnectionConf, CheckpointSyncerConf, CoreContractAddresses, Settings,
+    SignerConf,
 };
 
 /// Raw base settings.
@@ -24,8 +47,64 @@ use crate::settings::{
 pub struct DeprecatedRawSettings {
     chains: Option<HashMap<String, DeprecatedRawChainConf>>,
     defaultsigner: Option<DeprecatedRawSignerConf>,
+    /// Index settings used for any fields a chain does not set itself
+    default_index: Option<DeprecatedRawIndexSettings>,
     metrics: Option<StrOrInt>,
-    tracing: Option<TracingConfig>,
+    #[serde(default)]
+    allow_privileged_port: bool,
+    metrics_prefix: Option<String>,
+    /// Port (or `<ip>:<port>`) to serve `/health` on, disabled by default
+    health_port: Option<StrOrInt>,
+    tracing: Option<DeprecatedRawTracingConfig>,
+}
+
+/// A deprecated config key which was set, along with the key which replaces it
+/// in the new config format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeprecationWarning {
+    /// Path of the deprecated key that was used.
+    pub path: ConfigPath,
+    /// Path of the key to use instead in the new config format.
+    pub suggested_new_key: ConfigPath,
+}
+
+impl DeprecatedRawSettings {
+    /// List the deprecated keys which are set and what they should be replaced
+    /// with. Chains excluded by the filter are skipped.
+    pub fn deprecation_warnings(
+        &self,
+        cwp: &ConfigPath,
+        filter: Option<&HashSet<&str>>,
+    ) -> Vec<DeprecationWarning> {
+        let mut warnings = Vec::new();
+        if self.defaultsigner.is_some() {
+            warnings.push(DeprecationWarning {
+                path: cwp + "defaultsigner",
+                suggested_new_key: cwp + "default_signer",
+            });
+        }
+        if self.metrics.is_some() {
+            warnings.push(DeprecationWarning {
+                path: cwp + "metrics",
+                suggested_new_key: cwp + "metrics_port",
+            });
+        }
+        if self.tracing.is_some() {
+            warnings.push(DeprecationWarning {
+                path: cwp + "tracing",
+                suggested_new_key: cwp + "log",
+            });
+        }
+        if let Some(chains) = &self.chains {
+            let chains_path = cwp + "chains";
+            for (name, chain) in chains {
+                if !chain.disabled && filter.map_or(true, |f| f.contains(&name.as_str())) {
+                    warnings.extend(chain.deprecation_warnings(&(&chains_path + name)));
+                }
+            }
+        }
+        warnings
+    }
 }
 
 impl FromRawConf<DeprecatedRawSettings, Option<&HashSet<&str>>> for Settings {
@@ -35,22 +114,49 @@ impl FromRawConf<DeprecatedRawSettings, Option<&HashSet<&str>>> for Settings {
         filter: Option<&HashSet<&str>>,
     ) -> Result<Self, ConfigParsingError> {
         let mut err = ConfigParsingError::default();
+        for warning in raw.deprecation_warnings(cwp, filter) {
+            warn!(
+                path = %warning.path,
+                suggested_new_key = %warning.suggested_new_key,
+                "Deprecated config key used"
+            );
+        }
         let chains: HashMap<String, ChainConf> = if let Some(mut chains) = raw.chains {
             let default_signer: Option<SignerConf> = raw.defaultsigner.and_then(|r| {
                 r.parse_config(&cwp.join("defaultsigner"))
                     .take_config_err(&mut err)
             });
+            // only merge the default index settings if they are valid so errors
+            // in them are reported once rather than for every chain
+            let default_index = raw.default_index.filter(|r| {
+                IndexSettings::from_config(r.clone(), &(cwp + "default_index"))
+                    .take_config_err(&mut err)
+                    .is_some()
+            });
             if let Some(filter) = filter {
                 chains.retain(|k, _| filter.contains(&k.as_str()));
             }
+            chains.retain(|k, v| {
+                if v.disabled {
+                    info!(chain = %k, "Skipping disabled chain");
+                }
+                !v.disabled
+            });
             let chains_path = cwp + "chains";
             chains
                 .into_iter()
-                .map(|(k, v)| {
+                .map(|(k, mut v)| {
                     let cwp = &chains_path + &k;
                     let k = k.to_ascii_lowercase();
-                    let mut parsed: ChainConf = v.parse_config(&cwp)?;
-                    if let Some(default_signer) = &default_signer {
+                    if let Some(default_index) = &default_index {
+                        let index = v.index.take().unwrap_or_default();
+                        v.index = Some(index.or(default_index));
+                    }
+                    let mut parsed: ChainConf =
+                        v.parse_config_with_filter(&cwp, Some(k.as_str()))?;
+                    if parsed.index_only {
+                        parsed.signer.get_or_insert(SignerConf::Node);
+                    } else if let Some(default_signer) = &default_signer {
                         parsed.signer.get_or_insert_with(|| default_signer.clone());
                     }
                     Ok((k, parsed))
@@ -66,15 +172,49 @@ impl FromRawConf<DeprecatedRawSettings, Option<&HashSet<&str>>> for Settings {
         } else {
             Default::default()
         };
-        let tracing = raw.tracing.unwrap_or_default();
+        check_unique_domain_ids(&chains).take_err(&mut err, || cwp + "chains");
+        let tracing = raw
+            .tracing
+            .and_then(|v| {
+                v.parse_config(&cwp.join("tracing"))
+                    .take_config_err(&mut err)
+            })
+            .unwrap_or_default();
+        let allow_privileged_port = raw.allow_privileged_port;
+        let parse_unprivileged_addr = |key: &str, value: &StrOrInt| {
+            parse_listen_addr(key, value).and_then(|addr| {
+                if addr.port() < 1024 && !allow_privileged_port {
+                    bail!(
+                        "Invalid `{key}` port {}, ports below 1024 are privileged; \
+                         set `allowPrivilegedPort` to use one anyway",
+                        addr.port()
+                    )
+                }
+                Ok(addr)
+            })
+        };
         let metrics = raw
             .metrics
-            .and_then(|port| port.try_into().take_err(&mut err, || cwp + "metrics"))
-            .unwrap_or(9090);
+            .and_then(|metrics| {
+                parse_unprivileged_addr("metrics", &metrics).take_err(&mut err, || cwp + "metrics")
+            })
+            .unwrap_or_else(|| ([0, 0, 0, 0], 9090).into());
+        let health = raw.health_port.and_then(|health_port| {
+            parse_unprivileged_addr("healthPort", &health_port)
+                .take_err(&mut err, || cwp + "health_port")
+        });
+
+        let metrics_prefix = raw.metrics_prefix.and_then(|prefix| {
+            parse_metrics_prefix(&prefix)
+                .take_err(&mut err, || cwp + "metrics_prefix")
+                .flatten()
+        });
 
         err.into_result(Self {
             chains,
-            metrics_port: metrics,
+            metrics_addr: metrics,
+            metrics_prefix,
+            health_addr: health,
             tracing,
         })
     }
@@ -86,6 +226,8 @@ enum DeprecatedRawChainConnectionConf {
     Ethereum(h_eth::RawConnectionConf),
     Fuel(h_fuel::DeprecatedRawConnectionConf),
     Sealevel(h_sealevel::DeprecatedRawConnectionConf),
+    Cosmos(h_cosmos::DeprecatedRawConnectionConf),
+    Starknet(h_starknet::DeprecatedRawConnectionConf),
     #[serde(other)]
     Unknown,
 }
@@ -101,6 +243,8 @@ impl FromRawConf<DeprecatedRawChainConnectionConf> for ChainConnectionConf {
             Ethereum(r) => Ok(Self::Ethereum(r.parse_config(&cwp.join("connection"))?)),
             Fuel(r) => Ok(Self::Fuel(r.parse_config(&cwp.join("connection"))?)),
             Sealevel(r) => Ok(Self::Sealevel(r.parse_config(&cwp.join("connection"))?)),
+            Cosmos(r) => Ok(Self::Cosmos(r.parse_config(&cwp.join("connection"))?)),
+            Starknet(r) => Ok(Self::Starknet(r.parse_config(&cwp.join("connection"))?)),
             Unknown => {
                 Err(eyre!("Unknown chain protocol")).into_config_result(|| cwp.join("protocol"))
             }
@@ -111,9 +255,88 @@ impl FromRawConf<DeprecatedRawChainConnectionConf> for ChainConnectionConf {
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct DeprecatedRawCoreContractAddresses {
-    mailbox: Option<String>,
-    interchain_gas_paymaster: Option<String>,
-    validator_announce: Option<String>,
+    mailbox: Option<DeprecatedRawContractAddress>,
+    interchain_gas_paymaster: Option<DeprecatedRawContractAddress>,
+    validator_announce: Option<DeprecatedRawContractAddress>,
+}
+
+/// A core contract address, either on its own or together with the block the
+/// contract was deployed at, e.g. `{ "address": "0x...", "deployedBlock": 100 }`
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum DeprecatedRawContractAddress {
+    Address(String),
+    Deployment {
+        address: Option<String>,
+        #[serde(rename = "deployedBlock")]
+        deployed_block: Option<StrOrInt>,
+    },
+}
+
+impl DeprecatedRawContractAddress {
+    /// The address, if set.
+    fn address(&self) -> Option<&str> {
+        match self {
+            Self::Address(address) => Some(address),
+            Self::Deployment { address, .. } => address.as_deref(),
+        }
+    }
+
+    /// Split into the address and the deployment block.
+    fn into_parts(self) -> (Option<String>, Option<StrOrInt>) {
+        match self {
+            Self::Address(address) => (Some(address), None),
+            Self::Deployment {
+                address,
+                deployed_block,
+            } => (address, deployed_block),
+        }
+    }
+}
+
+impl DeprecatedRawCoreContractAddresses {
+    /// Iterate over the (field name, address) pairs which have been set.
+    fn iter(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        [
+            ("mailbox", &self.mailbox),
+            ("interchain_gas_paymaster", &self.interchain_gas_paymaster),
+            ("validator_announce", &self.validator_announce),
+        ]
+        .into_iter()
+        .filter_map(|(name, addr)| {
+            addr.as_ref()
+                .and_then(DeprecatedRawContractAddress::address)
+                .map(|addr| (name, addr))
+        })
+    }
+}
+
+/// Check that an address is encoded the way the given protocol expects.
+/// Addresses which can't be decoded at all are reported when parsing them.
+fn validate_address_encoding(addr: &str, protocol: HyperlaneDomainProtocol) -> eyre::Result<()> {
+    match protocol {
+        HyperlaneDomainProtocol::Ethereum => {
+            if let Ok(addr) = hex_or_base58_to_h256(addr) {
+                if addr[..12].iter().any(|b| *b != 0) {
+                    bail!("Ethereum addresses must fit in 20 bytes");
+                }
+            }
+        }
+        HyperlaneDomainProtocol::Sealevel => {
+            if addr.starts_with("0x") {
+                bail!("Sealevel addresses must be 32 byte base58 strings");
+            }
+        }
+        HyperlaneDomainProtocol::Starknet => {
+            if let Ok(addr) = hex_or_base58_to_h256(addr) {
+                if !h_starknet::is_valid_felt(&addr) {
+                    bail!("Starknet addresses must be felts below the field prime");
+                }
+            }
+        }
+        HyperlaneDomainProtocol::Fuel | HyperlaneDomainProtocol::Cosmos => {}
+    }
+    Ok(())
 }
 
 impl FromRawConf<DeprecatedRawCoreContractAddresses> for CoreContractAddresses {
@@ -123,11 +346,23 @@ impl FromRawConf<DeprecatedRawCoreContractAddresses> for CoreContractAddresses {
         _filter: (),
     ) -> ConfigResult<Self> {
         let mut err = ConfigParsingError::default();
+        let mut ens_names = Vec::new();
+        let mut deployed_blocks = HashMap::new();
 
         macro_rules! parse_addr {
             ($name:ident) => {
-                let $name = raw
+                let (address, deployed_block) = raw
                     .$name
+                    .map(DeprecatedRawContractAddress::into_parts)
+                    .unwrap_or_default();
+                if let Some(block) = deployed_block.and_then(|b| {
+                    u32::try_from(&b)
+                        .context("Invalid `deployedBlock`, expected a block number")
+                        .take_err(&mut err, || cwp + stringify!($name) + "deployed_block")
+                }) {
+                    deployed_blocks.insert(stringify!($name), block);
+                }
+                let $name = address
                     .ok_or_else(|| {
                         eyre!(
                             "Missing {} core contract address",
@@ -135,7 +370,16 @@ impl FromRawConf<DeprecatedRawCoreContractAddresses> for CoreContractAddresses {
                         )
                     })
                     .take_err(&mut err, || cwp + stringify!($name))
+                    .map(|v| normalize_address(&v))
                     .and_then(|v| {
+                        if is_ens_name(&v) {
+                            ens_names.push(EnsName {
+                                contract: stringify!($name),
+                                name: v,
+                                cwp: cwp + stringify!($name),
+                            });
+                            return Some(H256::zero());
+                        }
                         hex_or_base58_to_h256(&v).take_err(&mut err, || cwp + stringify!($name))
                     });
             };
@@ -151,16 +395,41 @@ impl FromRawConf<DeprecatedRawCoreContractAddresses> for CoreContractAddresses {
             mailbox,
             interchain_gas_paymaster,
             validator_announce,
+            ens_names,
+            deployed_blocks,
         })
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct DeprecatedRawIndexSettings {
     from: Option<StrOrInt>,
     chunk: Option<StrOrInt>,
-    mode: Option<String>,
+    mode: Option<serde_json::Value>,
+    event_topics: Option<Vec<String>>,
+    max_reorg_depth: Option<StrOrInt>,
+    adaptive_chunking: Option<bool>,
+    min_chunk: Option<StrOrInt>,
+    max_chunk: Option<StrOrInt>,
+}
+
+impl DeprecatedRawIndexSettings {
+    /// Fill in any fields which are not set from `defaults`.
+    fn or(self, defaults: &Self) -> Self {
+        Self {
+            from: self.from.or_else(|| defaults.from.clone()),
+            chunk: self.chunk.or_else(|| defaults.chunk.clone()),
+            mode: self.mode.or_else(|| defaults.mode.clone()),
+            event_topics: self.event_topics.or_else(|| defaults.event_topics.clone()),
+            max_reorg_depth: self
+                .max_reorg_depth
+                .or_else(|| defaults.max_reorg_depth.clone()),
+            adaptive_chunking: self.adaptive_chunking.or(defaults.adaptive_chunking),
+            min_chunk: self.min_chunk.or_else(|| defaults.min_chunk.clone()),
+            max_chunk: self.max_chunk.or_else(|| defaults.max_chunk.clone()),
+        }
+    }
 }
 
 impl FromRawConf<DeprecatedRawIndexSettings> for IndexSettings {
@@ -173,67 +442,330 @@ impl FromRawConf<DeprecatedRawIndexSettings> for IndexSettings {
 
         let from = raw
             .from
-            .and_then(|v| v.try_into().take_err(&mut err, || cwp + "from"))
+            .and_then(|v| parse_index_from(&v).take_err(&mut err, || cwp + "from"))
             .unwrap_or_default();
 
-        let chunk_size = raw
+        let (chunk_size, chunk_unit) = raw
             .chunk
-            .and_then(|v| v.try_into().take_err(&mut err, || cwp + "chunk"))
-            .unwrap_or(1999);
+            .and_then(|v| parse_index_chunk(&v).take_err(&mut err, || cwp + "chunk"))
+            .unwrap_or((1999, IndexChunkUnit::Blocks));
 
-        let mode = raw
+        let modes = raw
             .mode
-            .map(serde_json::Value::from)
-            .and_then(|m| {
-                serde_json::from_value(m)
-                    .context("Invalid mode")
-                    .take_err(&mut err, || cwp + "mode")
+            .and_then(|m| parse_index_modes(m).take_err(&mut err, || cwp + "mode"))
+            .unwrap_or_else(|| vec![IndexMode::default()]);
+
+        let event_topics = raw
+            .event_topics
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|t| parse_event_topic(t).take_err(&mut err, || cwp + "event_topics"))
+            .collect();
+
+        let max_reorg_depth = raw
+            .max_reorg_depth
+            .and_then(|v| parse_max_reorg_depth(&v).take_err(&mut err, || cwp + "max_reorg_depth"))
+            .unwrap_or(DEFAULT_MAX_REORG_DEPTH);
+
+        let min_chunk_size = raw
+            .min_chunk
+            .and_then(|v| {
+                parse_chunk_bound(&v, "minChunk").take_err(&mut err, || cwp + "min_chunk")
             })
-            .unwrap_or_default();
+            .unwrap_or(DEFAULT_MIN_CHUNK_SIZE);
+        let max_chunk_size = raw.max_chunk.and_then(|v| {
+            parse_chunk_bound(&v, "maxChunk").take_err(&mut err, || cwp + "max_chunk")
+        });
+        if max_chunk_size.map_or(false, |max| max < min_chunk_size) {
+            err.push(
+                cwp + "max_chunk",
+                eyre!("`maxChunk` must be at least `minChunk`"),
+            );
+        }
 
         err.into_result(Self {
             from,
             chunk_size,
-            mode,
+            chunk_unit,
+            modes,
+            finality_blocks: 0,
+            reorg_period: 0,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            event_topics,
+            max_reorg_depth,
+            adaptive_chunking: raw.adaptive_chunking.unwrap_or_default(),
+            min_chunk_size,
+            max_chunk_size,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DeprecatedRawTracingConfig {
+    /// `pretty`, `json`, `compact` or `full`
+    #[serde(alias = "fmt")]
+    format: Option<String>,
+    level: Option<Level>,
+    /// Levels of specific targets, as a map or a directive string
+    targets: Option<serde_json::Value>,
+    /// Fraction of the events below WARN to log, between 0.0 and 1.0
+    sampling_rate: Option<f64>,
+}
+
+impl FromRawConf<DeprecatedRawTracingConfig> for TracingConfig {
+    fn from_config_filtered(
+        raw: DeprecatedRawTracingConfig,
+        cwp: &ConfigPath,
+        _filter: (),
+    ) -> ConfigResult<Self> {
+        let mut err = ConfigParsingError::default();
+
+        let fmt = raw
+            .format
+            .and_then(|v| parse_log_format(&v).take_err(&mut err, || cwp + "format"))
+            .unwrap_or_default();
+        let sampling_rate = raw
+            .sampling_rate
+            .and_then(|v| parse_sampling_rate(v).take_err(&mut err, || cwp + "sampling_rate"))
+            .unwrap_or(1.0);
+        let targets = raw
+            .targets
+            .and_then(|v| parse_log_targets(&v).take_err(&mut err, || cwp.clone()))
+            .unwrap_or_default();
+
+        err.into_result(Self {
+            fmt,
+            level: raw.level.unwrap_or_default(),
+            targets,
+            sampling_rate,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DeprecatedRawGasOracleConf {
+    min_gas_price: Option<StrOrInt>,
+    max_gas_price: Option<StrOrInt>,
+    gas_price_multiplier: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DeprecatedRawNativeToken {
+    symbol: Option<String>,
+    decimals: Option<StrOrInt>,
+}
+
+impl FromRawConf<DeprecatedRawNativeToken> for NativeToken {
+    fn from_config_filtered(
+        raw: DeprecatedRawNativeToken,
+        cwp: &ConfigPath,
+        _filter: (),
+    ) -> ConfigResult<Self> {
+        let mut err = ConfigParsingError::default();
+        let default = Self::default();
+        let decimals = raw
+            .decimals
+            .and_then(|v| parse_native_token_decimals(&v).take_err(&mut err, || cwp + "decimals"))
+            .unwrap_or(default.decimals);
+        err.into_result(Self {
+            symbol: raw.symbol.unwrap_or(default.symbol),
+            decimals,
         })
     }
 }
 
+impl FromRawConf<DeprecatedRawGasOracleConf> for h_eth::GasOracleConf {
+    fn from_config_filtered(
+        raw: DeprecatedRawGasOracleConf,
+        cwp: &ConfigPath,
+        _filter: (),
+    ) -> ConfigResult<Self> {
+        let mut err = ConfigParsingError::default();
+
+        let min_gas_price = raw.min_gas_price.and_then(|v| {
+            v.try_into()
+                .context("Invalid `minGasPrice`, expected integer")
+                .take_err(&mut err, || cwp + "min_gas_price")
+        });
+        let max_gas_price = raw.max_gas_price.and_then(|v| {
+            v.try_into()
+                .context("Invalid `maxGasPrice`, expected integer")
+                .take_err(&mut err, || cwp + "max_gas_price")
+        });
+
+        let conf = Self {
+            min_gas_price,
+            max_gas_price,
+            gas_price_multiplier: raw.gas_price_multiplier.unwrap_or(1.0),
+        };
+        if err.is_ok() {
+            conf.validate().take_err(&mut err, || cwp.clone());
+        }
+        err.into_result(conf)
+    }
+}
+
 /// A raw chain setup is a domain ID, an address on that chain (where the
 /// mailbox is deployed) and details for connecting to the chain API.
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DeprecatedRawChainConf {
     name: Option<String>,
+    /// Allow `name` to differ from the key of the chain in `chains`.
+    #[serde(default)]
+    allow_name_key_mismatch: bool,
     domain: Option<StrOrInt>,
-    pub(super) signer: Option<DeprecatedRawSignerConf>,
+    /// The EVM chain id used to sign transactions if it differs from `domain`
+    chain_id: Option<StrOrInt>,
+    #[serde(default)]
+    disabled: bool,
+    /// Build the domain with this protocol instead of the one of the
+    /// `connection`, e.g. during a migration. Known domains keep their
+    /// protocol.
+    protocol_override: Option<String>,
+    /// Chains which are only indexed and never submitted to
+    #[serde(default)]
+    index_only: bool,
+    /// Skip waiting for finality, overriding `finalityBlocks`
+    #[serde(default)]
+    assume_instant_finality: bool,
+    /// Test networks, which are not warned about unsafe mainnet settings
+    #[serde(default)]
+    testnet: bool,
+    /// Either a single signer or a map of roles to signers, e.g.
+    /// `{ "relay": {...}, "validate": {...} }`, optionally next to the
+    /// fields of a default signer
+    pub(super) signer: Option<serde_json::Value>,
     finality_blocks: Option<StrOrInt>,
+    /// Alias of `finalityBlocks`, which takes precedence if both are set
+    confirmations: Option<StrOrInt>,
+    reorg_period: Option<StrOrInt>,
+    block_time: Option<StrOrInt>,
+    max_batch_size: Option<StrOrInt>,
+    max_in_flight_transactions: Option<StrOrInt>,
+    /// How long a partial batch waits to fill up, e.g. `500ms`. Integers are
+    /// seconds.
+    batch_flush_interval: Option<StrOrInt>,
+    /// `none` or `multicall3`
+    batch_strategy: Option<String>,
+    multicall_address: Option<String>,
+    /// `sequential`, `external` or `perTx`
+    nonce_strategy: Option<String>,
+    /// `legacy`, `eip1559` or `auto`
+    tx_type: Option<String>,
+    /// Milliseconds or a duration to wait between polls once caught up
+    poll_interval: Option<StrOrInt>,
+    /// How long to wait for a transaction to be confirmed before it is
+    /// resubmitted with a higher gas price, e.g. `2m`. Integers are seconds.
+    confirmation_timeout: Option<StrOrInt>,
+    gas_oracle: Option<DeprecatedRawGasOracleConf>,
+    max_gas_limit: Option<StrOrInt>,
+    gas_limit_multiplier: Option<f64>,
+    native_token: Option<DeprecatedRawNativeToken>,
     addresses: Option<DeprecatedRawCoreContractAddresses>,
     #[serde(flatten, default)]
     connection: Option<DeprecatedRawChainConnectionConf>,
     // TODO: if people actually use the metrics conf we should also add a raw form.
     #[serde(default)]
     metrics_conf: Option<PrometheusMiddlewareConf>,
+    /// Extra labels for the metrics of this chain, e.g. `region`
+    metrics_labels: Option<HashMap<String, String>>,
     #[serde(default)]
     index: Option<DeprecatedRawIndexSettings>,
 }
 
-impl FromRawConf<DeprecatedRawChainConf> for ChainConf {
+impl DeprecatedRawChainConf {
+    /// List the deprecated keys set on this chain and what they should be
+    /// replaced with.
+    fn deprecation_warnings(&self, cwp: &ConfigPath) -> Vec<DeprecationWarning> {
+        [
+            (self.domain.is_some(), "domain", cwp + "domain_id"),
+            (
+                self.finality_blocks.is_some(),
+                "finality_blocks",
+                cwp + "blocks" + "confirmations",
+            ),
+            (
+                self.confirmations.is_some(),
+                "confirmations",
+                cwp + "blocks" + "confirmations",
+            ),
+            (
+                self.reorg_period.is_some(),
+                "reorg_period",
+                cwp + "blocks" + "reorg_period",
+            ),
+            (
+                self.block_time.is_some(),
+                "block_time",
+                cwp + "blocks" + "estimate_block_time",
+            ),
+        ]
+        .into_iter()
+        .filter(|(is_set, ..)| *is_set)
+        .map(|(_, key, suggested_new_key)| DeprecationWarning {
+            path: cwp + key,
+            suggested_new_key,
+        })
+        .collect()
+    }
+}
+
+/// The filter is the key of the chain in `chains`, if known, which `name` must
+/// match unless `allowNameKeyMismatch` is set.
+impl FromRawConf<DeprecatedRawChainConf, Option<&str>> for ChainConf {
     fn from_config_filtered(
         raw: DeprecatedRawChainConf,
         cwp: &ConfigPath,
-        _filter: (),
+        key: Option<&str>,
     ) -> ConfigResult<Self> {
         let mut err = ConfigParsingError::default();
 
+        if let (Some(name), Some(key)) = (&raw.name, key) {
+            if !raw.allow_name_key_mismatch && !name.eq_ignore_ascii_case(key) {
+                err.push(
+                    cwp + "name",
+                    eyre!(
+                        "Chain name `{name}` does not match its key `{key}`; set \
+                         `allowNameKeyMismatch` if this is intended"
+                    ),
+                );
+            }
+        }
+
         let connection = raw
             .connection
             .ok_or_else(|| eyre!("Missing `connection` configuration"))
             .take_err(&mut err, || cwp + "connection")
             .and_then(|r| r.parse_config(cwp).take_config_err(&mut err));
 
+        let protocol_override = raw.protocol_override.as_deref().and_then(|p| {
+            p.parse::<HyperlaneDomainProtocol>()
+                .map_err(|_| {
+                    eyre!(
+                        "Invalid `protocolOverride` `{p}`, expected one of ethereum, fuel, \
+                         sealevel, cosmos or starknet"
+                    )
+                })
+                .take_err(&mut err, || cwp + "protocol_override")
+        });
+
         let domain = connection.as_ref().and_then(|c: &ChainConnectionConf| {
-            let protocol = c.protocol();
+            let protocol = match protocol_override {
+                Some(protocol) => {
+                    warn!(
+                        path = %(cwp + "protocol_override"),
+                        ?protocol,
+                        connection_protocol = ?c.protocol(),
+                        "Overriding the protocol of the domain derived from its connection"
+                    );
+                    protocol
+                }
+                None => c.protocol(),
+            };
             let domain_id = raw
                 .domain
                 .ok_or_else(|| eyre!("Missing `domain` configuration"))
@@ -243,15 +775,45 @@ impl FromRawConf<DeprecatedRawChainConf> for ChainConf {
                         .context("Invalid domain id, expected integer")
                         .take_err(&mut err, || cwp + "domain")
                 });
-            let name = raw
-                .name
-                .as_deref()
-                .ok_or_else(|| eyre!("Missing domain `name` configuration"))
-                .take_err(&mut err, || cwp + "name");
+            let name = match raw.name.as_deref() {
+                Some(name) => Some(name),
+                // the canonical name of well known domains can be inferred
+                None => domain_id.and_then(|id| {
+                    let name = KnownHyperlaneDomain::try_from(id)
+                        .map(KnownHyperlaneDomain::as_str)
+                        .map_err(|_| {
+                            eyre!(
+                                "Missing domain `name` configuration, it can only be inferred for \
+                                 known domains"
+                            )
+                        })
+                        .take_err(&mut err, || cwp + "name")?;
+                    if let Some(key) = key {
+                        if !raw.allow_name_key_mismatch && !name.eq_ignore_ascii_case(key) {
+                            err.push(
+                                cwp + "name",
+                                eyre!(
+                                    "Inferred chain name `{name}` of domain {id} does not match \
+                                     its key `{key}`; set `name` or `allowNameKeyMismatch`"
+                                ),
+                            );
+                        }
+                    }
+                    Some(name)
+                }),
+            };
             HyperlaneDomain::from_config(domain_id?, name?, protocol)
                 .take_err(&mut err, || cwp.clone())
         });
 
+        if let (Some(connection), Some(addresses)) = (&connection, &raw.addresses) {
+            for (name, addr) in addresses.iter() {
+                validate_address_encoding(&normalize_address(addr), connection.protocol())
+                    .with_context(|| format!("Invalid {} address `{addr}`", name.replace('_', " ")))
+                    .take_err(&mut err, || cwp + "addresses" + name);
+            }
+        }
+
         let addresses = raw
             .addresses
             .ok_or_else(|| eyre!("Missing `addresses` configuration for core contracts"))
@@ -260,42 +822,306 @@ impl FromRawConf<DeprecatedRawChainConf> for ChainConf {
                 v.parse_config(&cwp.join("addresses"))
                     .take_config_err(&mut err)
             });
+        if let (Some(connection), Some(addresses)) = (&connection, &addresses) {
+            validate_ens_names(&addresses.ens_names, connection.protocol(), &mut err);
+        }
+
+        let chain_id = raw.chain_id.as_ref().and_then(|v| {
+            if !matches!(connection, None | Some(ChainConnectionConf::Ethereum(_))) {
+                Err(eyre!("`chainId` is only supported on ethereum chains"))
+            } else {
+                v.try_into().context("Invalid `chainId`, expected integer")
+            }
+            .take_err(&mut err, || cwp + "chain_id")
+        });
+
+        let (raw_signer, raw_role_signers) = raw
+            .signer
+            .and_then(|s| split_role_signers(s).take_err(&mut err, || cwp + "signer"))
+            .unwrap_or_default();
+        let raw_signer = raw_signer.and_then(|s| {
+            serde_json::from_value::<DeprecatedRawSignerConf>(s)
+                .take_err(&mut err, || cwp + "signer")
+        });
 
-        let signer = raw.signer.and_then(|v| -> Option<SignerConf> {
+        if let Some(signer_chain_id) = raw_signer.as_ref().and_then(|s| s.chain_id.as_ref()) {
+            let chain_id_path = || cwp + "signer" + "chain_id";
+            let signer_chain_id: Option<u64> = signer_chain_id
+                .try_into()
+                .context("Invalid signer `chainId`, expected integer")
+                .take_err(&mut err, chain_id_path);
+            if let (Some(signer_chain_id), Some(domain)) = (signer_chain_id, &domain) {
+                let expected = chain_id.unwrap_or(domain.id() as u64);
+                if signer_chain_id != expected {
+                    Err(eyre!(
+                        "Signer is restricted to chain id {signer_chain_id} but is configured for chain id {expected}"
+                    ))
+                    .take_err(&mut err, chain_id_path);
+                }
+            }
+        }
+
+        let signer = raw_signer.and_then(|v| -> Option<SignerConf> {
             v.parse_config(&cwp.join("signer"))
                 .take_config_err(&mut err)
         });
+        let mut role_signers = RoleSigners::default();
+        for (role, raw_role_signer) in raw_role_signers {
+            let role_path = cwp.join("signer").join(role.as_str());
+            let signer = serde_json::from_value::<DeprecatedRawSignerConf>(raw_role_signer)
+                .take_err(&mut err, || role_path.clone())
+                .and_then(|s| s.parse_config(&role_path).take_config_err(&mut err));
+            if let Some(signer) = signer {
+                role_signers.insert(role, signer);
+            }
+        }
+
+        if raw.finality_blocks.is_some() && raw.confirmations.is_some() {
+            warn!(
+                path = %(cwp + "confirmations"),
+                "Both `finalityBlocks` and its alias `confirmations` are set, using `finalityBlocks`"
+            );
+        }
+        let finality = match (raw.finality_blocks, raw.confirmations) {
+            (Some(v), _) => Some((v, "finality_blocks")),
+            (None, v) => v.map(|v| (v, "confirmations")),
+        }
+        .and_then(|(v, key)| {
+            parse_finality(&v)
+                .take_err(&mut err, || cwp + key)
+                .map(|finality| (finality, key))
+        });
+        if let Some((finality, key)) = finality.as_ref().filter(|_| !raw.assume_instant_finality) {
+            if let Err(e) = check_mainnet_finality(finality) {
+                if !raw.testnet {
+                    warn!(path = %(cwp + *key), "{e}");
+                }
+            }
+        }
+        let finality = if raw.assume_instant_finality {
+            Finality::Blocks(0)
+        } else {
+            finality.map(|(finality, _)| finality).unwrap_or_default()
+        };
+        let finality_blocks = match finality {
+            Finality::Blocks(blocks) => blocks,
+            Finality::Time(_) | Finality::Tag(_) => 0,
+        };
+
+        let reorg_period = raw
+            .reorg_period
+            .and_then(|v| {
+                v.try_into()
+                    .context("Invalid `reorgPeriod`, expected integer")
+                    .take_err(&mut err, || cwp + "reorg_period")
+            })
+            .unwrap_or(finality_blocks);
+
+        let block_time = raw.block_time.and_then(|v| {
+            v.try_into()
+                .context("Invalid `blockTime`, expected a duration")
+                .take_err(&mut err, || cwp + "block_time")
+        });
 
-        let finality_blocks = raw
-            .finality_blocks
+        let max_batch_size = raw
+            .max_batch_size
             .and_then(|v| {
                 v.try_into()
-                    .context("Invalid `finalityBlocks`, expected integer")
-                    .take_err(&mut err, || cwp + "finality_blocks")
+                    .context("Invalid `maxBatchSize`, expected integer")
+                    .and_then(|v: u32| {
+                        if v == 0 {
+                            bail!("Invalid `maxBatchSize`, must be greater than 0")
+                        }
+                        if v > MAX_BATCH_SIZE_LIMIT {
+                            bail!("Invalid `maxBatchSize`, must be at most {MAX_BATCH_SIZE_LIMIT}")
+                        }
+                        Ok(v)
+                    })
+                    .take_err(&mut err, || cwp + "max_batch_size")
+            })
+            .unwrap_or(DEFAULT_MAX_BATCH_SIZE);
+
+        let max_in_flight_transactions = raw
+            .max_in_flight_transactions
+            .and_then(|v| {
+                v.try_into()
+                    .context("Invalid `maxInFlightTransactions`, expected integer")
+                    .and_then(|v: u32| {
+                        if v == 0 {
+                            bail!("Invalid `maxInFlightTransactions`, must be greater than 0")
+                        }
+                        Ok(v)
+                    })
+                    .take_err(&mut err, || cwp + "max_in_flight_transactions")
+            })
+            .unwrap_or(DEFAULT_MAX_IN_FLIGHT_TRANSACTIONS);
+
+        let multicall_address = raw.multicall_address.as_deref().and_then(|v| {
+            hex_or_base58_to_h256(v)
+                .context("Invalid `multicallAddress`, expected an address")
+                .take_err(&mut err, || cwp + "multicall_address")
+        });
+        let batch_strategy = domain
+            .as_ref()
+            .and_then(|d| {
+                parse_batch_strategy(raw.batch_strategy.as_deref(), multicall_address, d)
+                    .take_err(&mut err, || cwp + "batch_strategy")
+            })
+            .unwrap_or_default();
+
+        let nonce_strategy = raw
+            .nonce_strategy
+            .and_then(|v| {
+                if !matches!(connection, None | Some(ChainConnectionConf::Ethereum(_))) {
+                    Err(eyre!(
+                        "`nonceStrategy` is only supported on ethereum chains"
+                    ))
+                } else {
+                    parse_nonce_strategy(&v)
+                }
+                .take_err(&mut err, || cwp + "nonce_strategy")
+            })
+            .unwrap_or_default();
+
+        let tx_type = raw
+            .tx_type
+            .and_then(|v| {
+                if !matches!(connection, None | Some(ChainConnectionConf::Ethereum(_))) {
+                    Err(eyre!("`txType` is only supported on ethereum chains"))
+                } else {
+                    parse_tx_type(&v)
+                }
+                .take_err(&mut err, || cwp + "tx_type")
+            })
+            .unwrap_or_default();
+
+        let poll_interval = raw
+            .poll_interval
+            .and_then(|v| parse_poll_interval(&v).take_err(&mut err, || cwp + "poll_interval"))
+            .unwrap_or(DEFAULT_POLL_INTERVAL);
+
+        let batch_flush_interval = raw
+            .batch_flush_interval
+            .and_then(|v| {
+                parse_batch_flush_interval(&v).take_err(&mut err, || cwp + "batch_flush_interval")
+            })
+            .unwrap_or(DEFAULT_BATCH_FLUSH_INTERVAL);
+
+        let confirmation_timeout = raw
+            .confirmation_timeout
+            .and_then(|v| {
+                parse_confirmation_timeout(&v, block_time)
+                    .take_err(&mut err, || cwp + "confirmation_timeout")
+            })
+            .unwrap_or(DEFAULT_CONFIRMATION_TIMEOUT);
+
+        // index only chains are never submitted to, so their gas settings are
+        // not validated
+        let (raw_gas_oracle, raw_max_gas_limit, raw_gas_limit_multiplier) = if raw.index_only {
+            (None, None, None)
+        } else {
+            (raw.gas_oracle, raw.max_gas_limit, raw.gas_limit_multiplier)
+        };
+
+        let gas_oracle = raw_gas_oracle
+            .and_then(|v| {
+                if !matches!(connection, None | Some(ChainConnectionConf::Ethereum(_))) {
+                    Err(eyre!("`gasOracle` is only supported on ethereum chains"))
+                        .take_err(&mut err, || cwp + "gas_oracle")
+                } else {
+                    v.parse_config(&cwp.join("gas_oracle"))
+                        .take_config_err(&mut err)
+                }
+            })
+            .unwrap_or_default();
+
+        let max_gas_limit = raw_max_gas_limit.and_then(|v| {
+            v.try_into()
+                .context("Invalid `maxGasLimit`, expected integer")
+                .and_then(|v: U256| {
+                    if v.is_zero() {
+                        bail!("Invalid `maxGasLimit`, must be greater than 0")
+                    }
+                    Ok(v)
+                })
+                .take_err(&mut err, || cwp + "max_gas_limit")
+        });
+        let gas_limit_multiplier = raw_gas_limit_multiplier
+            .and_then(|v| {
+                parse_gas_limit_multiplier(v).take_err(&mut err, || cwp + "gas_limit_multiplier")
+            })
+            .unwrap_or(1.0);
+
+        let native_token = raw
+            .native_token
+            .and_then(|v| {
+                v.parse_config(&cwp.join("native_token"))
+                    .take_config_err(&mut err)
             })
-            .unwrap_or(0);
+            .unwrap_or_default();
 
-        let index = raw
+        let mut index: IndexSettings = raw
             .index
             .and_then(|v| v.parse_config(&cwp.join("index")).take_config_err(&mut err))
             .unwrap_or_default();
+        index
+            .resolve_chunk_size(block_time)
+            .take_err(&mut err, || cwp + "index" + "chunk");
 
-        let metrics_conf = raw.metrics_conf.unwrap_or_default();
+        let mut metrics_conf = raw.metrics_conf.unwrap_or_default();
+        if let Some(labels) = raw.metrics_labels.and_then(|labels| {
+            parse_metrics_labels(labels).take_err(&mut err, || cwp + "metrics_labels")
+        }) {
+            metrics_conf
+                .chain
+                .get_or_insert_with(ChainInfo::default)
+                .labels
+                .extend(labels);
+        }
 
         cfg_unwrap_all!(cwp, err: [connection, domain, addresses]);
 
         err.into_result(Self {
             connection,
             domain,
+            chain_id,
+            index_only: raw.index_only,
             addresses,
             signer,
+            role_signers,
+            assume_instant_finality: raw.assume_instant_finality,
+            testnet: raw.testnet,
+            finality,
             finality_blocks,
+            reorg_period,
+            block_time,
+            max_batch_size,
+            max_in_flight_transactions,
+            batch_flush_interval,
+            batch_strategy,
+            nonce_strategy,
+            tx_type,
+            poll_interval,
+            confirmation_timeout,
+            gas_oracle,
+            max_gas_limit,
+            gas_limit_multiplier,
+            native_token,
             index,
             metrics_conf,
         })
     }
 }
 
+/// The standard Ethereum derivation path used by Ledger devices
+const DEFAULT_LEDGER_HD_PATH: &str = "m/44'/60'/0'/0/0";
+/// The default mount path of the Vault transit secrets engine
+const DEFAULT_VAULT_MOUNT_PATH: &str = "transit";
+/// The default env var holding the Vault token
+const DEFAULT_VAULT_TOKEN_ENV: &str = "VAULT_TOKEN";
+/// The default env var holding the relay service API key
+const DEFAULT_RELAY_SERVICE_API_KEY_ENV: &str = "RELAY_SERVICE_API_KEY";
+
 /// Raw signer types
 #[derive(Debug, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
@@ -303,8 +1129,36 @@ pub struct DeprecatedRawSignerConf {
     #[serde(rename = "type")]
     signer_type: Option<String>,
     key: Option<String>,
+    /// Path to a file holding the key, in place of an inline `key`.
+    key_file: Option<String>,
     id: Option<String>,
     region: Option<String>,
+    /// An IAM role for an Aws signer to assume before using KMS
+    role_arn: Option<String>,
+    external_id: Option<String>,
+    /// A named profile in the shared AWS credentials file for an Aws signer
+    profile: Option<String>,
+    project_id: Option<String>,
+    location: Option<String>,
+    key_ring: Option<String>,
+    key_name: Option<String>,
+    version: Option<StrOrInt>,
+    hd_path: Option<String>,
+    device_index: Option<StrOrInt>,
+    weight: Option<StrOrInt>,
+    /// If set, the signer may only be used on the domain with this id.
+    chain_id: Option<StrOrInt>,
+    address: Option<String>,
+    mount_path: Option<String>,
+    token_env: Option<String>,
+    command: Option<String>,
+    /// Timeout of an external signer command, in seconds or e.g. `500ms`
+    timeout: Option<StrOrInt>,
+    /// Url of a relay service to submit transactions through
+    endpoint: Option<String>,
+    api_key_env: Option<String>,
+    /// The signer a relay service signer signs its payloads with
+    signer: Option<Box<DeprecatedRawSignerConf>>,
 }
 
 /// Raw checkpoint syncer types
@@ -315,6 +1169,12 @@ pub enum DeprecatedRawCheckpointSyncerConf {
     LocalStorage {
         /// Path
         path: Option<String>,
+        /// Create the directory if it does not exist, defaults to true
+        #[serde(rename = "createIfMissing")]
+        create_if_missing: Option<bool>,
+        /// Number of the most recent checkpoints to keep, 0 or absent keeps
+        /// everything
+        retain: Option<StrOrInt>,
     },
     /// A checkpoint syncer on S3
     S3 {
@@ -324,58 +1184,448 @@ pub enum DeprecatedRawCheckpointSyncerConf {
         region: Option<String>,
         /// Folder name inside bucket - defaults to the root of the bucket
         folder: Option<String>,
+        /// Url of an S3 compatible store to use instead of AWS
+        endpoint: Option<String>,
+        /// Address the bucket in the request path
+        #[serde(default, rename = "forcePathStyle")]
+        force_path_style: bool,
+        /// KMS key to encrypt written checkpoints with
+        #[serde(rename = "sseKmsKeyId")]
+        sse_kms_key_id: Option<String>,
+        /// A named profile in the shared AWS credentials file
+        profile: Option<String>,
+    },
+    /// A checkpoint syncer on Google Cloud Storage
+    Gcs {
+        /// Bucket name
+        bucket: Option<String>,
+        /// Folder name inside bucket - defaults to the root of the bucket
+        folder: Option<String>,
+        /// Path to a service account JSON key file, or a `base64:` prefixed
+        /// inline JSON key
+        #[serde(rename = "serviceAccountKey")]
+        service_account_key: Option<String>,
+        /// Inline base64 encoded service account JSON key
+        #[serde(rename = "serviceAccountKeyBase64")]
+        service_account_key_base64: Option<String>,
+    },
+    /// A checkpoint syncer on IPFS
+    Ipfs {
+        /// Url of the IPFS HTTP API
+        #[serde(rename = "apiUrl")]
+        api_url: Option<String>,
+        /// Url of the gateway used for reads - defaults to the API
+        #[serde(rename = "gatewayUrl")]
+        gateway_url: Option<String>,
+        /// Bearer token for the pinning service API
+        #[serde(rename = "pinServiceToken")]
+        pin_service_token: Option<String>,
+        /// IPNS name the checkpoint index is published under
+        #[serde(rename = "ipnsName")]
+        ipns_name: Option<String>,
     },
     /// Unknown checkpoint syncer type was specified
     #[serde(other)]
     Unknown,
 }
 
-impl FromRawConf<DeprecatedRawSignerConf> for SignerConf {
-    fn from_config_filtered(
-        raw: DeprecatedRawSignerConf,
-        cwp: &ConfigPath,
-        _filter: (),
-    ) -> ConfigResult<Self> {
-        let key_path = || cwp + "key";
-        let region_path = || cwp + "region";
+/// Parse a listen address setting such as `metrics`, which is either a port to
+/// bind on all interfaces or a full socket address.
+fn parse_listen_addr(key: &str, value: &StrOrInt) -> eyre::Result<SocketAddr> {
+    if let Ok(port) = u32::try_from(value) {
+        if port > u16::MAX as u32 {
+            bail!("Invalid `{key}` port {port}, must be at most 65535")
+        }
+        return Ok(([0, 0, 0, 0], port as u16).into());
+    }
+    match value {
+        StrOrInt::Str(addr) => addr.parse().with_context(|| {
+            format!("Invalid `{key}` address `{addr}`, expected a port or `<ip>:<port>` such as `127.0.0.1:9090`")
+        }),
+        StrOrInt::Int(port) => bail!("Invalid `{key}` port {port}"),
+    }
+}
 
-        match raw.signer_type.as_deref() {
-            Some("hexKey") => Ok(Self::HexKey {
-                key: raw
-                    .key
-                    .ok_or_else(|| eyre!("Missing `key` for HexKey signer"))
-                    .into_config_result(key_path)?
-                    .parse()
+/// Split the signers of single roles, e.g. `{ "relay": {...} }`, from the
+/// default signer of a chain. Signer fields are never objects, so every field
+/// which is one is taken to be a role. There is no default signer if only
+/// roles are given.
+#[allow(clippy::type_complexity)]
+fn split_role_signers(
+    signer: serde_json::Value,
+) -> eyre::Result<(
+    Option<serde_json::Value>,
+    Vec<(SignerRole, serde_json::Value)>,
+)> {
+    let serde_json::Value::Object(fields) = signer else {
+        return Ok((Some(signer), Vec::new()));
+    };
+    let mut default = serde_json::Map::new();
+    let mut roles: Vec<(SignerRole, serde_json::Value)> = Vec::new();
+    for (key, value) in fields {
+        if !value.is_object() {
+            default.insert(key, value);
+            continue;
+        }
+        let role = parse_signer_role(&key)?;
+        if roles.iter().any(|(r, _)| *r == role) {
+            bail!(
+                "The `{}` signer role is configured more than once",
+                role.as_str()
+            );
+        }
+        roles.push((role, value));
+    }
+    let default = (!default.is_empty() || roles.is_empty()).then_some(default.into());
+    Ok((default, roles))
+}
+
+/// Parse a 32 byte private key given either as hex (with or without a `0x`
+/// prefix) or as base58.
+fn parse_signer_key(key: &str) -> eyre::Result<H256> {
+    let unprefixed_hex = key.len() == 64 && key.bytes().all(|b| b.is_ascii_hexdigit());
+    let parsed = if unprefixed_hex {
+        key.parse::<H256>().map_err(Into::into)
+    } else if key.starts_with("0x") && key.len() != 66 {
+        Err(eyre!("Invalid length of hex string"))
+    } else {
+        hex_or_base58_to_h256(key)
+    };
+    parsed.context(
+        "Invalid `key`, expected a 32 byte private key as a hex string (e.g. `0x1234...`) \
+         or a base58 encoded string",
+    )
+}
+
+/// Substitute `${VAR}` tokens in a string with values from `lookup`. `$${`
+/// yields a literal `${`.
+fn interpolate_env_vars(s: &str, lookup: impl Fn(&str) -> Option<String>) -> eyre::Result<String> {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = rest.find('$') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if let Some(escaped) = rest.strip_prefix("$${") {
+            out.push_str("${");
+            rest = escaped;
+        } else if let Some(token) = rest.strip_prefix("${") {
+            let Some(end) = token.find('}') else {
+                bail!("Unterminated `${{` in `{s}`");
+            };
+            let name = &token[..end];
+            let value =
+                lookup(name).ok_or_else(|| eyre!("Environment variable `{name}` is not set"))?;
+            out.push_str(&value);
+            rest = &token[end + 1..];
+        } else {
+            out.push('$');
+            rest = &rest[1..];
+        }
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Expand `${VAR}` tokens in an optional string field from the process
+/// environment, recording an error at the field's path if it fails.
+fn interpolate_field(
+    field: &mut Option<String>,
+    err: &mut ConfigParsingError,
+    path: impl FnOnce() -> ConfigPath,
+) {
+    if let Some(value) = field.as_deref() {
+        *field = interpolate_env_vars(value, |name| std::env::var(name).ok()).take_err(err, path);
+    }
+}
+
+impl DeprecatedRawSignerConf {
+    /// Expand environment variables referenced in the string fields.
+    fn interpolate_env(mut self, cwp: &ConfigPath) -> ConfigResult<Self> {
+        let mut err = ConfigParsingError::default();
+        interpolate_field(&mut self.key, &mut err, || cwp + "key");
+        interpolate_field(&mut self.key_file, &mut err, || cwp + "key_file");
+        interpolate_field(&mut self.id, &mut err, || cwp + "id");
+        interpolate_field(&mut self.region, &mut err, || cwp + "region");
+        interpolate_field(&mut self.role_arn, &mut err, || cwp + "role_arn");
+        interpolate_field(&mut self.external_id, &mut err, || cwp + "external_id");
+        interpolate_field(&mut self.profile, &mut err, || cwp + "profile");
+        interpolate_field(&mut self.project_id, &mut err, || cwp + "project_id");
+        interpolate_field(&mut self.location, &mut err, || cwp + "location");
+        interpolate_field(&mut self.key_ring, &mut err, || cwp + "key_ring");
+        interpolate_field(&mut self.key_name, &mut err, || cwp + "key_name");
+        interpolate_field(&mut self.hd_path, &mut err, || cwp + "hd_path");
+        interpolate_field(&mut self.address, &mut err, || cwp + "address");
+        interpolate_field(&mut self.mount_path, &mut err, || cwp + "mount_path");
+        interpolate_field(&mut self.token_env, &mut err, || cwp + "token_env");
+        interpolate_field(&mut self.command, &mut err, || cwp + "command");
+        interpolate_field(&mut self.endpoint, &mut err, || cwp + "endpoint");
+        interpolate_field(&mut self.api_key_env, &mut err, || cwp + "api_key_env");
+        err.into_result(self)
+    }
+}
+
+impl DeprecatedRawCheckpointSyncerConf {
+    /// Expand environment variables referenced in the string fields.
+    fn interpolate_env(mut self, cwp: &ConfigPath) -> ConfigResult<Self> {
+        let mut err = ConfigParsingError::default();
+        match &mut self {
+            Self::LocalStorage { path, .. } => {
+                interpolate_field(path, &mut err, || cwp + "path");
+            }
+            Self::S3 {
+                bucket,
+                region,
+                folder,
+                endpoint,
+                sse_kms_key_id,
+                profile,
+                ..
+            } => {
+                interpolate_field(bucket, &mut err, || cwp + "bucket");
+                interpolate_field(region, &mut err, || cwp + "region");
+                interpolate_field(folder, &mut err, || cwp + "folder");
+                interpolate_field(endpoint, &mut err, || cwp + "endpoint");
+                interpolate_field(sse_kms_key_id, &mut err, || cwp + "sse_kms_key_id");
+                interpolate_field(profile, &mut err, || cwp + "profile");
+            }
+            Self::Gcs {
+                bucket,
+                folder,
+                service_account_key,
+                service_account_key_base64,
+            } => {
+                interpolate_field(bucket, &mut err, || cwp + "bucket");
+                interpolate_field(folder, &mut err, || cwp + "folder");
+                interpolate_field(service_account_key, &mut err, || {
+                    cwp + "service_account_key"
+                });
+                interpolate_field(service_account_key_base64, &mut err, || {
+                    cwp + "service_account_key_base64"
+                });
+            }
+            Self::Ipfs {
+                api_url,
+                gateway_url,
+                pin_service_token,
+                ipns_name,
+            } => {
+                interpolate_field(api_url, &mut err, || cwp + "api_url");
+                interpolate_field(gateway_url, &mut err, || cwp + "gateway_url");
+                interpolate_field(pin_service_token, &mut err, || cwp + "pin_service_token");
+                interpolate_field(ipns_name, &mut err, || cwp + "ipns_name");
+            }
+            Self::Unknown => {}
+        }
+        err.into_result(self)
+    }
+}
+
+impl FromRawConf<DeprecatedRawSignerConf> for SignerConf {
+    fn from_config_filtered(
+        raw: DeprecatedRawSignerConf,
+        cwp: &ConfigPath,
+        _filter: (),
+    ) -> ConfigResult<Self> {
+        let mut raw = raw.interpolate_env(cwp)?;
+        let key_field = if raw.key_file.is_some() {
+            "key_file"
+        } else {
+            "key"
+        };
+        if let Some(key_file) = raw.key_file.take() {
+            if raw.key.is_some() {
+                return Err(eyre!("Only one of `key` or `keyFile` may be set"))
+                    .into_config_result(|| cwp + "key_file");
+            }
+            let key = std::fs::read_to_string(&key_file)
+                .with_context(|| format!("Failed to read signer key file `{key_file}`"))
+                .into_config_result(|| cwp + "key_file")?;
+            raw.key = Some(key.trim().to_owned());
+        }
+        let key_path = || cwp + key_field;
+        let weight = raw.weight;
+        let parse_weight = || -> ConfigResult<u32> {
+            weight
+                .as_ref()
+                .map(|v| {
+                    v.try_into()
+                        .context("Invalid `weight` for HexKey signer, expected integer")
+                        .into_config_result(|| cwp + "weight")
+                })
+                .transpose()
+                .map(|w| w.unwrap_or(1))
+        };
+        let parse_profile = || -> ConfigResult<Option<String>> {
+            raw.profile
+                .as_deref()
+                .map(parse_aws_profile)
+                .transpose()
+                .into_config_result(|| cwp + "profile")
+        };
+        let parse_aws_role = || -> ConfigResult<(Option<String>, Option<String>)> {
+            let role_arn = raw
+                .role_arn
+                .as_deref()
+                .map(parse_role_arn)
+                .transpose()
+                .into_config_result(|| cwp + "role_arn")?;
+            if role_arn.is_none() && raw.external_id.is_some() {
+                return Err(eyre!("`externalId` requires `roleArn` to be set"))
+                    .into_config_result(|| cwp + "external_id");
+            }
+            Ok((role_arn, raw.external_id.clone()))
+        };
+
+        match raw.signer_type.as_deref() {
+            Some("hexKey") => Ok(Self::HexKey {
+                key: raw
+                    .key
+                    .ok_or_else(|| eyre!("Missing `key` for HexKey signer"))
+                    .and_then(|k| parse_signer_key(&k))
                     .into_config_result(key_path)?,
+                weight: parse_weight()?,
             }),
-            Some("aws") => Ok(Self::Aws {
-                id: raw
-                    .id
-                    .ok_or_else(|| eyre!("Missing `id` for Aws signer"))
-                    .into_config_result(|| cwp + "id")?,
-                region: raw
-                    .region
-                    .ok_or_else(|| eyre!("Missing `region` for Aws signer"))
-                    .into_config_result(region_path)?
-                    .parse()
-                    .into_config_result(region_path)?,
+            Some("aws") => {
+                let (role_arn, external_id) = parse_aws_role()?;
+                Ok(Self::Aws {
+                    id: raw
+                        .id
+                        .ok_or_else(|| eyre!("Missing `id` for Aws signer"))
+                        .into_config_result(|| cwp + "id")?,
+                    region: parse_aws_region(raw.region.as_deref(), |var| std::env::var(var).ok())
+                        .into_config_result(|| cwp + "region")?,
+                    role_arn,
+                    external_id,
+                    profile: parse_profile()?,
+                })
+            }
+            Some("gcpKms") => {
+                let mut err = ConfigParsingError::default();
+                let project_id = raw
+                    .project_id
+                    .ok_or_else(|| eyre!("Missing `projectId` for GcpKms signer"))
+                    .take_err(&mut err, || cwp + "project_id");
+                let key_ring = raw
+                    .key_ring
+                    .ok_or_else(|| eyre!("Missing `keyRing` for GcpKms signer"))
+                    .take_err(&mut err, || cwp + "key_ring");
+                let key_name = raw
+                    .key_name
+                    .ok_or_else(|| eyre!("Missing `keyName` for GcpKms signer"))
+                    .take_err(&mut err, || cwp + "key_name");
+                let version = raw
+                    .version
+                    .and_then(|v| {
+                        v.try_into()
+                            .context("Invalid `version` for GcpKms signer, expected integer")
+                            .take_err(&mut err, || cwp + "version")
+                    })
+                    .unwrap_or(1);
+                cfg_unwrap_all!(cwp, err: [project_id, key_ring, key_name]);
+                err.into_result(Self::GcpKms {
+                    project_id,
+                    location: raw.location.unwrap_or_else(|| "global".into()),
+                    key_ring,
+                    key_name,
+                    version,
+                })
+            }
+            Some("ledger") => Ok(Self::Ledger {
+                hd_path: raw.hd_path.unwrap_or_else(|| DEFAULT_LEDGER_HD_PATH.into()),
+                device_index: raw
+                    .device_index
+                    .map(|v| {
+                        v.try_into()
+                            .context("Invalid `deviceIndex` for Ledger signer, expected integer")
+                            .into_config_result(|| cwp + "device_index")
+                    })
+                    .transpose()?
+                    .unwrap_or(0),
+            }),
+            Some("vault") => {
+                let mut err = ConfigParsingError::default();
+                let address = raw
+                    .address
+                    .ok_or_else(|| eyre!("Missing `address` for Vault signer"))
+                    .and_then(|a| {
+                        a.parse()
+                            .context("Invalid `address` for Vault signer, expected a url")
+                    })
+                    .take_err(&mut err, || cwp + "address");
+                let key_name = raw
+                    .key_name
+                    .ok_or_else(|| eyre!("Missing `keyName` for Vault signer"))
+                    .take_err(&mut err, || cwp + "key_name");
+                cfg_unwrap_all!(cwp, err: [address, key_name]);
+                err.into_result(Self::Vault {
+                    address,
+                    mount_path: raw
+                        .mount_path
+                        .unwrap_or_else(|| DEFAULT_VAULT_MOUNT_PATH.into()),
+                    key_name,
+                    token_env: raw
+                        .token_env
+                        .unwrap_or_else(|| DEFAULT_VAULT_TOKEN_ENV.into()),
+                })
+            }
+            Some("external") => Ok(Self::External {
+                command: raw
+                    .command
+                    .ok_or_else(|| eyre!("Missing `command` for External signer"))
+                    .into_config_result(|| cwp + "command")?,
+                timeout: raw
+                    .timeout
+                    .map(|v| {
+                        parse_external_signer_timeout(&v).into_config_result(|| cwp + "timeout")
+                    })
+                    .transpose()?
+                    .unwrap_or(DEFAULT_EXTERNAL_SIGNER_TIMEOUT),
             }),
+            Some("relayService") => {
+                let mut err = ConfigParsingError::default();
+                let endpoint = raw
+                    .endpoint
+                    .ok_or_else(|| eyre!("Missing `endpoint` for RelayService signer"))
+                    .and_then(|e| parse_relay_service_endpoint(&e))
+                    .take_err(&mut err, || cwp + "endpoint");
+                let signer = raw
+                    .signer
+                    .ok_or_else(|| eyre!("Missing `signer` for RelayService signer"))
+                    .take_err(&mut err, || cwp + "signer")
+                    .and_then(|s| {
+                        SignerConf::from_config(*s, &(cwp + "signer")).take_config_err(&mut err)
+                    })
+                    .and_then(|s| {
+                        check_relay_service_signer(s).take_err(&mut err, || cwp + "signer")
+                    });
+                cfg_unwrap_all!(cwp, err: [endpoint, signer]);
+                err.into_result(Self::RelayService {
+                    endpoint,
+                    api_key_env: raw
+                        .api_key_env
+                        .unwrap_or_else(|| DEFAULT_RELAY_SERVICE_API_KEY_ENV.into()),
+                    signer: Box::new(signer),
+                })
+            }
             Some(t) => Err(eyre!("Unknown signer type `{t}`")).into_config_result(|| cwp + "type"),
             None if raw.key.is_some() => Ok(Self::HexKey {
-                key: raw.key.unwrap().parse().into_config_result(key_path)?,
-            }),
-            None if raw.id.is_some() | raw.region.is_some() => Ok(Self::Aws {
-                id: raw
-                    .id
-                    .ok_or_else(|| eyre!("Missing `id` for Aws signer"))
-                    .into_config_result(|| cwp + "id")?,
-                region: raw
-                    .region
-                    .ok_or_else(|| eyre!("Missing `region` for Aws signer"))
-                    .into_config_result(region_path)?
-                    .parse()
-                    .into_config_result(region_path)?,
+                key: parse_signer_key(&raw.key.unwrap()).into_config_result(key_path)?,
+                weight: parse_weight()?,
             }),
+            None if raw.id.is_some() | raw.region.is_some() => {
+                let (role_arn, external_id) = parse_aws_role()?;
+                Ok(Self::Aws {
+                    id: raw
+                        .id
+                        .ok_or_else(|| eyre!("Missing `id` for Aws signer"))
+                        .into_config_result(|| cwp + "id")?,
+                    region: parse_aws_region(raw.region.as_deref(), |var| std::env::var(var).ok())
+                        .into_config_result(|| cwp + "region")?,
+                    role_arn,
+                    external_id,
+                    profile: parse_profile()?,
+                })
+            }
             None => Ok(Self::Node),
         }
     }
@@ -387,14 +1637,24 @@ impl FromRawConf<DeprecatedRawCheckpointSyncerConf> for CheckpointSyncerConf {
         cwp: &ConfigPath,
         _filter: (),
     ) -> ConfigResult<Self> {
-        match raw {
-            DeprecatedRawCheckpointSyncerConf::LocalStorage { path } => {
+        match raw.interpolate_env(cwp)? {
+            DeprecatedRawCheckpointSyncerConf::LocalStorage {
+                path,
+                create_if_missing,
+                retain,
+            } => {
                 let path: PathBuf = path
                     .ok_or_else(|| eyre!("Missing `path` for LocalStorage checkpoint syncer"))
                     .into_config_result(|| cwp + "path")?
                     .parse()
                     .into_config_result(|| cwp + "path")?;
-                if !path.exists() {
+                let create_if_missing = create_if_missing.unwrap_or(true);
+                if !path.exists() && !create_if_missing {
+                    Err(eyre!(
+                        "LocalStorage checkpoint syncer path {path:?} does not exist and `createIfMissing` is false"
+                    ))
+                    .into_config_result(|| cwp + "path")?;
+                } else if !path.exists() {
                     std::fs::create_dir_all(&path)
                         .with_context(|| {
                             format!(
@@ -409,23 +1669,130 @@ impl FromRawConf<DeprecatedRawCheckpointSyncerConf> for CheckpointSyncerConf {
                     ))
                     .into_config_result(|| cwp + "path")?;
                 }
-                Ok(Self::LocalStorage { path })
+                let retain = retain
+                    .map(|r| {
+                        u32::try_from(&r)
+                            .context("Invalid `retain`, expected a number of checkpoints")
+                    })
+                    .transpose()
+                    .into_config_result(|| cwp + "retain")?
+                    .filter(|&r| r > 0);
+                Ok(Self::LocalStorage {
+                    path,
+                    create_if_missing,
+                    retain,
+                })
             }
             DeprecatedRawCheckpointSyncerConf::S3 {
                 bucket,
                 folder,
                 region,
-            } => Ok(Self::S3 {
-                bucket: bucket
-                    .ok_or_else(|| eyre!("Missing `bucket` for S3 checkpoint syncer"))
-                    .into_config_result(|| cwp + "bucket")?,
+                endpoint,
+                force_path_style,
+                sse_kms_key_id,
+                profile,
+            } => {
+                let endpoint = endpoint
+                    .map(|e| CheckpointSyncerConf::parse_s3_endpoint(&e))
+                    .transpose()
+                    .into_config_result(|| cwp + "endpoint")?;
+                let sse_kms_key_id = sse_kms_key_id
+                    .map(|k| CheckpointSyncerConf::parse_s3_sse_kms_key_id(&k))
+                    .transpose()
+                    .into_config_result(|| cwp + "sse_kms_key_id")?;
+                let profile = profile
+                    .map(|p| parse_aws_profile(&p))
+                    .transpose()
+                    .into_config_result(|| cwp + "profile")?;
+                Ok(Self::S3 {
+                    bucket: bucket
+                        .ok_or_else(|| eyre!("Missing `bucket` for S3 checkpoint syncer"))
+                        .into_config_result(|| cwp + "bucket")?,
+                    folder,
+                    region: CheckpointSyncerConf::s3_region(region.as_deref(), endpoint.as_ref())
+                        .into_config_result(|| cwp + "region")?,
+                    endpoint,
+                    force_path_style,
+                    sse_kms_key_id,
+                    profile,
+                })
+            }
+            DeprecatedRawCheckpointSyncerConf::Gcs {
+                bucket,
                 folder,
-                region: region
-                    .ok_or_else(|| eyre!("Missing `region` for S3 checkpoint syncer"))
-                    .into_config_result(|| cwp + "region")?
-                    .parse()
-                    .into_config_result(|| cwp + "region")?,
-            }),
+                service_account_key,
+                service_account_key_base64,
+            } => {
+                let mut err = ConfigParsingError::default();
+                let bucket = bucket
+                    .ok_or_else(|| eyre!("Missing `bucket` for GCS checkpoint syncer"))
+                    .take_err(&mut err, || cwp + "bucket");
+                let key = service_account_key.and_then(|key| {
+                    CheckpointSyncerConf::parse_gcs_service_account_key(&key)
+                        .take_err(&mut err, || cwp + "service_account_key")
+                });
+                let key_base64 = service_account_key_base64.and_then(|encoded| {
+                    CheckpointSyncerConf::decode_gcs_service_account_key(&encoded)
+                        .take_err(&mut err, || cwp + "service_account_key_base64")
+                });
+                let service_account_key = match (key, key_base64) {
+                    (Some(_), Some(_)) => {
+                        err.push(
+                            cwp + "service_account_key_base64",
+                            eyre!(
+                                "Only one of `serviceAccountKey` and `serviceAccountKeyBase64` \
+                                 may be set"
+                            ),
+                        );
+                        None
+                    }
+                    (key, key_base64) => key.or(key_base64),
+                };
+                cfg_unwrap_all!(cwp, err: [bucket]);
+                err.into_result(Self::Gcs {
+                    bucket,
+                    folder,
+                    service_account_key,
+                })
+            }
+            DeprecatedRawCheckpointSyncerConf::Ipfs {
+                api_url,
+                gateway_url,
+                pin_service_token,
+                ipns_name,
+            } => {
+                let mut err = ConfigParsingError::default();
+                let api_url = api_url
+                    .ok_or_else(|| eyre!("Missing `apiUrl` for IPFS checkpoint syncer"))
+                    .take_err(&mut err, || cwp + "api_url")
+                    .and_then(|url| {
+                        url.parse()
+                            .context("Invalid `apiUrl` for IPFS checkpoint syncer")
+                            .take_err(&mut err, || cwp + "api_url")
+                    });
+                let gateway_url = gateway_url.and_then(|url| {
+                    url.parse()
+                        .context("Invalid `gatewayUrl` for IPFS checkpoint syncer")
+                        .take_err(&mut err, || cwp + "gateway_url")
+                });
+                let ipns_name = ipns_name.and_then(|n| {
+                    CheckpointSyncerConf::parse_ipns_name(&n)
+                        .take_err(&mut err, || cwp + "ipns_name")
+                });
+                if ipns_name.is_some() && gateway_url.is_none() {
+                    err.push(
+                        cwp + "gateway_url",
+                        eyre!("Missing public `gatewayUrl` to resolve the `ipnsName` through"),
+                    );
+                }
+                cfg_unwrap_all!(cwp, err: [api_url]);
+                err.into_result(Self::Ipfs {
+                    api_url,
+                    gateway_url,
+                    pin_service_token,
+                    ipns_name,
+                })
+            }
             DeprecatedRawCheckpointSyncerConf::Unknown => {
                 Err(eyre!("Missing `type` for checkpoint syncer"))
                     .into_config_result(|| cwp + "type")
@@ -433,3 +1800,2151 @@ impl FromRawConf<DeprecatedRawCheckpointSyncerConf> for CheckpointSyncerConf {
         }
     }
 }
+
+/// Either a single checkpoint syncer or a list of syncers which are all
+/// written to
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum DeprecatedRawCheckpointSyncers {
+    /// A single checkpoint syncer
+    Single(DeprecatedRawCheckpointSyncerConf),
+    /// Checkpoint syncers in the order they are read from
+    Mirrored(Vec<DeprecatedRawCheckpointSyncerConf>),
+}
+
+impl FromRawConf<DeprecatedRawCheckpointSyncers> for CheckpointSyncerConf {
+    fn from_config_filtered(
+        raw: DeprecatedRawCheckpointSyncers,
+        cwp: &ConfigPath,
+        _filter: (),
+    ) -> ConfigResult<Self> {
+        match raw {
+            DeprecatedRawCheckpointSyncers::Single(raw) => raw.parse_config::<Self>(cwp),
+            DeprecatedRawCheckpointSyncers::Mirrored(raw) => {
+                let mut err = ConfigParsingError::default();
+                if raw.is_empty() {
+                    err.push(
+                        cwp.clone(),
+                        eyre!("Expected at least one checkpoint syncer"),
+                    );
+                }
+                let syncers = raw
+                    .into_iter()
+                    .enumerate()
+                    .filter_map(|(i, raw)| {
+                        raw.parse_config::<Self>(&cwp.join(i.to_string()))
+                            .take_config_err(&mut err)
+                    })
+                    .collect();
+                err.into_result(Self::Mirrored(syncers))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::settings::{test_utils::test_chain, BatchStrategy};
+
+    fn parse_chain(overrides: serde_json::Value) -> ConfigResult<ChainConf> {
+        serde_json::from_value::<DeprecatedRawChainConf>(test_chain(overrides))
+            .unwrap()
+            .parse_config(&ConfigPath::default())
+    }
+
+    #[test]
+    fn parses_ledger_signer() {
+        let raw: DeprecatedRawSignerConf = serde_json::from_value(json!({
+            "type": "ledger",
+            "hdPath": "m/44'/60'/1'/0/0",
+            "deviceIndex": "1",
+        }))
+        .unwrap();
+        let SignerConf::Ledger { hd_path, device_index } =
+            raw.parse_config(&ConfigPath::default()).unwrap() else {
+            panic!("Expected a ledger signer");
+        };
+        assert_eq!(hd_path, "m/44'/60'/1'/0/0");
+        assert_eq!(device_index, 1);
+
+        let raw: DeprecatedRawSignerConf =
+            serde_json::from_value(json!({ "type": "ledger" })).unwrap();
+        let SignerConf::Ledger { hd_path, device_index } =
+            raw.parse_config(&ConfigPath::default()).unwrap() else {
+            panic!("Expected a ledger signer");
+        };
+        assert_eq!(hd_path, DEFAULT_LEDGER_HD_PATH);
+        assert_eq!(device_index, 0);
+    }
+
+    #[test]
+    fn parses_index_chunk_units() {
+        let parse = |chunk: serde_json::Value| -> IndexSettings {
+            serde_json::from_value::<DeprecatedRawIndexSettings>(json!({ "chunk": chunk }))
+                .unwrap()
+                .parse_config(&ConfigPath::default())
+                .unwrap()
+        };
+
+        let index = parse(json!(1000));
+        assert_eq!(index.chunk_size, 1000);
+        assert_eq!(index.chunk_unit, IndexChunkUnit::Blocks);
+
+        let index = parse(json!("50000blocks"));
+        assert_eq!(index.chunk_size, 50000);
+        assert_eq!(index.chunk_unit, IndexChunkUnit::Blocks);
+
+        let mut index = parse(json!("2min"));
+        assert_eq!(
+            index.chunk_unit,
+            IndexChunkUnit::Time(Duration::from_secs(120))
+        );
+        assert!(index.resolve_chunk_size(None).is_err());
+        index
+            .resolve_chunk_size(Some(Duration::from_millis(400)))
+            .unwrap();
+        assert_eq!(index.chunk_size, 300);
+
+        let index: IndexSettings = serde_json::from_value::<DeprecatedRawIndexSettings>(json!({}))
+            .unwrap()
+            .parse_config(&ConfigPath::default())
+            .unwrap();
+        assert_eq!(index.chunk_size, 1999);
+    }
+
+    #[test]
+    fn rejects_hex_address_for_sealevel() {
+        let raw: DeprecatedRawChainConf = serde_json::from_value(json!({
+            "name": "sealeveltest",
+            "domain": 13375,
+            "protocol": "sealevel",
+            "connection": { "url": "http://localhost:8899" },
+            "addresses": {
+                "mailbox": "0x1111111111111111111111111111111111111111",
+                "interchainGasPaymaster": "DrFtxirPPsfdY4HLiHrdL6Nk4G28NoW6hMVDnxPYSbGR",
+                "validatorAnnounce": "DH43ae1LwemXAboWwSh8zc9pG8j72gKUEXNi57w8fEnn",
+            },
+        }))
+        .unwrap();
+        let err = ChainConf::from_config(raw, &ConfigPath::default())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("config_path: `addresses.mailbox`"));
+        assert!(!err.contains("config_path: `addresses.interchainGasPaymaster`"));
+    }
+
+    #[test]
+    fn lists_deprecated_keys() {
+        let raw: DeprecatedRawSettings = serde_json::from_value(json!({
+            "metrics": 9091,
+            "chains": {
+                "test1": { "finalityBlocks": 2 },
+                "test2": { "reorgPeriod": 3 },
+            },
+        }))
+        .unwrap();
+        let root = ConfigPath::default();
+        let filter = HashSet::from(["test1"]);
+        let warnings = raw.deprecation_warnings(&root, Some(&filter));
+        assert_eq!(
+            warnings,
+            vec![
+                DeprecationWarning {
+                    path: &root + "metrics",
+                    suggested_new_key: &root + "metrics_port",
+                },
+                DeprecationWarning {
+                    path: &root + "chains" + "test1" + "finality_blocks",
+                    suggested_new_key: &root + "chains" + "test1" + "blocks" + "confirmations",
+                },
+            ]
+        );
+        assert_eq!(
+            warnings[1].suggested_new_key.to_string(),
+            "chains.test1.blocks.confirmations"
+        );
+    }
+
+    #[test]
+    fn interpolates_env_vars() {
+        let lookup = |name: &str| (name == "VALIDATOR_KEY").then(|| "0xabc".to_owned());
+        assert_eq!(
+            interpolate_env_vars("${VALIDATOR_KEY}", lookup).unwrap(),
+            "0xabc"
+        );
+        assert_eq!(
+            interpolate_env_vars("a/${VALIDATOR_KEY}/$b", lookup).unwrap(),
+            "a/0xabc/$b"
+        );
+        assert_eq!(
+            interpolate_env_vars("$${VALIDATOR_KEY}", lookup).unwrap(),
+            "${VALIDATOR_KEY}"
+        );
+        assert!(interpolate_env_vars("${MISSING}", lookup).is_err());
+        assert!(interpolate_env_vars("${VALIDATOR_KEY", lookup).is_err());
+
+        let raw: DeprecatedRawSignerConf = serde_json::from_value(json!({
+            "type": "hexKey",
+            "key": "${HYP_TEST_DEFINITELY_UNSET_VAR}",
+        }))
+        .unwrap();
+        let err = SignerConf::from_config(raw, &ConfigPath::default())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("config_path: `key`"));
+    }
+
+    #[test]
+    fn parses_hex_key_signer_weight() {
+        let key = "0x0000000000000000000000000000000000000000000000000000000000000001";
+        let raw: DeprecatedRawSignerConf =
+            serde_json::from_value(json!({ "type": "hexKey", "key": key, "weight": "3" })).unwrap();
+        let SignerConf::HexKey { weight, .. } = raw.parse_config(&ConfigPath::default()).unwrap() else {
+            panic!("Expected a hex key signer");
+        };
+        assert_eq!(weight, 3);
+
+        let raw: DeprecatedRawSignerConf = serde_json::from_value(json!({ "key": key })).unwrap();
+        let SignerConf::HexKey { weight, .. } = raw.parse_config(&ConfigPath::default()).unwrap() else {
+            panic!("Expected a hex key signer");
+        };
+        assert_eq!(weight, 1);
+
+        let raw: DeprecatedRawSignerConf =
+            serde_json::from_value(json!({ "type": "hexKey", "key": key, "weight": "heavy" }))
+                .unwrap();
+        let err = SignerConf::from_config(raw, &ConfigPath::default())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("config_path: `weight`"));
+    }
+
+    #[test]
+    fn parses_hex_and_base58_signer_keys() {
+        let parse = |key: &str| -> ConfigResult<SignerConf> {
+            serde_json::from_value::<DeprecatedRawSignerConf>(
+                json!({ "type": "hexKey", "key": key }),
+            )
+            .unwrap()
+            .parse_config(&ConfigPath::default())
+        };
+        let expected = H256::from_low_u64_be(1);
+
+        let hex = "0x0000000000000000000000000000000000000000000000000000000000000001";
+        let Ok(SignerConf::HexKey { key, .. }) = parse(hex) else {
+            panic!("Expected a hex key signer");
+        };
+        assert_eq!(key, expected);
+
+        let base58 = bs58::encode(expected.as_bytes()).into_string();
+        let Ok(SignerConf::HexKey { key, .. }) = parse(&base58) else {
+            panic!("Expected a hex key signer");
+        };
+        assert_eq!(key, expected);
+
+        let err = parse("not a key").unwrap_err().to_string();
+        assert!(err.contains("config_path: `key`"));
+        assert!(err.contains("hex string"));
+        assert!(err.contains("base58"));
+    }
+
+    #[test]
+    fn skips_disabled_chains() {
+        let raw: DeprecatedRawSettings = serde_json::from_value(json!({
+            "chains": { "test1": { "disabled": true } },
+        }))
+        .unwrap();
+        let settings: Settings = raw.parse_config(&ConfigPath::default()).unwrap();
+        assert!(settings.chains.is_empty());
+    }
+
+    #[test]
+    fn validates_metrics_port() {
+        let parse = |raw: serde_json::Value| -> ConfigResult<Settings> {
+            serde_json::from_value::<DeprecatedRawSettings>(raw)
+                .unwrap()
+                .parse_config(&ConfigPath::default())
+        };
+        assert_eq!(
+            parse(json!({ "metrics": 9091 })).unwrap().metrics_addr,
+            ([0, 0, 0, 0], 9091).into()
+        );
+        assert_eq!(parse(json!({})).unwrap().metrics_addr.port(), 9090);
+        assert_eq!(
+            parse(json!({ "metrics": "127.0.0.1:9092" }))
+                .unwrap()
+                .metrics_addr,
+            ([127, 0, 0, 1], 9092).into()
+        );
+
+        let err = parse(json!({ "metrics": 80 })).unwrap_err().to_string();
+        assert!(err.contains("config_path: `metrics`"));
+        assert_eq!(
+            parse(json!({ "metrics": 80, "allowPrivilegedPort": true }))
+                .unwrap()
+                .metrics_addr
+                .port(),
+            80
+        );
+
+        let err = parse(json!({ "metrics": 70000 })).unwrap_err().to_string();
+        assert!(err.contains("config_path: `metrics`"));
+        let err = parse(json!({ "metrics": "localhost" }))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("config_path: `metrics`"));
+        assert!(err.contains("127.0.0.1:9090"));
+    }
+
+    #[test]
+    fn validates_health_port() {
+        let parse = |raw: serde_json::Value| -> ConfigResult<Settings> {
+            serde_json::from_value::<DeprecatedRawSettings>(raw)
+                .unwrap()
+                .parse_config(&ConfigPath::default())
+        };
+        assert_eq!(parse(json!({})).unwrap().health_addr, None);
+        assert_eq!(
+            parse(json!({ "healthPort": "8080" })).unwrap().health_addr,
+            Some(([0, 0, 0, 0], 8080).into())
+        );
+
+        for health_port in [json!(80), json!(70000), json!("localhost")] {
+            let err = parse(json!({ "healthPort": health_port }))
+                .unwrap_err()
+                .to_string();
+            assert!(err.contains("config_path: `healthPort`"));
+        }
+    }
+
+    #[test]
+    fn validates_metrics_prefix() {
+        let parse = |raw: serde_json::Value| -> ConfigResult<Settings> {
+            serde_json::from_value::<DeprecatedRawSettings>(raw)
+                .unwrap()
+                .parse_config(&ConfigPath::default())
+        };
+        assert_eq!(parse(json!({})).unwrap().metrics_prefix, None);
+        assert_eq!(
+            parse(json!({ "metricsPrefix": "" }))
+                .unwrap()
+                .metrics_prefix,
+            None
+        );
+        assert_eq!(
+            parse(json!({ "metricsPrefix": "testnet_relayer" }))
+                .unwrap()
+                .metrics_prefix
+                .as_deref(),
+            Some("testnet_relayer")
+        );
+
+        let err = parse(json!({ "metricsPrefix": "9relayer" }))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("config_path: `metricsPrefix`"));
+        let err = parse(json!({ "metricsPrefix": "relayer-1" }))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("config_path: `metricsPrefix`"));
+    }
+
+    #[test]
+    fn parses_index_from_tags() {
+        let parse = |from: serde_json::Value| -> ConfigResult<IndexSettings> {
+            serde_json::from_value::<DeprecatedRawIndexSettings>(json!({ "from": from }))
+                .unwrap()
+                .parse_config(&ConfigPath::default())
+        };
+        assert_eq!(parse(json!(42)).unwrap().from, IndexFrom::Height(42));
+        assert_eq!(parse(json!("42")).unwrap().from, IndexFrom::Height(42));
+        assert_eq!(parse(json!("latest")).unwrap().from, IndexFrom::Latest);
+        assert_eq!(
+            parse(json!("finalized")).unwrap().from,
+            IndexFrom::Finalized
+        );
+
+        let err = parse(json!("earliest")).unwrap_err().to_string();
+        assert!(err.contains("config_path: `from`"));
+    }
+
+    #[test]
+    fn parses_index_modes() {
+        let parse = |mode: serde_json::Value| -> ConfigResult<IndexSettings> {
+            serde_json::from_value::<DeprecatedRawIndexSettings>(json!({ "mode": mode }))
+                .unwrap()
+                .parse_config(&ConfigPath::default())
+        };
+        assert_eq!(
+            parse(json!("sequence")).unwrap().modes,
+            [IndexMode::Sequence]
+        );
+        let settings = parse(json!(["block", "sequence"])).unwrap();
+        assert_eq!(settings.modes, [IndexMode::Block, IndexMode::Sequence]);
+        assert_eq!(settings.mode(IndexMode::Sequence), IndexMode::Sequence);
+        assert_eq!(
+            parse(json!(["block"])).unwrap().mode(IndexMode::Sequence),
+            IndexMode::Block
+        );
+        assert_eq!(parse(json!(null)).unwrap().modes, [IndexMode::default()]);
+
+        for mode in [json!(["block", "block"]), json!([]), json!("unknown")] {
+            let err = parse(mode).unwrap_err().to_string();
+            assert!(err.contains("config_path: `mode`"));
+        }
+    }
+    #[test]
+    fn checks_signer_chain_id() {
+        let parse = |chain_id: serde_json::Value| {
+            parse_chain(json!({
+                "signer": {
+                    "type": "hexKey",
+                    "key": "0x0000000000000000000000000000000000000000000000000000000000000001",
+                    "chainId": chain_id,
+                },
+            }))
+        };
+        assert!(parse(json!(13371)).is_ok());
+        assert!(parse(json!(null)).is_ok());
+
+        let err = parse(json!(1)).unwrap_err().to_string();
+        assert!(err.contains("config_path: `signer.chainId`"));
+        let err = parse(json!("mainnet")).unwrap_err().to_string();
+        assert!(err.contains("config_path: `signer.chainId`"));
+    }
+
+    #[test]
+    fn parses_connection_retry_config() {
+        let parse = |retry: serde_json::Value| -> ConfigResult<h_eth::ConnectionConf> {
+            serde_json::from_value::<h_eth::RawConnectionConf>(json!({
+                "type": "http",
+                "url": "http://localhost:8545",
+                "retry": retry,
+            }))
+            .unwrap()
+            .parse_config(&ConfigPath::default())
+        };
+        let h_eth::ConnectionConf::Http { retry, .. } = parse(json!({
+            "maxRetries": 3,
+            "initialBackoffMs": "100",
+            "maxBackoffMs": 2000,
+        }))
+        .unwrap() else {
+            panic!("Expected an http connection");
+        };
+        assert_eq!(
+            retry,
+            h_eth::RetryConfig {
+                max_retries: Some(3),
+                initial_backoff_ms: Some(100),
+                max_backoff_ms: Some(2000),
+            }
+        );
+
+        let err = parse(json!({ "initialBackoffMs": 2000, "maxBackoffMs": 100 }))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("config_path: `retry.initialBackoffMs`"));
+
+        let h_eth::ConnectionConf::HttpFallback { retry, .. } =
+            serde_json::from_value::<h_eth::RawConnectionConf>(json!({
+                "type": "httpFallback",
+                "urls": "http://localhost:8545,http://localhost:8546",
+                "retry": { "maxRetries": 5 },
+            }))
+            .unwrap()
+            .parse_config(&ConfigPath::default())
+            .unwrap()
+        else {
+            panic!("Expected a fallback connection");
+        };
+        assert_eq!(retry.max_retries, Some(5));
+    }
+
+    #[test]
+    fn parses_ws_connection_urls() {
+        let parse = |conn: serde_json::Value| -> ConfigResult<h_eth::ConnectionConf> {
+            serde_json::from_value::<h_eth::RawConnectionConf>(conn)
+                .unwrap()
+                .parse_config(&ConfigPath::default())
+        };
+        let conn = parse(json!({ "type": "http", "url": "wss://rpc.example.com" })).unwrap();
+        assert!(matches!(conn, h_eth::ConnectionConf::Ws { .. }));
+
+        let h_eth::ConnectionConf::HttpFallback { urls, .. } = parse(json!({
+            "type": "httpFallback",
+            "urls": "ws://localhost:8546,http://localhost:8545",
+        }))
+        .unwrap() else {
+            panic!("Expected a fallback connection");
+        };
+        assert_eq!(urls.len(), 2);
+
+        let err = parse(json!({ "type": "http", "url": "ftp://localhost" }))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("config_path: `url`"));
+        let err = parse(json!({ "type": "httpQuorum", "urls": "ws://localhost:8546" }))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("config_path: `urls`"));
+
+        // http only options would be dropped by a websocket connection
+        for (option, value) in [
+            ("headers", json!({ "x-api-key": "secret" })),
+            ("userAgent", json!("hyperlane")),
+            ("requestTimeout", json!("10s")),
+        ] {
+            let mut conn = json!({ "type": "http", "url": "wss://rpc.example.com" });
+            conn[option] = value.clone();
+            let err = parse(conn).unwrap_err().to_string();
+            assert!(
+                err.contains(&format!("`{option}` is not supported")),
+                "{err}"
+            );
+
+            let mut conn = json!({
+                "type": "http",
+                "url": "http://localhost:8545",
+                "indexUrls": "ws://localhost:8546",
+            });
+            conn[option] = value;
+            let err = serde_json::from_value::<h_eth::RawConnectionConf>(conn)
+                .unwrap()
+                .parse_config::<h_eth::ChainConnections>(&ConfigPath::default())
+                .unwrap_err()
+                .to_string();
+            assert!(err.contains("config_path: `index_urls`"), "{err}");
+        }
+    }
+
+    #[test]
+    fn rejects_several_urls_for_single_consensus() {
+        let parse = |conn: serde_json::Value| -> ConfigResult<h_eth::ConnectionConf> {
+            serde_json::from_value::<h_eth::RawConnectionConf>(conn)
+                .unwrap()
+                .parse_config(&ConfigPath::default())
+        };
+        let conn = parse(json!({
+            "rpcConsensusType": "single",
+            "urls": "http://localhost:8545",
+        }))
+        .unwrap();
+        assert!(matches!(conn, h_eth::ConnectionConf::Http { .. }));
+
+        let err = parse(json!({
+            "rpcConsensusType": "single",
+            "urls": "http://localhost:8545,http://localhost:8546",
+        }))
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("config_path: `urls`"), "{err}");
+        assert!(err.contains("takes one url, got 2"), "{err}");
+    }
+
+    #[test]
+    fn validates_connectivity_when_enabled() {
+        let parse = |validate: bool| -> ConfigResult<h_eth::ConnectionConf> {
+            serde_json::from_value::<h_eth::RawConnectionConf>(json!({
+                "type": "http",
+                // nothing listens on port 1 so connecting is refused
+                "url": "http://127.0.0.1:1",
+                "validateConnectivity": validate,
+            }))
+            .unwrap()
+            .parse_config(&ConfigPath::default())
+        };
+        assert!(parse(false).is_ok());
+        let err = parse(true).unwrap_err().to_string();
+        assert!(err.contains("config_path: `url`"));
+
+        // every unreachable url is reported, including the index urls
+        let err = serde_json::from_value::<h_eth::RawConnectionConf>(json!({
+            "type": "httpFallback",
+            "urls": "http://127.0.0.1:1,http://127.0.0.1:2",
+            "indexUrls": "http://127.0.0.1:3",
+            "validateConnectivity": true,
+        }))
+        .unwrap()
+        .parse_config::<h_eth::ChainConnections>(&ConfigPath::default())
+        .unwrap_err()
+        .to_string();
+        for url in ["127.0.0.1:1", "127.0.0.1:2", "127.0.0.1:3"] {
+            assert!(err.contains(url), "{err}");
+        }
+        assert!(err.contains("config_path: `index_urls`"), "{err}");
+    }
+
+    #[test]
+    fn parses_vault_signer() {
+        let raw: DeprecatedRawSignerConf = serde_json::from_value(json!({
+            "type": "vault",
+            "address": "https://vault.example.com:8200",
+            "keyName": "relayer",
+        }))
+        .unwrap();
+        let SignerConf::Vault {
+            address,
+            mount_path,
+            key_name,
+            token_env,
+        } = SignerConf::from_config(raw, &ConfigPath::default()).unwrap()
+        else {
+            panic!("Expected a vault signer");
+        };
+        assert_eq!(address.as_str(), "https://vault.example.com:8200/");
+        assert_eq!(mount_path, "transit");
+        assert_eq!(key_name, "relayer");
+        assert_eq!(token_env, "VAULT_TOKEN");
+
+        let raw: DeprecatedRawSignerConf =
+            serde_json::from_value(json!({ "type": "vault", "keyName": "relayer" })).unwrap();
+        let err = SignerConf::from_config(raw, &ConfigPath::default())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("config_path: `address`"));
+    }
+
+    #[test]
+    fn reads_signer_key_file() {
+        let key = "0x0101010101010101010101010101010101010101010101010101010101010101";
+        let dir = tempfile::tempdir().unwrap();
+        let key_file = dir.path().join("key");
+        std::fs::write(&key_file, format!("  {key}\n")).unwrap();
+        let key_file = key_file.to_str().unwrap();
+
+        let raw: DeprecatedRawSignerConf =
+            serde_json::from_value(json!({ "type": "hexKey", "keyFile": key_file })).unwrap();
+        let SignerConf::HexKey { key: parsed, .. } =
+            SignerConf::from_config(raw, &ConfigPath::default()).unwrap()
+        else {
+            panic!("Expected a hex key signer");
+        };
+        assert_eq!(parsed, parse_signer_key(key).unwrap());
+
+        let raw: DeprecatedRawSignerConf =
+            serde_json::from_value(json!({ "key": key, "keyFile": key_file })).unwrap();
+        let err = SignerConf::from_config(raw, &ConfigPath::default())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("config_path: `keyFile`"));
+
+        let missing = dir.path().join("missing");
+        let raw: DeprecatedRawSignerConf = serde_json::from_value(json!({
+            "type": "hexKey",
+            "keyFile": missing.to_str().unwrap(),
+        }))
+        .unwrap();
+        let err = SignerConf::from_config(raw, &ConfigPath::default())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("config_path: `keyFile`"));
+    }
+
+    #[test]
+    fn parses_s3_compatible_endpoint() {
+        let raw: DeprecatedRawCheckpointSyncerConf = serde_json::from_value(json!({
+            "type": "s3",
+            "bucket": "checkpoints",
+            "region": "minio",
+            "endpoint": "http://localhost:9000",
+            "forcePathStyle": true,
+        }))
+        .unwrap();
+        let CheckpointSyncerConf::S3 {
+            region,
+            endpoint,
+            force_path_style,
+            ..
+        } = CheckpointSyncerConf::from_config(raw, &ConfigPath::default()).unwrap()
+        else {
+            panic!("Expected an S3 checkpoint syncer");
+        };
+        assert_eq!(
+            region,
+            rusoto_core::Region::Custom {
+                name: "minio".into(),
+                endpoint: "http://localhost:9000".into(),
+            }
+        );
+        assert_eq!(endpoint.unwrap().as_str(), "http://localhost:9000/");
+        assert!(force_path_style);
+
+        let raw: DeprecatedRawCheckpointSyncerConf = serde_json::from_value(json!({
+            "type": "s3",
+            "bucket": "checkpoints",
+            "endpoint": "localhost:9000",
+        }))
+        .unwrap();
+        let err = CheckpointSyncerConf::from_config(raw, &ConfigPath::default())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("config_path: `endpoint`"));
+    }
+
+    #[test]
+    fn parses_external_signer() {
+        let raw: DeprecatedRawSignerConf = serde_json::from_value(json!({
+            "type": "external",
+            "command": "/usr/local/bin/hsm-sign",
+            "timeout": "3s",
+        }))
+        .unwrap();
+        let SignerConf::External { command, timeout } =
+            SignerConf::from_config(raw, &ConfigPath::default()).unwrap()
+        else {
+            panic!("Expected an external signer");
+        };
+        assert_eq!(command, "/usr/local/bin/hsm-sign");
+        assert_eq!(timeout, Duration::from_secs(3));
+
+        let raw: DeprecatedRawSignerConf =
+            serde_json::from_value(json!({ "type": "external" })).unwrap();
+        let err = SignerConf::from_config(raw, &ConfigPath::default())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("config_path: `command`"));
+    }
+
+    #[test]
+    fn rejects_duplicate_domain_ids() {
+        let chain = |name: &str| test_chain(json!({ "name": name, "domain": 99999 }));
+        let raw: DeprecatedRawSettings = serde_json::from_value(json!({
+            "chains": { "alpha": chain("alpha"), "beta": chain("beta") },
+        }))
+        .unwrap();
+        let err = raw
+            .parse_config::<Settings>(&ConfigPath::default())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("config_path: `chains`"));
+        assert!(err.contains("alpha, beta (domain 99999)"));
+    }
+
+    #[test]
+    fn checks_chain_name_matches_key() {
+        let parse = |allow_mismatch: bool| -> ConfigResult<Settings> {
+            serde_json::from_value::<DeprecatedRawSettings>(json!({
+                "chains": {
+                    "beta": test_chain(json!({
+                        "name": "alpha",
+                        "allowNameKeyMismatch": allow_mismatch,
+                        "domain": 99999,
+                    })),
+                },
+            }))
+            .unwrap()
+            .parse_config(&ConfigPath::default())
+        };
+        let err = parse(false).unwrap_err().to_string();
+        assert!(err.contains("config_path: `chains.beta.name`"));
+        assert!(parse(true).is_ok());
+    }
+
+    #[test]
+    fn parses_finality_duration() {
+        let parse =
+            |finality: serde_json::Value| parse_chain(json!({ "finalityBlocks": finality }));
+        let chain = parse(json!(20)).unwrap();
+        assert_eq!(chain.finality, Finality::Blocks(20));
+        assert_eq!(chain.finality_blocks, 20);
+        assert_eq!(chain.reorg_period, 20);
+
+        let chain = parse(json!("15min")).unwrap();
+        assert_eq!(chain.finality, Finality::Time(Duration::from_secs(900)));
+
+        let err = parse(json!("soon")).unwrap_err().to_string();
+        assert!(err.contains("config_path: `finalityBlocks`"));
+    }
+
+    #[test]
+    fn merges_default_index_settings() {
+        let chain = |index: serde_json::Value| test_chain(json!({ "index": index }));
+        let parse = |raw: serde_json::Value| -> ConfigResult<Settings> {
+            serde_json::from_value::<DeprecatedRawSettings>(raw)
+                .unwrap()
+                .parse_config(&ConfigPath::default())
+        };
+        let settings = parse(json!({
+            "defaultIndex": { "chunk": 500, "from": 10 },
+            "chains": { "test1": chain(json!({ "from": 20 })) },
+        }))
+        .unwrap();
+        let index = &settings.chains["test1"].index;
+        assert_eq!(index.chunk_size, 500);
+        assert_eq!(index.from, IndexFrom::Height(20));
+
+        let err = parse(json!({
+            "defaultIndex": { "chunk": "often" },
+            "chains": { "test1": chain(json!({})) },
+        }))
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("config_path: `defaultIndex.chunk`"));
+        assert!(!err.contains("config_path: `chains.test1.index.chunk`"));
+    }
+
+    #[test]
+    fn parses_aws_assume_role_signer() {
+        let parse = |raw: serde_json::Value| {
+            let raw: DeprecatedRawSignerConf = serde_json::from_value(raw).unwrap();
+            SignerConf::from_config(raw, &ConfigPath::default())
+        };
+        let SignerConf::Aws {
+            role_arn,
+            external_id,
+            ..
+        } = parse(json!({
+            "type": "aws",
+            "id": "alias/relayer",
+            "region": "us-east-1",
+            "roleArn": "arn:aws:iam::123456789012:role/relayer-signer",
+            "externalId": "hyperlane",
+        }))
+        .unwrap()
+        else {
+            panic!("Expected an aws signer");
+        };
+        assert_eq!(
+            role_arn.as_deref(),
+            Some("arn:aws:iam::123456789012:role/relayer-signer")
+        );
+        assert_eq!(external_id.as_deref(), Some("hyperlane"));
+
+        let SignerConf::Aws {
+            id,
+            role_arn,
+            external_id,
+            ..
+        } = parse(json!({ "id": "alias/relayer", "region": "us-east-1" })).unwrap()
+        else {
+            panic!("Expected an aws signer");
+        };
+        assert_eq!(id, "alias/relayer");
+        assert_eq!(role_arn, None);
+        assert_eq!(external_id, None);
+
+        let err = parse(json!({
+            "type": "aws",
+            "id": "alias/relayer",
+            "region": "us-east-1",
+            "roleArn": "arn:aws:iam::1234:user/relayer",
+        }))
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("config_path: `roleArn`"));
+    }
+
+    #[test]
+    fn parses_index_event_topics() {
+        let parse = |raw: serde_json::Value| -> ConfigResult<IndexSettings> {
+            serde_json::from_value::<DeprecatedRawIndexSettings>(raw)
+                .unwrap()
+                .parse_config(&ConfigPath::default())
+        };
+        assert!(parse(json!({})).unwrap().event_topics.is_empty());
+
+        let topic = "0x769f711d20c679153d382254f59892613b58a97cc876b249134ac25c80f9c814";
+        let index = parse(json!({ "eventTopics": [topic] })).unwrap();
+        assert_eq!(index.event_topics, vec![topic.parse::<H256>().unwrap()]);
+
+        let err = parse(json!({ "eventTopics": [topic, "0x1234"] }))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("config_path: `eventTopics`"));
+    }
+
+    #[test]
+    fn parses_relay_service_signer() {
+        let parse = |raw: serde_json::Value| {
+            let raw: DeprecatedRawSignerConf = serde_json::from_value(raw).unwrap();
+            SignerConf::from_config(raw, &ConfigPath::default())
+        };
+        let key = "0x1111111111111111111111111111111111111111111111111111111111111111";
+        let SignerConf::RelayService {
+            endpoint,
+            api_key_env,
+            signer,
+        } = parse(json!({
+            "type": "relayService",
+            "endpoint": "https://relay.example.com/submit",
+            "signer": { "type": "hexKey", "key": key },
+        }))
+        .unwrap()
+        else {
+            panic!("Expected a relay service signer");
+        };
+        assert_eq!(endpoint.as_str(), "https://relay.example.com/submit");
+        assert_eq!(api_key_env, "RELAY_SERVICE_API_KEY");
+        assert!(matches!(*signer, SignerConf::HexKey { .. }));
+
+        let signer = json!({ "type": "hexKey", "key": key });
+        for raw in [
+            json!({ "type": "relayService", "signer": signer }),
+            json!({ "type": "relayService", "endpoint": "relay.example.com", "signer": signer }),
+            json!({ "type": "relayService", "endpoint": "ftp://relay.example.com", "signer": signer }),
+        ] {
+            let err = parse(raw).unwrap_err().to_string();
+            assert!(err.contains("config_path: `endpoint`"));
+        }
+
+        let endpoint = "https://relay.example.com/submit";
+        for raw in [
+            json!({ "type": "relayService", "endpoint": endpoint }),
+            json!({ "type": "relayService", "endpoint": endpoint, "signer": {} }),
+            json!({
+                "type": "relayService",
+                "endpoint": endpoint,
+                "signer": { "type": "relayService", "endpoint": endpoint, "signer": signer },
+            }),
+        ] {
+            let err = parse(raw).unwrap_err().to_string();
+            assert!(err.contains("config_path: `signer`"));
+        }
+    }
+
+    #[test]
+    fn parses_poll_interval() {
+        let parse = |poll_interval: serde_json::Value| {
+            parse_chain(json!({ "pollInterval": poll_interval }))
+        };
+        let chain = parse_chain(json!({})).unwrap();
+        assert_eq!(chain.index_settings().poll_interval, DEFAULT_POLL_INTERVAL);
+
+        let chain = parse(json!(500)).unwrap();
+        assert_eq!(chain.poll_interval, Duration::from_millis(500));
+        assert_eq!(
+            chain.index_settings().poll_interval,
+            Duration::from_millis(500)
+        );
+
+        let chain = parse(json!("2s")).unwrap();
+        assert_eq!(chain.poll_interval, Duration::from_secs(2));
+
+        for invalid in [json!(0), json!("0s"), json!("often")] {
+            let err = parse(invalid).unwrap_err().to_string();
+            assert!(err.contains("config_path: `pollInterval`"));
+        }
+    }
+
+    #[test]
+    fn parses_gcs_service_account_key() {
+        let parse = |key: serde_json::Value| {
+            let mut raw = json!({ "type": "gcs", "bucket": "checkpoints" });
+            raw.as_object_mut()
+                .unwrap()
+                .extend(key.as_object().unwrap().clone());
+            let raw: DeprecatedRawCheckpointSyncerConf = serde_json::from_value(raw).unwrap();
+            CheckpointSyncerConf::from_config(raw, &ConfigPath::default())
+        };
+
+        let CheckpointSyncerConf::Gcs {
+            service_account_key: Some(crate::GcsServiceAccountKey::File(path)),
+            ..
+        } = parse(json!({ "serviceAccountKey": "/secrets/key.json" })).unwrap()
+        else {
+            panic!("Expected a GCS checkpoint syncer with a key file");
+        };
+        assert_eq!(path, PathBuf::from("/secrets/key.json"));
+
+        for (key, path) in [
+            (
+                json!({ "serviceAccountKey": "base64:%%%" }),
+                "serviceAccountKey",
+            ),
+            (
+                json!({ "serviceAccountKey": "base64:bm90IGpzb24=" }),
+                "serviceAccountKey",
+            ),
+            (
+                json!({ "serviceAccountKeyBase64": "bm90IGpzb24=" }),
+                "serviceAccountKeyBase64",
+            ),
+        ] {
+            let err = parse(key).unwrap_err().to_string();
+            assert!(err.contains(&format!("config_path: `{path}`")));
+        }
+    }
+
+    #[test]
+    fn parses_connection_tls_config() {
+        let parse = |tls: Option<serde_json::Value>| -> ConfigResult<h_eth::ConnectionConf> {
+            let mut conn = json!({ "type": "http", "url": "https://localhost:8545" });
+            if let Some(tls) = tls {
+                conn["tls"] = tls;
+            }
+            serde_json::from_value::<h_eth::RawConnectionConf>(conn)
+                .unwrap()
+                .parse_config(&ConfigPath::default())
+        };
+        let h_eth::ConnectionConf::Http { tls, .. } = parse(None).unwrap() else {
+            panic!("Expected an http connection");
+        };
+        assert!(tls.is_none());
+
+        let dir = tempfile::tempdir().unwrap();
+        let invalid_pem = dir.path().join("client.pem");
+        std::fs::write(&invalid_pem, "not a certificate").unwrap();
+        let missing = dir.path().join("missing.pem");
+
+        let err = parse(Some(json!({ "clientCertPath": invalid_pem })))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("config_path: `tls.clientKeyPath`"));
+
+        let err = parse(Some(json!({
+            "clientCertPath": missing,
+            "clientKeyPath": invalid_pem,
+            "caCertPath": invalid_pem,
+        })))
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("config_path: `tls.clientCertPath`"));
+        assert!(err.contains(&missing.display().to_string()));
+        assert!(err.contains("config_path: `tls.caCertPath`"));
+
+        let err = parse(Some(json!({
+            "clientCertPath": invalid_pem,
+            "clientKeyPath": invalid_pem,
+        })))
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("config_path: `tls.clientKeyPath`"));
+    }
+
+    #[test]
+    fn falls_back_to_aws_region_env_vars() {
+        use rusoto_core::Region;
+
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(var, _)| *var == name)
+                    .map(|(_, value)| value.to_string())
+            }
+        };
+        let both = env(&[
+            ("AWS_REGION", "eu-west-1"),
+            ("AWS_DEFAULT_REGION", "us-west-2"),
+        ]);
+        assert_eq!(
+            parse_aws_region(Some("us-east-1"), &both).unwrap(),
+            Region::UsEast1
+        );
+        assert_eq!(parse_aws_region(None, &both).unwrap(), Region::EuWest1);
+        assert_eq!(
+            parse_aws_region(None, env(&[("AWS_DEFAULT_REGION", "us-west-2")])).unwrap(),
+            Region::UsWest2
+        );
+        assert!(parse_aws_region(None, env(&[])).is_err());
+        assert!(parse_aws_region(None, env(&[("AWS_REGION", "mars-1")])).is_err());
+    }
+
+    #[test]
+    fn parses_batch_strategy() {
+        let multicall =
+            |addr: &str| BatchStrategy::Multicall3(hex_or_base58_to_h256(addr).unwrap());
+
+        let chain = parse_chain(json!({})).unwrap();
+        assert_eq!(chain.batch_strategy, BatchStrategy::None);
+
+        let chain = parse_chain(json!({
+            "name": "ethereum",
+            "domain": 1,
+            "batchStrategy": "multicall3",
+        }))
+        .unwrap();
+        assert_eq!(
+            chain.batch_strategy,
+            multicall("0xcA11bde05977b3631167028862bE2a173976CA11")
+        );
+
+        let chain = parse_chain(json!({
+            "batchStrategy": "multicall3",
+            "multicallAddress": "0x4444444444444444444444444444444444444444",
+        }))
+        .unwrap();
+        assert_eq!(
+            chain.batch_strategy,
+            multicall("0x4444444444444444444444444444444444444444")
+        );
+
+        for batching in [
+            json!({ "batchStrategy": "multicall3" }),
+            json!({ "batchStrategy": "multicall2" }),
+            json!({ "multicallAddress": "0x4444444444444444444444444444444444444444" }),
+        ] {
+            let err = parse_chain(batching).unwrap_err().to_string();
+            assert!(err.contains("config_path: `batchStrategy`"));
+        }
+    }
+
+    #[test]
+    fn parses_tracing_format() {
+        use crate::settings::trace::fmt::Style;
+
+        let parse = |tracing: serde_json::Value| -> ConfigResult<Settings> {
+            serde_json::from_value::<DeprecatedRawSettings>(json!({ "tracing": tracing }))
+                .unwrap()
+                .parse_config(&ConfigPath::default())
+        };
+        assert_eq!(parse(json!({})).unwrap().tracing.fmt, Style::Pretty);
+        for (format, style) in [
+            ("pretty", Style::Pretty),
+            ("json", Style::Json),
+            ("compact", Style::Compact),
+        ] {
+            let settings = parse(json!({ "format": format, "level": "debug" })).unwrap();
+            assert_eq!(settings.tracing.fmt, style);
+            assert_eq!(settings.tracing.level, Level::Debug);
+        }
+        // the old key is still accepted
+        assert_eq!(
+            parse(json!({ "fmt": "json" })).unwrap().tracing.fmt,
+            Style::Json
+        );
+
+        let err = parse(json!({ "format": "xml" })).unwrap_err().to_string();
+        assert!(err.contains("config_path: `tracing.format`"));
+    }
+
+    #[test]
+    fn parses_nonce_strategy() {
+        let parse = |nonce_strategy: &str| parse_chain(json!({ "nonceStrategy": nonce_strategy }));
+        assert_eq!(
+            parse_chain(json!({})).unwrap().nonce_strategy,
+            h_eth::NonceStrategy::Sequential
+        );
+        assert_eq!(
+            parse("external").unwrap().nonce_strategy,
+            h_eth::NonceStrategy::External
+        );
+        assert_eq!(
+            parse("perTx").unwrap().nonce_strategy,
+            h_eth::NonceStrategy::PerTx
+        );
+
+        let err = parse("random").unwrap_err().to_string();
+        assert!(err.contains("config_path: `nonceStrategy`"));
+    }
+
+    #[test]
+    fn parses_chain_id_separate_from_domain() {
+        let chain = parse_chain(json!({})).unwrap();
+        assert_eq!(chain.chain_id, None);
+
+        let chain = parse_chain(json!({ "chainId": "31337" })).unwrap();
+        assert_eq!(chain.domain.id(), 13371);
+        assert_eq!(chain.chain_id, Some(31337));
+
+        // a signer restricted to a chain id is checked against the chain id
+        let signer = json!({
+            "type": "hexKey",
+            "key": "0x0000000000000000000000000000000000000000000000000000000000000001",
+            "chainId": 31337,
+        });
+        assert!(parse_chain(json!({ "chainId": 31337, "signer": signer.clone() })).is_ok());
+        let err = parse_chain(json!({ "signer": signer }))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("config_path: `signer.chainId`"));
+
+        let err = parse_chain(json!({ "chainId": "mainnet" }))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("config_path: `chainId`"));
+    }
+
+    #[test]
+    fn relaxes_index_only_chains() {
+        let parse = |index_only: bool| -> ConfigResult<Settings> {
+            serde_json::from_value::<DeprecatedRawSettings>(json!({
+                "defaultsigner": {
+                    "type": "hexKey",
+                    "key": "0x0000000000000000000000000000000000000000000000000000000000000001",
+                },
+                "chains": {
+                    "test1": test_chain(json!({
+                        "indexOnly": index_only,
+                        "maxGasLimit": 0,
+                        "gasOracle": { "minGasPrice": 2, "maxGasPrice": 1 },
+                    })),
+                },
+            }))
+            .unwrap()
+            .parse_config(&ConfigPath::default())
+        };
+        let settings = parse(true).unwrap();
+        let chain = &settings.chains["test1"];
+        assert!(chain.index_only);
+        assert!(matches!(chain.signer, Some(SignerConf::Node)));
+        assert_eq!(chain.max_gas_limit, None);
+
+        let err = parse(false).unwrap_err().to_string();
+        assert!(err.contains("config_path: `chains.test1.maxGasLimit`"));
+        assert!(err.contains("config_path: `chains.test1.gasOracle`"));
+    }
+
+    #[test]
+    fn parses_separate_index_and_submit_urls() {
+        let parse =
+            |connection: serde_json::Value| parse_chain(json!({ "connection": connection }));
+        let urls = |conf: &h_eth::ConnectionConf| -> Vec<String> {
+            match conf {
+                h_eth::ConnectionConf::HttpQuorum { urls, .. }
+                | h_eth::ConnectionConf::HttpFallback { urls, .. } => {
+                    urls.iter().map(|u| u.to_string()).collect()
+                }
+                h_eth::ConnectionConf::Http { url, .. } | h_eth::ConnectionConf::Ws { url } => {
+                    vec![url.to_string()]
+                }
+            }
+        };
+        let connections = |chain: ChainConf| match chain.connection {
+            ChainConnectionConf::Ethereum(conf) => conf,
+            _ => panic!("expected an ethereum connection"),
+        };
+
+        // both fall back to `urls`
+        let conf = connections(
+            parse(json!({ "type": "httpFallback", "urls": "http://a:8545,http://b:8545" }))
+                .unwrap(),
+        );
+        assert_eq!(urls(&conf.index), urls(&conf.submit));
+
+        let conf = connections(
+            parse(json!({
+                "type": "httpFallback",
+                "urls": "http://a:8545,http://b:8545",
+                "indexUrls": "http://replica:8545",
+            }))
+            .unwrap(),
+        );
+        assert_eq!(urls(&conf.index), vec!["http://replica:8545/"]);
+        assert_eq!(urls(&conf.submit), vec!["http://a:8545/", "http://b:8545/"]);
+
+        // `urls` is not needed when both lists are given
+        let conf = connections(
+            parse(json!({
+                "type": "http",
+                "indexUrls": "http://replica1:8545,http://replica2:8545",
+                "submitUrls": "http://submit:8545",
+            }))
+            .unwrap(),
+        );
+        assert!(matches!(
+            conf.index,
+            h_eth::ConnectionConf::HttpFallback { .. }
+        ));
+        assert_eq!(urls(&conf.submit), vec!["http://submit:8545/"]);
+
+        let err = parse(json!({
+            "type": "http",
+            "url": "http://a:8545",
+            "indexUrls": "not a url",
+            "submitUrls": "ftp://submit",
+        }))
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("config_path: `connection.indexUrls`"));
+        assert!(err.contains("config_path: `connection.submitUrls`"));
+    }
+
+    #[test]
+    fn parses_log_sampling_rate() {
+        let parse = |tracing: serde_json::Value| -> ConfigResult<Settings> {
+            serde_json::from_value::<DeprecatedRawSettings>(json!({ "tracing": tracing }))
+                .unwrap()
+                .parse_config(&ConfigPath::default())
+        };
+        assert_eq!(parse(json!({})).unwrap().tracing.sampling_rate, 1.0);
+        assert_eq!(
+            parse(json!({ "samplingRate": 0.25 }))
+                .unwrap()
+                .tracing
+                .sampling_rate,
+            0.25
+        );
+        for rate in [-0.1, 1.5] {
+            let err = parse(json!({ "samplingRate": rate }))
+                .unwrap_err()
+                .to_string();
+            assert!(err.contains("config_path: `tracing.samplingRate`"));
+        }
+    }
+
+    #[test]
+    fn parses_confirmation_timeout() {
+        assert_eq!(
+            parse_chain(json!({})).unwrap().confirmation_timeout,
+            DEFAULT_CONFIRMATION_TIMEOUT
+        );
+        assert_eq!(
+            parse_chain(json!({ "confirmationTimeout": "2m" }))
+                .unwrap()
+                .confirmation_timeout,
+            Duration::from_secs(120)
+        );
+        assert_eq!(
+            parse_chain(json!({ "confirmationTimeout": 90 }))
+                .unwrap()
+                .confirmation_timeout,
+            Duration::from_secs(90)
+        );
+
+        for chain in [
+            json!({ "confirmationTimeout": "soon" }),
+            json!({ "confirmationTimeout": "5s", "blockTime": "12s" }),
+        ] {
+            let err = parse_chain(chain).unwrap_err().to_string();
+            assert!(err.contains("config_path: `confirmationTimeout`"));
+        }
+    }
+
+    #[test]
+    fn parses_s3_sse_kms_key_id() {
+        let parse = |key_id: Option<&str>| {
+            let mut raw = json!({
+                "type": "s3",
+                "bucket": "checkpoints",
+                "region": "us-east-1",
+            });
+            if let Some(key_id) = key_id {
+                raw["sseKmsKeyId"] = json!(key_id);
+            }
+            let raw: DeprecatedRawCheckpointSyncerConf = serde_json::from_value(raw).unwrap();
+            CheckpointSyncerConf::from_config(raw, &ConfigPath::default())
+        };
+        let sse_kms_key_id = |conf: CheckpointSyncerConf| match conf {
+            CheckpointSyncerConf::S3 { sse_kms_key_id, .. } => sse_kms_key_id,
+            _ => panic!("Expected an S3 checkpoint syncer"),
+        };
+        assert_eq!(sse_kms_key_id(parse(None).unwrap()), None);
+        assert_eq!(
+            sse_kms_key_id(parse(Some("alias/checkpoints")).unwrap()).as_deref(),
+            Some("alias/checkpoints")
+        );
+
+        let err = parse(Some(" ")).unwrap_err().to_string();
+        assert!(err.contains("config_path: `sseKmsKeyId`"));
+    }
+
+    #[test]
+    fn infers_name_of_known_domains() {
+        let parse = |domain: u32, key: Option<&str>| -> ConfigResult<ChainConf> {
+            let mut raw = test_chain(json!({ "domain": domain }));
+            raw.as_object_mut().unwrap().remove("name");
+            serde_json::from_value::<DeprecatedRawChainConf>(raw)
+                .unwrap()
+                .parse_config_with_filter(&ConfigPath::default(), key)
+        };
+        let chain = parse(1, None).unwrap();
+        assert_eq!(chain.domain.name(), "ethereum");
+        assert!(parse(1, Some("ethereum")).is_ok());
+
+        let err = parse(1, Some("eth")).unwrap_err().to_string();
+        assert!(err.contains("config_path: `name`"));
+        let err = parse(987654, None).unwrap_err().to_string();
+        assert!(err.contains("config_path: `name`"));
+    }
+
+    #[test]
+    fn parses_max_reorg_depth() {
+        let parse = |raw: serde_json::Value| -> ConfigResult<IndexSettings> {
+            serde_json::from_value::<DeprecatedRawIndexSettings>(raw)
+                .unwrap()
+                .parse_config(&ConfigPath::default())
+        };
+        assert_eq!(
+            parse(json!({})).unwrap().max_reorg_depth,
+            DEFAULT_MAX_REORG_DEPTH
+        );
+        assert_eq!(
+            parse(json!({ "maxReorgDepth": "64" }))
+                .unwrap()
+                .max_reorg_depth,
+            64
+        );
+        assert_eq!(
+            parse(json!({ "maxReorgDepth": 0 }))
+                .unwrap()
+                .max_reorg_depth,
+            0
+        );
+
+        let err = parse(json!({ "maxReorgDepth": "deep" }))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("config_path: `maxReorgDepth`"));
+    }
+
+    #[test]
+    fn parses_connection_user_agent() {
+        let parse = |user_agent: &str| -> ConfigResult<h_eth::ConnectionConf> {
+            serde_json::from_value::<h_eth::RawConnectionConf>(json!({
+                "type": "http",
+                "url": "http://localhost:8545",
+                "userAgent": user_agent,
+            }))
+            .unwrap()
+            .parse_config(&ConfigPath::default())
+        };
+        let h_eth::ConnectionConf::Http { headers, .. } = parse("hyperlane-relayer/1.0").unwrap()
+        else {
+            panic!("Expected an http connection");
+        };
+        assert_eq!(
+            headers.get("user-agent").map(String::as_str),
+            Some("hyperlane-relayer/1.0")
+        );
+
+        let err = parse(" ").unwrap_err().to_string();
+        assert!(err.contains("config_path: `userAgent`"));
+    }
+
+    #[test]
+    fn parses_signers_by_role() {
+        let parse = |signer: serde_json::Value| parse_chain(json!({ "signer": signer }));
+        let key = |n: u8| format!("0x{}", format!("{n:02x}").repeat(32));
+
+        let chain = parse(json!({
+            "relay": { "type": "hexKey", "key": key(1) },
+            "validator": { "type": "hexKey", "key": key(2) },
+        }))
+        .unwrap();
+        assert!(chain.signer.is_none());
+        assert!(matches!(
+            chain.signer_for(SignerRole::Validate),
+            Some(SignerConf::HexKey { key: k, .. }) if *k == key(2).parse::<H256>().unwrap()
+        ));
+
+        // roles fall back to the default signer
+        let mut chain = parse(json!({
+            "type": "hexKey",
+            "key": key(3),
+            "relay": { "type": "hexKey", "key": key(1) },
+        }))
+        .unwrap();
+        assert!(matches!(
+            chain.signer_for(SignerRole::Validate),
+            Some(SignerConf::HexKey { key: k, .. }) if *k == key(3).parse::<H256>().unwrap()
+        ));
+        chain.use_signer_role(SignerRole::Relay);
+        assert!(matches!(
+            chain.signer,
+            Some(SignerConf::HexKey { key: k, .. }) if k == key(1).parse::<H256>().unwrap()
+        ));
+
+        let err = parse(json!({ "submit": { "type": "hexKey", "key": key(1) } }))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("config_path: `signer`"));
+        let err = parse(json!({
+            "relay": { "type": "hexKey", "key": key(1) },
+            "relayer": { "type": "hexKey", "key": key(2) },
+        }))
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("config_path: `signer`"));
+    }
+
+    #[test]
+    fn parses_local_storage_create_if_missing() {
+        let parse = |path: &std::path::Path, create_if_missing: Option<bool>| {
+            let mut raw = json!({
+                "type": "localStorage",
+                "path": path.to_str().unwrap(),
+            });
+            if let Some(create_if_missing) = create_if_missing {
+                raw["createIfMissing"] = json!(create_if_missing);
+            }
+            let raw: DeprecatedRawCheckpointSyncerConf = serde_json::from_value(raw).unwrap();
+            CheckpointSyncerConf::from_config(raw, &ConfigPath::default())
+        };
+        let dir = tempfile::tempdir().unwrap();
+
+        let missing = dir.path().join("unmounted");
+        let err = parse(&missing, Some(false)).unwrap_err().to_string();
+        assert!(err.contains("config_path: `path`"));
+        assert!(!missing.exists());
+
+        assert!(parse(&missing, None).is_ok());
+        assert!(missing.is_dir());
+        assert!(parse(&missing, Some(false)).is_ok());
+    }
+
+    #[test]
+    fn parses_adaptive_chunking() {
+        let parse = |raw: serde_json::Value| -> ConfigResult<IndexSettings> {
+            serde_json::from_value::<DeprecatedRawIndexSettings>(raw)
+                .unwrap()
+                .parse_config(&ConfigPath::default())
+        };
+        assert_eq!(
+            parse(json!({ "chunk": 2000 }))
+                .unwrap()
+                .adaptive_chunk_bounds(),
+            None
+        );
+
+        let index =
+            parse(json!({ "chunk": 2000, "adaptiveChunking": true, "minChunk": "50" })).unwrap();
+        assert_eq!(index.adaptive_chunk_bounds(), Some(50..=2000));
+        let index = parse(json!({ "adaptiveChunking": true, "maxChunk": 500 })).unwrap();
+        assert_eq!(
+            index.adaptive_chunk_bounds(),
+            Some(DEFAULT_MIN_CHUNK_SIZE..=500)
+        );
+
+        let err = parse(json!({ "minChunk": 100, "maxChunk": 10 }))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("config_path: `maxChunk`"));
+        let err = parse(json!({ "minChunk": 0 })).unwrap_err().to_string();
+        assert!(err.contains("config_path: `minChunk`"));
+    }
+
+    #[test]
+    fn parses_native_token() {
+        let parse = |raw: serde_json::Value| -> ConfigResult<NativeToken> {
+            serde_json::from_value::<DeprecatedRawNativeToken>(raw)
+                .unwrap()
+                .parse_config(&ConfigPath::default())
+        };
+        let token = parse(json!({ "symbol": "ETH", "decimals": "18" })).unwrap();
+        assert_eq!(token.symbol, "ETH");
+        assert_eq!(token.token_info().symbol, "ETH");
+        assert_eq!(NativeToken::default().token_info().symbol, "Native");
+        assert_eq!(parse(json!({})).unwrap(), NativeToken::default());
+        assert_eq!(parse(json!({ "decimals": 6 })).unwrap().decimals, 6);
+
+        let err = parse(json!({ "decimals": 37 })).unwrap_err().to_string();
+        assert!(err.contains("config_path: `decimals`"));
+    }
+
+    #[test]
+    fn records_ens_addresses() {
+        let raw: DeprecatedRawChainConf = serde_json::from_value(test_chain(json!({
+            "addresses": {
+                "mailbox": "mailbox.hyperlane.eth",
+                "interchainGasPaymaster": "0x2222222222222222222222222222222222222222",
+                "validatorAnnounce": "0x3333333333333333333333333333333333333333",
+            },
+        })))
+        .unwrap();
+        let chain = ChainConf::from_config(raw, &ConfigPath::default()).unwrap();
+        let [ens_name] = chain.addresses.ens_names.as_slice() else {
+            panic!("expected one ens name");
+        };
+        assert_eq!(ens_name.contract, "mailbox");
+        assert_eq!(ens_name.name, "mailbox.hyperlane.eth");
+        assert_eq!(
+            ens_name.cwp,
+            ConfigPath::default() + "addresses" + "mailbox"
+        );
+
+        let raw: DeprecatedRawChainConf = serde_json::from_value(json!({
+            "name": "sealeveltest",
+            "domain": 13375,
+            "protocol": "sealevel",
+            "connection": { "url": "http://localhost:8899" },
+            "addresses": {
+                "mailbox": "DrFtxirPPsfdY4HLiHrdL6Nk4G28NoW6hMVDnxPYSbGR",
+                "interchainGasPaymaster": "igp.hyperlane.eth",
+                "validatorAnnounce": "DH43ae1LwemXAboWwSh8zc9pG8j72gKUEXNi57w8fEnn",
+            },
+        }))
+        .unwrap();
+        let err = ChainConf::from_config(raw, &ConfigPath::default())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("config_path: `addresses.interchainGasPaymaster`"));
+        assert!(!err.contains("config_path: `addresses.mailbox`"));
+    }
+
+    #[test]
+    fn parses_mirrored_checkpoint_syncers() {
+        let parse = |raw: serde_json::Value| -> ConfigResult<CheckpointSyncerConf> {
+            serde_json::from_value::<DeprecatedRawCheckpointSyncers>(raw)
+                .unwrap()
+                .parse_config(&(ConfigPath::default() + "checkpointsyncer"))
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let local = json!({ "type": "localStorage", "path": dir.path() });
+        assert!(matches!(
+            parse(local.clone()).unwrap(),
+            CheckpointSyncerConf::LocalStorage { .. }
+        ));
+
+        let syncers = parse(json!([
+            local,
+            { "type": "s3", "bucket": "standby", "region": "us-east-1" },
+        ]))
+        .unwrap();
+        let CheckpointSyncerConf::Mirrored(syncers) = syncers else {
+            panic!("expected mirrored checkpoint syncers");
+        };
+        assert!(matches!(
+            syncers.as_slice(),
+            [
+                CheckpointSyncerConf::LocalStorage { .. },
+                CheckpointSyncerConf::S3 { .. }
+            ]
+        ));
+
+        let err = parse(json!([
+            { "type": "localStorage", "path": dir.path() },
+            { "type": "s3", "region": "us-east-1" },
+        ]))
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("config_path: `checkpointsyncer.1.bucket`"));
+    }
+
+    #[test]
+    fn parses_tx_type() {
+        let parse = |tx_type: &str| parse_chain(json!({ "txType": tx_type }));
+        assert_eq!(parse_chain(json!({})).unwrap().tx_type, h_eth::TxType::Auto);
+        assert_eq!(parse("legacy").unwrap().tx_type, h_eth::TxType::Legacy);
+        assert_eq!(parse("eip1559").unwrap().tx_type, h_eth::TxType::Eip1559);
+
+        let err = parse("eip2930").unwrap_err().to_string();
+        assert!(err.contains("config_path: `txType`"));
+    }
+
+    #[test]
+    fn parses_connection_request_timeout() {
+        let parse = |timeout: serde_json::Value| -> ConfigResult<h_eth::ConnectionConf> {
+            serde_json::from_value::<h_eth::RawConnectionConf>(json!({
+                "type": "httpFallback",
+                "urls": "http://localhost:8545,http://localhost:8546",
+                "requestTimeout": timeout,
+            }))
+            .unwrap()
+            .parse_config(&ConfigPath::default())
+        };
+        let h_eth::ConnectionConf::HttpFallback {
+            request_timeout, ..
+        } = parse(json!("500ms")).unwrap()
+        else {
+            panic!("Expected a fallback connection");
+        };
+        assert_eq!(request_timeout, Some(Duration::from_millis(500)));
+
+        let h_eth::ConnectionConf::HttpFallback {
+            request_timeout, ..
+        } = parse(serde_json::Value::Null).unwrap()
+        else {
+            panic!("Expected a fallback connection");
+        };
+        assert_eq!(request_timeout, None);
+
+        let err = parse(json!(0)).unwrap_err().to_string();
+        assert!(err.contains("config_path: `requestTimeout`"));
+    }
+
+    #[test]
+    fn rejects_unknown_s3_regions() {
+        let parse = |region: &str, endpoint: Option<&str>| {
+            let raw: DeprecatedRawCheckpointSyncerConf = serde_json::from_value(json!({
+                "type": "s3",
+                "bucket": "checkpoints",
+                "region": region,
+                "endpoint": endpoint,
+            }))
+            .unwrap();
+            CheckpointSyncerConf::from_config(raw, &ConfigPath::default())
+        };
+        let CheckpointSyncerConf::S3 { region, .. } = parse("eu-west-2", None).unwrap() else {
+            panic!("Expected an S3 checkpoint syncer");
+        };
+        assert_eq!(region, rusoto_core::Region::EuWest2);
+
+        let err = parse("euwest2", None).unwrap_err().to_string();
+        assert!(err.contains("config_path: `region`"));
+        assert!(err.contains("us-east-1"));
+
+        // S3 compatible stores may use any region name
+        assert!(parse("garage", Some("http://localhost:3900")).is_ok());
+    }
+
+    #[test]
+    fn assumes_instant_finality() {
+        let parse = |assume_instant_finality: bool| -> ChainConf {
+            parse_chain(json!({
+                "finalityBlocks": "15min",
+                "assumeInstantFinality": assume_instant_finality,
+            }))
+            .unwrap()
+        };
+        let chain = parse(true);
+        assert!(chain.assume_instant_finality);
+        assert_eq!(chain.finality, Finality::Blocks(0));
+        assert_eq!(chain.index_settings().finality_blocks, 0);
+        assert_eq!(chain.reorg_period, 0);
+
+        let chain = parse(false);
+        assert!(!chain.assume_instant_finality);
+        assert_eq!(chain.finality, Finality::Time(Duration::from_secs(900)));
+    }
+
+    #[test]
+    fn normalizes_core_contract_addresses() {
+        let parse = |mailbox: &str| -> ConfigResult<CoreContractAddresses> {
+            serde_json::from_value::<DeprecatedRawCoreContractAddresses>(json!({
+                "mailbox": mailbox,
+                "interchainGasPaymaster": "0x2222222222222222222222222222222222222222",
+                "validatorAnnounce": "0x3333333333333333333333333333333333333333",
+            }))
+            .unwrap()
+            .parse_config(&ConfigPath::default())
+        };
+        let expected = hex_or_base58_to_h256("0xabcdef0123456789abcdef0123456789abcdef01").unwrap();
+        for mailbox in [
+            "0xabcdef0123456789abcdef0123456789abcdef01",
+            "abcdef0123456789abcdef0123456789abcdef01",
+            "0XABCDEF0123456789ABCDEF0123456789ABCDEF01",
+            "ABCDEF0123456789abcdef0123456789ABCDEF01",
+            "  0xabcdef0123456789abcdef0123456789abcdef01 \n",
+        ] {
+            assert_eq!(parse(mailbox).unwrap().mailbox, expected, "{mailbox:?}");
+        }
+
+        let full = format!("0x{}", "ab".repeat(32));
+        assert_eq!(
+            parse(&format!(" {} ", "AB".repeat(32))).unwrap().mailbox,
+            hex_or_base58_to_h256(&full).unwrap()
+        );
+
+        // base58 is case sensitive and only trimmed
+        let base58 = bs58::encode([7u8; 32]).into_string();
+        assert_eq!(
+            parse(&format!("{base58} ")).unwrap().mailbox,
+            H256::from([7u8; 32])
+        );
+
+        let err = parse("0xabcdef0123456789abcdef0123456789abcdefzz")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("config_path: `mailbox`"));
+    }
+
+    #[test]
+    fn accepts_confirmations_alias() {
+        let chain = parse_chain(json!({ "confirmations": 12 })).unwrap();
+        assert_eq!(chain.finality, Finality::Blocks(12));
+        assert_eq!(chain.finality_blocks, 12);
+
+        let chain = parse_chain(json!({ "finalityBlocks": 5, "confirmations": 12 })).unwrap();
+        assert_eq!(chain.finality_blocks, 5);
+
+        let err = parse_chain(json!({ "confirmations": "soon" }))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("config_path: `confirmations`"));
+    }
+
+    #[test]
+    fn parses_max_in_flight_transactions() {
+        let parse = |max: serde_json::Value| parse_chain(json!({ "maxInFlightTransactions": max }));
+        assert_eq!(
+            parse_chain(json!({})).unwrap().max_in_flight_transactions,
+            DEFAULT_MAX_IN_FLIGHT_TRANSACTIONS
+        );
+        assert_eq!(parse(json!("3")).unwrap().max_in_flight_transactions, 3);
+        assert_eq!(parse(json!(16)).unwrap().max_in_flight_transactions, 16);
+
+        let err = parse(json!(0)).unwrap_err().to_string();
+        assert!(
+            err.contains("config_path: `maxInFlightTransactions`"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn parses_aws_profiles() {
+        let signer = |profile: Option<&str>| {
+            let mut raw = json!({
+                "type": "aws",
+                "id": "alias/relayer",
+                "region": "us-east-1",
+            });
+            if let Some(profile) = profile {
+                raw["profile"] = json!(profile);
+            }
+            let raw: DeprecatedRawSignerConf = serde_json::from_value(raw).unwrap();
+            SignerConf::from_config(raw, &ConfigPath::default()).map(|conf| match conf {
+                SignerConf::Aws { profile, .. } => profile,
+                _ => panic!("Expected an aws signer"),
+            })
+        };
+        assert_eq!(signer(None).unwrap(), None);
+        assert_eq!(
+            signer(Some("hyperlane")).unwrap().as_deref(),
+            Some("hyperlane")
+        );
+        let err = signer(Some("")).unwrap_err().to_string();
+        assert!(err.contains("config_path: `profile`"));
+
+        let syncer = |profile: &str| {
+            let raw: DeprecatedRawCheckpointSyncerConf = serde_json::from_value(json!({
+                "type": "s3",
+                "bucket": "checkpoints",
+                "region": "us-east-1",
+                "profile": profile,
+            }))
+            .unwrap();
+            CheckpointSyncerConf::from_config(raw, &ConfigPath::default()).map(|conf| match conf {
+                CheckpointSyncerConf::S3 { profile, .. } => profile,
+                _ => panic!("Expected an S3 checkpoint syncer"),
+            })
+        };
+        assert_eq!(syncer(" validator ").unwrap().as_deref(), Some("validator"));
+        let err = syncer(" ").unwrap_err().to_string();
+        assert!(err.contains("config_path: `profile`"));
+    }
+
+    #[test]
+    fn parses_finality_tags() {
+        let parse = |finality: &str| parse_chain(json!({ "finalityBlocks": finality }));
+        let chain = parse("safe").unwrap();
+        assert_eq!(chain.finality, Finality::Tag(h_eth::FinalityTag::Safe));
+        assert_eq!(chain.finality_tag(), Some(h_eth::FinalityTag::Safe));
+        assert_eq!(chain.finality_blocks, 0);
+
+        let chain = parse("finalized").unwrap();
+        assert_eq!(chain.finality_tag(), Some(h_eth::FinalityTag::Finalized));
+        assert_eq!(parse("20").unwrap().finality_tag(), None);
+
+        let err = parse("latest").unwrap_err().to_string();
+        assert!(err.contains("config_path: `finalityBlocks`"));
+    }
+
+    #[test]
+    fn parses_gas_limit_multiplier() {
+        assert_eq!(parse_chain(json!({})).unwrap().gas_limit_multiplier, 1.0);
+        assert_eq!(
+            parse_chain(json!({ "gasLimitMultiplier": 1.25 }))
+                .unwrap()
+                .gas_limit_multiplier,
+            1.25
+        );
+        // index only chains ignore their gas settings
+        assert_eq!(
+            parse_chain(json!({ "gasLimitMultiplier": 0.5, "indexOnly": true }))
+                .unwrap()
+                .gas_limit_multiplier,
+            1.0
+        );
+
+        let err = parse_chain(json!({ "gasLimitMultiplier": 0.9 }))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("config_path: `gasLimitMultiplier`"));
+    }
+
+    #[test]
+    fn parses_cosmos_websocket_subscription() {
+        let parse = |rpc_url: &str, subscribe: bool| -> ConfigResult<h_cosmos::ConnectionConf> {
+            serde_json::from_value::<DeprecatedRawChainConnectionConf>(json!({
+                "protocol": "cosmos",
+                "connection": {
+                    "grpcUrl": "http://localhost:9090",
+                    "rpcUrl": rpc_url,
+                    "chainId": "testchain",
+                    "prefix": "neutron",
+                    "gasPrice": "0.025untrn",
+                    "subscribe": subscribe,
+                },
+            }))
+            .unwrap()
+            .parse_config::<ChainConnectionConf>(&ConfigPath::default())
+            .map(|conf| match conf {
+                ChainConnectionConf::Cosmos(conf) => conf,
+                _ => panic!("Expected a cosmos connection"),
+            })
+        };
+        let conf = parse("http://localhost:26657", false).unwrap();
+        assert!(!conf.subscribe);
+        assert_eq!(conf.websocket_url(), None);
+
+        let conf = parse("https://rpc.example.com/", true).unwrap();
+        assert_eq!(
+            conf.websocket_url().unwrap().as_str(),
+            "wss://rpc.example.com/websocket"
+        );
+        let conf = parse("ws://localhost:26657/websocket", true).unwrap();
+        assert_eq!(
+            conf.websocket_url().unwrap().as_str(),
+            "ws://localhost:26657/websocket"
+        );
+
+        let err = parse("tcp://localhost:26657", true)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("config_path: `connection.subscribe`"), "{err}");
+        assert_eq!(conf.retry, h_cosmos::RetryConf::default());
+    }
+
+    #[test]
+    fn parses_tracing_targets() {
+        use tracing_subscriber::filter::LevelFilter;
+
+        let parse = |tracing: serde_json::Value| -> ConfigResult<Settings> {
+            serde_json::from_value::<DeprecatedRawSettings>(json!({ "tracing": tracing }))
+                .unwrap()
+                .parse_config(&ConfigPath::default())
+        };
+        let targets = |settings: Settings| {
+            let mut targets = settings
+                .tracing
+                .targets
+                .iter()
+                .map(|(target, level)| (target.to_owned(), level))
+                .collect::<Vec<_>>();
+            targets.sort_by(|a, b| a.0.cmp(&b.0));
+            targets
+        };
+        assert!(targets(parse(json!({})).unwrap()).is_empty());
+        let expected = vec![
+            ("hyperlane_base".to_owned(), LevelFilter::DEBUG),
+            ("relayer::msg".to_owned(), LevelFilter::TRACE),
+        ];
+        assert_eq!(
+            targets(
+                parse(json!({ "targets": "hyperlane_base=debug,relayer::msg=trace" })).unwrap()
+            ),
+            expected
+        );
+        assert_eq!(
+            targets(
+                parse(json!({
+                    "targets": { "hyperlane_base": "debug", "relayer::msg": "trace" }
+                }))
+                .unwrap()
+            ),
+            expected
+        );
+
+        // every directive has to name a target
+        for invalid in [json!("hyperlane_base=loud"), json!("debug")] {
+            let err = parse(json!({ "targets": invalid }))
+                .unwrap_err()
+                .to_string();
+            assert!(err.contains("config_path: `tracing`"));
+        }
+    }
+
+    #[test]
+    fn parses_local_storage_retain() {
+        let dir = tempfile::tempdir().unwrap();
+        let parse = |retain: Option<serde_json::Value>| {
+            let mut raw = json!({
+                "type": "localStorage",
+                "path": dir.path().to_str().unwrap(),
+            });
+            if let Some(retain) = retain {
+                raw["retain"] = retain;
+            }
+            let raw: DeprecatedRawCheckpointSyncerConf = serde_json::from_value(raw).unwrap();
+            CheckpointSyncerConf::from_config(raw, &ConfigPath::default())
+        };
+        let retain = |conf: CheckpointSyncerConf| match conf {
+            CheckpointSyncerConf::LocalStorage { retain, .. } => retain,
+            _ => panic!("Expected a LocalStorage checkpoint syncer"),
+        };
+        assert_eq!(retain(parse(None).unwrap()), None);
+        assert_eq!(retain(parse(Some(json!(0))).unwrap()), None);
+        assert_eq!(retain(parse(Some(json!(100))).unwrap()), Some(100));
+        assert_eq!(retain(parse(Some(json!("100"))).unwrap()), Some(100));
+
+        let err = parse(Some(json!(-1))).unwrap_err().to_string();
+        assert!(err.contains("config_path: `retain`"));
+    }
+
+    #[test]
+    fn parses_protocol_override() {
+        let parse =
+            |protocol_override: &str| parse_chain(json!({ "protocolOverride": protocol_override }));
+        assert_eq!(
+            parse_chain(json!({})).unwrap().domain.domain_protocol(),
+            HyperlaneDomainProtocol::Ethereum
+        );
+        let chain = parse("cosmos").unwrap();
+        assert_eq!(
+            chain.domain.domain_protocol(),
+            HyperlaneDomainProtocol::Cosmos
+        );
+        // the connection is still built from the `protocol`
+        assert!(matches!(chain.connection, ChainConnectionConf::Ethereum(_)));
+
+        let err = parse("bitcoin").unwrap_err().to_string();
+        assert!(err.contains("config_path: `protocolOverride`"));
+    }
+
+    #[test]
+    fn parses_batch_flush_interval() {
+        let parse =
+            |interval: serde_json::Value| parse_chain(json!({ "batchFlushInterval": interval }));
+        assert_eq!(
+            parse_chain(json!({})).unwrap().batch_flush_interval,
+            DEFAULT_BATCH_FLUSH_INTERVAL
+        );
+        assert_eq!(
+            parse(json!("500ms")).unwrap().batch_flush_interval,
+            Duration::from_millis(500)
+        );
+        assert_eq!(
+            parse(json!(2)).unwrap().batch_flush_interval,
+            Duration::from_secs(2)
+        );
+
+        for invalid in [json!(0), json!("0s"), json!("soon")] {
+            let err = parse(invalid).unwrap_err().to_string();
+            assert!(err.contains("config_path: `batchFlushInterval`"));
+        }
+    }
+
+    #[test]
+    fn parses_contract_deployed_blocks() {
+        let parse = |igp: serde_json::Value| {
+            parse_chain(json!({
+                "addresses": {
+                    "mailbox": "0x1111111111111111111111111111111111111111",
+                    "interchainGasPaymaster": igp,
+                    "validatorAnnounce": "0x3333333333333333333333333333333333333333",
+                },
+                "index": { "from": 10 },
+            }))
+        };
+        let chain = parse(json!({
+            "address": "0x2222222222222222222222222222222222222222",
+            "deployedBlock": "1000",
+        }))
+        .unwrap();
+        assert!(!chain.addresses.interchain_gas_paymaster.is_zero());
+        assert_eq!(
+            chain
+                .contract_index_settings("interchain_gas_paymaster")
+                .from,
+            IndexFrom::Height(1000)
+        );
+        // contracts without a deployment block start at `index.from`
+        assert_eq!(
+            chain.contract_index_settings("mailbox").from,
+            IndexFrom::Height(10)
+        );
+
+        let err = parse(json!({
+            "address": "0x2222222222222222222222222222222222222222",
+            "deployedBlock": "soon",
+        }))
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("config_path: `addresses.interchainGasPaymaster.deployedBlock`"));
+    }
+
+    #[test]
+    fn parses_connection_max_connections() {
+        let parse = |connection_type: &str,
+                     max_connections: serde_json::Value|
+         -> ConfigResult<h_eth::ConnectionConf> {
+            serde_json::from_value::<h_eth::RawConnectionConf>(json!({
+                "type": connection_type,
+                "url": "http://localhost:8545",
+                "urls": "http://localhost:8545,http://localhost:8546",
+                "maxConnections": max_connections,
+            }))
+            .unwrap()
+            .parse_config(&ConfigPath::default())
+        };
+        let h_eth::ConnectionConf::Http {
+            max_connections, ..
+        } = parse("http", json!("64")).unwrap()
+        else {
+            panic!("Expected an http connection");
+        };
+        assert_eq!(max_connections, Some(64));
+
+        let h_eth::ConnectionConf::HttpQuorum {
+            max_connections, ..
+        } = parse("httpQuorum", json!(8)).unwrap()
+        else {
+            panic!("Expected a quorum connection");
+        };
+        assert_eq!(max_connections, Some(8));
+
+        let h_eth::ConnectionConf::Http {
+            max_connections, ..
+        } = parse("http", serde_json::Value::Null).unwrap()
+        else {
+            panic!("Expected an http connection");
+        };
+        assert_eq!(max_connections, None);
+
+        for invalid in [json!(0), json!(-1), json!("many")] {
+            let err = parse("http", invalid).unwrap_err().to_string();
+            assert!(err.contains("config_path: `maxConnections`"));
+        }
+    }
+
+    #[test]
+    fn parses_testnet_flag() {
+        assert!(!parse_chain(json!({})).unwrap().testnet);
+
+        // zero finality is only warned about on mainnets
+        let chain = parse_chain(json!({ "finalityBlocks": 0 })).unwrap();
+        assert_eq!(chain.finality, Finality::Blocks(0));
+        let chain = parse_chain(json!({ "confirmations": "0" })).unwrap();
+        assert_eq!(chain.finality, Finality::Blocks(0));
+
+        let chain = parse_chain(json!({ "finalityBlocks": 0, "testnet": true })).unwrap();
+        assert!(chain.testnet);
+        assert_eq!(chain.finality, Finality::Blocks(0));
+        assert!(parse_chain(json!({ "finalityBlocks": 0, "assumeInstantFinality": true })).is_ok());
+    }
+
+    #[test]
+    fn rejects_oversized_max_batch_size() {
+        let parse = |max_batch_size: serde_json::Value| {
+            parse_chain(json!({ "maxBatchSize": max_batch_size }))
+        };
+        assert_eq!(
+            parse(json!(MAX_BATCH_SIZE_LIMIT)).unwrap().max_batch_size,
+            MAX_BATCH_SIZE_LIMIT
+        );
+        for max_batch_size in [json!(MAX_BATCH_SIZE_LIMIT + 1), json!("4294967295")] {
+            let err = parse(max_batch_size).unwrap_err().to_string();
+            assert!(err.contains("config_path: `maxBatchSize`"));
+        }
+    }
+
+    #[test]
+    fn parses_metrics_labels() {
+        let parse = |labels: serde_json::Value| parse_chain(json!({ "metricsLabels": labels }));
+        let chain = parse(json!({ "environment": "staging", "region": "eu" })).unwrap();
+        let labels = chain.metrics_conf.chain.unwrap().labels;
+        assert_eq!(labels["environment"], "staging");
+        assert_eq!(labels["region"], "eu");
+
+        for name in [
+            "chain",
+            "agent",
+            "wallet_name",
+            "__name__",
+            "1region",
+            "re-gion",
+        ] {
+            let err = parse(json!({ name: "x" })).unwrap_err().to_string();
+            assert!(
+                err.contains("config_path: `metricsLabels`"),
+                "{name}: {err}"
+            );
+        }
+    }
+}
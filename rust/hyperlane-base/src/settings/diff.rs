@@ -0,0 +1,217 @@
+use std::collections::BTreeMap;
+
+use itertools::Itertools;
+use serde_json::Value;
+
+use crate::settings::{ChainConf, Settings};
+
+/// The changes between two [`Settings`], e.g. so a config reload only has to
+/// re-initialize the chains it affects.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SettingsDiff {
+    /// Chains which are only in the new settings
+    pub added_chains: Vec<String>,
+    /// Chains which are only in the old settings
+    pub removed_chains: Vec<String>,
+    /// Chains in both settings with the paths of their changed fields, e.g.
+    /// `index.chunk`
+    pub changed_chains: BTreeMap<String, Vec<String>>,
+    /// Paths of changed settings which are not specific to a chain, e.g.
+    /// `metricsAddr`
+    pub changed_fields: Vec<String>,
+}
+
+impl SettingsDiff {
+    /// Whether the settings are the same.
+    pub fn is_empty(&self) -> bool {
+        self.added_chains.is_empty()
+            && self.removed_chains.is_empty()
+            && self.changed_chains.is_empty()
+            && self.changed_fields.is_empty()
+    }
+
+    /// The chains which were added, removed or changed.
+    pub fn affected_chains(&self) -> impl Iterator<Item = &str> {
+        self.added_chains
+            .iter()
+            .chain(&self.removed_chains)
+            .chain(self.changed_chains.keys())
+            .map(String::as_str)
+    }
+}
+
+impl Settings {
+    /// Compare these settings with `other`, e.g. a freshly loaded config.
+    /// Fields are compared by their value in the effective config, except for
+    /// signers whose secrets are redacted there.
+    pub fn diff(&self, other: &Settings) -> SettingsDiff {
+        let mut diff = SettingsDiff {
+            added_chains: other
+                .chains
+                .keys()
+                .filter(|name| !self.chains.contains_key(*name))
+                .cloned()
+                .sorted()
+                .collect(),
+            removed_chains: self
+                .chains
+                .keys()
+                .filter(|name| !other.chains.contains_key(*name))
+                .cloned()
+                .sorted()
+                .collect(),
+            ..Default::default()
+        };
+        for (name, chain) in &self.chains {
+            let Some(other_chain) = other.chains.get(name) else {
+                continue;
+            };
+            let changed = chain.changed_fields(other_chain);
+            if !changed.is_empty() {
+                diff.changed_chains.insert(name.clone(), changed);
+            }
+        }
+
+        let without_chains = |settings: &Settings| {
+            let mut json = settings.to_effective_json();
+            if let Value::Object(fields) = &mut json {
+                fields.remove("chains");
+            }
+            json
+        };
+        diff_json(
+            "",
+            &without_chains(self),
+            &without_chains(other),
+            &mut diff.changed_fields,
+        );
+        diff
+    }
+}
+
+impl ChainConf {
+    /// The paths of the fields which differ from `other`.
+    fn changed_fields(&self, other: &ChainConf) -> Vec<String> {
+        let mut changed = Vec::new();
+        diff_json(
+            "",
+            &self.to_effective_json(),
+            &other.to_effective_json(),
+            &mut changed,
+        );
+        let debug = |chain: &ChainConf| {
+            let role_signers = chain
+                .role_signers
+                .iter()
+                .map(|(role, signer)| (role.as_str(), format!("{signer:?}")))
+                .sorted()
+                .collect_vec();
+            (format!("{:?}", chain.signer), role_signers)
+        };
+        let (signer, role_signers) = debug(self);
+        let (other_signer, other_role_signers) = debug(other);
+        if signer != other_signer && !changed.iter().any(|p| p.starts_with("signer")) {
+            changed.push("signer".to_owned());
+        }
+        if role_signers != other_role_signers
+            && !changed.iter().any(|p| p.starts_with("roleSigners"))
+        {
+            changed.push("roleSigners".to_owned());
+        }
+        // urls are redacted down to their host, e.g. a rotated api key in the
+        // path only shows up here
+        if format!("{:?}", self.connection) != format!("{:?}", other.connection)
+            && !changed.iter().any(|p| p.starts_with("connection"))
+        {
+            changed.push("connection".to_owned());
+        }
+        changed.sort();
+        changed
+    }
+}
+
+/// Collect the paths at which two JSON values differ, objects are compared
+/// field by field.
+fn diff_json(path: &str, old: &Value, new: &Value, changed: &mut Vec<String>) {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            let keys = old.keys().chain(new.keys()).unique();
+            for key in keys {
+                let path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                diff_json(
+                    &path,
+                    old.get(key).unwrap_or(&Value::Null),
+                    new.get(key).unwrap_or(&Value::Null),
+                    changed,
+                );
+            }
+        }
+        (old, new) if old != new => changed.push(path.to_owned()),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use hyperlane_core::config::{ConfigPath, IntoParsedConf};
+    use serde_json::json;
+
+    use super::*;
+    use crate::settings::{deprecated_parser::DeprecatedRawSettings, test_utils::test_chain};
+
+    fn settings(raw: Value) -> Settings {
+        serde_json::from_value::<DeprecatedRawSettings>(raw)
+            .unwrap()
+            .parse_config::<Settings>(&ConfigPath::default())
+            .unwrap()
+    }
+
+    fn chain(name: &str, domain: u32, chunk: u32, key: &str) -> Value {
+        test_chain(json!({
+            "name": name,
+            "domain": domain,
+            "signer": { "type": "hexKey", "key": key },
+            "index": { "chunk": chunk },
+        }))
+    }
+
+    #[test]
+    fn diffs_settings() {
+        let key1 = format!("0x{}", "01".repeat(32));
+        let key2 = format!("0x{}", "02".repeat(32));
+        let old = settings(json!({
+            "chains": {
+                "test1": chain("test1", 13371, 1999, &key1),
+                "test2": chain("test2", 13372, 1999, &key1),
+            },
+        }));
+        assert!(old.diff(&old).is_empty());
+
+        let new = settings(json!({
+            "chains": {
+                "test1": chain("test1", 13371, 500, &key2),
+                "test3": chain("test3", 13373, 1999, &key1),
+            },
+            "metricsPrefix": "hyp",
+        }));
+        let diff = old.diff(&new);
+        assert_eq!(diff.added_chains, vec!["test3"]);
+        assert_eq!(diff.removed_chains, vec!["test2"]);
+        assert_eq!(
+            diff.changed_chains,
+            BTreeMap::from([(
+                "test1".to_owned(),
+                vec!["index.chunk".to_owned(), "signer".to_owned()]
+            )])
+        );
+        assert_eq!(diff.changed_fields, vec!["metricsPrefix"]);
+        assert_eq!(
+            diff.affected_chains().sorted().collect_vec(),
+            vec!["test1", "test2", "test3"]
+        );
+    }
+}
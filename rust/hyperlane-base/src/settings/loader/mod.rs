@@ -1,8 +1,21 @@
 //! Load a settings object from the config locations.
 
-use std::{collections::HashMap, env, error::Error, fmt::Debug, path::PathBuf};
+use std::{
+    collections::HashMap,
+    env,
+    error::Error,
+    ffi::OsStr,
+    fmt::Debug,
+    fs, iter,
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
 
-use config::{Config, Environment as DeprecatedEnvironment, File};
+use config::{
+    builder::{ConfigBuilder, DefaultState},
+    Config, Environment as DeprecatedEnvironment, File, FileFormat, FileSourceFile,
+};
 use convert_case::{Case, Casing};
 use eyre::{bail, Context, Result};
 use hyperlane_core::config::*;
@@ -15,6 +28,9 @@ mod arguments;
 mod deprecated_arguments;
 mod environment;
 
+/// How long to wait for a config served over http before giving up.
+const CONFIG_FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
 /// Deserialize a settings object from the configs.
 pub fn load_settings<T, R>(name: &str) -> ConfigResult<R>
 where
@@ -48,7 +64,9 @@ where
     let mut base_config_sources = vec![];
     let mut builder = Config::builder();
 
-    // Always load the default config files (`rust/config/*.json`)
+    // Always load the default config files (`rust/config/*.json` and
+    // `rust/config/*.toml`)
+    let mut files = Vec::new();
     for entry in PathBuf::from("./config")
         .read_dir()
         .expect("Failed to open config directory")
@@ -58,26 +76,42 @@ where
             continue;
         }
 
-        let fname = entry.file_name();
-        let ext = fname.to_str().unwrap().split('.').last().unwrap_or("");
-        if ext == "json" {
-            base_config_sources.push(format!("{:?}", entry.path()));
-            builder = builder.add_source(File::from(entry.path()));
+        if config_file_format(&entry.path()).is_ok() {
+            expand_includes(&entry.path(), &mut Vec::new(), &mut files)?;
         }
     }
+    for file in unique_config_files(files) {
+        base_config_sources.push(format!("{file:?}"));
+        builder = add_config_file(builder, &file)?;
+    }
 
     // Load a set of additional user specified config files
     let config_file_paths: Vec<String> = env::var("CONFIG_FILES")
         .map(|s| s.split(',').map(|s| s.to_owned()).collect())
         .unwrap_or_default();
 
+    let mut loaded_config_files = vec![];
     for path in &config_file_paths {
+        if is_config_url(path) {
+            let body = fetch_config(path)?;
+            loaded_config_files.push(path.clone());
+            builder = builder.add_source(File::from_str(&body, FileFormat::Json));
+            continue;
+        }
         let p = PathBuf::from(path);
         if p.is_file() {
-            if p.extension() == Some("json".as_ref()) {
-                builder = builder.add_source(File::from(p));
+            if config_file_format(&p).is_ok() {
+                let mut files = Vec::new();
+                expand_includes(&p, &mut Vec::new(), &mut files)?;
+                for file in files {
+                    loaded_config_files.push(format!("{file:?}"));
+                    builder = add_config_file(builder, &file)?;
+                }
             } else {
-                bail!("Provided config path via CONFIG_FILES is of an unsupported type ({p:?})")
+                bail!(
+                    "Provided config path via CONFIG_FILES is of an unsupported type ({p:?}), \
+                     expected a `.json` or `.toml` file"
+                )
             }
         } else if !p.exists() {
             bail!("Provided config path via CONFIG_FILES does not exist ({p:?})")
@@ -122,7 +156,7 @@ where
             Err(err.into())
         };
 
-        for cfg_path in base_config_sources.iter().chain(config_file_paths.iter()) {
+        for cfg_path in base_config_sources.iter().chain(loaded_config_files.iter()) {
             err = err.with_context(|| format!("Config loaded: {cfg_path}"));
         }
 
@@ -131,6 +165,129 @@ where
     })
 }
 
+/// Whether a `CONFIG_FILES` entry is a url to fetch rather than a file path.
+fn is_config_url(path: &str) -> bool {
+    path.starts_with("http://") || path.starts_with("https://")
+}
+
+/// Fetch a JSON config served over http, failing on any non-2xx response.
+fn fetch_config(url: &str) -> Result<String> {
+    let fetch = {
+        let url = url.to_owned();
+        // the blocking client can not be used from within the async runtime
+        // the agents are started in, so the request is made on its own thread
+        move || -> Result<String> {
+            let res = reqwest::blocking::Client::builder()
+                .timeout(CONFIG_FETCH_TIMEOUT)
+                .build()?
+                .get(&url)
+                .send()?;
+            let status = res.status();
+            if !status.is_success() {
+                bail!("Server responded with status {status}");
+            }
+            let body = res.text()?;
+            serde_json::from_str::<serde_json::Value>(&body).context("Invalid json in config")?;
+            Ok(body)
+        }
+    };
+    thread::spawn(fetch)
+        .join()
+        .map_err(|_| eyre::eyre!("Config fetch thread panicked"))?
+        .with_context(|| format!("Failed to fetch config via CONFIG_FILES ({url})"))
+}
+
+/// The format of a config file, given by its extension.
+fn config_file_format(path: &Path) -> Result<FileFormat> {
+    match path.extension().and_then(OsStr::to_str) {
+        Some("json") => Ok(FileFormat::Json),
+        Some("toml") => Ok(FileFormat::Toml),
+        _ => bail!("Unsupported config file ({path:?}), expected a `.json` or `.toml` file"),
+    }
+}
+
+/// A config file as a source which is decoded according to its extension.
+fn config_file_source(path: &Path) -> Result<File<FileSourceFile, FileFormat>> {
+    Ok(File::from(path).format(config_file_format(path)?))
+}
+
+/// Add a config file on top of the sources of the builder. Errors parsing the
+/// file or merging it with the earlier sources name the file.
+fn add_config_file(
+    builder: ConfigBuilder<DefaultState>,
+    path: &Path,
+) -> Result<ConfigBuilder<DefaultState>> {
+    let builder = builder.add_source(config_file_source(path)?);
+    builder
+        .build_cloned()
+        .with_context(|| format!("Failed to load config ({path:?})"))?;
+    Ok(builder)
+}
+
+/// Decode the contents of a config file into JSON, e.g. to read its
+/// `includes`.
+fn decode_config_file(path: &Path, contents: &str) -> Result<serde_json::Value> {
+    match config_file_format(path)? {
+        FileFormat::Toml => Config::builder()
+            .add_source(File::from_str(contents, FileFormat::Toml))
+            .build()
+            .and_then(Config::try_deserialize)
+            .with_context(|| format!("Invalid toml in config ({path:?})")),
+        _ => serde_json::from_str(contents)
+            .with_context(|| format!("Invalid json in config ({path:?})")),
+    }
+}
+
+/// Expand a config file into the list of files to load: the files listed in
+/// its `includes` array followed by the file itself, so it overrides keys set
+/// by the files it includes. Include paths are relative to the file which
+/// lists them.
+fn expand_includes(path: &Path, stack: &mut Vec<PathBuf>, files: &mut Vec<PathBuf>) -> Result<()> {
+    let canonical = path
+        .canonicalize()
+        .with_context(|| format!("Config file does not exist ({path:?})"))?;
+    if stack.contains(&canonical) {
+        let cycle = stack
+            .iter()
+            .chain(iter::once(&canonical))
+            .map(|p| format!("{p:?}"))
+            .join(" -> ");
+        bail!("Cyclic config includes: {cycle}");
+    }
+
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("Failed to read config ({path:?})"))?;
+    let value = decode_config_file(path, &contents)?;
+    let includes: Vec<String> = value
+        .get("includes")
+        .map(|v| serde_json::from_value(v.clone()))
+        .transpose()
+        .with_context(|| {
+            format!("Invalid `includes` in config ({path:?}), expected a list of paths")
+        })?
+        .unwrap_or_default();
+
+    stack.push(canonical);
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    for include in includes {
+        expand_includes(&dir.join(include), stack, files)
+            .with_context(|| format!("Included from config ({path:?})"))?;
+    }
+    stack.pop();
+    files.push(path.to_owned());
+    Ok(())
+}
+
+/// Drop every file which is already earlier in the list, e.g. a file in the
+/// config directory which is also included by another one there, so it is
+/// only loaded once.
+fn unique_config_files(files: Vec<PathBuf>) -> Vec<PathBuf> {
+    files
+        .into_iter()
+        .unique_by(|file| file.canonicalize().unwrap_or_else(|_| file.clone()))
+        .collect()
+}
+
 /// Load a settings object from the config locations and re-join the components with the standard
 /// `config` crate separator `.`.
 fn split_and_recase_key(sep: &str, case: Option<Case>, key: String) -> String {
@@ -145,3 +302,167 @@ fn split_and_recase_key(sep: &str, case: Option<Case>, key: String) -> String {
         key
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn expands_config_includes() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, contents: &str| {
+            fs::write(dir.path().join(name), contents).unwrap();
+            dir.path().join(name)
+        };
+        let root = write("root.json", r#"{ "includes": ["a.json", "b.json"] }"#);
+        write("a.json", r#"{ "includes": ["c.json"] }"#);
+        write("b.json", "{}");
+        write("c.json", "{}");
+
+        let mut files = Vec::new();
+        expand_includes(&root, &mut Vec::new(), &mut files).unwrap();
+        let names = files
+            .iter()
+            .map(|f| f.file_name().unwrap().to_str().unwrap())
+            .collect_vec();
+        assert_eq!(names, ["c.json", "a.json", "b.json", "root.json"]);
+
+        write("c.json", r#"{ "includes": ["root.json"] }"#);
+        let err = expand_includes(&root, &mut Vec::new(), &mut Vec::new()).unwrap_err();
+        assert!(format!("{err:?}").contains("Cyclic config includes"));
+    }
+
+    #[test]
+    fn loads_included_config_files_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, contents: &str| {
+            fs::write(dir.path().join(name), contents).unwrap();
+            dir.path().join(name)
+        };
+        let a = write("a.json", r#"{ "includes": ["./shared.json"] }"#);
+        let b = write("b.json", r#"{ "includes": ["shared.json"] }"#);
+        let shared = write("shared.json", "{}");
+
+        let mut files = Vec::new();
+        for file in [&a, &shared, &b] {
+            expand_includes(file, &mut Vec::new(), &mut files).unwrap();
+        }
+        assert_eq!(files.len(), 5);
+        let names = unique_config_files(files)
+            .iter()
+            .map(|f| f.file_name().unwrap().to_str().unwrap().to_owned())
+            .collect_vec();
+        assert_eq!(names, ["shared.json", "a.json", "b.json"]);
+    }
+
+    #[test]
+    fn includer_overrides_included_configs() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, contents: &str| {
+            fs::write(dir.path().join(name), contents).unwrap();
+            dir.path().join(name)
+        };
+        let root = write(
+            "root.json",
+            r#"{ "includes": ["a.json"], "chains": { "test1": { "finalityBlocks": 20 } } }"#,
+        );
+        write(
+            "a.json",
+            r#"{ "chains": { "test1": { "finalityBlocks": 10, "domain": 13371 } } }"#,
+        );
+
+        let mut files = Vec::new();
+        expand_includes(&root, &mut Vec::new(), &mut files).unwrap();
+        let mut builder = Config::builder();
+        for file in &files {
+            builder = add_config_file(builder, file).unwrap();
+        }
+        let config = builder.build().unwrap();
+        assert_eq!(
+            config.get::<u32>("chains.test1.finalityBlocks").unwrap(),
+            20
+        );
+        assert_eq!(config.get::<u32>("chains.test1.domain").unwrap(), 13371);
+
+        let broken = write("broken.json", r#"{ "chains": "#);
+        let err = format!(
+            "{:?}",
+            add_config_file(Config::builder(), &broken).unwrap_err()
+        );
+        assert!(err.contains("broken.json"));
+    }
+
+    #[test]
+    fn fetches_config_from_url() {
+        use std::{
+            io::{Read, Write},
+            net::TcpListener,
+        };
+
+        // serve a single canned response on a local port
+        let serve = |response: &'static str| {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let url = format!("http://{}/config.json", listener.local_addr().unwrap());
+            thread::spawn(move || {
+                let (mut stream, _) = listener.accept().unwrap();
+                let _ = stream.read(&mut [0; 1024]);
+                stream.write_all(response.as_bytes()).unwrap();
+            });
+            url
+        };
+
+        assert!(is_config_url("https://config.local/relayer.json"));
+        assert!(!is_config_url("./config/relayer.json"));
+
+        let url = serve(
+            "HTTP/1.1 200 OK\r\nContent-Length: 11\r\nConnection: close\r\n\r\n{\"a\": true}",
+        );
+        assert_eq!(fetch_config(&url).unwrap(), r#"{"a": true}"#);
+
+        let url = serve("HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+        let err = format!("{:?}", fetch_config(&url).unwrap_err());
+        assert!(err.contains("404"));
+        assert!(err.contains(&url));
+    }
+
+    #[test]
+    fn decodes_toml_configs() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, contents: &str| {
+            fs::write(dir.path().join(name), contents).unwrap();
+            dir.path().join(name)
+        };
+        let root = write(
+            "root.toml",
+            "includes = [\"a.json\"]\n\n[chains.test1]\nfinalityBlocks = 20\n",
+        );
+        write(
+            "a.json",
+            r#"{ "chains": { "test1": { "finalityBlocks": 20 } } }"#,
+        );
+
+        let mut files = Vec::new();
+        expand_includes(&root, &mut Vec::new(), &mut files).unwrap();
+        let names = files
+            .iter()
+            .map(|f| f.file_name().unwrap().to_str().unwrap())
+            .collect_vec();
+        assert_eq!(names, ["a.json", "root.toml"]);
+
+        // both formats produce the same keys for the raw settings
+        let decode = |path: &Path| {
+            Config::builder()
+                .add_source(config_file_source(path).unwrap())
+                .build()
+                .unwrap()
+                .get::<serde_json::Value>("chains")
+                .unwrap()
+        };
+        assert_eq!(decode(&files[0]), decode(&files[1]));
+
+        let yaml = write("b.yaml", "chains: {}");
+        let err = format!("{:?}", config_file_source(&yaml).unwrap_err());
+        assert!(err.contains("expected a `.json` or `.toml` file"));
+        assert!(config_file_format(Path::new("config")).is_err());
+    }
+}
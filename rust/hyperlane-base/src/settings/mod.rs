@@ -75,10 +75,28 @@
 //!    E.g. `export HYP_RELAYER_ORIGINCHAIN="ethereum"`
 //! 5. Arguments passed to the agent on the command line.
 //!    E.g. `--originChainName ethereum`
+//!
+//! Any config file may list further files to load in an `includes` array,
+//! e.g. `"includes": ["chains/mainnet.json"]`. Paths are relative to the file
+//! listing them, and each included file is loaded directly before the file
+//! that includes it, so the includer's keys take precedence. Cyclic includes
+//! are an error.
+//!
+//! Entries in `CONFIG_FILES` may also be `http://` or `https://` urls, in
+//! which case the JSON config is fetched from the url when the agent starts.
+//! Remote configs can not use `includes`.
+//!
+//! Config files may be written in TOML instead of JSON, the format is chosen
+//! by the `.json` or `.toml` extension of each file. Keys are the same in both
+//! formats, e.g. `[chains.ethereum]` with `finalityBlocks = 20`. Files with
+//! any other extension are skipped in `config/` and rejected in
+//! `CONFIG_FILES`.
 
 pub use base::*;
+pub use builder::*;
 pub use chains::*;
 pub use checkpoint_syncer::*;
+pub use diff::*;
 /// Export this so they don't need to import paste.
 #[doc(hidden)]
 pub use paste;
@@ -86,22 +104,34 @@ pub use signers::*;
 pub use trace::*;
 
 mod envs {
+    pub use hyperlane_cosmos as h_cosmos;
     pub use hyperlane_ethereum as h_eth;
     pub use hyperlane_fuel as h_fuel;
     pub use hyperlane_sealevel as h_sealevel;
+    pub use hyperlane_starknet as h_starknet;
 }
 
 /// AWS Credentials provider.
 pub(crate) mod aws_credentials;
 mod base;
+/// Programmatic construction of settings
+mod builder;
 /// Chain configuration
 mod chains;
+/// Differences between two configurations
+mod diff;
+/// Redacted view of the parsed configuration
+mod effective;
 pub mod loader;
 /// Signer configuration
 mod signers;
 /// Tracing subscriber management
 mod trace;
 
+/// Settings test utilities.
+#[cfg(test)]
+mod test_utils;
+
 mod checkpoint_syncer;
 pub mod deprecated_parser;
 pub mod parser;
@@ -10,21 +10,44 @@ use std::{
     cmp::Reverse,
     collections::{HashMap, HashSet},
     default::Default,
+    net::SocketAddr,
+    time::Duration,
 };
 
+use ethers_prometheus::middleware::{ChainInfo, PrometheusMiddlewareConf};
 use eyre::{eyre, Context};
 use hyperlane_core::{
-    cfg_unwrap_all, config::*, HyperlaneDomain, HyperlaneDomainProtocol, IndexMode,
+    cfg_unwrap_all, config::*, HyperlaneDomain, HyperlaneDomainProtocol, IndexMode, H256,
 };
 use itertools::Itertools;
+use reqwest::Url;
 use serde::Deserialize;
 use serde_json::Value;
+use tracing::warn;
 
 pub use self::json_value_parser::ValueParser;
 pub use super::envs::*;
 use crate::settings::{
-    chains::IndexSettings, parser::json_value_parser::ParseChain, trace::TracingConfig, ChainConf,
-    ChainConnectionConf, CoreContractAddresses, Settings, SignerConf,
+    base::{check_unique_domain_ids, parse_metrics_prefix},
+    chains::{
+        check_mainnet_finality, is_ens_name, parse_batch_flush_interval, parse_batch_strategy,
+        parse_chunk_bound, parse_confirmation_timeout, parse_event_topic, parse_finality,
+        parse_gas_limit_multiplier, parse_index_chunk, parse_index_from, parse_index_modes,
+        parse_max_reorg_depth, parse_metrics_labels, parse_native_token_decimals,
+        parse_nonce_strategy, parse_poll_interval, parse_tx_type, validate_ens_names, EnsName,
+        Finality, IndexChunkUnit, IndexSettings, NativeToken, DEFAULT_BATCH_FLUSH_INTERVAL,
+        DEFAULT_CONFIRMATION_TIMEOUT, DEFAULT_MAX_BATCH_SIZE, DEFAULT_MAX_IN_FLIGHT_TRANSACTIONS,
+        DEFAULT_MAX_REORG_DEPTH, DEFAULT_MIN_CHUNK_SIZE, DEFAULT_POLL_INTERVAL,
+        MAX_BATCH_SIZE_LIMIT,
+    },
+    parser::json_value_parser::ParseChain,
+    signers::{
+        check_relay_service_signer, parse_aws_profile, parse_aws_region,
+        parse_external_signer_timeout, parse_relay_service_endpoint, parse_role_arn,
+        DEFAULT_EXTERNAL_SIGNER_TIMEOUT,
+    },
+    trace::{parse_log_format, parse_log_targets, parse_sampling_rate, TracingConfig},
+    ChainConf, ChainConnectionConf, CoreContractAddresses, Settings, SignerConf,
 };
 
 mod json_value_parser;
@@ -49,12 +72,29 @@ impl FromRawConf<RawAgentConf, Option<&HashSet<&str>>> for Settings {
             .get_opt_key("metricsPort")
             .parse_u16()
             .unwrap_or(9090);
+        let metrics_addr = SocketAddr::from(([0, 0, 0, 0], metrics_port));
+        let health_addr = p
+            .chain(&mut err)
+            .get_opt_key("healthPort")
+            .parse_u16()
+            .map(|port| SocketAddr::from(([0, 0, 0, 0], port)))
+            .end();
+        let metrics_prefix = p
+            .chain(&mut err)
+            .get_opt_key("metricsPrefix")
+            .parse_string()
+            .and_then(|prefix| {
+                parse_metrics_prefix(prefix).into_config_result(|| cwp + "metrics_prefix")
+            })
+            .end()
+            .flatten();
 
         let fmt = p
             .chain(&mut err)
             .get_opt_key("log")
             .get_opt_key("format")
-            .parse_value("Invalid log format")
+            .parse_string()
+            .and_then(|v| parse_log_format(v).into_config_result(|| cwp + "log" + "format"))
             .unwrap_or_default();
 
         let level = p
@@ -64,6 +104,23 @@ impl FromRawConf<RawAgentConf, Option<&HashSet<&str>>> for Settings {
             .parse_value("Invalid log level")
             .unwrap_or_default();
 
+        let sampling_rate = p
+            .chain(&mut err)
+            .get_opt_key("log")
+            .get_opt_key("samplingRate")
+            .parse_f64()
+            .and_then(|v| {
+                parse_sampling_rate(v).into_config_result(|| cwp + "log" + "sampling_rate")
+            })
+            .unwrap_or(1.0);
+
+        let targets = p
+            .chain(&mut err)
+            .get_opt_key("log")
+            .get_opt_key("targets")
+            .and_then(|v| parse_log_targets(v.val).into_config_result(|| cwp + "log"))
+            .unwrap_or_default();
+
         let raw_chains: Vec<(String, ValueParser)> = if let Some(filter) = filter {
             p.chain(&mut err)
                 .get_opt_key("chains")
@@ -91,17 +148,27 @@ impl FromRawConf<RawAgentConf, Option<&HashSet<&str>>> for Settings {
                     .map(|v| (name, v))
             })
             .map(|(name, mut chain)| {
-                if let Some(default_signer) = &default_signer {
+                if chain.index_only {
+                    chain.signer.get_or_insert(SignerConf::Node);
+                } else if let Some(default_signer) = &default_signer {
                     chain.signer.get_or_insert_with(|| default_signer.clone());
                 }
                 (name, chain)
             })
             .collect();
+        check_unique_domain_ids(&chains).take_err(&mut err, || cwp + "chains");
 
         err.into_result(Self {
             chains,
-            metrics_port,
-            tracing: TracingConfig { fmt, level },
+            metrics_addr,
+            metrics_prefix,
+            health_addr,
+            tracing: TracingConfig {
+                fmt,
+                level,
+                targets,
+                sampling_rate,
+            },
         })
     }
 }
@@ -119,12 +186,172 @@ fn parse_chain(chain: ValueParser, name: &str) -> ConfigResult<ChainConf> {
 
     // TODO(2214): is it correct to define finality blocks as `confirmations` and not `reorgPeriod`?
     // TODO(2214): should we rename `finalityBlocks` in ChainConf?
-    let finality_blocks = chain
+    let finality = chain
         .chain(&mut err)
         .get_opt_key("blocks")
         .get_key("confirmations")
+        .and_then(|v| {
+            v.parse_value::<StrOrInt>("Invalid confirmations")
+                .and_then(|f| parse_finality(&f).into_config_result(|| v.cwp.clone()))
+                .map(|f| (f, v.cwp.clone()))
+        });
+    let assume_instant_finality = chain
+        .chain(&mut err)
+        .get_opt_key("assumeInstantFinality")
+        .parse_bool()
+        .unwrap_or(false);
+    let testnet = chain
+        .chain(&mut err)
+        .get_opt_key("testnet")
+        .parse_bool()
+        .unwrap_or(false);
+    if let Some((finality, path)) = finality.as_ref().filter(|_| !assume_instant_finality) {
+        if let Err(e) = check_mainnet_finality(finality) {
+            if !testnet {
+                warn!(%path, "{e}");
+            }
+        }
+    }
+    let finality = if assume_instant_finality {
+        Finality::Blocks(0)
+    } else {
+        finality.map(|(f, _)| f).unwrap_or(Finality::Blocks(1))
+    };
+    let finality_blocks = match finality {
+        Finality::Blocks(blocks) => blocks,
+        Finality::Time(_) | Finality::Tag(_) => 0,
+    };
+    let reorg_period = chain
+        .chain(&mut err)
+        .get_opt_key("blocks")
+        .get_opt_key("reorgPeriod")
         .parse_u32()
-        .unwrap_or(1);
+        .unwrap_or(finality_blocks);
+
+    let max_batch_size = chain
+        .chain(&mut err)
+        .get_opt_key("maxBatchSize")
+        .parse_u32()
+        .and_then(|v| {
+            if v == 0 {
+                Err(eyre!("`maxBatchSize` must be greater than 0"))
+                    .into_config_result(|| &chain.cwp + "max_batch_size")
+            } else if v > MAX_BATCH_SIZE_LIMIT {
+                Err(eyre!(
+                    "`maxBatchSize` must be at most {MAX_BATCH_SIZE_LIMIT}"
+                ))
+                .into_config_result(|| &chain.cwp + "max_batch_size")
+            } else {
+                Ok(v)
+            }
+        })
+        .unwrap_or(DEFAULT_MAX_BATCH_SIZE);
+    let max_in_flight_transactions = chain
+        .chain(&mut err)
+        .get_opt_key("maxInFlightTransactions")
+        .parse_u32()
+        .and_then(|v| {
+            if v == 0 {
+                Err(eyre!("`maxInFlightTransactions` must be greater than 0"))
+                    .into_config_result(|| &chain.cwp + "max_in_flight_transactions")
+            } else {
+                Ok(v)
+            }
+        })
+        .unwrap_or(DEFAULT_MAX_IN_FLIGHT_TRANSACTIONS);
+
+    let batch_flush_interval = chain
+        .chain(&mut err)
+        .get_opt_key("batchFlushInterval")
+        .and_then(|v| {
+            v.parse_value::<StrOrInt>("Invalid batch flush interval")
+                .and_then(|i| parse_batch_flush_interval(&i).into_config_result(|| v.cwp.clone()))
+        })
+        .unwrap_or(DEFAULT_BATCH_FLUSH_INTERVAL);
+
+    let poll_interval = chain
+        .chain(&mut err)
+        .get_opt_key("pollInterval")
+        .and_then(|v| {
+            v.parse_value::<StrOrInt>("Invalid poll interval")
+                .and_then(|i| parse_poll_interval(&i).into_config_result(|| v.cwp.clone()))
+        })
+        .unwrap_or(DEFAULT_POLL_INTERVAL);
+
+    let index_only = chain
+        .chain(&mut err)
+        .get_opt_key("indexOnly")
+        .parse_bool()
+        .unwrap_or(false);
+
+    // index only chains are never submitted to, so their gas settings are not
+    // validated
+    let max_gas_limit = if index_only {
+        None
+    } else {
+        chain
+            .chain(&mut err)
+            .get_opt_key("maxGasLimit")
+            .parse_u256()
+            .and_then(|v| {
+                if v.is_zero() {
+                    Err(eyre!("`maxGasLimit` must be greater than 0"))
+                        .into_config_result(|| &chain.cwp + "max_gas_limit")
+                } else {
+                    Ok(v)
+                }
+            })
+            .end()
+    };
+    let gas_limit_multiplier = if index_only {
+        1.0
+    } else {
+        chain
+            .chain(&mut err)
+            .get_opt_key("gasLimitMultiplier")
+            .parse_f64()
+            .and_then(|v| {
+                parse_gas_limit_multiplier(v)
+                    .into_config_result(|| &chain.cwp + "gas_limit_multiplier")
+            })
+            .unwrap_or(1.0)
+    };
+
+    let gas_oracle = if index_only {
+        h_eth::GasOracleConf::default()
+    } else {
+        chain
+            .chain(&mut err)
+            .get_opt_key("gasOracle")
+            .and_then(|oracle| {
+                let mut err = ConfigParsingError::default();
+                let min_gas_price = oracle
+                    .chain(&mut err)
+                    .get_opt_key("minGasPrice")
+                    .parse_u256()
+                    .end();
+                let max_gas_price = oracle
+                    .chain(&mut err)
+                    .get_opt_key("maxGasPrice")
+                    .parse_u256()
+                    .end();
+                let gas_price_multiplier = oracle
+                    .chain(&mut err)
+                    .get_opt_key("gasPriceMultiplier")
+                    .parse_f64()
+                    .unwrap_or(1.0);
+                let conf = h_eth::GasOracleConf {
+                    min_gas_price,
+                    max_gas_price,
+                    gas_price_multiplier,
+                };
+                if err.is_ok() {
+                    conf.validate().take_err(&mut err, || oracle.cwp.clone());
+                }
+                err.into_result(conf)
+            })
+            .unwrap_or_default()
+    };
 
     let rpcs: Vec<ValueParser> =
         if let Some(custom_rpc_urls) = chain.get_opt_key("customRpcUrls").unwrap_or_default() {
@@ -168,67 +395,270 @@ fn parse_chain(chain: ValueParser, name: &str) -> ConfigResult<ChainConf> {
         .chain(&mut err)
         .get_opt_key("index")
         .get_opt_key("from")
-        .parse_u32()
-        .unwrap_or(0);
-    let chunk_size = chain
+        .parse_value::<StrOrInt>("Expected a block height or tag")
+        .and_then(|v| parse_index_from(&v).into_config_result(|| &chain.cwp + "index" + "from"))
+        .unwrap_or_default();
+    let (chunk_size, chunk_unit) = chain
         .chain(&mut err)
         .get_opt_key("index")
         .get_opt_key("chunk")
-        .parse_u32()
-        .unwrap_or(1999);
-    let mode = chain
+        .and_then(|v| {
+            v.parse_value::<StrOrInt>("Invalid index chunk size")
+                .and_then(|c| parse_index_chunk(&c).into_config_result(|| v.cwp.clone()))
+        })
+        .unwrap_or((1999, IndexChunkUnit::Blocks));
+    let block_time = chain
+        .chain(&mut err)
+        .get_opt_key("blocks")
+        .get_opt_key("estimateBlockTime")
+        .parse_f64()
+        .end()
+        .map(Duration::from_secs_f64);
+    let confirmation_timeout = chain
+        .chain(&mut err)
+        .get_opt_key("confirmationTimeout")
+        .and_then(|v| {
+            v.parse_value::<StrOrInt>("Invalid confirmation timeout")
+                .and_then(|t| {
+                    parse_confirmation_timeout(&t, block_time).into_config_result(|| v.cwp.clone())
+                })
+        })
+        .unwrap_or(DEFAULT_CONFIRMATION_TIMEOUT);
+    let modes = chain
         .chain(&mut err)
         .get_opt_key("index")
         .get_opt_key("mode")
-        .parse_value("Invalid index mode")
+        .parse_value::<Value>("Invalid index mode")
+        .and_then(|m| parse_index_modes(m).into_config_result(|| &chain.cwp + "index" + "mode"))
         .unwrap_or_else(|| {
-            domain
+            vec![domain
                 .as_ref()
                 .and_then(|d| match d.domain_protocol() {
                     HyperlaneDomainProtocol::Ethereum => Some(IndexMode::Block),
                     HyperlaneDomainProtocol::Sealevel => Some(IndexMode::Sequence),
                     _ => None,
                 })
-                .unwrap_or_default()
+                .unwrap_or_default()]
         });
 
-    let mailbox = chain
+    let event_topics = chain
         .chain(&mut err)
-        .get_key("mailbox")
-        .parse_address_hash()
-        .end();
-    let interchain_gas_paymaster = chain
+        .get_opt_key("index")
+        .get_opt_key("eventTopics")
+        .into_array_iter()
+        .map(|topics| {
+            topics
+                .filter_map(|t| {
+                    t.parse_string()
+                        .and_then(|t| {
+                            parse_event_topic(t)
+                                .into_config_result(|| &chain.cwp + "index" + "event_topics")
+                        })
+                        .take_config_err(&mut err)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let max_reorg_depth = chain
+        .chain(&mut err)
+        .get_opt_key("index")
+        .get_opt_key("maxReorgDepth")
+        .and_then(|v| {
+            v.parse_value::<StrOrInt>("Invalid max reorg depth")
+                .and_then(|d| parse_max_reorg_depth(&d).into_config_result(|| v.cwp.clone()))
+        })
+        .unwrap_or(DEFAULT_MAX_REORG_DEPTH);
+
+    let adaptive_chunking = chain
+        .chain(&mut err)
+        .get_opt_key("index")
+        .get_opt_key("adaptiveChunking")
+        .parse_bool()
+        .unwrap_or(false);
+    let parse_bound = |err: &mut ConfigParsingError, key: &str| {
+        chain
+            .chain(err)
+            .get_opt_key("index")
+            .get_opt_key(key)
+            .and_then(|v| {
+                v.parse_value::<StrOrInt>("Invalid chunk size")
+                    .and_then(|b| parse_chunk_bound(&b, key).into_config_result(|| v.cwp.clone()))
+            })
+            .end()
+    };
+    let min_chunk_size = parse_bound(&mut err, "minChunk").unwrap_or(DEFAULT_MIN_CHUNK_SIZE);
+    let max_chunk_size = parse_bound(&mut err, "maxChunk");
+    if max_chunk_size.map_or(false, |max| max < min_chunk_size) {
+        err.push(
+            &chain.cwp + "index" + "max_chunk",
+            eyre!("`maxChunk` must be at least `minChunk`"),
+        );
+    }
+
+    let mut ens_names = Vec::new();
+    let mut parse_address = |err: &mut ConfigParsingError, key: &str, contract: &'static str| {
+        chain
+            .chain(err)
+            .get_key(key)
+            .and_then(|v| match v.val {
+                Value::String(s) if is_ens_name(s) => {
+                    ens_names.push(EnsName {
+                        contract,
+                        name: s.clone(),
+                        cwp: v.cwp.clone(),
+                    });
+                    Ok(H256::zero())
+                }
+                _ => v.parse_address_hash(),
+            })
+            .end()
+    };
+    let mailbox = parse_address(&mut err, "mailbox", "mailbox");
+    let interchain_gas_paymaster = parse_address(
+        &mut err,
+        "interchainGasPaymaster",
+        "interchain_gas_paymaster",
+    );
+    let validator_announce = parse_address(&mut err, "validatorAnnounce", "validator_announce");
+
+    let mut deployed_blocks = HashMap::new();
+    for (key, contract) in [
+        ("mailbox", "mailbox"),
+        ("interchainGasPaymaster", "interchain_gas_paymaster"),
+        ("validatorAnnounce", "validator_announce"),
+    ] {
+        if let Some(block) = chain
+            .chain(&mut err)
+            .get_opt_key("deployedBlocks")
+            .get_opt_key(key)
+            .parse_u32()
+            .end()
+        {
+            deployed_blocks.insert(contract, block);
+        }
+    }
+
+    cfg_unwrap_all!(&chain.cwp, err: [domain]);
+    validate_ens_names(&ens_names, domain.domain_protocol(), &mut err);
+
+    let multicall_address = chain
         .chain(&mut err)
-        .get_key("interchainGasPaymaster")
+        .get_opt_key("multicallAddress")
         .parse_address_hash()
         .end();
-    let validator_announce = chain
+    let batch_strategy = chain
         .chain(&mut err)
-        .get_key("validatorAnnounce")
-        .parse_address_hash()
+        .get_opt_key("batchStrategy")
+        .parse_string()
         .end();
-
-    cfg_unwrap_all!(&chain.cwp, err: [domain]);
+    let batch_strategy = parse_batch_strategy(batch_strategy, multicall_address, &domain)
+        .take_err(&mut err, || &chain.cwp + "batch_strategy")
+        .unwrap_or_default();
+    let nonce_strategy = chain
+        .chain(&mut err)
+        .get_opt_key("nonceStrategy")
+        .parse_string()
+        .and_then(|v| {
+            if domain.domain_protocol() != HyperlaneDomainProtocol::Ethereum {
+                Err(eyre!(
+                    "`nonceStrategy` is only supported on ethereum chains"
+                ))
+            } else {
+                parse_nonce_strategy(v)
+            }
+            .into_config_result(|| &chain.cwp + "nonce_strategy")
+        })
+        .unwrap_or_default();
+    let tx_type = chain
+        .chain(&mut err)
+        .get_opt_key("txType")
+        .parse_string()
+        .and_then(|v| {
+            if domain.domain_protocol() != HyperlaneDomainProtocol::Ethereum {
+                Err(eyre!("`txType` is only supported on ethereum chains"))
+            } else {
+                parse_tx_type(v)
+            }
+            .into_config_result(|| &chain.cwp + "tx_type")
+        })
+        .unwrap_or_default();
 
     let connection: Option<ChainConnectionConf> = match domain.domain_protocol() {
         HyperlaneDomainProtocol::Ethereum => {
-            if rpcs.len() <= 1 {
-                let into_connection =
-                    |url| ChainConnectionConf::Ethereum(h_eth::ConnectionConf::Http { url });
-                rpcs.into_iter().next().and_then(|rpc| {
-                    rpc.chain(&mut err)
-                        .get_key("http")
-                        .parse_from_str("Invalid http url")
-                        .end()
-                        .map(into_connection)
+            let mut headers: HashMap<String, String> = chain
+                .chain(&mut err)
+                .get_opt_key("rpcHeaders")
+                .parse_value("Expected `rpcHeaders` to be a map of header names to values")
+                .unwrap_or_default();
+            chain
+                .chain(&mut err)
+                .get_opt_key("rpcUserAgent")
+                .parse_string()
+                .and_then(|user_agent| {
+                    h_eth::add_user_agent(&mut headers, user_agent)
+                        .into_config_result(|| &chain.cwp + "rpc_user_agent")
+                })
+                .end();
+            let retry = parse_retry_config(&chain)
+                .take_config_err(&mut err)
+                .unwrap_or_default();
+            let tls = chain
+                .chain(&mut err)
+                .get_opt_key("rpcTls")
+                .and_then(|tls| {
+                    tls.parse_value::<h_eth::RawTlsConfig>("Expected `rpcTls` to be an object")?
+                        .parse_config(&tls.cwp)
+                })
+                .end();
+            let request_timeout = chain
+                .chain(&mut err)
+                .get_opt_key("rpcRequestTimeout")
+                .parse_value::<StrOrInt>("Expected `rpcRequestTimeout` to be a duration")
+                .and_then(|v| {
+                    h_eth::parse_request_timeout(&v)
+                        .into_config_result(|| &chain.cwp + "rpc_request_timeout")
+                })
+                .end();
+            let max_connections = chain
+                .chain(&mut err)
+                .get_opt_key("rpcMaxConnections")
+                .parse_value::<StrOrInt>("Expected `rpcMaxConnections` to be an integer")
+                .and_then(|v| {
+                    h_eth::parse_max_connections(&v)
+                        .into_config_result(|| &chain.cwp + "rpc_max_connections")
                 })
+                .end();
+            if rpcs.len() <= 1 {
+                rpcs.into_iter()
+                    .next()
+                    .and_then(|rpc| {
+                        rpc.chain(&mut err)
+                            .get_key("http")
+                            .and_then(parse_eth_rpc_url)
+                            .end()
+                    })
+                    .and_then(|url| {
+                        h_eth::ConnectionConf::single(
+                            url,
+                            headers,
+                            tls,
+                            request_timeout,
+                            max_connections,
+                            retry,
+                        )
+                        .take_err(&mut err, || &chain.cwp + "rpcs")
+                    })
+                    .map(|conf| {
+                        ChainConnectionConf::Ethereum(h_eth::ChainConnections::shared(conf))
+                    })
             } else {
                 let urls = rpcs
                     .into_iter()
                     .filter_map(|rpc| {
                         rpc.chain(&mut err)
                             .get_key("http")
-                            .parse_from_str("Invalid http url")
+                            .and_then(parse_eth_rpc_url)
                             .end()
                     })
                     .collect_vec();
@@ -238,13 +668,55 @@ fn parse_chain(chain: ValueParser, name: &str) -> ConfigResult<ChainConf> {
                     .get_opt_key("rpcConsensusType")
                     .parse_string()
                     .unwrap_or("fallback");
+                let threshold = chain
+                    .chain(&mut err)
+                    .get_opt_key("quorumThreshold")
+                    .parse_u32()
+                    .end()
+                    .map(|t| t as usize);
                 match rpc_consensus_type {
-                    "fallback" => Some(h_eth::ConnectionConf::HttpFallback { urls }),
-                    "quorum" => Some(h_eth::ConnectionConf::HttpQuorum { urls }),
+                    "single" if urls.len() > 1 => {
+                        err.push(
+                            &chain.cwp + "rpcs",
+                            eyre!(
+                                "rpc consensus type `single` takes one rpc, got {}",
+                                urls.len()
+                            ),
+                        );
+                        None
+                    }
+                    "single" => urls.into_iter().next().and_then(|url| {
+                        h_eth::ConnectionConf::single(
+                            url,
+                            headers,
+                            tls,
+                            request_timeout,
+                            max_connections,
+                            retry,
+                        )
+                        .take_err(&mut err, || &chain.cwp + "rpcs")
+                    }),
+                    "fallback" => Some(h_eth::ConnectionConf::HttpFallback {
+                        urls,
+                        headers,
+                        tls,
+                        request_timeout,
+                        max_connections,
+                        retry,
+                    }),
+                    "quorum" => Some(h_eth::ConnectionConf::HttpQuorum {
+                        urls,
+                        threshold,
+                        headers,
+                        tls,
+                        request_timeout,
+                        max_connections,
+                        retry,
+                    }),
                     ty => Err(eyre!("unknown rpc consensus type `{ty}`"))
                         .take_err(&mut err, || &chain.cwp + "rpc_consensus_type"),
                 }
-                .map(ChainConnectionConf::Ethereum)
+                .map(|conf| ChainConnectionConf::Ethereum(h_eth::ChainConnections::shared(conf)))
             }
         }
         HyperlaneDomainProtocol::Fuel => ParseChain::from_option(rpcs.into_iter().next(), &mut err)
@@ -259,35 +731,263 @@ fn parse_chain(chain: ValueParser, name: &str) -> ConfigResult<ChainConf> {
                 .end()
                 .map(|url| ChainConnectionConf::Sealevel(h_sealevel::ConnectionConf { url }))
         }
+        HyperlaneDomainProtocol::Cosmos => {
+            let rpc = rpcs.into_iter().next();
+            let rpc_url = ParseChain::from_option(rpc.clone(), &mut err)
+                .get_key("http")
+                .parse_from_str("Invalid http url")
+                .end();
+            let subscribe = ParseChain::from_option(rpc.clone(), &mut err)
+                .get_opt_key("subscribe")
+                .parse_bool()
+                .unwrap_or(false);
+            if let (true, Some(rpc), Some(rpc_url)) = (subscribe, &rpc, &rpc_url) {
+                h_cosmos::to_websocket_url(rpc_url).take_err(&mut err, || &rpc.cwp + "subscribe");
+            }
+            let retry = parse_retry_config(&chain)
+                .take_config_err(&mut err)
+                .and_then(|retry| {
+                    h_cosmos::RetryConf::new(
+                        retry.max_retries,
+                        retry.initial_backoff_ms,
+                        retry.max_backoff_ms,
+                    )
+                    .take_err(&mut err, || &chain.cwp + "retry" + "initial_backoff_ms")
+                });
+            let grpc_url = ParseChain::from_option(rpc, &mut err)
+                .get_key("grpc")
+                .parse_from_str("Invalid grpc url")
+                .end();
+            let chain_id = chain
+                .chain(&mut err)
+                .get_key("chainId")
+                .parse_string()
+                .end()
+                .map(str::to_owned);
+            let prefix = chain
+                .chain(&mut err)
+                .get_key("bech32Prefix")
+                .parse_string()
+                .end()
+                .map(str::to_owned);
+            let gas_price = chain
+                .chain(&mut err)
+                .get_key("gasPrice")
+                .parse_from_str("Invalid gas price")
+                .end();
+            if let (
+                Some(rpc_url),
+                Some(grpc_url),
+                Some(chain_id),
+                Some(prefix),
+                Some(gas_price),
+                Some(retry),
+            ) = (rpc_url, grpc_url, chain_id, prefix, gas_price, retry)
+            {
+                Some(ChainConnectionConf::Cosmos(h_cosmos::ConnectionConf {
+                    grpc_url,
+                    rpc_url,
+                    chain_id,
+                    prefix,
+                    gas_price,
+                    subscribe,
+                    retry,
+                }))
+            } else {
+                None
+            }
+        }
+        HyperlaneDomainProtocol::Starknet => {
+            let url = ParseChain::from_option(rpcs.into_iter().next(), &mut err)
+                .get_key("http")
+                .parse_from_str("Invalid http url")
+                .end();
+            let chain_id = chain
+                .chain(&mut err)
+                .get_key("chainId")
+                .parse_string()
+                .and_then(|id| {
+                    h_starknet::parse_felt(id).into_config_result(|| &chain.cwp + "chain_id")
+                })
+                .end();
+            let account_address = chain
+                .chain(&mut err)
+                .get_key("accountAddress")
+                .parse_string()
+                .and_then(|addr| {
+                    h_starknet::parse_felt(addr)
+                        .into_config_result(|| &chain.cwp + "account_address")
+                })
+                .end();
+            if let (Some(url), Some(chain_id), Some(account_address)) =
+                (url, chain_id, account_address)
+            {
+                Some(ChainConnectionConf::Starknet(h_starknet::ConnectionConf {
+                    url,
+                    chain_id,
+                    account_address,
+                }))
+            } else {
+                None
+            }
+        }
     };
 
     cfg_unwrap_all!(&chain.cwp, err: [connection, mailbox, interchain_gas_paymaster, validator_announce]);
+    let mut index = IndexSettings {
+        from,
+        chunk_size,
+        chunk_unit,
+        modes,
+        finality_blocks: 0,
+        reorg_period: 0,
+        poll_interval: DEFAULT_POLL_INTERVAL,
+        event_topics,
+        max_reorg_depth,
+        adaptive_chunking,
+        min_chunk_size,
+        max_chunk_size,
+    };
+    index
+        .resolve_chunk_size(block_time)
+        .take_err(&mut err, || &chain.cwp + "index" + "chunk");
+    let native_token = chain
+        .chain(&mut err)
+        .get_opt_key("nativeToken")
+        .and_then(|token| {
+            let mut err = ConfigParsingError::default();
+            let default = NativeToken::default();
+            let symbol = token
+                .chain(&mut err)
+                .get_opt_key("symbol")
+                .parse_string()
+                .map(str::to_owned)
+                .unwrap_or(default.symbol);
+            let decimals = token
+                .chain(&mut err)
+                .get_opt_key("decimals")
+                .and_then(|v| {
+                    v.parse_value::<StrOrInt>("Invalid decimals").and_then(|d| {
+                        parse_native_token_decimals(&d).into_config_result(|| v.cwp.clone())
+                    })
+                })
+                .unwrap_or(default.decimals);
+            err.into_result(NativeToken { symbol, decimals })
+        })
+        .unwrap_or_default();
+
+    let metrics_labels = chain
+        .chain(&mut err)
+        .get_opt_key("metricsLabels")
+        .and_then(|v| {
+            v.parse_value::<HashMap<String, String>>("Invalid metricsLabels")
+                .and_then(|l| parse_metrics_labels(l).into_config_result(|| v.cwp.clone()))
+        })
+        .unwrap_or_default();
+    let metrics_conf = PrometheusMiddlewareConf {
+        chain: (!metrics_labels.is_empty()).then(|| ChainInfo {
+            labels: metrics_labels,
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
     err.into_result(ChainConf {
         domain,
+        chain_id: None,
+        index_only,
         signer,
+        role_signers: Default::default(),
+        assume_instant_finality,
+        testnet,
+        finality,
         finality_blocks,
+        reorg_period,
+        block_time,
+        max_batch_size,
+        max_in_flight_transactions,
+        batch_flush_interval,
+        batch_strategy,
+        nonce_strategy,
+        tx_type,
+        poll_interval,
+        confirmation_timeout,
+        gas_oracle,
+        max_gas_limit,
+        gas_limit_multiplier,
+        native_token,
         addresses: CoreContractAddresses {
             mailbox,
             interchain_gas_paymaster,
             validator_announce,
+            ens_names,
+            deployed_blocks,
         },
         connection,
-        metrics_conf: Default::default(),
-        index: IndexSettings {
-            from,
-            chunk_size,
-            mode,
-        },
+        metrics_conf,
+        index,
     })
 }
 
+/// Parse an ethereum rpc url, which may use either an HTTP or a websocket
+/// transport.
+fn parse_eth_rpc_url(rpc: ValueParser) -> ConfigResult<Url> {
+    let url: Url = rpc.parse_from_str("Invalid rpc url")?;
+    match url.scheme() {
+        "http" | "https" | "ws" | "wss" => Ok(url),
+        scheme => Err(eyre!(
+            "Unsupported rpc url scheme `{scheme}`, expected one of `http`, `https`, `ws` or `wss`"
+        ))
+        .into_config_result(|| rpc.cwp),
+    }
+}
+
+/// Expects the optional `retry` block of ChainMetadata.
+fn parse_retry_config(chain: &ValueParser) -> ConfigResult<h_eth::RetryConfig> {
+    let mut err = ConfigParsingError::default();
+
+    let max_retries = chain
+        .chain(&mut err)
+        .get_opt_key("retry")
+        .get_opt_key("maxRetries")
+        .parse_u32()
+        .end();
+    let initial_backoff_ms = chain
+        .chain(&mut err)
+        .get_opt_key("retry")
+        .get_opt_key("initialBackoffMs")
+        .parse_u64()
+        .end();
+    let max_backoff_ms = chain
+        .chain(&mut err)
+        .get_opt_key("retry")
+        .get_opt_key("maxBackoffMs")
+        .parse_u64()
+        .end();
+
+    let retry = h_eth::RetryConfig {
+        max_retries,
+        initial_backoff_ms,
+        max_backoff_ms,
+    };
+    retry
+        .validate()
+        .take_err(&mut err, || &chain.cwp + "retry" + "initial_backoff_ms");
+    err.into_result(retry)
+}
+
 /// Expects ChainMetadata
 fn parse_domain(chain: ValueParser, name: &str) -> ConfigResult<HyperlaneDomain> {
     let mut err = ConfigParsingError::default();
     let internal_name = chain.chain(&mut err).get_key("name").parse_string().end();
+    let allow_name_key_mismatch = chain
+        .chain(&mut err)
+        .get_opt_key("allowNameKeyMismatch")
+        .parse_bool()
+        .unwrap_or(false);
 
     if let Some(internal_name) = internal_name {
-        if internal_name != name {
+        if internal_name != name && !allow_name_key_mismatch {
             Err(eyre!(
                 "detected chain name mismatch, the config may be corrupted"
             ))
@@ -343,7 +1043,12 @@ fn parse_signer(signer: ValueParser) -> ConfigResult<SignerConf> {
                 .get_key("key")
                 .parse_private_key()
                 .unwrap_or_default();
-            err.into_result(SignerConf::HexKey { key })
+            let weight = signer
+                .chain(&mut err)
+                .get_opt_key("weight")
+                .parse_u32()
+                .unwrap_or(1);
+            err.into_result(SignerConf::HexKey { key, weight })
         }};
         (aws) => {{
             let id = signer
@@ -354,16 +1059,168 @@ fn parse_signer(signer: ValueParser) -> ConfigResult<SignerConf> {
                 .to_owned();
             let region = signer
                 .chain(&mut err)
-                .get_key("region")
-                .parse_from_str("Expected AWS region")
+                .get_opt_key("region")
+                .parse_string()
+                .end();
+            let region = parse_aws_region(region, |var| std::env::var(var).ok())
+                .take_err(&mut err, || &signer.cwp + "region")
                 .unwrap_or_default();
-            err.into_result(SignerConf::Aws { id, region })
+            let role_arn = signer
+                .chain(&mut err)
+                .get_opt_key("roleArn")
+                .parse_string()
+                .and_then(|arn| parse_role_arn(arn).into_config_result(|| &signer.cwp + "role_arn"))
+                .end();
+            let external_id = signer
+                .chain(&mut err)
+                .get_opt_key("externalId")
+                .parse_string()
+                .map(str::to_owned)
+                .end();
+            let profile = signer
+                .chain(&mut err)
+                .get_opt_key("profile")
+                .parse_string()
+                .and_then(|p| parse_aws_profile(p).into_config_result(|| &signer.cwp + "profile"))
+                .end();
+            err.into_result(SignerConf::Aws {
+                id,
+                region,
+                role_arn,
+                external_id,
+                profile,
+            })
+        }};
+        (gcpKms) => {{
+            let project_id = signer
+                .chain(&mut err)
+                .get_key("projectId")
+                .parse_string()
+                .unwrap_or("")
+                .to_owned();
+            let location = signer
+                .chain(&mut err)
+                .get_opt_key("location")
+                .parse_string()
+                .unwrap_or("global")
+                .to_owned();
+            let key_ring = signer
+                .chain(&mut err)
+                .get_key("keyRing")
+                .parse_string()
+                .unwrap_or("")
+                .to_owned();
+            let key_name = signer
+                .chain(&mut err)
+                .get_key("keyName")
+                .parse_string()
+                .unwrap_or("")
+                .to_owned();
+            let version = signer
+                .chain(&mut err)
+                .get_opt_key("version")
+                .parse_u32()
+                .unwrap_or(1);
+            err.into_result(SignerConf::GcpKms {
+                project_id,
+                location,
+                key_ring,
+                key_name,
+                version,
+            })
+        }};
+        (vault) => {{
+            let address = signer
+                .chain(&mut err)
+                .get_key("address")
+                .parse_from_str("Expected Vault address url")
+                .end();
+            let mount_path = signer
+                .chain(&mut err)
+                .get_opt_key("mountPath")
+                .parse_string()
+                .unwrap_or("transit")
+                .to_owned();
+            let key_name = signer
+                .chain(&mut err)
+                .get_key("keyName")
+                .parse_string()
+                .unwrap_or("")
+                .to_owned();
+            let token_env = signer
+                .chain(&mut err)
+                .get_opt_key("tokenEnv")
+                .parse_string()
+                .unwrap_or("VAULT_TOKEN")
+                .to_owned();
+            cfg_unwrap_all!(&signer.cwp, err: [address]);
+            err.into_result(SignerConf::Vault {
+                address,
+                mount_path,
+                key_name,
+                token_env,
+            })
+        }};
+        (external) => {{
+            let command = signer
+                .chain(&mut err)
+                .get_key("command")
+                .parse_string()
+                .end()
+                .map(str::to_owned);
+            let timeout = signer
+                .chain(&mut err)
+                .get_opt_key("timeout")
+                .and_then(|v| {
+                    v.parse_value::<StrOrInt>("Invalid external signer timeout")
+                        .and_then(|t| {
+                            parse_external_signer_timeout(&t).into_config_result(|| v.cwp.clone())
+                        })
+                })
+                .unwrap_or(DEFAULT_EXTERNAL_SIGNER_TIMEOUT);
+            cfg_unwrap_all!(&signer.cwp, err: [command]);
+            err.into_result(SignerConf::External { command, timeout })
+        }};
+        (relayService) => {{
+            let endpoint = signer
+                .chain(&mut err)
+                .get_key("endpoint")
+                .parse_string()
+                .and_then(|e| {
+                    parse_relay_service_endpoint(e)
+                        .into_config_result(|| &signer.cwp + "endpoint")
+                })
+                .end();
+            let api_key_env = signer
+                .chain(&mut err)
+                .get_opt_key("apiKeyEnv")
+                .parse_string()
+                .unwrap_or("RELAY_SERVICE_API_KEY")
+                .to_owned();
+            let inner = signer
+                .chain(&mut err)
+                .get_key("signer")
+                .and_then(parse_signer)
+                .and_then(|s| {
+                    check_relay_service_signer(s).into_config_result(|| &signer.cwp + "signer")
+                })
+                .end();
+            cfg_unwrap_all!(&signer.cwp, err: [endpoint, inner]);
+            err.into_result(SignerConf::RelayService {
+                endpoint,
+                api_key_env,
+                signer: Box::new(inner),
+            })
         }};
     }
 
     match signer_type {
         Some("hexKey") => parse_signer!(hexKey),
         Some("aws") => parse_signer!(aws),
+        Some("gcpKms") => parse_signer!(gcpKms),
+        Some("vault") => parse_signer!(vault),
+        Some("external") => parse_signer!(external),
+        Some("relayService") => parse_signer!(relayService),
         Some(t) => {
             Err(eyre!("Unknown signer type `{t}`")).into_config_result(|| &signer.cwp + "type")
         }
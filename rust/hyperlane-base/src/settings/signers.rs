@@ -1,17 +1,27 @@
-use std::time::Duration;
+use std::{collections::HashMap, time::Duration};
 
 use async_trait::async_trait;
 use ed25519_dalek::SecretKey;
 use ethers::prelude::{AwsSigner, LocalWallet};
-use eyre::{bail, Context, Report};
-use hyperlane_core::H256;
+use eyre::{bail, eyre, Context, Report};
+use hyperlane_core::{config::StrOrInt, H256};
+use hyperlane_ethereum::{ExternalSigner, GcpKmsSigner, LedgerSigner, RelayService, VaultSigner};
 use hyperlane_sealevel::Keypair;
-use rusoto_core::{HttpClient, HttpConfig, Region};
+use reqwest::Url;
+use rusoto_core::{credential::AutoRefreshingProvider, HttpClient, HttpConfig, Region};
 use rusoto_kms::KmsClient;
+use rusoto_sts::{StsAssumeRoleSessionCredentialsProvider, StsClient};
 use tracing::instrument;
 
 use super::aws_credentials::AwsChainCredentialsProvider;
 
+/// Session name used when an AWS signer assumes an IAM role.
+const ASSUME_ROLE_SESSION_NAME: &str = "hyperlane-agent";
+/// Env vars the region of an AWS signer falls back to, in order of priority.
+const AWS_REGION_ENV_VARS: [&str; 2] = ["AWS_REGION", "AWS_DEFAULT_REGION"];
+/// How long to wait for an external signer command by default
+pub(crate) const DEFAULT_EXTERNAL_SIGNER_TIMEOUT: Duration = Duration::from_secs(10);
+
 /// Signer types
 #[derive(Default, Debug, Clone)]
 pub enum SignerConf {
@@ -19,6 +29,9 @@ pub enum SignerConf {
     HexKey {
         /// Private key value
         key: H256,
+        /// Declared weight of this signer within a multisig validator set.
+        /// This is metadata only and does not affect signing.
+        weight: u32,
     },
     /// An AWS signer. Note that AWS credentials must be inserted into the env
     /// separately.
@@ -27,12 +40,129 @@ pub enum SignerConf {
         id: String,
         /// The AWS region
         region: Region,
+        /// An IAM role to assume before using KMS
+        role_arn: Option<String>,
+        /// The external id to pass when assuming `role_arn`
+        external_id: Option<String>,
+        /// A named profile in the shared AWS credentials file to use instead
+        /// of the default credential chain
+        profile: Option<String>,
+    },
+    /// A GCP Cloud KMS signer. Note that GCP credentials must be available
+    /// in the environment separately.
+    GcpKms {
+        /// The GCP project the key belongs to
+        project_id: String,
+        /// The location of the key ring, e.g. `global` or `us-east1`
+        location: String,
+        /// The key ring containing the key
+        key_ring: String,
+        /// The name of the key
+        key_name: String,
+        /// The version of the key to sign with
+        version: u32,
+    },
+    /// A signer using a key stored on an attached Ledger device
+    Ledger {
+        /// The HD derivation path of the key, e.g. `m/44'/60'/0'/0/0`
+        hd_path: String,
+        /// The index of the Ledger device to use among the connected ones,
+        /// ordered by their USB path
+        device_index: u32,
+    },
+    /// A signer using a secp256k1 key in a HashiCorp Vault transit compatible
+    /// secrets engine. The builtin transit engine has no secp256k1 keys, so the
+    /// mount must be served by a plugin providing `ecdsa-secp256k1` keys.
+    Vault {
+        /// Base url of the Vault server
+        address: Url,
+        /// Mount path of the transit secrets engine
+        mount_path: String,
+        /// The name of the transit key
+        key_name: String,
+        /// The env var holding the Vault token
+        token_env: String,
+    },
+    /// A signer delegating to an external executable which reads a digest on
+    /// stdin and prints its signature on stdout
+    External {
+        /// The executable to run for every signing request
+        command: String,
+        /// How long to wait for the executable to finish
+        timeout: Duration,
+    },
+    /// Submit transactions through a meta-transaction relay service rather
+    /// than broadcasting them locally
+    RelayService {
+        /// Url transactions are posted to
+        endpoint: Url,
+        /// The env var holding the relay service API key
+        api_key_env: String,
+        /// The signer relay payloads are signed with and transactions are
+        /// relayed for
+        signer: Box<SignerConf>,
     },
     /// Assume the local node will sign on RPC calls automatically
     #[default]
     Node,
 }
 
+/// What a signer is used for, so a chain can use distinct keys for each
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SignerRole {
+    /// Submitting transactions as a relayer
+    Relay,
+    /// Signing checkpoints and announcements as a validator
+    Validate,
+}
+
+impl SignerRole {
+    /// The name of the role in configs.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Relay => "relay",
+            Self::Validate => "validate",
+        }
+    }
+}
+
+/// Parse a signer role, `relay` or `validate`, also accepting the agent names
+/// `relayer` and `validator`.
+pub(crate) fn parse_signer_role(role: &str) -> eyre::Result<SignerRole> {
+    match role {
+        "relay" | "relayer" => Ok(SignerRole::Relay),
+        "validate" | "validator" => Ok(SignerRole::Validate),
+        r => bail!("Unknown signer role `{r}`, expected `relay` or `validate`"),
+    }
+}
+
+/// Signers of a chain which are used for a single role in place of its
+/// default signer.
+#[derive(Debug, Clone, Default)]
+pub struct RoleSigners(HashMap<SignerRole, SignerConf>);
+
+impl RoleSigners {
+    /// Use `signer` for `role`.
+    pub fn insert(&mut self, role: SignerRole, signer: SignerConf) {
+        self.0.insert(role, signer);
+    }
+
+    /// The signer for `role`, falling back to `default` if there is none
+    /// specific to it.
+    pub fn get<'a>(
+        &'a self,
+        role: SignerRole,
+        default: Option<&'a SignerConf>,
+    ) -> Option<&'a SignerConf> {
+        self.0.get(&role).or(default)
+    }
+
+    /// Iterate over the roles which have their own signer.
+    pub fn iter(&self) -> impl Iterator<Item = (&SignerRole, &SignerConf)> {
+        self.0.iter()
+    }
+}
+
 impl SignerConf {
     /// Try to convert the ethereum signer to a local wallet
     #[instrument(err)]
@@ -52,27 +182,100 @@ pub trait BuildableWithSignerConf: Sized {
 impl BuildableWithSignerConf for hyperlane_ethereum::Signers {
     async fn build(conf: &SignerConf) -> Result<Self, Report> {
         Ok(match conf {
-            SignerConf::HexKey { key } => hyperlane_ethereum::Signers::Local(LocalWallet::from(
-                ethers::core::k256::ecdsa::SigningKey::from(
+            SignerConf::HexKey { key, .. } => hyperlane_ethereum::Signers::Local(
+                LocalWallet::from(ethers::core::k256::ecdsa::SigningKey::from(
                     ethers::core::k256::SecretKey::from_be_bytes(key.as_bytes())
                         .context("Invalid ethereum signer key")?,
-                ),
-            )),
-            SignerConf::Aws { id, region } => {
-                let mut config = HttpConfig::new();
-                // see https://github.com/hyperium/hyper/issues/2136#issuecomment-589345238
-                config.pool_idle_timeout(Duration::from_secs(20));
-                let client = KmsClient::new_with_client(
-                    rusoto_core::Client::new_with(
-                        AwsChainCredentialsProvider::new(),
-                        HttpClient::new_with_config(config).unwrap(),
-                    ),
-                    region.clone(),
-                );
+                )),
+            ),
+            SignerConf::Aws {
+                id,
+                region,
+                role_arn,
+                external_id,
+                profile,
+            } => {
+                let credentials = || AwsChainCredentialsProvider::with_profile(profile.clone());
+                let http_client = || {
+                    let mut config = HttpConfig::new();
+                    // see https://github.com/hyperium/hyper/issues/2136#issuecomment-589345238
+                    config.pool_idle_timeout(Duration::from_secs(20));
+                    HttpClient::new_with_config(config).unwrap()
+                };
+                let client = if let Some(role_arn) = role_arn {
+                    let sts = StsClient::new_with(http_client(), credentials(), region.clone());
+                    let provider = StsAssumeRoleSessionCredentialsProvider::new(
+                        sts,
+                        role_arn.clone(),
+                        ASSUME_ROLE_SESSION_NAME.to_owned(),
+                        external_id.clone(),
+                        None,
+                        None,
+                        None,
+                    );
+                    KmsClient::new_with_client(
+                        rusoto_core::Client::new_with(
+                            AutoRefreshingProvider::new(provider)?,
+                            http_client(),
+                        ),
+                        region.clone(),
+                    )
+                } else {
+                    KmsClient::new_with_client(
+                        rusoto_core::Client::new_with(credentials(), http_client()),
+                        region.clone(),
+                    )
+                };
 
                 let signer = AwsSigner::new(client, id, 0).await?;
                 hyperlane_ethereum::Signers::Aws(signer)
             }
+            SignerConf::GcpKms {
+                project_id,
+                location,
+                key_ring,
+                key_name,
+                version,
+            } => {
+                let signer =
+                    GcpKmsSigner::new(project_id, location, key_ring, key_name, *version, 0)
+                        .await?;
+                hyperlane_ethereum::Signers::GcpKms(signer)
+            }
+            SignerConf::Ledger {
+                hd_path,
+                device_index,
+            } => {
+                let signer = LedgerSigner::new(hd_path, *device_index, 0)
+                    .await
+                    .context("Failed to open Ledger, is it connected and unlocked?")?;
+                hyperlane_ethereum::Signers::Ledger(signer)
+            }
+            SignerConf::Vault {
+                address,
+                mount_path,
+                key_name,
+                token_env,
+            } => {
+                let signer =
+                    VaultSigner::new(address.clone(), mount_path, key_name, token_env, 0).await?;
+                hyperlane_ethereum::Signers::Vault(signer)
+            }
+            SignerConf::External { command, timeout } => {
+                let signer = ExternalSigner::new(command, *timeout, 0).await?;
+                hyperlane_ethereum::Signers::External(signer)
+            }
+            SignerConf::RelayService {
+                endpoint,
+                api_key_env,
+                signer,
+            } => {
+                let signer = Self::build(signer)
+                    .await
+                    .context("Failed to build the signer of the relay service")?;
+                let relay = RelayService::new(endpoint.clone(), api_key_env, 0, signer)?;
+                hyperlane_ethereum::Signers::RelayService(relay)
+            }
             SignerConf::Node => bail!("Node signer"),
         })
     }
@@ -82,12 +285,19 @@ impl BuildableWithSignerConf for hyperlane_ethereum::Signers {
 impl BuildableWithSignerConf for fuels::prelude::WalletUnlocked {
     async fn build(conf: &SignerConf) -> Result<Self, Report> {
         Ok(match conf {
-            SignerConf::HexKey { key } => {
+            SignerConf::HexKey { key, .. } => {
                 let key = fuels::signers::fuel_crypto::SecretKey::try_from(key.as_bytes())
                     .context("Invalid fuel signer key")?;
                 fuels::prelude::WalletUnlocked::new_from_private_key(key, None)
             }
             SignerConf::Aws { .. } => bail!("Aws signer is not supported by fuel"),
+            SignerConf::GcpKms { .. } => bail!("GcpKms signer is not supported by fuel"),
+            SignerConf::Ledger { .. } => bail!("Ledger signer is not supported by fuel"),
+            SignerConf::Vault { .. } => bail!("Vault signer is not supported by fuel"),
+            SignerConf::External { .. } => bail!("External signer is not supported by fuel"),
+            SignerConf::RelayService { .. } => {
+                bail!("RelayService signer is not supported by fuel")
+            }
             SignerConf::Node => bail!("Node signer is not supported by fuel"),
         })
     }
@@ -97,14 +307,109 @@ impl BuildableWithSignerConf for fuels::prelude::WalletUnlocked {
 impl BuildableWithSignerConf for Keypair {
     async fn build(conf: &SignerConf) -> Result<Self, Report> {
         Ok(match conf {
-            SignerConf::HexKey { key } => {
+            SignerConf::HexKey { key, .. } => {
                 let secret = SecretKey::from_bytes(key.as_bytes())
                     .context("Invalid sealevel ed25519 secret key")?;
                 Keypair::from_bytes(&ed25519_dalek::Keypair::from(secret).to_bytes())
                     .context("Unable to create Keypair")?
             }
             SignerConf::Aws { .. } => bail!("Aws signer is not supported by fuel"),
+            SignerConf::GcpKms { .. } => bail!("GcpKms signer is not supported by sealevel"),
+            SignerConf::Ledger { .. } => bail!("Ledger signer is not supported by sealevel"),
+            SignerConf::Vault { .. } => bail!("Vault signer is not supported by sealevel"),
+            SignerConf::External { .. } => {
+                bail!("External signer is not supported by sealevel")
+            }
+            SignerConf::RelayService { .. } => {
+                bail!("RelayService signer is not supported by sealevel")
+            }
             SignerConf::Node => bail!("Node signer is not supported by fuel"),
         })
     }
 }
+
+/// Check that `arn` names an IAM role, i.e. looks like
+/// `arn:aws:iam::123456789012:role/name`.
+pub(crate) fn parse_role_arn(arn: &str) -> eyre::Result<String> {
+    let valid = match arn.splitn(6, ':').collect::<Vec<_>>()[..] {
+        ["arn", partition, "iam", "", account, resource] => {
+            partition.starts_with("aws")
+                && account.len() == 12
+                && account.chars().all(|c| c.is_ascii_digit())
+                && resource
+                    .strip_prefix("role/")
+                    .map_or(false, |name| !name.is_empty())
+        }
+        _ => false,
+    };
+    if !valid {
+        bail!("Invalid `roleArn` `{arn}`, expected `arn:aws:iam::<account id>:role/<name>`");
+    }
+    Ok(arn.to_owned())
+}
+
+/// Check the name of an AWS credentials profile is not empty.
+pub fn parse_aws_profile(profile: &str) -> eyre::Result<String> {
+    let profile = profile.trim();
+    if profile.is_empty() {
+        bail!("Invalid AWS `profile`, expected a non-empty profile name");
+    }
+    Ok(profile.to_owned())
+}
+
+/// Parse the region of an AWS signer. When it is not configured the standard
+/// `AWS_REGION` and `AWS_DEFAULT_REGION` env vars are looked up with `env`.
+pub(crate) fn parse_aws_region(
+    region: Option<&str>,
+    env: impl Fn(&str) -> Option<String>,
+) -> eyre::Result<Region> {
+    let region = match region {
+        Some(region) => region.to_owned(),
+        None => AWS_REGION_ENV_VARS
+            .iter()
+            .find_map(|var| env(var).filter(|v| !v.is_empty()))
+            .ok_or_else(|| {
+                eyre!(
+                    "Missing `region` for Aws signer and neither `AWS_REGION` nor \
+                     `AWS_DEFAULT_REGION` is set"
+                )
+            })?,
+    };
+    region
+        .parse()
+        .with_context(|| format!("Invalid AWS region `{region}`"))
+}
+
+/// Parse how long to wait for an external signer command, where integers are
+/// seconds.
+pub(crate) fn parse_external_signer_timeout(timeout: &StrOrInt) -> eyre::Result<Duration> {
+    let timeout = Duration::try_from(timeout)
+        .context("Invalid `timeout` for External signer, expected a duration")?;
+    if timeout.is_zero() {
+        bail!("Invalid `timeout` for External signer, must be greater than 0");
+    }
+    Ok(timeout)
+}
+
+/// Parse the url of a relay service, which must be served over http(s).
+pub(crate) fn parse_relay_service_endpoint(endpoint: &str) -> eyre::Result<Url> {
+    let url: Url = endpoint
+        .parse()
+        .with_context(|| format!("Invalid relay service `endpoint` `{endpoint}`"))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        bail!("Invalid relay service `endpoint` `{endpoint}`, expected an http or https url");
+    }
+    Ok(url)
+}
+
+/// Check that the nested signer of a relay service signer can sign payloads
+/// itself.
+pub(crate) fn check_relay_service_signer(signer: SignerConf) -> eyre::Result<SignerConf> {
+    match signer {
+        SignerConf::Node => bail!("Relay service `signer` must be set to a signer holding a key"),
+        SignerConf::RelayService { .. } => {
+            bail!("Relay service `signer` can not be a relay service")
+        }
+        signer => Ok(signer),
+    }
+}
@@ -0,0 +1,22 @@
+use serde_json::{json, Value};
+
+/// The raw config of a minimal ethereum chain named `test1`, with the
+/// top level fields of `overrides` replacing its own.
+pub(crate) fn test_chain(overrides: Value) -> Value {
+    let mut chain = json!({
+        "name": "test1",
+        "domain": 13371,
+        "protocol": "ethereum",
+        "connection": { "type": "http", "url": "http://localhost:8545" },
+        "addresses": {
+            "mailbox": "0x1111111111111111111111111111111111111111",
+            "interchainGasPaymaster": "0x2222222222222222222222222222222222222222",
+            "validatorAnnounce": "0x3333333333333333333333333333333333333333",
+        },
+    });
+    chain
+        .as_object_mut()
+        .unwrap()
+        .extend(overrides.as_object().unwrap().clone());
+    chain
+}
@@ -5,6 +5,7 @@ use tracing_subscriber::{
     fmt::{
         self,
         format::{Compact, DefaultFields, Format, Full, Json, JsonFields, Pretty},
+        MakeWriter,
     },
     registry::LookupSpan,
     Layer,
@@ -57,20 +58,39 @@ impl<S> Default for LogOutputLayer<S> {
     }
 }
 
-impl<S> From<Style> for LogOutputLayer<S> {
-    fn from(style: Style) -> Self {
+impl<S, W> LogOutputLayer<S, DefaultFields, W>
+where
+    W: for<'w> MakeWriter<'w> + 'static,
+{
+    /// Create the layer for a style, writing the logs to `make_writer`.
+    pub fn new(style: Style, make_writer: W) -> Self {
         match style {
-            Style::Full => Self::Full(fmt::layer()),
-            Style::Pretty => Self::Pretty(fmt::layer().pretty()),
-            Style::Compact => Self::Compact(fmt::layer().compact()),
-            Style::Json => Self::Json(fmt::layer().json()),
+            Style::Full => Self::Full(fmt::layer().with_writer(make_writer)),
+            Style::Pretty => Self::Pretty(fmt::layer().pretty().with_writer(make_writer)),
+            Style::Compact => Self::Compact(fmt::layer().compact().with_writer(make_writer)),
+            // log aggregators need the fields of the enclosing spans, e.g. the
+            // chain a task is running for, to make sense of an event
+            Style::Json => Self::Json(
+                fmt::layer()
+                    .json()
+                    .with_current_span(true)
+                    .with_span_list(true)
+                    .with_writer(make_writer),
+            ),
         }
     }
 }
 
-impl<S> Layer<S> for LogOutputLayer<S>
+impl<S> From<Style> for LogOutputLayer<S> {
+    fn from(style: Style) -> Self {
+        Self::new(style, std::io::stdout)
+    }
+}
+
+impl<S, W> Layer<S> for LogOutputLayer<S, DefaultFields, W>
 where
     S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'w> MakeWriter<'w> + 'static,
 {
     fn register_callsite(
         &self,
@@ -241,4 +261,50 @@ mod test {
             Style::Pretty
         );
     }
+
+    #[test]
+    fn json_output_includes_span_fields() {
+        use std::{
+            io,
+            sync::{Arc, Mutex},
+        };
+
+        use tracing_subscriber::prelude::*;
+
+        #[derive(Clone, Default)]
+        struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+        impl io::Write for Buffer {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.0.lock().unwrap().write(buf)
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let buffer = Buffer::default();
+        let make_writer = {
+            let buffer = buffer.clone();
+            move || buffer.clone()
+        };
+        let subscriber = tracing_subscriber::Registry::default()
+            .with(LogOutputLayer::new(Style::Json, make_writer));
+        tracing::subscriber::with_default(subscriber, || {
+            let _agent = tracing::info_span!("agent_main", agent = "relayer").entered();
+            let _chain = tracing::info_span!("ContractSync", domain = "test1").entered();
+            tracing::info!(nonce = 7, "Indexed message");
+        });
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let line: serde_json::Value = serde_json::from_str(output.trim()).unwrap();
+        assert_eq!(line["fields"]["message"], "Indexed message");
+        assert_eq!(line["fields"]["nonce"], 7);
+        assert_eq!(line["span"]["name"], "ContractSync");
+        assert_eq!(line["span"]["domain"], "test1");
+        assert_eq!(line["spans"][0]["name"], "agent_main");
+        assert_eq!(line["spans"][0]["agent"], "relayer");
+        assert_eq!(line["spans"][1]["domain"], "test1");
+    }
 }
@@ -1,16 +1,20 @@
-use eyre::Result;
+use std::fmt::Write;
+
+use eyre::{bail, eyre, Result};
+use serde_json::Value;
 pub use span_metrics::TimeSpanLifetime;
 use tracing_subscriber::{
     filter::{LevelFilter, Targets},
     prelude::*,
 };
 
-use self::fmt::LogOutputLayer;
+use self::{fmt::LogOutputLayer, sampling::SamplingLayer};
 use crate::{settings::trace::fmt::Style, CoreMetrics};
 
 /// Configure a `tracing_subscriber::fmt` Layer outputting to stdout
 pub mod fmt;
 
+mod sampling;
 mod span_metrics;
 
 /// Logging level. A "higher level" means more will be logged.
@@ -49,12 +53,88 @@ impl From<Level> for LevelFilter {
 }
 
 /// Configuration for the tracing subscribers used by Hyperlane agents
-#[derive(Debug, Clone, Default, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Deserialize)]
 pub struct TracingConfig {
+    /// The output format of the logs, configured as `format`
     #[serde(default)]
     pub(crate) fmt: Style,
     #[serde(default)]
     pub(crate) level: Level,
+    /// Levels of specific targets, e.g. `hyperlane_base=debug`. Other targets
+    /// log at `level`.
+    #[serde(skip)]
+    pub(crate) targets: Targets,
+    /// Fraction of the events below WARN which are logged
+    #[serde(default = "default_sampling_rate")]
+    pub(crate) sampling_rate: f64,
+}
+
+impl Default for TracingConfig {
+    fn default() -> Self {
+        Self {
+            fmt: Style::default(),
+            level: Level::default(),
+            targets: Targets::default(),
+            sampling_rate: default_sampling_rate(),
+        }
+    }
+}
+
+fn default_sampling_rate() -> f64 {
+    1.0
+}
+
+/// Check that the log sampling rate is between 0.0 and 1.0.
+pub(crate) fn parse_sampling_rate(rate: f64) -> Result<f64> {
+    if !(0.0..=1.0).contains(&rate) {
+        bail!("Invalid log sampling rate `{rate}`, expected a value between 0.0 and 1.0");
+    }
+    Ok(rate)
+}
+
+/// Parse the log output format, one of `"pretty"`, `"json"`, `"compact"` or
+/// `"full"`.
+pub(crate) fn parse_log_format(format: &str) -> Result<Style> {
+    Ok(match format {
+        "pretty" => Style::Pretty,
+        "json" => Style::Json,
+        "compact" => Style::Compact,
+        "full" => Style::Full,
+        _ => bail!(
+            "Invalid log format `{format}`, expected one of `pretty`, `json`, `compact` or `full`"
+        ),
+    })
+}
+
+/// Parse the log levels of specific targets, either given as a map, e.g.
+/// `{ "hyperlane_base": "debug" }`, or as a string of directives in `EnvFilter`
+/// syntax, e.g. `"hyperlane_base=debug,hyperlane_core=info"`. Every directive
+/// must name a target since `level` applies to all others.
+pub(crate) fn parse_log_targets(targets: &Value) -> Result<Targets> {
+    let directives = match targets {
+        Value::String(directives) => directives.clone(),
+        Value::Object(targets) => {
+            let mut directives = String::new();
+            for (target, level) in targets {
+                let Value::String(level) = level else {
+                    bail!("Invalid log level for target `{target}`, expected a string");
+                };
+                if !directives.is_empty() {
+                    directives.push(',');
+                }
+                write!(directives, "{target}={level}").unwrap();
+            }
+            directives
+        }
+        _ => bail!("Invalid log targets, expected a map of targets to levels or a string"),
+    };
+    let targets: Targets = directives
+        .parse()
+        .map_err(|e| eyre!("Invalid log targets `{directives}`: {e}"))?;
+    if targets.default_level().is_some() {
+        bail!("Invalid log targets `{directives}`, every directive must name a target");
+    }
+    Ok(targets)
 }
 
 impl TracingConfig {
@@ -78,11 +158,15 @@ impl TracingConfig {
             // only show sqlx query logs at trace level
             target_layer = target_layer.with_target("sqlx::query", Level::Warn);
         }
+        for (target, level) in self.targets.iter() {
+            target_layer = target_layer.with_target(target, level);
+        }
         let fmt_layer: LogOutputLayer<_> = self.fmt.into();
         let err_layer = tracing_error::ErrorLayer::default();
 
         let subscriber = tracing_subscriber::Registry::default()
             .with(target_layer)
+            .with(SamplingLayer::new(self.sampling_rate))
             .with(TimeSpanLifetime::new(metrics))
             .with(fmt_layer)
             .with(err_layer);
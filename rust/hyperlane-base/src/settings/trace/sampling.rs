@@ -0,0 +1,165 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    fmt::Debug,
+    hash::{Hash, Hasher},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use tracing::{
+    field::{Field, Visit},
+    span, Event, Level, Subscriber,
+};
+use tracing_subscriber::{layer::Context, registry::LookupSpan, Layer};
+
+/// Fields identifying the message an event or span is about.
+const SAMPLING_KEY_FIELDS: [&str; 2] = ["message_id", "msg_id"];
+
+/// Drops a fraction of the events below `WARN` so only about `rate` of them
+/// are logged.
+///
+/// Events about a message, i.e. those with a `message_id` or `msg_id` field on
+/// themselves or an enclosing span, are kept or dropped based on a hash of the
+/// id, so either the whole lifecycle of a message is logged or none of it.
+/// Other events are spread evenly.
+pub struct SamplingLayer {
+    /// Events are kept if their sample is below this threshold
+    threshold: u64,
+    counter: AtomicU64,
+}
+
+impl SamplingLayer {
+    /// Keep about `rate` of the events below `WARN`, `rate` must be between
+    /// 0.0 and 1.0.
+    pub fn new(rate: f64) -> Self {
+        Self {
+            threshold: (rate.clamp(0.0, 1.0) * u64::MAX as f64) as u64,
+            counter: AtomicU64::new(0),
+        }
+    }
+
+    fn keep(&self, sample: u64) -> bool {
+        self.threshold == u64::MAX || sample < self.threshold
+    }
+
+    fn next_sample(&self) -> u64 {
+        // stepping by the golden ratio spreads consecutive samples evenly
+        self.counter
+            .fetch_add(1, Ordering::Relaxed)
+            .wrapping_mul(0x9E37_79B9_7F4A_7C15)
+    }
+}
+
+/// The hashed message id of a span, used to sample the events inside of it.
+struct SamplingKey(u64);
+
+#[derive(Default)]
+struct SamplingKeyVisitor(Option<u64>);
+
+impl Visit for SamplingKeyVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        if SAMPLING_KEY_FIELDS.contains(&field.name()) {
+            let mut hasher = DefaultHasher::new();
+            format!("{value:?}").hash(&mut hasher);
+            self.0 = Some(hasher.finish());
+        }
+    }
+}
+
+impl<S> Layer<S> for SamplingLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        let mut visitor = SamplingKeyVisitor::default();
+        attrs.record(&mut visitor);
+        if let (Some(key), Some(span)) = (visitor.0, ctx.span(id)) {
+            span.extensions_mut().insert(SamplingKey(key));
+        }
+    }
+
+    fn on_record(&self, id: &span::Id, values: &span::Record<'_>, ctx: Context<'_, S>) {
+        let mut visitor = SamplingKeyVisitor::default();
+        values.record(&mut visitor);
+        if let (Some(key), Some(span)) = (visitor.0, ctx.span(id)) {
+            span.extensions_mut().replace(SamplingKey(key));
+        }
+    }
+
+    fn event_enabled(&self, event: &Event<'_>, ctx: Context<'_, S>) -> bool {
+        if self.threshold == u64::MAX || *event.metadata().level() <= Level::WARN {
+            return true;
+        }
+        let mut visitor = SamplingKeyVisitor::default();
+        event.record(&mut visitor);
+        let key = visitor.0.or_else(|| {
+            ctx.event_scope(event)?
+                .find_map(|span| span.extensions().get::<SamplingKey>().map(|key| key.0))
+        });
+        self.keep(key.unwrap_or_else(|| self.next_sample()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use tracing::{info, info_span, warn};
+    use tracing_subscriber::prelude::*;
+
+    use super::*;
+
+    struct CountEvents(Arc<AtomicUsize>);
+
+    impl<S: Subscriber> Layer<S> for CountEvents {
+        fn on_event(&self, _event: &Event<'_>, _ctx: Context<'_, S>) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn count_logged(rate: f64, log: impl FnOnce()) -> usize {
+        let count = Arc::new(AtomicUsize::new(0));
+        let subscriber = tracing_subscriber::Registry::default()
+            .with(SamplingLayer::new(rate))
+            .with(CountEvents(count.clone()));
+        tracing::subscriber::with_default(subscriber, log);
+        count.load(Ordering::Relaxed)
+    }
+
+    #[test]
+    fn samples_events_below_warn() {
+        let log = || {
+            for i in 0..1000 {
+                info!(i, "info");
+                warn!(i, "warn");
+            }
+        };
+        assert_eq!(count_logged(1.0, log), 2000);
+        assert_eq!(count_logged(0.0, log), 1000);
+        let logged = count_logged(0.5, log) - 1000;
+        assert!((450..=550).contains(&logged), "logged {logged} events");
+    }
+
+    #[test]
+    fn samples_messages_as_a_whole() {
+        let logged = count_logged(0.5, || {
+            for id in 0..100 {
+                let _span = info_span!("message", message_id = id).entered();
+                for _ in 0..10 {
+                    info!("processing");
+                }
+            }
+        });
+        assert_eq!(logged % 10, 0);
+        assert!(logged > 0 && logged < 1000);
+
+        let logged = count_logged(0.5, || {
+            for _ in 0..10 {
+                info!(message_id = "0xabc", "processing");
+            }
+        });
+        assert!(logged == 0 || logged == 10);
+    }
+}
@@ -0,0 +1,228 @@
+use std::{fmt, path::PathBuf, time::Duration};
+
+use async_trait::async_trait;
+use derive_new::new;
+use eyre::{bail, Result};
+use gcp_auth::{AuthenticationManager, CustomServiceAccount};
+use hyperlane_core::{SignedAnnouncement, SignedCheckpoint, SignedCheckpointWithMessageId};
+use prometheus::IntGauge;
+use reqwest::{Client, StatusCode};
+use tokio::sync::OnceCell;
+
+use crate::CheckpointSyncer;
+
+/// The timeout for GCS requests.
+const GCS_REQUEST_TIMEOUT_SECONDS: u64 = 30;
+/// The OAuth scope required to write objects to GCS.
+const GCS_WRITE_SCOPE: &str = "https://www.googleapis.com/auth/devstorage.read_write";
+const GCS_DOWNLOAD_URL: &str = "https://storage.googleapis.com";
+const GCS_UPLOAD_URL: &str = "https://storage.googleapis.com/upload/storage/v1/b";
+
+/// Credentials of the service account used to write to GCS.
+#[derive(Clone)]
+pub enum GcsServiceAccountKey {
+    /// Path to a service account JSON key file
+    File(PathBuf),
+    /// The contents of a service account JSON key, e.g. decoded from an
+    /// inline base64 config value
+    Json(String),
+}
+
+impl fmt::Debug for GcsServiceAccountKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::File(path) => f.debug_tuple("File").field(path).finish(),
+            Self::Json(_) => f.write_str("Json(..)"),
+        }
+    }
+}
+
+#[derive(new)]
+/// Type for reading/writing to Google Cloud Storage
+pub struct GcsStorage {
+    /// The name of the bucket.
+    bucket: String,
+    /// A specific folder inside the above bucket - set to empty string to use the root of the bucket
+    folder: Option<String>,
+    /// The service account key used for writes. If not set, application
+    /// default credentials are used.
+    service_account_key: Option<GcsServiceAccountKey>,
+    /// The http client used for requests.
+    #[new(default)]
+    client: Client,
+    /// The authentication manager used for writes, created on first use.
+    #[new(default)]
+    auth: OnceCell<AuthenticationManager>,
+    /// The latest seen signed checkpoint index.
+    latest_index: Option<IntGauge>,
+}
+
+impl fmt::Debug for GcsStorage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GcsStorage")
+            .field("bucket", &self.bucket)
+            .field("folder", &self.folder)
+            .field("service_account_key", &self.service_account_key)
+            .finish()
+    }
+}
+
+impl GcsStorage {
+    async fn write_to_bucket(&self, key: String, body: &str) -> Result<()> {
+        let token = self.auth().await?.get_token(&[GCS_WRITE_SCOPE]).await?;
+        self.client
+            .post(format!("{GCS_UPLOAD_URL}/{}/o", self.bucket))
+            .query(&[
+                ("uploadType", "media"),
+                ("name", &self.get_composite_key(key)),
+            ])
+            .bearer_auth(token.as_str())
+            .header("Content-Type", "application/json")
+            .body(body.to_owned())
+            .timeout(Duration::from_secs(GCS_REQUEST_TIMEOUT_SECONDS))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    /// Uses an unauthenticated request. This should only be used for publicly
+    /// accessible buckets.
+    async fn anonymously_read_from_bucket(&self, key: String) -> Result<Option<Vec<u8>>> {
+        let res = self
+            .client
+            .get(format!(
+                "{GCS_DOWNLOAD_URL}/{}/{}",
+                self.bucket,
+                self.get_composite_key(key)
+            ))
+            .timeout(Duration::from_secs(GCS_REQUEST_TIMEOUT_SECONDS))
+            .send()
+            .await?;
+
+        match res.status() {
+            StatusCode::NOT_FOUND => Ok(None),
+            s if s.is_success() => Ok(Some(res.bytes().await?.to_vec())),
+            s => bail!("Unexpected status {s} when reading from GCS bucket"),
+        }
+    }
+
+    /// Gets the authentication manager, creating it if it doesn't already exist.
+    async fn auth(&self) -> Result<&AuthenticationManager> {
+        self.auth
+            .get_or_try_init(|| async {
+                Ok::<_, eyre::Report>(match &self.service_account_key {
+                    Some(GcsServiceAccountKey::File(path)) => {
+                        CustomServiceAccount::from_file(path)?.into()
+                    }
+                    Some(GcsServiceAccountKey::Json(json)) => {
+                        CustomServiceAccount::from_json(json)?.into()
+                    }
+                    None => AuthenticationManager::new().await?,
+                })
+            })
+            .await
+    }
+
+    fn get_composite_key(&self, key: String) -> String {
+        match self.folder.as_deref() {
+            None | Some("") => key,
+            Some(folder_str) => format!("{}/{}", folder_str, key),
+        }
+    }
+
+    fn legacy_checkpoint_key(index: u32) -> String {
+        format!("checkpoint_{index}.json")
+    }
+
+    fn checkpoint_key(index: u32) -> String {
+        format!("checkpoint_{index}_with_id.json")
+    }
+
+    fn index_key() -> String {
+        "checkpoint_latest_index.json".to_owned()
+    }
+
+    fn announcement_key() -> String {
+        "announcement.json".to_owned()
+    }
+}
+
+#[async_trait]
+impl CheckpointSyncer for GcsStorage {
+    async fn latest_index(&self) -> Result<Option<u32>> {
+        let ret = self
+            .anonymously_read_from_bucket(GcsStorage::index_key())
+            .await?
+            .map(|data| serde_json::from_slice(&data))
+            .transpose()
+            .map_err(Into::into);
+
+        if let Ok(Some(latest_index)) = ret {
+            if let Some(gauge) = &self.latest_index {
+                gauge.set(latest_index as i64);
+            }
+        }
+
+        ret
+    }
+
+    async fn legacy_fetch_checkpoint(&self, index: u32) -> Result<Option<SignedCheckpoint>> {
+        self.anonymously_read_from_bucket(GcsStorage::legacy_checkpoint_key(index))
+            .await?
+            .map(|data| serde_json::from_slice(&data))
+            .transpose()
+            .map_err(Into::into)
+    }
+
+    async fn fetch_checkpoint(&self, index: u32) -> Result<Option<SignedCheckpointWithMessageId>> {
+        self.anonymously_read_from_bucket(GcsStorage::checkpoint_key(index))
+            .await?
+            .map(|data| serde_json::from_slice(&data))
+            .transpose()
+            .map_err(Into::into)
+    }
+
+    async fn legacy_write_checkpoint(&self, signed_checkpoint: &SignedCheckpoint) -> Result<()> {
+        let serialized_checkpoint = serde_json::to_string_pretty(signed_checkpoint)?;
+        self.write_to_bucket(
+            GcsStorage::legacy_checkpoint_key(signed_checkpoint.value.index),
+            &serialized_checkpoint,
+        )
+        .await?;
+
+        self.write_to_bucket(
+            GcsStorage::index_key(),
+            &signed_checkpoint.value.index.to_string(),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn write_checkpoint(
+        &self,
+        signed_checkpoint: &SignedCheckpointWithMessageId,
+    ) -> Result<()> {
+        let serialized_checkpoint = serde_json::to_string_pretty(signed_checkpoint)?;
+        self.write_to_bucket(
+            GcsStorage::checkpoint_key(signed_checkpoint.value.index),
+            &serialized_checkpoint,
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn write_announcement(&self, signed_announcement: &SignedAnnouncement) -> Result<()> {
+        let serialized_announcement = serde_json::to_string_pretty(signed_announcement)?;
+        self.write_to_bucket(GcsStorage::announcement_key(), &serialized_announcement)
+            .await?;
+        Ok(())
+    }
+
+    fn announcement_location(&self) -> String {
+        match self.folder.as_deref() {
+            None | Some("") => format!("gs://{}", self.bucket),
+            Some(folder_str) => format!("gs://{}/{}", self.bucket, folder_str),
+        }
+    }
+}
@@ -0,0 +1,520 @@
+use std::{collections::BTreeMap, fmt, time::Duration};
+
+use async_trait::async_trait;
+use derive_new::new;
+use eyre::{bail, Context, Result};
+use hyperlane_core::{SignedAnnouncement, SignedCheckpoint, SignedCheckpointWithMessageId};
+use prometheus::IntGauge;
+use reqwest::{multipart, Client, RequestBuilder, StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::CheckpointSyncer;
+
+/// The timeout for IPFS API and gateway requests.
+const IPFS_REQUEST_TIMEOUT_SECONDS: u64 = 30;
+/// Path of the index object within the node's mutable file system.
+const IPFS_INDEX_PATH: &str = "/hyperlane/index.json";
+
+/// Maps object keys to the CID of their latest pinned content. The writer keeps
+/// it in its node's mutable file system and publishes it under its IPNS name
+/// so readers can resolve it.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+struct IpfsIndex {
+    objects: BTreeMap<String, String>,
+}
+
+/// Error response of the IPFS HTTP API.
+#[derive(Debug, Deserialize)]
+struct ApiError {
+    #[serde(rename = "Message")]
+    message: String,
+}
+
+/// Response to `/api/v0/add` and `/api/v0/files/stat`.
+#[derive(Debug, Deserialize)]
+struct HashResponse {
+    #[serde(rename = "Hash")]
+    hash: String,
+}
+
+#[derive(new)]
+/// Type for reading/writing checkpoints to IPFS through a node's HTTP API
+/// (e.g. a pinning service). Every written object is pinned and its CID is
+/// recorded in an index object kept in the node's mutable file system, which
+/// is published under `ipns_name` if set.
+pub struct IpfsStorage {
+    /// Base url of the IPFS HTTP API, e.g. `http://localhost:5001`.
+    api_url: Url,
+    /// Base url of a gateway used to fetch content by CID. If not set, the
+    /// API is used.
+    gateway_url: Option<Url>,
+    /// Bearer token sent with API requests.
+    pin_service_token: Option<String>,
+    /// IPNS name the index is published under and resolved from. Without it
+    /// the index only lives in the node's mutable file system, which only
+    /// this node can read.
+    ipns_name: Option<String>,
+    /// The http client used for requests.
+    #[new(default)]
+    client: Client,
+    /// Serializes read-modify-write updates of the index.
+    #[new(default)]
+    index_lock: Mutex<()>,
+    /// The latest seen signed checkpoint index.
+    latest_index: Option<IntGauge>,
+}
+
+impl fmt::Debug for IpfsStorage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IpfsStorage")
+            .field("api_url", &self.api_url)
+            .field("gateway_url", &self.gateway_url)
+            .field("ipns_name", &self.ipns_name)
+            .finish()
+    }
+}
+
+impl IpfsStorage {
+    fn api_request(&self, method: &str) -> Result<RequestBuilder> {
+        let url = self
+            .api_url
+            .join(&format!("api/v0/{method}"))
+            .context("Invalid IPFS API url")?;
+        let req = self
+            .client
+            .post(url)
+            .timeout(Duration::from_secs(IPFS_REQUEST_TIMEOUT_SECONDS));
+        Ok(match &self.pin_service_token {
+            Some(token) => req.bearer_auth(token),
+            None => req,
+        })
+    }
+
+    async fn send(&self, req: RequestBuilder) -> Result<reqwest::Response> {
+        req.send()
+            .await
+            .with_context(|| format!("Failed to reach IPFS API at {}", self.api_url))?
+            .error_for_status()
+            .context("IPFS API request failed")
+    }
+
+    /// Add and pin the content, returning its CID.
+    async fn pin(&self, key: &str, body: &str) -> Result<String> {
+        let form = multipart::Form::new().part(
+            "file",
+            multipart::Part::text(body.to_owned()).file_name(key.to_owned()),
+        );
+        let req = self
+            .api_request("add")?
+            .query(&[("pin", "true"), ("cid-version", "1")])
+            .multipart(form);
+        let res = self.send(req).await?.bytes().await?;
+        let res: HashResponse =
+            serde_json::from_slice(&res).context("Unexpected response from IPFS add")?;
+        Ok(res.hash)
+    }
+
+    /// Read the index, resolving it through IPNS if the storage has a name.
+    async fn read_index(&self) -> Result<IpfsIndex> {
+        let Some(ipns_name) = &self.ipns_name else {
+            return self.read_local_index().await;
+        };
+        match self.fetch(&format!("ipns/{ipns_name}")).await? {
+            Some(index) => serde_json::from_slice(&index).context("Invalid IPFS checkpoint index"),
+            None => Ok(IpfsIndex::default()),
+        }
+    }
+
+    /// Read the index from the node's mutable file system.
+    async fn read_local_index(&self) -> Result<IpfsIndex> {
+        let req = self
+            .api_request("files/read")?
+            .query(&[("arg", IPFS_INDEX_PATH)]);
+        let res = req
+            .send()
+            .await
+            .with_context(|| format!("Failed to reach IPFS API at {}", self.api_url))?;
+        let status = res.status();
+        if status.is_success() {
+            return serde_json::from_slice(&res.bytes().await?)
+                .context("Invalid IPFS checkpoint index");
+        }
+        let body = res.text().await.unwrap_or_default();
+        // The API reports a missing file as a server error. Any other failure
+        // must not be mistaken for an empty index, since writing that back
+        // would drop every recorded CID.
+        if status == StatusCode::INTERNAL_SERVER_ERROR && is_missing_file_error(&body) {
+            return Ok(IpfsIndex::default());
+        }
+        bail!("Failed to read the IPFS checkpoint index, status {status}: {body}")
+    }
+
+    async fn write_index(&self, index: &IpfsIndex) -> Result<()> {
+        let form = multipart::Form::new().part(
+            "file",
+            multipart::Part::text(serde_json::to_string_pretty(index)?),
+        );
+        let req = self
+            .api_request("files/write")?
+            .query(&[
+                ("arg", IPFS_INDEX_PATH),
+                ("create", "true"),
+                ("truncate", "true"),
+                ("parents", "true"),
+            ])
+            .multipart(form);
+        self.send(req).await?;
+        self.publish_index().await
+    }
+
+    /// Point the IPNS name of the storage at the current index, so readers
+    /// which can not access the node's mutable file system can resolve it.
+    async fn publish_index(&self) -> Result<()> {
+        let Some(ipns_name) = &self.ipns_name else {
+            return Ok(());
+        };
+        let req = self
+            .api_request("files/stat")?
+            .query(&[("arg", IPFS_INDEX_PATH)]);
+        let res = self.send(req).await?.bytes().await?;
+        let res: HashResponse =
+            serde_json::from_slice(&res).context("Unexpected response from IPFS files/stat")?;
+        let req = self.api_request("name/publish")?.query(&[
+            ("arg", format!("/ipfs/{}", res.hash).as_str()),
+            ("key", ipns_name),
+        ]);
+        self.send(req).await.with_context(|| {
+            format!("Failed to publish the IPFS checkpoint index as {ipns_name}")
+        })?;
+        Ok(())
+    }
+
+    /// Pin the content and record its CID in the index under `key`.
+    async fn write_object(&self, key: String, body: &str) -> Result<()> {
+        let cid = self.pin(&key, body).await?;
+        let _guard = self.index_lock.lock().await;
+        // the local copy is authoritative, the published one may lag behind
+        let mut index = self.read_local_index().await?;
+        index.objects.insert(key, cid);
+        self.write_index(&index).await
+    }
+
+    /// Look up `key` in the index and fetch its content.
+    async fn read_object(&self, key: String) -> Result<Option<Vec<u8>>> {
+        let Some(cid) = self.read_index().await?.objects.remove(&key) else {
+            return Ok(None);
+        };
+        self.fetch(&format!("ipfs/{cid}")).await
+    }
+
+    /// Fetch the content at an `ipfs/<cid>` or `ipns/<name>` path through the
+    /// gateway, or the API if there is none.
+    async fn fetch(&self, path: &str) -> Result<Option<Vec<u8>>> {
+        let res = match &self.gateway_url {
+            Some(gateway) => self
+                .client
+                .get(gateway.join(path)?)
+                .timeout(Duration::from_secs(IPFS_REQUEST_TIMEOUT_SECONDS))
+                .send()
+                .await
+                .with_context(|| format!("Failed to reach IPFS gateway at {gateway}"))?,
+            None => {
+                let req = self
+                    .api_request("cat")?
+                    .query(&[("arg", format!("/{path}"))]);
+                self.send(req).await?
+            }
+        };
+        match res.status() {
+            StatusCode::NOT_FOUND => Ok(None),
+            s if s.is_success() => Ok(Some(res.bytes().await?.to_vec())),
+            s => bail!("Unexpected status {s} when fetching {path} from IPFS"),
+        }
+    }
+
+    /// List the keys of all objects recorded in the index.
+    pub async fn list_objects(&self) -> Result<Vec<String>> {
+        Ok(self.read_index().await?.objects.into_keys().collect())
+    }
+
+    fn legacy_checkpoint_key(index: u32) -> String {
+        format!("checkpoint_{index}.json")
+    }
+
+    fn checkpoint_key(index: u32) -> String {
+        format!("checkpoint_{index}_with_id.json")
+    }
+
+    fn index_key() -> String {
+        "checkpoint_latest_index.json".to_owned()
+    }
+
+    fn announcement_key() -> String {
+        "announcement.json".to_owned()
+    }
+}
+
+/// Whether an API error response says the requested file does not exist.
+fn is_missing_file_error(body: &str) -> bool {
+    serde_json::from_str::<ApiError>(body)
+        .map(|e| e.message.contains("file does not exist"))
+        .unwrap_or(false)
+}
+
+#[async_trait]
+impl CheckpointSyncer for IpfsStorage {
+    async fn latest_index(&self) -> Result<Option<u32>> {
+        let ret = self
+            .read_object(IpfsStorage::index_key())
+            .await?
+            .map(|data| serde_json::from_slice(&data))
+            .transpose()
+            .map_err(Into::into);
+
+        if let Ok(Some(latest_index)) = ret {
+            if let Some(gauge) = &self.latest_index {
+                gauge.set(latest_index as i64);
+            }
+        }
+
+        ret
+    }
+
+    async fn legacy_fetch_checkpoint(&self, index: u32) -> Result<Option<SignedCheckpoint>> {
+        self.read_object(IpfsStorage::legacy_checkpoint_key(index))
+            .await?
+            .map(|data| serde_json::from_slice(&data))
+            .transpose()
+            .map_err(Into::into)
+    }
+
+    async fn fetch_checkpoint(&self, index: u32) -> Result<Option<SignedCheckpointWithMessageId>> {
+        self.read_object(IpfsStorage::checkpoint_key(index))
+            .await?
+            .map(|data| serde_json::from_slice(&data))
+            .transpose()
+            .map_err(Into::into)
+    }
+
+    async fn legacy_write_checkpoint(&self, signed_checkpoint: &SignedCheckpoint) -> Result<()> {
+        let serialized_checkpoint = serde_json::to_string_pretty(signed_checkpoint)?;
+        self.write_object(
+            IpfsStorage::legacy_checkpoint_key(signed_checkpoint.value.index),
+            &serialized_checkpoint,
+        )
+        .await?;
+
+        self.write_object(
+            IpfsStorage::index_key(),
+            &signed_checkpoint.value.index.to_string(),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn write_checkpoint(
+        &self,
+        signed_checkpoint: &SignedCheckpointWithMessageId,
+    ) -> Result<()> {
+        let serialized_checkpoint = serde_json::to_string_pretty(signed_checkpoint)?;
+        self.write_object(
+            IpfsStorage::checkpoint_key(signed_checkpoint.value.index),
+            &serialized_checkpoint,
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn write_announcement(&self, signed_announcement: &SignedAnnouncement) -> Result<()> {
+        let serialized_announcement = serde_json::to_string_pretty(signed_announcement)?;
+        self.write_object(IpfsStorage::announcement_key(), &serialized_announcement)
+            .await?;
+        Ok(())
+    }
+
+    /// `ipfs://<gateway url>/ipns/<name>` if the index is published, so
+    /// relayers can resolve it, and `ipfs://<api url>` otherwise. The url
+    /// keeps its scheme so it is parsed back as announced.
+    fn announcement_location(&self) -> String {
+        match (&self.ipns_name, &self.gateway_url) {
+            (Some(ipns_name), Some(gateway)) => format!(
+                "ipfs://{}/ipns/{ipns_name}",
+                gateway.as_str().trim_end_matches('/')
+            ),
+            _ => format!("ipfs://{}", self.api_url.as_str().trim_end_matches('/')),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        sync::{Arc, Mutex as StdMutex},
+        thread,
+    };
+
+    use super::*;
+    use crate::settings::CheckpointSyncerConf;
+
+    /// Serve the canned responses in order, one per connection, and record
+    /// the request line of every request.
+    fn serve(responses: Vec<String>) -> (Url, Arc<StdMutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+        let requests = Arc::new(StdMutex::new(Vec::new()));
+        let recorded = requests.clone();
+        thread::spawn(move || {
+            for response in responses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut content_length = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header == "\r\n" {
+                        break;
+                    }
+                    if let Some((name, value)) = header.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            content_length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+                reader
+                    .by_ref()
+                    .take(content_length)
+                    .read_to_end(&mut Vec::new())
+                    .unwrap();
+                recorded.lock().unwrap().push(request_line);
+                reader.get_mut().write_all(response.as_bytes()).unwrap();
+            }
+        });
+        (url, requests)
+    }
+
+    fn response(status: &str, body: &str) -> String {
+        format!(
+            "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+    }
+
+    #[tokio::test]
+    async fn keeps_index_when_reading_it_fails() {
+        let (url, requests) = serve(vec![
+            response("200 OK", r#"{"Hash": "cid1"}"#),
+            response("401 Unauthorized", r#"{"Message": "invalid token"}"#),
+        ]);
+        let storage = IpfsStorage::new(url, None, None, None, None);
+        let err = storage
+            .write_object("announcement.json".to_owned(), "{}")
+            .await
+            .unwrap_err();
+        assert!(format!("{err:?}").contains("401"));
+        // the index was never written back
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(requests.iter().all(|r| !r.contains("files/write")));
+    }
+
+    #[tokio::test]
+    async fn reads_missing_index_as_empty() {
+        let (url, _) = serve(vec![
+            response(
+                "500 Internal Server Error",
+                r#"{"Message": "file does not exist", "Code": 0, "Type": "error"}"#,
+            ),
+            response("500 Internal Server Error", r#"{"Message": "repo locked"}"#),
+        ]);
+        let storage = IpfsStorage::new(url, None, None, None, None);
+        assert!(storage.list_objects().await.unwrap().is_empty());
+        assert!(storage.latest_index().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn publishes_index_after_writing_it() {
+        let (url, requests) = serve(vec![
+            response("200 OK", r#"{"Hash": "cid1"}"#),
+            response(
+                "500 Internal Server Error",
+                r#"{"Message": "file does not exist", "Code": 0, "Type": "error"}"#,
+            ),
+            response("200 OK", ""),
+            response("200 OK", r#"{"Hash": "index1"}"#),
+            response("200 OK", r#"{"Name": "k51name", "Value": "/ipfs/index1"}"#),
+        ]);
+        let storage = IpfsStorage::new(url, None, None, Some("k51name".to_owned()), None);
+        storage
+            .write_object("announcement.json".to_owned(), "{}")
+            .await
+            .unwrap();
+        let requests = requests.lock().unwrap();
+        assert!(requests[3].contains("files/stat"));
+        assert!(requests[4].contains("name/publish?arg=%2Fipfs%2Findex1&key=k51name"));
+    }
+
+    #[tokio::test]
+    async fn reads_index_through_ipns() {
+        let (url, requests) = serve(vec![
+            response(
+                "200 OK",
+                r#"{"objects": {"checkpoint_latest_index.json": "cid1"}}"#,
+            ),
+            response("200 OK", "7"),
+        ]);
+        let storage = IpfsStorage::new(
+            url.clone(),
+            Some(url),
+            None,
+            Some("k51name".to_owned()),
+            None,
+        );
+        assert_eq!(storage.latest_index().await.unwrap(), Some(7));
+        let requests = requests.lock().unwrap();
+        assert!(requests[0].starts_with("GET /ipns/k51name "));
+        assert!(requests[1].starts_with("GET /ipfs/cid1 "));
+    }
+
+    #[test]
+    fn announcement_location_round_trips() {
+        let gateway: Url = "http://127.0.0.1:8080/".parse().unwrap();
+        let storage = IpfsStorage::new(
+            "http://127.0.0.1:5001/".parse().unwrap(),
+            Some(gateway.clone()),
+            None,
+            Some("k51name".to_owned()),
+            None,
+        );
+        let location = storage.announcement_location();
+        assert_eq!(location, "ipfs://http://127.0.0.1:8080/ipns/k51name");
+        let CheckpointSyncerConf::Ipfs {
+            gateway_url,
+            ipns_name,
+            ..
+        } = location.parse().unwrap()
+        else {
+            panic!("expected an IPFS checkpoint syncer");
+        };
+        assert_eq!(gateway_url, Some(gateway));
+        assert_eq!(ipns_name.as_deref(), Some("k51name"));
+
+        let storage = IpfsStorage::new(
+            "http://127.0.0.1:5001/".parse().unwrap(),
+            None,
+            None,
+            None,
+            None,
+        );
+        let CheckpointSyncerConf::Ipfs { api_url, .. } =
+            storage.announcement_location().parse().unwrap()
+        else {
+            panic!("expected an IPFS checkpoint syncer");
+        };
+        assert_eq!(api_url.as_str(), "http://127.0.0.1:5001/");
+    }
+}
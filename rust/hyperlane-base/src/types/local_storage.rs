@@ -1,7 +1,7 @@
 use std::path::PathBuf;
 
 use async_trait::async_trait;
-use eyre::{Context, Result};
+use eyre::{bail, Context, Result};
 use prometheus::IntGauge;
 
 use hyperlane_core::{SignedAnnouncement, SignedCheckpoint, SignedCheckpointWithMessageId};
@@ -13,13 +13,26 @@ use crate::traits::CheckpointSyncer;
 pub struct LocalStorage {
     /// base path
     path: PathBuf,
+    /// number of the most recent checkpoints to keep, keeps everything when
+    /// `None`
+    retain: Option<u32>,
     latest_index: Option<IntGauge>,
 }
 
 impl LocalStorage {
-    /// Create a new LocalStorage checkpoint syncer instance.
-    pub fn new(path: PathBuf, latest_index: Option<IntGauge>) -> Result<Self> {
+    /// Create a new LocalStorage checkpoint syncer instance. The directory is
+    /// created if it does not exist yet and `create_if_missing` is set. When
+    /// `retain` is set only that many of the most recent checkpoints are kept.
+    pub fn new(
+        path: PathBuf,
+        create_if_missing: bool,
+        retain: Option<u32>,
+        latest_index: Option<IntGauge>,
+    ) -> Result<Self> {
         if !path.exists() {
+            if !create_if_missing {
+                bail!("Local checkpoint syncer storage directory {path:?} does not exist");
+            }
             std::fs::create_dir_all(&path).with_context(|| {
                 format!(
                     "Failed to create local checkpoint syncer storage directory at {:?}",
@@ -27,7 +40,11 @@ impl LocalStorage {
                 )
             })?;
         }
-        Ok(Self { path, latest_index })
+        Ok(Self {
+            path,
+            retain,
+            latest_index,
+        })
     }
 
     fn legacy_checkpoint_file_path(&self, index: u32) -> PathBuf {
@@ -53,6 +70,47 @@ impl LocalStorage {
     fn announcement_file_path(&self) -> PathBuf {
         self.path.join("announcement.json")
     }
+
+    /// The index of a checkpoint file, `None` for any other file such as the
+    /// latest index or the announcement.
+    fn checkpoint_file_index(file_name: &str) -> Option<u32> {
+        file_name
+            .strip_suffix("_with_id.json")
+            .or_else(|| file_name.strip_suffix(".json"))?
+            .parse()
+            .ok()
+    }
+
+    /// Delete the checkpoints which are older than the `retain` most recent
+    /// ones after `written` was written. The latest index file and the
+    /// checkpoint it points to are always kept.
+    async fn prune(&self, written: u32) -> Result<()> {
+        let Some(retain) = self.retain else {
+            return Ok(());
+        };
+        let pointer = self.latest_index().await?;
+        let latest = pointer.map_or(written, |pointer| pointer.max(written));
+        // keeps the checkpoints in `(latest - retain, latest]`
+        let Some(oldest_pruned) = latest.checked_sub(retain) else {
+            return Ok(());
+        };
+        let mut entries = tokio::fs::read_dir(&self.path)
+            .await
+            .with_context(|| format!("Reading checkpoints in {:?}", self.path))?;
+        while let Some(entry) = entries.next_entry().await? {
+            let file_name = entry.file_name();
+            let Some(index) = file_name.to_str().and_then(Self::checkpoint_file_index) else {
+                continue;
+            };
+            if index <= oldest_pruned && Some(index) != pointer {
+                let path = entry.path();
+                tokio::fs::remove_file(&path)
+                    .await
+                    .with_context(|| format!("Pruning checkpoint {path:?}"))?;
+            }
+        }
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -109,6 +167,7 @@ impl CheckpointSyncer for LocalStorage {
             None => self.write_index(signed_checkpoint.value.index).await?,
         }
 
+        self.prune(signed_checkpoint.value.index).await?;
         Ok(())
     }
 
@@ -122,6 +181,7 @@ impl CheckpointSyncer for LocalStorage {
             .await
             .with_context(|| format!("Writing (checkpoint, messageId) to {path:?}"))?;
 
+        self.prune(signed_checkpoint.value.index).await?;
         Ok(())
     }
 
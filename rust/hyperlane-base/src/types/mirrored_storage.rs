@@ -0,0 +1,148 @@
+use async_trait::async_trait;
+use eyre::{bail, Result};
+use futures_util::future::join_all;
+use tracing::warn;
+
+use hyperlane_core::{SignedAnnouncement, SignedCheckpoint, SignedCheckpointWithMessageId};
+
+use crate::traits::CheckpointSyncer;
+
+/// A checkpoint syncer which writes to all of its syncers, so a standby has
+/// every checkpoint when the primary has an outage. Reads are served by the
+/// first syncer which has the value.
+#[derive(Debug)]
+pub struct MirroredStorage {
+    syncers: Vec<Box<dyn CheckpointSyncer>>,
+}
+
+impl MirroredStorage {
+    /// Mirror writes to all `syncers`, reads prefer them in the given order.
+    pub fn new(syncers: Vec<Box<dyn CheckpointSyncer>>) -> Result<Self> {
+        if syncers.is_empty() {
+            bail!("At least one checkpoint syncer is required");
+        }
+        Ok(Self { syncers })
+    }
+
+    /// Succeeds if at least one of the syncers was written to, the other
+    /// failures are only logged.
+    fn check_writes(results: Vec<Result<()>>, data: &str) -> Result<()> {
+        let mut written = false;
+        let mut last_err = None;
+        for (i, res) in results.into_iter().enumerate() {
+            match res {
+                Ok(()) => written = true,
+                Err(err) => {
+                    warn!(
+                        syncer = i,
+                        ?err,
+                        "Failed to write {data} to checkpoint syncer"
+                    );
+                    last_err = Some(err);
+                }
+            }
+        }
+        match last_err {
+            Some(err) if !written => Err(err),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[async_trait]
+impl CheckpointSyncer for MirroredStorage {
+    async fn latest_index(&self) -> Result<Option<u32>> {
+        let mut last_err = None;
+        for (i, syncer) in self.syncers.iter().enumerate() {
+            match syncer.latest_index().await {
+                Ok(Some(index)) => return Ok(Some(index)),
+                Ok(None) => {}
+                Err(err) => {
+                    warn!(
+                        syncer = i,
+                        ?err,
+                        "Failed to read latest index from checkpoint syncer"
+                    );
+                    last_err = Some(err);
+                }
+            }
+        }
+        last_err.map_or(Ok(None), Err)
+    }
+
+    async fn legacy_fetch_checkpoint(&self, index: u32) -> Result<Option<SignedCheckpoint>> {
+        let mut last_err = None;
+        for (i, syncer) in self.syncers.iter().enumerate() {
+            match syncer.legacy_fetch_checkpoint(index).await {
+                Ok(Some(checkpoint)) => return Ok(Some(checkpoint)),
+                Ok(None) => {}
+                Err(err) => {
+                    warn!(
+                        syncer = i,
+                        ?err,
+                        "Failed to fetch checkpoint from checkpoint syncer"
+                    );
+                    last_err = Some(err);
+                }
+            }
+        }
+        last_err.map_or(Ok(None), Err)
+    }
+
+    async fn fetch_checkpoint(&self, index: u32) -> Result<Option<SignedCheckpointWithMessageId>> {
+        let mut last_err = None;
+        for (i, syncer) in self.syncers.iter().enumerate() {
+            match syncer.fetch_checkpoint(index).await {
+                Ok(Some(checkpoint)) => return Ok(Some(checkpoint)),
+                Ok(None) => {}
+                Err(err) => {
+                    warn!(
+                        syncer = i,
+                        ?err,
+                        "Failed to fetch checkpoint from checkpoint syncer"
+                    );
+                    last_err = Some(err);
+                }
+            }
+        }
+        last_err.map_or(Ok(None), Err)
+    }
+
+    async fn legacy_write_checkpoint(&self, signed_checkpoint: &SignedCheckpoint) -> Result<()> {
+        let results = join_all(
+            self.syncers
+                .iter()
+                .map(|s| s.legacy_write_checkpoint(signed_checkpoint)),
+        )
+        .await;
+        Self::check_writes(results, "checkpoint")
+    }
+
+    async fn write_checkpoint(
+        &self,
+        signed_checkpoint: &SignedCheckpointWithMessageId,
+    ) -> Result<()> {
+        let results = join_all(
+            self.syncers
+                .iter()
+                .map(|s| s.write_checkpoint(signed_checkpoint)),
+        )
+        .await;
+        Self::check_writes(results, "checkpoint")
+    }
+
+    async fn write_announcement(&self, signed_announcement: &SignedAnnouncement) -> Result<()> {
+        let results = join_all(
+            self.syncers
+                .iter()
+                .map(|s| s.write_announcement(signed_announcement)),
+        )
+        .await;
+        Self::check_writes(results, "announcement")
+    }
+
+    /// Only the location of the primary is announced.
+    fn announcement_location(&self) -> String {
+        self.syncers[0].announcement_location()
+    }
+}
@@ -1,7 +1,13 @@
+mod gcs_storage;
+mod ipfs_storage;
 mod local_storage;
+mod mirrored_storage;
 mod multisig;
 mod s3_storage;
 
+pub use gcs_storage::*;
+pub use ipfs_storage::*;
 pub use local_storage::*;
+pub use mirrored_storage::*;
 pub use multisig::*;
 pub use s3_storage::*;
@@ -3,24 +3,142 @@ use std::sync::Arc;
 
 use derive_new::new;
 use eyre::Result;
-use tracing::{debug, instrument, trace};
+use futures_util::{stream, StreamExt};
+use prometheus::IntCounterVec;
+use tracing::{debug, instrument, trace, warn};
 
 use hyperlane_core::{
-    Checkpoint, CheckpointWithMessageId, MultisigSignedCheckpoint, SignedCheckpointWithSigner,
-    H160, H256,
+    Checkpoint, CheckpointWithMessageId, MultisigSignedCheckpoint, Signable, SignatureScheme,
+    SignedCheckpointWithSigner, SignedType, H160, H256,
 };
 
 use crate::CheckpointSyncer;
 
+/// Default number of validator checkpoint syncers which are read from
+/// concurrently
+pub const DEFAULT_CHECKPOINT_FETCH_CONCURRENCY: usize = 4;
+
 /// Fetches signed checkpoints from multiple validators to create
 /// MultisigSignedCheckpoints
 #[derive(Clone, Debug, new)]
 pub struct MultisigCheckpointSyncer {
     /// The checkpoint syncer for each valid validator signer address
     checkpoint_syncers: HashMap<H160, Arc<dyn CheckpointSyncer>>,
+    /// The maximum number of checkpoint syncers to read from at once
+    fetch_concurrency: usize,
+    /// Whether fetched checkpoints are only used if they were signed with the
+    /// signature scheme they declare. The signer is checked either way, this
+    /// only allows checkpoints with a wrong `signature_scheme` when disabled.
+    verify_signatures: bool,
+    /// Number of fetched checkpoints rejected because they were not signed by
+    /// their validator, by validator
+    signature_mismatches: IntCounterVec,
 }
 
 impl MultisigCheckpointSyncer {
+    /// The signer of a checkpoint fetched from the syncer of `validator`, or
+    /// `None` if it was signed by anyone else. The signer is recovered from
+    /// the digest of the `signature_scheme` the checkpoint was signed with.
+    /// When signatures are not verified strictly, a checkpoint declaring the
+    /// wrong scheme is accepted if another scheme recovers the validator, and
+    /// its `signature_scheme` is corrected.
+    fn checkpoint_signer<T: Signable>(
+        &self,
+        validator: &H256,
+        signed_checkpoint: &mut SignedType<T>,
+    ) -> Option<H160> {
+        match signed_checkpoint.recover() {
+            Ok(signer) if H256::from(signer) == *validator => Some(signer),
+            recovered => {
+                if !self.verify_signatures {
+                    if let Some(signer) =
+                        self.signer_with_other_scheme(validator, signed_checkpoint)
+                    {
+                        return Some(signer);
+                    }
+                }
+                warn!(
+                    validator = format!("{validator:#x}"),
+                    ?recovered,
+                    "Rejecting checkpoint which was not signed by its validator"
+                );
+                self.signature_mismatches
+                    .with_label_values(&[&format!("{validator:#x}")])
+                    .inc();
+                None
+            }
+        }
+    }
+
+    /// Multisig ISMs verify the EIP-191 hash of a checkpoint, so metadata can
+    /// not be built from checkpoints signed with any other scheme.
+    fn is_ism_verifiable<T: Signable>(validator: &H256, signed_checkpoint: &SignedType<T>) -> bool {
+        if signed_checkpoint.signature_scheme == SignatureScheme::Eip191 {
+            return true;
+        }
+        warn!(
+            validator = format!("{validator:#x}"),
+            signature_scheme = ?signed_checkpoint.signature_scheme,
+            "Skipping checkpoint which multisig ISMs can not verify, only `eip191` signatures are supported"
+        );
+        false
+    }
+
+    /// Recover `validator` as the signer of a checkpoint using a scheme other
+    /// than the one it declares, correcting its `signature_scheme` if found.
+    fn signer_with_other_scheme<T: Signable>(
+        &self,
+        validator: &H256,
+        signed_checkpoint: &mut SignedType<T>,
+    ) -> Option<H160> {
+        let declared = signed_checkpoint.signature_scheme;
+        for scheme in [SignatureScheme::Eip191, SignatureScheme::Ecdsa] {
+            if scheme == declared {
+                continue;
+            }
+            signed_checkpoint.signature_scheme = scheme;
+            match signed_checkpoint.recover() {
+                Ok(signer) if H256::from(signer) == *validator => {
+                    debug!(
+                        validator = format!("{validator:#x}"),
+                        ?declared,
+                        ?scheme,
+                        "Accepting checkpoint which declares the wrong signature scheme"
+                    );
+                    return Some(signer);
+                }
+                _ => signed_checkpoint.signature_scheme = declared,
+            }
+        }
+        None
+    }
+
+    /// Get the latest index from each validator's checkpoint syncer, reading
+    /// from up to `fetch_concurrency` syncers at once.
+    async fn latest_indices(&self, validators: &[H256]) -> Vec<u32> {
+        stream::iter(validators.iter().filter_map(|validator| {
+            let address = H160::from(*validator);
+            let checkpoint_syncer = self.checkpoint_syncers.get(&address)?;
+            Some(async move { (address, checkpoint_syncer.latest_index().await) })
+        }))
+        .buffered(self.fetch_concurrency.max(1))
+        .filter_map(|(address, latest_index)| async move {
+            // Gracefully handle errors getting the latest_index
+            match latest_index {
+                Ok(Some(index)) => {
+                    trace!(?address, ?index, "Validator returned latest index");
+                    Some(index)
+                }
+                err => {
+                    debug!(?address, ?err, "Failed to get latest index from validator");
+                    None
+                }
+            }
+        })
+        .collect()
+        .await
+    }
+
     /// Attempts to get the latest checkpoint with a quorum of signatures among
     /// validators.
     ///
@@ -42,22 +160,7 @@ impl MultisigCheckpointSyncer {
         maximum_index: u32,
     ) -> Result<Option<MultisigSignedCheckpoint<Checkpoint>>> {
         // Get the latest_index from each validator's checkpoint syncer.
-        let mut latest_indices = Vec::with_capacity(validators.len());
-        for validator in validators {
-            let address = H160::from(*validator);
-            if let Some(checkpoint_syncer) = self.checkpoint_syncers.get(&address) {
-                // Gracefully handle errors getting the latest_index
-                match checkpoint_syncer.latest_index().await {
-                    Ok(Some(index)) => {
-                        trace!(?address, ?index, "Validator returned latest index");
-                        latest_indices.push(index);
-                    }
-                    err => {
-                        debug!(?address, ?err, "Failed to get latest index from validator");
-                    }
-                }
-            }
-        }
+        let mut latest_indices = self.latest_indices(validators).await;
         debug!(
             ?latest_indices,
             "Fetched latest indices from checkpoint syncers"
@@ -110,80 +213,85 @@ impl MultisigCheckpointSyncer {
             Vec<SignedCheckpointWithSigner<Checkpoint>>,
         > = HashMap::new();
 
-        for validator in validators {
-            let addr = H160::from(*validator);
-            if let Some(checkpoint_syncer) = self.checkpoint_syncers.get(&addr) {
-                // Gracefully ignore an error fetching the checkpoint from a validator's
-                // checkpoint syncer, which can happen if the validator has not
-                // signed the checkpoint at `index`.
-                if let Ok(Some(signed_checkpoint)) =
-                    checkpoint_syncer.legacy_fetch_checkpoint(index).await
-                {
-                    // If the signed checkpoint is for a different index, ignore it
-                    if signed_checkpoint.value.index != index {
-                        debug!(
-                            validator = format!("{:#x}", validator),
-                            index = index,
-                            checkpoint_index = signed_checkpoint.value.index,
-                            "Checkpoint index mismatch"
-                        );
-                        continue;
-                    }
-                    // Ensure that the signature is actually by the validator
-                    let signer = signed_checkpoint.recover()?;
-                    if H256::from(signer) != *validator {
-                        debug!(
-                            validator = format!("{:#x}", validator),
-                            index = index,
-                            "Checkpoint signature mismatch"
-                        );
-                        continue;
+        // Fetch from up to `fetch_concurrency` checkpoint syncers at once, in
+        // validator order so we can stop as soon as a quorum is reached.
+        let mut fetched_checkpoints = stream::iter(validators)
+            .map(|validator| async move {
+                let fetched = match self.checkpoint_syncers.get(&H160::from(*validator)) {
+                    Some(checkpoint_syncer) => {
+                        Some(checkpoint_syncer.legacy_fetch_checkpoint(index).await)
                     }
+                    None => None,
+                };
+                (validator, fetched)
+            })
+            .buffered(self.fetch_concurrency.max(1));
 
-                    // Insert the SignedCheckpointWithSigner into signed_checkpoints_per_root
-                    let signed_checkpoint_with_signer = SignedCheckpointWithSigner {
-                        signer,
-                        signed_checkpoint,
-                    };
-                    let root = signed_checkpoint_with_signer.signed_checkpoint.value.root;
-
-                    let signature_count = match signed_checkpoints_per_root.entry(root) {
-                        Entry::Occupied(mut entry) => {
-                            let vec = entry.get_mut();
-                            vec.push(signed_checkpoint_with_signer);
-                            vec.len()
-                        }
-                        Entry::Vacant(entry) => {
-                            entry.insert(vec![signed_checkpoint_with_signer]);
-                            1 // length of 1
-                        }
-                    };
-                    debug!(
-                        validator = format!("{validator:#x}"),
-                        index,
-                        root = format!("{root:#x}"),
-                        signature_count,
-                        "Found signed checkpoint"
-                    );
-                    // If we've hit a quorum, create a MultisigSignedCheckpoint
-                    if signature_count >= threshold {
-                        if let Some(signed_checkpoints) = signed_checkpoints_per_root.get(&root) {
-                            let checkpoint =
-                                MultisigSignedCheckpoint::try_from(signed_checkpoints)?;
-                            debug!(?checkpoint, "Fetched multisig checkpoint");
-                            return Ok(Some(checkpoint));
-                        }
-                    }
-                } else {
+        while let Some((validator, fetched)) = fetched_checkpoints.next().await {
+            let Some(fetched) = fetched else {
+                debug!(%validator, "Unable to find checkpoint syncer");
+                continue;
+            };
+            // Gracefully ignore an error fetching the checkpoint from a validator's
+            // checkpoint syncer, which can happen if the validator has not
+            // signed the checkpoint at `index`.
+            if let Ok(Some(mut signed_checkpoint)) = fetched {
+                // If the signed checkpoint is for a different index, ignore it
+                if signed_checkpoint.value.index != index {
                     debug!(
-                        validator = format!("{validator:#x}"),
+                        validator = format!("{:#x}", validator),
                         index = index,
-                        "Unable to find signed checkpoint"
+                        checkpoint_index = signed_checkpoint.value.index,
+                        "Checkpoint index mismatch"
                     );
+                    continue;
+                }
+                let Some(signer) = self.checkpoint_signer(validator, &mut signed_checkpoint) else {
+                    continue;
+                };
+                if !Self::is_ism_verifiable(validator, &signed_checkpoint) {
+                    continue;
+                }
+
+                // Insert the SignedCheckpointWithSigner into signed_checkpoints_per_root
+                let signed_checkpoint_with_signer = SignedCheckpointWithSigner {
+                    signer,
+                    signed_checkpoint,
+                };
+                let root = signed_checkpoint_with_signer.signed_checkpoint.value.root;
+
+                let signature_count = match signed_checkpoints_per_root.entry(root) {
+                    Entry::Occupied(mut entry) => {
+                        let vec = entry.get_mut();
+                        vec.push(signed_checkpoint_with_signer);
+                        vec.len()
+                    }
+                    Entry::Vacant(entry) => {
+                        entry.insert(vec![signed_checkpoint_with_signer]);
+                        1 // length of 1
+                    }
+                };
+                debug!(
+                    validator = format!("{validator:#x}"),
+                    index,
+                    root = format!("{root:#x}"),
+                    signature_count,
+                    "Found signed checkpoint"
+                );
+                // If we've hit a quorum, create a MultisigSignedCheckpoint
+                if signature_count >= threshold {
+                    if let Some(signed_checkpoints) = signed_checkpoints_per_root.get(&root) {
+                        let checkpoint = MultisigSignedCheckpoint::try_from(signed_checkpoints)?;
+                        debug!(?checkpoint, "Fetched multisig checkpoint");
+                        return Ok(Some(checkpoint));
+                    }
                 }
             } else {
-                debug!(%validator, "Unable to find checkpoint syncer");
-                continue;
+                debug!(
+                    validator = format!("{validator:#x}"),
+                    index = index,
+                    "Unable to find signed checkpoint"
+                );
             }
         }
         Ok(None)
@@ -210,22 +318,7 @@ impl MultisigCheckpointSyncer {
         maximum_index: u32,
     ) -> Result<Option<MultisigSignedCheckpoint<CheckpointWithMessageId>>> {
         // Get the latest_index from each validator's checkpoint syncer.
-        let mut latest_indices = Vec::with_capacity(validators.len());
-        for validator in validators {
-            let address = H160::from(*validator);
-            if let Some(checkpoint_syncer) = self.checkpoint_syncers.get(&address) {
-                // Gracefully handle errors getting the latest_index
-                match checkpoint_syncer.latest_index().await {
-                    Ok(Some(index)) => {
-                        trace!(?address, ?index, "Validator returned latest index");
-                        latest_indices.push(index);
-                    }
-                    err => {
-                        debug!(?address, ?err, "Failed to get latest index from validator");
-                    }
-                }
-            }
-        }
+        let mut latest_indices = self.latest_indices(validators).await;
         debug!(
             ?latest_indices,
             "Fetched latest indices from checkpoint syncers"
@@ -277,85 +370,200 @@ impl MultisigCheckpointSyncer {
             Vec<SignedCheckpointWithSigner<CheckpointWithMessageId>>,
         > = HashMap::new();
 
-        for validator in validators.iter() {
-            let addr = H160::from(*validator);
-            if let Some(checkpoint_syncer) = self.checkpoint_syncers.get(&addr) {
-                // Gracefully ignore an error fetching the checkpoint from a validator's
-                // checkpoint syncer, which can happen if the validator has not
-                // signed the checkpoint at `index`.
-                if let Ok(Some(signed_checkpoint)) = checkpoint_syncer.fetch_checkpoint(index).await
-                {
-                    // If the signed checkpoint is for a different index, ignore it
-                    if signed_checkpoint.value.index != index {
-                        debug!(
-                            validator = format!("{:#x}", validator),
-                            index = index,
-                            checkpoint_index = signed_checkpoint.value.index,
-                            "Checkpoint index mismatch"
-                        );
-                        continue;
-                    }
-                    // Ensure that the signature is actually by the validator
-                    let signer = signed_checkpoint.recover()?;
-                    if H256::from(signer) != *validator {
-                        debug!(
-                            validator = format!("{:#x}", validator),
-                            index = index,
-                            "Checkpoint signature mismatch"
-                        );
-                        continue;
+        // Fetch from up to `fetch_concurrency` checkpoint syncers at once, in
+        // validator order so we can stop as soon as a quorum is reached.
+        let mut fetched_checkpoints = stream::iter(validators)
+            .map(|validator| async move {
+                let fetched = match self.checkpoint_syncers.get(&H160::from(*validator)) {
+                    Some(checkpoint_syncer) => {
+                        Some(checkpoint_syncer.fetch_checkpoint(index).await)
                     }
+                    None => None,
+                };
+                (validator, fetched)
+            })
+            .buffered(self.fetch_concurrency.max(1));
 
-                    // Insert the SignedCheckpointWithSigner into signed_checkpoints_per_root
-                    let signed_checkpoint_with_signer =
-                        SignedCheckpointWithSigner::<CheckpointWithMessageId> {
-                            signer,
-                            signed_checkpoint,
-                        };
-                    let root = signed_checkpoint_with_signer.signed_checkpoint.value.root;
-
-                    let signature_count = match signed_checkpoints_per_root.entry(root) {
-                        Entry::Occupied(mut entry) => {
-                            let vec = entry.get_mut();
-                            vec.push(signed_checkpoint_with_signer);
-                            vec.len()
-                        }
-                        Entry::Vacant(entry) => {
-                            entry.insert(vec![signed_checkpoint_with_signer]);
-                            1 // length of 1
-                        }
-                    };
+        while let Some((validator, fetched)) = fetched_checkpoints.next().await {
+            let Some(fetched) = fetched else {
+                debug!(%validator, "Unable to find checkpoint syncer");
+                continue;
+            };
+            // Gracefully ignore an error fetching the checkpoint from a validator's
+            // checkpoint syncer, which can happen if the validator has not
+            // signed the checkpoint at `index`.
+            if let Ok(Some(mut signed_checkpoint)) = fetched {
+                // If the signed checkpoint is for a different index, ignore it
+                if signed_checkpoint.value.index != index {
                     debug!(
                         validator = format!("{:#x}", validator),
                         index = index,
-                        root = format!("{:#x}", root),
-                        signature_count = signature_count,
-                        "Found signed checkpoint"
+                        checkpoint_index = signed_checkpoint.value.index,
+                        "Checkpoint index mismatch"
                     );
-                    // If we've hit a quorum, create a MultisigSignedCheckpoint
-                    if signature_count >= threshold {
-                        if let Some(signed_checkpoints) = signed_checkpoints_per_root.get(&root) {
-                            let checkpoint =
-                                MultisigSignedCheckpoint::<CheckpointWithMessageId>::try_from(
-                                    signed_checkpoints,
-                                )?;
-                            debug!(checkpoint=?checkpoint, "Fetched multisig checkpoint");
-                            return Ok(Some(checkpoint));
-                        }
+                    continue;
+                }
+                let Some(signer) = self.checkpoint_signer(validator, &mut signed_checkpoint) else {
+                    continue;
+                };
+                if !Self::is_ism_verifiable(validator, &signed_checkpoint) {
+                    continue;
+                }
+
+                // Insert the SignedCheckpointWithSigner into signed_checkpoints_per_root
+                let signed_checkpoint_with_signer =
+                    SignedCheckpointWithSigner::<CheckpointWithMessageId> {
+                        signer,
+                        signed_checkpoint,
+                    };
+                let root = signed_checkpoint_with_signer.signed_checkpoint.value.root;
+
+                let signature_count = match signed_checkpoints_per_root.entry(root) {
+                    Entry::Occupied(mut entry) => {
+                        let vec = entry.get_mut();
+                        vec.push(signed_checkpoint_with_signer);
+                        vec.len()
+                    }
+                    Entry::Vacant(entry) => {
+                        entry.insert(vec![signed_checkpoint_with_signer]);
+                        1 // length of 1
+                    }
+                };
+                debug!(
+                    validator = format!("{:#x}", validator),
+                    index = index,
+                    root = format!("{:#x}", root),
+                    signature_count = signature_count,
+                    "Found signed checkpoint"
+                );
+                // If we've hit a quorum, create a MultisigSignedCheckpoint
+                if signature_count >= threshold {
+                    if let Some(signed_checkpoints) = signed_checkpoints_per_root.get(&root) {
+                        let checkpoint =
+                            MultisigSignedCheckpoint::<CheckpointWithMessageId>::try_from(
+                                signed_checkpoints,
+                            )?;
+                        debug!(checkpoint=?checkpoint, "Fetched multisig checkpoint");
+                        return Ok(Some(checkpoint));
                     }
-                } else {
-                    debug!(
-                        validator = format!("{:#x}", validator),
-                        index = index,
-                        "Unable to find signed checkpoint"
-                    );
                 }
             } else {
-                debug!(%validator, "Unable to find checkpoint syncer");
-                continue;
+                debug!(
+                    validator = format!("{:#x}", validator),
+                    index = index,
+                    "Unable to find signed checkpoint"
+                );
             }
         }
         debug!("No quorum checkpoint found for message");
         Ok(None)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use ethers::signers::LocalWallet;
+    use hyperlane_core::{HyperlaneSigner, HyperlaneSignerExt, SignatureScheme};
+    use hyperlane_ethereum::Signers;
+    use prometheus::Opts;
+
+    use super::*;
+
+    fn syncer(verify_signatures: bool) -> MultisigCheckpointSyncer {
+        let signature_mismatches =
+            IntCounterVec::new(Opts::new("signature_mismatches", "test"), &["validator"]).unwrap();
+        MultisigCheckpointSyncer::new(HashMap::new(), 1, verify_signatures, signature_mismatches)
+    }
+
+    async fn signed_checkpoint(
+        signature_scheme: SignatureScheme,
+    ) -> (H256, SignedType<Checkpoint>) {
+        let signer: Signers = "1111111111111111111111111111111111111111111111111111111111111111"
+            .parse::<LocalWallet>()
+            .unwrap()
+            .into();
+        let checkpoint = Checkpoint {
+            mailbox_address: H256::repeat_byte(2),
+            mailbox_domain: 5,
+            root: H256::repeat_byte(1),
+            index: 123,
+        };
+        let signed = signer
+            .sign_with_scheme(checkpoint, signature_scheme)
+            .await
+            .unwrap();
+        (signer.eth_address().into(), signed)
+    }
+
+    #[tokio::test]
+    async fn verifies_ecdsa_checkpoints() {
+        let syncer = syncer(true);
+        let (validator, mut signed) = signed_checkpoint(SignatureScheme::Ecdsa).await;
+        assert_eq!(
+            syncer.checkpoint_signer(&validator, &mut signed),
+            Some(H160::from(validator))
+        );
+        assert_eq!(
+            syncer
+                .signature_mismatches
+                .with_label_values(&[&format!("{validator:#x}")])
+                .get(),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn only_eip191_checkpoints_are_ism_verifiable() {
+        let (validator, signed) = signed_checkpoint(SignatureScheme::Ecdsa).await;
+        assert!(!MultisigCheckpointSyncer::is_ism_verifiable(
+            &validator, &signed
+        ));
+        let (validator, signed) = signed_checkpoint(SignatureScheme::Eip191).await;
+        assert!(MultisigCheckpointSyncer::is_ism_verifiable(
+            &validator, &signed
+        ));
+    }
+
+    #[tokio::test]
+    async fn counts_checkpoints_with_mismatched_scheme() {
+        let syncer = syncer(true);
+        let (validator, mut signed) = signed_checkpoint(SignatureScheme::Ecdsa).await;
+        signed.signature_scheme = SignatureScheme::Eip191;
+        assert_eq!(syncer.checkpoint_signer(&validator, &mut signed), None);
+        assert_eq!(
+            syncer
+                .signature_mismatches
+                .with_label_values(&[&format!("{validator:#x}")])
+                .get(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn unverified_checkpoints_still_need_their_validator_as_signer() {
+        let syncer = syncer(false);
+
+        // a wrong scheme is corrected when not verifying strictly
+        let (validator, mut signed) = signed_checkpoint(SignatureScheme::Ecdsa).await;
+        signed.signature_scheme = SignatureScheme::Eip191;
+        assert_eq!(
+            syncer.checkpoint_signer(&validator, &mut signed),
+            Some(H160::from(validator))
+        );
+        assert_eq!(signed.signature_scheme, SignatureScheme::Ecdsa);
+
+        // but checkpoints signed by anyone else are always rejected
+        let other_validator = H256::repeat_byte(3);
+        assert_eq!(
+            syncer.checkpoint_signer(&other_validator, &mut signed),
+            None
+        );
+        assert_eq!(
+            syncer
+                .signature_mismatches
+                .with_label_values(&[&format!("{other_validator:#x}")])
+                .get(),
+            1
+        );
+    }
+}
@@ -29,6 +29,12 @@ pub struct S3Storage {
     folder: Option<String>,
     /// The region of the bucket.
     region: Region,
+    /// KMS key checkpoints are encrypted with on the server side, if the
+    /// bucket requires SSE-KMS
+    sse_kms_key_id: Option<String>,
+    /// Profile in the shared AWS credentials file the authenticated client
+    /// uses, if not the default credential chain.
+    profile: Option<String>,
     /// A client with AWS credentials.
     #[new(default)]
     authenticated_client: OnceLock<S3Client>,
@@ -45,6 +51,8 @@ impl fmt::Debug for S3Storage {
             .field("bucket", &self.bucket)
             .field("folder", &self.folder)
             .field("region", &self.region)
+            .field("sse_kms_key_id", &self.sse_kms_key_id)
+            .field("profile", &self.profile)
             .finish()
     }
 }
@@ -56,6 +64,8 @@ impl S3Storage {
             bucket: self.bucket.clone(),
             body: Some(Vec::from(body).into()),
             content_type: Some("application/json".to_owned()),
+            server_side_encryption: self.sse_kms_key_id.as_ref().map(|_| "aws:kms".to_owned()),
+            ssekms_key_id: self.sse_kms_key_id.clone(),
             ..Default::default()
         };
         timeout(
@@ -94,7 +104,7 @@ impl S3Storage {
         self.authenticated_client.get_or_init(|| {
             S3Client::new_with(
                 HttpClient::new().unwrap(),
-                AwsChainCredentialsProvider::new(),
+                AwsChainCredentialsProvider::with_profile(self.profile.clone()),
                 self.region.clone(),
             )
         })
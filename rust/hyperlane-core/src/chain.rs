@@ -157,6 +157,10 @@ pub enum HyperlaneDomainProtocol {
     Fuel,
     /// A Sealevel-based chain type which uses hyperlane-sealevel.
     Sealevel,
+    /// A Cosmos SDK based chain type which uses hyperlane-cosmos.
+    Cosmos,
+    /// A StarkNet chain type which uses hyperlane-starknet.
+    Starknet,
 }
 
 impl HyperlaneDomainProtocol {
@@ -166,6 +170,8 @@ impl HyperlaneDomainProtocol {
             Ethereum => format!("{:?}", H160::from(addr)),
             Fuel => format!("{:?}", addr),
             Sealevel => format!("{:?}", addr),
+            Cosmos => format!("{:?}", addr),
+            Starknet => format!("{:?}", addr),
         }
     }
 }
@@ -363,7 +369,7 @@ impl HyperlaneDomain {
         use HyperlaneDomainProtocol::*;
         let protocol = self.domain_protocol();
         many_to_one!(match protocol {
-            IndexMode::Block: [Ethereum],
+            IndexMode::Block: [Ethereum, Cosmos, Starknet],
             IndexMode::Sequence : [Sealevel, Fuel],
         })
     }
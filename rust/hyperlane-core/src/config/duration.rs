@@ -0,0 +1,62 @@
+use std::time::Duration;
+
+use crate::config::{StrOrInt, StrOrIntParseError};
+
+/// Parse a human readable duration such as `"400ms"`, `"12s"`, `"2min"` or
+/// `"1h"`. A number without a unit is interpreted as seconds.
+pub fn parse_duration(s: &str) -> Result<Duration, StrOrIntParseError> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(s.len());
+    let (value, unit) = s.split_at(split);
+    let value: f64 = value
+        .parse()
+        .map_err(|_| StrOrIntParseError::Other(format!("Invalid duration `{s}`")))?;
+    let secs = match unit.trim() {
+        "ms" => value / 1000.,
+        "" | "s" | "sec" | "secs" => value,
+        "m" | "min" | "mins" => value * 60.,
+        "h" | "hr" | "hrs" => value * 3600.,
+        unit => {
+            return Err(StrOrIntParseError::Other(format!(
+                "Unknown duration unit `{unit}` in `{s}`"
+            )))
+        }
+    };
+    Ok(Duration::from_secs_f64(secs))
+}
+
+impl TryFrom<StrOrInt> for Duration {
+    type Error = StrOrIntParseError;
+
+    fn try_from(v: StrOrInt) -> Result<Self, Self::Error> {
+        (&v).try_into()
+    }
+}
+
+impl TryFrom<&StrOrInt> for Duration {
+    type Error = StrOrIntParseError;
+
+    fn try_from(v: &StrOrInt) -> Result<Self, Self::Error> {
+        match v {
+            StrOrInt::Str(s) => parse_duration(s),
+            StrOrInt::Int(i) => Ok(Duration::from_secs((*i).try_into()?)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("400ms").unwrap(), Duration::from_millis(400));
+        assert_eq!(parse_duration("12").unwrap(), Duration::from_secs(12));
+        assert_eq!(parse_duration("2min").unwrap(), Duration::from_secs(120));
+        assert_eq!(parse_duration("1.5h").unwrap(), Duration::from_secs(5400));
+        assert!(parse_duration("2 blocks").is_err());
+        assert!(parse_duration("min").is_err());
+    }
+}
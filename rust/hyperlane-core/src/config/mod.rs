@@ -6,11 +6,13 @@
 use std::fmt::{Debug, Display, Formatter};
 
 pub use config_path::ConfigPath;
+pub use duration::parse_duration;
 use eyre::Report;
 pub use str_or_int::{StrOrInt, StrOrIntParseError};
 pub use trait_ext::*;
 
 mod config_path;
+mod duration;
 mod str_or_int;
 mod trait_ext;
 
@@ -102,6 +104,20 @@ impl ConfigParsingError {
     pub fn is_ok(&self) -> bool {
         self.0.is_empty()
     }
+
+    /// The errors as a JSON array of `{ "path", "message" }` objects for
+    /// tooling, with the dotted config path of every error.
+    pub fn to_json(&self) -> serde_json::Value {
+        self.0
+            .iter()
+            .map(|(path, report)| {
+                serde_json::json!({
+                    "path": path.to_string(),
+                    "message": format!("{report:#}"),
+                })
+            })
+            .collect()
+    }
 }
 
 impl FromIterator<ConfigParsingError> for ConfigParsingError {
@@ -157,3 +173,42 @@ macro_rules! cfg_unwrap_all {
         };
     };
 }
+
+#[cfg(test)]
+mod test {
+    use eyre::{eyre, WrapErr};
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn serializes_errors_as_json() {
+        let root = ConfigPath::default();
+        let mut err = ConfigParsingError::default();
+        err.push(&root + "metrics_port", eyre!("Invalid port"));
+
+        let mut chain_err = ConfigParsingError::default();
+        let chain = root.join("chains").join("test1");
+        chain_err.push(&chain + "max_batch_size", eyre!("Must be greater than 0"));
+        chain_err.push(
+            &chain + "index" + "chunk",
+            Err::<(), _>(eyre!("Invalid number of blocks"))
+                .context("Invalid `chunk`")
+                .unwrap_err(),
+        );
+        err.merge(chain_err);
+
+        assert_eq!(
+            err.to_json(),
+            json!([
+                { "path": "metricsPort", "message": "Invalid port" },
+                { "path": "chains.test1.maxBatchSize", "message": "Must be greater than 0" },
+                {
+                    "path": "chains.test1.index.chunk",
+                    "message": "Invalid `chunk`: Invalid number of blocks",
+                },
+            ])
+        );
+        assert_eq!(ConfigParsingError::default().to_json(), json!([]));
+    }
+}
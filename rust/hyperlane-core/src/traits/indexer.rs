@@ -14,7 +14,7 @@ use serde::Deserialize;
 use crate::{ChainResult, LogMeta};
 
 /// Indexing mode.
-#[derive(Copy, Debug, Default, Deserialize, Clone)]
+#[derive(Copy, Debug, Default, Deserialize, Clone, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub enum IndexMode {
     /// Block based indexing.
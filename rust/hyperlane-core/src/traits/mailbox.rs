@@ -6,7 +6,8 @@ use auto_impl::auto_impl;
 
 use crate::{
     accumulator::incremental::IncrementalMerkle, traits::TxOutcome, utils::domain_hash,
-    ChainResult, Checkpoint, HyperlaneContract, HyperlaneMessage, TxCostEstimate, H256, U256,
+    ChainCommunicationError, ChainResult, Checkpoint, HyperlaneContract, HyperlaneMessage,
+    TxCostEstimate, H256, U256,
 };
 
 /// Interface for the Mailbox chain contract. Allows abstraction over different
@@ -54,6 +55,15 @@ pub trait Mailbox: HyperlaneContract + Send + Sync + Debug {
         tx_gas_limit: Option<U256>,
     ) -> ChainResult<TxOutcome>;
 
+    /// Process several messages in a single transaction. Every message is
+    /// processed on its own, so some of them may fail while the others are
+    /// delivered.
+    async fn process_batch(&self, _batch: &[BatchItem]) -> ChainResult<BatchResult> {
+        Err(ChainCommunicationError::from_other_str(
+            "Batching is not supported by this mailbox",
+        ))
+    }
+
     /// Estimate transaction costs to process a message.
     async fn process_estimate_costs(
         &self,
@@ -65,3 +75,23 @@ pub trait Mailbox: HyperlaneContract + Send + Sync + Debug {
     /// against the provided signed checkpoint
     fn process_calldata(&self, message: &HyperlaneMessage, metadata: &[u8]) -> Vec<u8>;
 }
+
+/// A message to process as part of a batch
+#[derive(Debug, Clone)]
+pub struct BatchItem {
+    /// The message to process
+    pub message: HyperlaneMessage,
+    /// ISM metadata to verify the message with
+    pub metadata: Vec<u8>,
+    /// Gas limit of processing the message on its own
+    pub gas_limit: U256,
+}
+
+/// The outcome of processing a batch of messages
+#[derive(Debug, Clone)]
+pub struct BatchResult {
+    /// The transaction the batch was submitted in
+    pub outcome: TxOutcome,
+    /// Indexes of the batch items which were not delivered
+    pub failed_indexes: Vec<usize>,
+}
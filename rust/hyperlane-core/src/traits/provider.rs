@@ -1,4 +1,5 @@
 use std::fmt::Debug;
+use std::time::Duration;
 
 use async_trait::async_trait;
 use auto_impl::auto_impl;
@@ -24,6 +25,13 @@ pub trait HyperlaneProvider: HyperlaneChain + Send + Sync + Debug {
 
     /// Returns whether a contract exists at the provided address
     async fn is_contract(&self, address: &H256) -> ChainResult<bool>;
+
+    /// Estimate the average time between blocks over the last
+    /// `sample_blocks` blocks. Returns None if the chain does not support
+    /// this.
+    async fn average_block_time(&self, _sample_blocks: u32) -> ChainResult<Option<Duration>> {
+        Ok(None)
+    }
 }
 
 /// Errors when querying for provider information.
@@ -26,6 +26,40 @@ pub trait HyperlaneSigner: Send + Sync + Debug {
     /// Sign a hyperlane checkpoint hash. This must be a signature without eip
     /// 155.
     async fn sign_hash(&self, hash: &H256) -> Result<Signature, HyperlaneSignerError>;
+
+    /// Sign a digest as is, without applying the EIP-191 prefix first. This
+    /// must be a signature without eip 155.
+    async fn sign_digest(&self, _digest: &H256) -> Result<Signature, HyperlaneSignerError> {
+        let err: Box<dyn std::error::Error + Send + Sync> =
+            "This signer does not support signing raw digests".into();
+        Err(err.into())
+    }
+}
+
+/// How the signing hash of a `Signable` is turned into a signature.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "strum", derive(strum::Display, strum::EnumString))]
+#[cfg_attr(
+    feature = "strum",
+    strum(serialize_all = "lowercase", ascii_case_insensitive)
+)]
+#[serde(rename_all = "lowercase")]
+pub enum SignatureScheme {
+    /// ECDSA over the EIP-191 hash of the signing hash.
+    #[default]
+    Eip191,
+    /// ECDSA directly over the signing hash.
+    Ecdsa,
+}
+
+impl SignatureScheme {
+    /// The digest which is signed for `value` under this scheme.
+    pub fn digest<T: Signable>(&self, value: &T) -> H256 {
+        match self {
+            SignatureScheme::Eip191 => value.eth_signed_message_hash(),
+            SignatureScheme::Ecdsa => value.signing_hash(),
+        }
+    }
 }
 
 /// Auto-implemented extension trait for HyperlaneSigner.
@@ -37,6 +71,13 @@ pub trait HyperlaneSignerExt {
         value: T,
     ) -> Result<SignedType<T>, HyperlaneSignerError>;
 
+    /// Sign a `Signable` value using the given signature scheme
+    async fn sign_with_scheme<T: Signable + Send>(
+        &self,
+        value: T,
+        signature_scheme: SignatureScheme,
+    ) -> Result<SignedType<T>, HyperlaneSignerError>;
+
     /// Check whether a message was signed by a specific address.
     #[cfg(feature = "ethers")]
     fn verify<T: Signable>(
@@ -50,10 +91,26 @@ impl<S: HyperlaneSigner> HyperlaneSignerExt for S {
     async fn sign<T: Signable + Send>(
         &self,
         value: T,
+    ) -> Result<SignedType<T>, HyperlaneSignerError> {
+        self.sign_with_scheme(value, SignatureScheme::default())
+            .await
+    }
+
+    async fn sign_with_scheme<T: Signable + Send>(
+        &self,
+        value: T,
+        signature_scheme: SignatureScheme,
     ) -> Result<SignedType<T>, HyperlaneSignerError> {
         let signing_hash = value.signing_hash();
-        let signature = self.sign_hash(&signing_hash).await?;
-        Ok(SignedType { value, signature })
+        let signature = match signature_scheme {
+            SignatureScheme::Eip191 => self.sign_hash(&signing_hash).await?,
+            SignatureScheme::Ecdsa => self.sign_digest(&signing_hash).await?,
+        };
+        Ok(SignedType {
+            value,
+            signature,
+            signature_scheme,
+        })
     }
 
     #[cfg(feature = "ethers")]
@@ -88,6 +145,9 @@ pub struct SignedType<T: Signable> {
     pub value: T,
     /// The signature for the value
     pub signature: Signature,
+    /// The scheme used to produce the signature
+    #[serde(default)]
+    pub signature_scheme: SignatureScheme,
 }
 
 impl<T: Signable + Serialize> Serialize for SignedType<T> {
@@ -95,11 +155,12 @@ impl<T: Signable + Serialize> Serialize for SignedType<T> {
     where
         S: Serializer,
     {
-        let mut state = serializer.serialize_struct("SignedType", 3)?;
+        let mut state = serializer.serialize_struct("SignedType", 4)?;
         state.serialize_field("value", &self.value)?;
         state.serialize_field("signature", &self.signature)?;
         let sig: [u8; 65] = self.signature.into();
         state.serialize_field("serialized_signature", &fmt_bytes(&sig))?;
+        state.serialize_field("signature_scheme", &self.signature_scheme)?;
         state.end()
     }
 }
@@ -108,7 +169,7 @@ impl<T: Signable> SignedType<T> {
     /// Recover the Ethereum address of the signer
     #[cfg(feature = "ethers")]
     pub fn recover(&self) -> Result<H160, crate::HyperlaneProtocolError> {
-        let hash = ethers_core::types::H256::from(self.value.eth_signed_message_hash());
+        let hash = ethers_core::types::H256::from(self.signature_scheme.digest(&self.value));
         let sig = ethers_core::types::Signature::from(self.signature);
         Ok(sig.recover(hash)?.into())
     }
@@ -116,7 +177,7 @@ impl<T: Signable> SignedType<T> {
     /// Check whether a message was signed by a specific address
     #[cfg(feature = "ethers")]
     pub fn verify(&self, signer: H160) -> Result<(), crate::HyperlaneProtocolError> {
-        let hash = ethers_core::types::H256::from(self.value.eth_signed_message_hash());
+        let hash = ethers_core::types::H256::from(self.signature_scheme.digest(&self.value));
         let sig = ethers_core::types::Signature::from(self.signature);
         let signer = ethers_core::types::H160::from(signer);
         Ok(sig.verify(hash, signer)?)